@@ -0,0 +1,34 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use slab_tree::*;
+
+// a chain `depth` levels deep where every node also has a leaf child, so the traversal keeps
+// descending while siblings are still pending at every level
+fn deep_tree(depth: u32) -> Tree<u32> {
+    let mut tree = TreeBuilder::new().with_root(0).build();
+    let mut node_id = tree.root_id().unwrap();
+    for i in 1..=depth {
+        let mut node = tree.get_mut(node_id).unwrap();
+        let next_id = node.append(i).node_id();
+        node.append(i);
+        node_id = next_id;
+    }
+    tree
+}
+
+fn post_order(c: &mut Criterion) {
+    let mut group = c.benchmark_group("post_order");
+    for depth in [1_000, 10_000, 100_000] {
+        let tree = deep_tree(depth);
+        group.bench_with_input(BenchmarkId::new("deep", depth), &tree, |b, tree| {
+            b.iter(|| {
+                let root = tree.root().unwrap();
+                root.traverse_post_order()
+                    .fold(0u64, |sum, node| sum + u64::from(*black_box(node.data())))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, post_order);
+criterion_main!(benches);
@@ -0,0 +1,781 @@
+//!
+//! Trees whose `Node`s are allocated from a caller-provided `bumpalo::Bump`.
+//!
+//! An `ArenaTree` keeps its slots in the `Bump` it borrows instead of in its own heap
+//! allocation, so building a batch of trees costs little more than bumping a pointer, and the
+//! whole batch is freed at once when the `Bump` is reset or dropped.  The borrow makes sure no
+//! `ArenaTree` outlives its `Bump`; it also means an `ArenaTree` is neither `Send` nor `Sync`.
+//!
+//! Growing an `ArenaTree` past its capacity copies its slots to a bigger block and leaves the old
+//! one in the `Bump` until it is reset, so it pays to create it with enough capacity up front.
+//!
+//! `ArenaTree`s hand out the same kind of `NodeId`s as `Tree`s, but have a smaller API built
+//! around them.  `ArenaTree::into_tree` moves the data into a regular `Tree` when the rest of the
+//! crate is needed.
+//!
+//! ```
+//! use bumpalo::Bump;
+//! use slab_tree::arena::ArenaTree;
+//!
+//! let bump = Bump::new();
+//! let mut files = Vec::new();
+//! for name in &["a.rs", "b.rs"] {
+//!     let mut tree = ArenaTree::with_capacity_in(16, &bump);
+//!     let root_id = tree.set_root(name.to_string());
+//!     tree.append(root_id, "fn main".to_string());
+//!     files.push(tree);
+//! }
+//!
+//! assert_eq!(files[1].len(), 2);
+//!
+//! // dropping the trees runs their data's destructors; dropping the `Bump` frees their slots
+//! drop(files);
+//! drop(bump);
+//! ```
+//!
+
+use crate::slab::ArenaSlab;
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use bumpalo::Bump;
+use snowflake::ProcessUniqueId;
+use std::fmt;
+use std::iter::FusedIterator;
+
+#[derive(Debug)]
+struct ArenaNode<T> {
+    data: T,
+    parent: Option<NodeId>,
+    prev_sibling: Option<NodeId>,
+    next_sibling: Option<NodeId>,
+    first_child: Option<NodeId>,
+    last_child: Option<NodeId>,
+}
+
+impl<T> ArenaNode<T> {
+    fn new(data: T) -> ArenaNode<T> {
+        ArenaNode {
+            data,
+            parent: None,
+            prev_sibling: None,
+            next_sibling: None,
+            first_child: None,
+            last_child: None,
+        }
+    }
+}
+
+///
+/// A tree whose `Node`s are stored in a `bumpalo::Bump` borrowed for `'bump`.
+///
+#[derive(Debug)]
+pub struct ArenaTree<'bump, T> {
+    id: ProcessUniqueId,
+    root_id: Option<NodeId>,
+    slab: ArenaSlab<'bump, ArenaNode<T>>,
+}
+
+impl<'bump, T> ArenaTree<'bump, T> {
+    ///
+    /// Creates a new, empty `ArenaTree` whose `Node`s will be allocated from `bump`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let tree: ArenaTree<i32> = ArenaTree::new_in(&bump);
+    ///
+    /// assert!(tree.is_empty());
+    /// ```
+    ///
+    pub fn new_in(bump: &'bump Bump) -> ArenaTree<'bump, T> {
+        ArenaTree::with_capacity_in(0, bump)
+    }
+
+    ///
+    /// Creates a new, empty `ArenaTree` with room for `capacity` `Node`s allocated from `bump`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let tree: ArenaTree<i32> = ArenaTree::with_capacity_in(10, &bump);
+    ///
+    /// assert_eq!(tree.capacity(), 10);
+    /// assert!(bump.allocated_bytes() > 0);
+    /// ```
+    ///
+    pub fn with_capacity_in(capacity: usize, bump: &'bump Bump) -> ArenaTree<'bump, T> {
+        ArenaTree {
+            id: ProcessUniqueId::new(),
+            root_id: None,
+            slab: ArenaSlab::new_in(capacity, bump),
+        }
+    }
+
+    ///
+    /// Returns the number of `Node`s the `ArenaTree` can hold before it must grow.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let tree: ArenaTree<i32> = ArenaTree::with_capacity_in(5, &bump);
+    ///
+    /// assert_eq!(tree.capacity(), 5);
+    /// ```
+    ///
+    pub fn capacity(&self) -> usize {
+        self.slab.capacity()
+    }
+
+    ///
+    /// Returns the number of `Node`s in the `ArenaTree`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// tree.append(root_id, 2);
+    ///
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    ///
+    pub fn len(&self) -> usize {
+        self.slab.len()
+    }
+
+    ///
+    /// Returns `true` if the `ArenaTree` has no `Node`s.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// assert!(tree.is_empty());
+    ///
+    /// tree.set_root(1);
+    /// assert!(!tree.is_empty());
+    /// ```
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    /// Returns the `NodeId` of the root `Node`, if there is one.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    ///
+    /// assert_eq!(tree.root_id(), Some(root_id));
+    /// ```
+    ///
+    pub fn root_id(&self) -> Option<NodeId> {
+        self.root_id
+    }
+
+    ///
+    /// Sets the root of the `ArenaTree` to a new `Node` holding `data`.
+    ///
+    /// If there is already a root `Node`, it is shifted down to become the only child of the new
+    /// one, as with `Tree::set_root`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let old_root_id = tree.set_root(1);
+    /// let root_id = tree.set_root(0);
+    ///
+    /// assert_eq!(tree.root_id(), Some(root_id));
+    /// assert_eq!(tree.parent(old_root_id), Some(root_id));
+    /// ```
+    ///
+    pub fn set_root(&mut self, data: T) -> NodeId {
+        let old_root_id = self.root_id.take();
+        let root_id = self.insert(ArenaNode {
+            first_child: old_root_id,
+            last_child: old_root_id,
+            ..ArenaNode::new(data)
+        });
+
+        if let Some(node) = old_root_id.and_then(|id| self.node_mut(id)) {
+            node.parent = Some(root_id);
+        }
+
+        self.root_id = Some(root_id);
+        root_id
+    }
+
+    ///
+    /// Adds a new `Node` holding `data` as the last child of `parent_id`, and returns its
+    /// `NodeId`.
+    ///
+    /// Returns `None` (and drops `data`) if `parent_id` doesn't refer to a `Node` in this
+    /// `ArenaTree`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// let child_id = tree.append(root_id, 2).unwrap();
+    ///
+    /// assert_eq!(tree.first_child(root_id), Some(child_id));
+    /// assert_eq!(tree.get(child_id), Some(&2));
+    /// ```
+    ///
+    pub fn append(&mut self, parent_id: NodeId, data: T) -> Option<NodeId> {
+        let last_child = self.node(parent_id)?.last_child;
+        let node_id = self.insert(ArenaNode {
+            parent: Some(parent_id),
+            prev_sibling: last_child,
+            ..ArenaNode::new(data)
+        });
+
+        if let Some(last_child) = last_child.and_then(|id| self.node_mut(id)) {
+            last_child.next_sibling = Some(node_id);
+        }
+        if let Some(parent) = self.node_mut(parent_id) {
+            parent.first_child = parent.first_child.or(Some(node_id));
+            parent.last_child = Some(node_id);
+        }
+
+        Some(node_id)
+    }
+
+    ///
+    /// Returns a reference to the data of the `Node` with the given `NodeId`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    ///
+    /// assert_eq!(tree.get(root_id), Some(&1));
+    /// ```
+    ///
+    pub fn get(&self, node_id: NodeId) -> Option<&T> {
+        self.node(node_id).map(|node| &node.data)
+    }
+
+    ///
+    /// Returns a mutable reference to the data of the `Node` with the given `NodeId`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// *tree.get_mut(root_id).unwrap() = 2;
+    ///
+    /// assert_eq!(tree.get(root_id), Some(&2));
+    /// ```
+    ///
+    pub fn get_mut(&mut self, node_id: NodeId) -> Option<&mut T> {
+        self.node_mut(node_id).map(|node| &mut node.data)
+    }
+
+    ///
+    /// Returns the `NodeId` of the parent of the `Node` with the given `NodeId`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// let child_id = tree.append(root_id, 2).unwrap();
+    ///
+    /// assert_eq!(tree.parent(child_id), Some(root_id));
+    /// assert_eq!(tree.parent(root_id), None);
+    /// ```
+    ///
+    pub fn parent(&self, node_id: NodeId) -> Option<NodeId> {
+        self.node(node_id)?.parent
+    }
+
+    ///
+    /// Returns the `NodeId` of the first child of the `Node` with the given `NodeId`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// let first_id = tree.append(root_id, 2).unwrap();
+    /// tree.append(root_id, 3);
+    ///
+    /// assert_eq!(tree.first_child(root_id), Some(first_id));
+    /// ```
+    ///
+    pub fn first_child(&self, node_id: NodeId) -> Option<NodeId> {
+        self.node(node_id)?.first_child
+    }
+
+    ///
+    /// Returns the `NodeId` of the last child of the `Node` with the given `NodeId`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// tree.append(root_id, 2);
+    /// let last_id = tree.append(root_id, 3).unwrap();
+    ///
+    /// assert_eq!(tree.last_child(root_id), Some(last_id));
+    /// ```
+    ///
+    pub fn last_child(&self, node_id: NodeId) -> Option<NodeId> {
+        self.node(node_id)?.last_child
+    }
+
+    ///
+    /// Returns the `NodeId` of the previous sibling of the `Node` with the given `NodeId`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// let first_id = tree.append(root_id, 2).unwrap();
+    /// let second_id = tree.append(root_id, 3).unwrap();
+    ///
+    /// assert_eq!(tree.prev_sibling(second_id), Some(first_id));
+    /// ```
+    ///
+    pub fn prev_sibling(&self, node_id: NodeId) -> Option<NodeId> {
+        self.node(node_id)?.prev_sibling
+    }
+
+    ///
+    /// Returns the `NodeId` of the next sibling of the `Node` with the given `NodeId`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// let first_id = tree.append(root_id, 2).unwrap();
+    /// let second_id = tree.append(root_id, 3).unwrap();
+    ///
+    /// assert_eq!(tree.next_sibling(first_id), Some(second_id));
+    /// ```
+    ///
+    pub fn next_sibling(&self, node_id: NodeId) -> Option<NodeId> {
+        self.node(node_id)?.next_sibling
+    }
+
+    ///
+    /// Returns an iterator over the `NodeId`s of the children of the `Node` with the given
+    /// `NodeId`, in order.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// tree.append(root_id, 2);
+    /// tree.append(root_id, 3);
+    ///
+    /// let children: Vec<i32> = tree
+    ///     .children(root_id)
+    ///     .map(|id| *tree.get(id).unwrap())
+    ///     .collect();
+    /// assert_eq!(children, vec![2, 3]);
+    /// ```
+    ///
+    pub fn children(&self, node_id: NodeId) -> Children<'_, 'bump, T> {
+        Children {
+            tree: self,
+            next: self.first_child(node_id),
+        }
+    }
+
+    ///
+    /// Removes the `Node` with the given `NodeId` and drops all of its descendants, returning
+    /// its data.
+    ///
+    /// Their slots are reused by later insertions, but the memory stays in the `Bump` until it
+    /// is reset.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut tree = ArenaTree::new_in(&bump);
+    /// let root_id = tree.set_root(1);
+    /// let child_id = tree.append(root_id, 2).unwrap();
+    /// tree.append(child_id, 3);
+    ///
+    /// assert_eq!(tree.remove(child_id), Some(2));
+    /// assert_eq!(tree.len(), 1);
+    /// assert_eq!(tree.first_child(root_id), None);
+    /// ```
+    ///
+    pub fn remove(&mut self, node_id: NodeId) -> Option<T> {
+        let node = self.node(node_id)?;
+        let (parent, prev_sibling, next_sibling) =
+            (node.parent, node.prev_sibling, node.next_sibling);
+
+        match prev_sibling.and_then(|id| self.node_mut(id)) {
+            Some(prev) => prev.next_sibling = next_sibling,
+            None => {
+                if let Some(parent) = parent.and_then(|id| self.node_mut(id)) {
+                    parent.first_child = next_sibling;
+                }
+            }
+        }
+        match next_sibling.and_then(|id| self.node_mut(id)) {
+            Some(next) => next.prev_sibling = prev_sibling,
+            None => {
+                if let Some(parent) = parent.and_then(|id| self.node_mut(id)) {
+                    parent.last_child = prev_sibling;
+                }
+            }
+        }
+        if self.root_id == Some(node_id) {
+            self.root_id = None;
+        }
+
+        let node = self.slab.remove(node_id.index)?;
+        // every descendant is reachable from the first child through first children and next
+        // siblings, so an explicit stack drops them all without recursing
+        let mut stack: Vec<NodeId> = node.first_child.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            if let Some(descendant) = self.slab.remove(id.index) {
+                stack.extend(descendant.next_sibling);
+                stack.extend(descendant.first_child);
+            }
+        }
+
+        Some(node.data)
+    }
+
+    ///
+    /// Moves the data out of the `ArenaTree` into a new `Tree` with the same shape.
+    ///
+    /// The `Node`s get new `NodeId`s in the `Tree`.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use slab_tree::arena::ArenaTree;
+    ///
+    /// let bump = Bump::new();
+    /// let mut arena_tree = ArenaTree::new_in(&bump);
+    /// let root_id = arena_tree.set_root(1);
+    /// arena_tree.append(root_id, 2);
+    /// arena_tree.append(root_id, 3);
+    ///
+    /// let tree = arena_tree.into_tree();
+    /// let children: Vec<i32> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .children()
+    ///     .map(|child| *child.data())
+    ///     .collect();
+    /// assert_eq!(children, vec![2, 3]);
+    /// ```
+    ///
+    pub fn into_tree(mut self) -> Tree<T> {
+        let mut tree = TreeBuilder::new().with_capacity(self.len()).build();
+
+        // each entry pairs a `Node` still in the arena with its new parent in `tree`
+        let mut stack: Vec<(NodeId, Option<NodeId>)> =
+            self.root_id.map(|id| (id, None)).into_iter().collect();
+        while let Some((id, parent_id)) = stack.pop() {
+            let children: Vec<NodeId> = self.children(id).collect();
+            let node = match self.slab.remove(id.index) {
+                Some(node) => node,
+                None => continue,
+            };
+
+            let new_id = match parent_id {
+                Some(parent_id) => match tree.get_mut(parent_id) {
+                    Some(mut parent) => parent.append(node.data).node_id(),
+                    None => continue,
+                },
+                None => tree.set_root(node.data),
+            };
+
+            // pushed in reverse so the first child is appended first
+            stack.extend(
+                children
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, Some(new_id))),
+            );
+        }
+
+        tree
+    }
+
+    fn insert(&mut self, node: ArenaNode<T>) -> NodeId {
+        NodeId {
+            tree_id: self.id,
+            index: self.slab.insert(node),
+        }
+    }
+
+    fn node(&self, node_id: NodeId) -> Option<&ArenaNode<T>> {
+        if node_id.tree_id != self.id {
+            return None;
+        }
+        self.slab.get(node_id.index)
+    }
+
+    fn node_mut(&mut self, node_id: NodeId) -> Option<&mut ArenaNode<T>> {
+        if node_id.tree_id != self.id {
+            return None;
+        }
+        self.slab.get_mut(node_id.index)
+    }
+}
+
+///
+/// An iterator over the `NodeId`s of a `Node`'s children in an `ArenaTree`, created by
+/// `ArenaTree::children`.
+///
+pub struct Children<'a, 'bump, T> {
+    tree: &'a ArenaTree<'bump, T>,
+    next: Option<NodeId>,
+}
+
+impl<'a, 'bump, T> Clone for Children<'a, 'bump, T> {
+    fn clone(&self) -> Self {
+        Children {
+            tree: self.tree,
+            next: self.next,
+        }
+    }
+}
+
+impl<'a, 'bump, T> fmt::Debug for Children<'a, 'bump, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Children")
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+impl<'a, 'bump, T> Iterator for Children<'a, 'bump, T> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.next.take()?;
+        self.next = self.tree.next_sibling(id);
+        Some(id)
+    }
+}
+
+impl<'a, 'bump, T> FusedIterator for Children<'a, 'bump, T> {}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod arena_tests {
+    use super::*;
+    use std::rc::Rc;
+
+    // 0 -> [1 -> [2, 3], 4]
+    fn sample(bump: &Bump) -> ArenaTree<'_, i32> {
+        let mut tree = ArenaTree::new_in(bump);
+        let root_id = tree.set_root(0);
+        let one_id = tree.append(root_id, 1).unwrap();
+        tree.append(one_id, 2);
+        tree.append(one_id, 3);
+        tree.append(root_id, 4);
+        tree
+    }
+
+    fn pre_order<T: Clone>(tree: &ArenaTree<'_, T>) -> Vec<(T, usize)> {
+        let mut out = Vec::new();
+        let mut stack: Vec<(NodeId, usize)> =
+            tree.root_id().map(|id| (id, 0)).into_iter().collect();
+        while let Some((id, depth)) = stack.pop() {
+            out.push((tree.get(id).unwrap().clone(), depth));
+            let children: Vec<NodeId> = tree.children(id).collect();
+            stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+        }
+        out
+    }
+
+    #[test]
+    fn slots_live_in_the_bump() {
+        let bump = Bump::new();
+        let before = bump.allocated_bytes();
+        let tree: ArenaTree<'_, [u64; 8]> = ArenaTree::with_capacity_in(100, &bump);
+
+        assert!(bump.allocated_bytes() - before >= 100 * 64);
+        assert_eq!(tree.capacity(), 100);
+    }
+
+    #[test]
+    fn links() {
+        let bump = Bump::new();
+        let tree = sample(&bump);
+
+        assert_eq!(tree.len(), 5);
+        assert_eq!(
+            pre_order(&tree),
+            vec![(0, 0), (1, 1), (2, 2), (3, 2), (4, 1)]
+        );
+
+        let root_id = tree.root_id().unwrap();
+        let one_id = tree.first_child(root_id).unwrap();
+        let four_id = tree.last_child(root_id).unwrap();
+        assert_eq!(tree.next_sibling(one_id), Some(four_id));
+        assert_eq!(tree.prev_sibling(four_id), Some(one_id));
+        assert_eq!(tree.parent(four_id), Some(root_id));
+        assert_eq!(tree.children(four_id).count(), 0);
+    }
+
+    #[test]
+    fn set_root_shifts_old_root_down() {
+        let bump = Bump::new();
+        let mut tree = sample(&bump);
+        let old_root_id = tree.root_id().unwrap();
+        let root_id = tree.set_root(-1);
+
+        assert_eq!(
+            tree.children(root_id).collect::<Vec<_>>(),
+            vec![old_root_id]
+        );
+        assert_eq!(tree.last_child(root_id), Some(old_root_id));
+        assert_eq!(tree.parent(old_root_id), Some(root_id));
+        assert_eq!(pre_order(&tree)[..2], [(-1, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn remove_drops_the_subtree() {
+        let bump = Bump::new();
+        let data = Rc::new(());
+        let mut tree = ArenaTree::new_in(&bump);
+        let root_id = tree.set_root(data.clone());
+        let first_id = tree.append(root_id, data.clone()).unwrap();
+        let child_id = tree.append(first_id, data.clone()).unwrap();
+        tree.append(child_id, data.clone());
+        let last_id = tree.append(root_id, data.clone()).unwrap();
+        assert_eq!(Rc::strong_count(&data), 6);
+
+        assert!(tree.remove(first_id).is_some());
+        assert_eq!(Rc::strong_count(&data), 3);
+        assert_eq!(tree.len(), 2);
+        assert!(tree.get(child_id).is_none());
+        assert_eq!(tree.first_child(root_id), Some(last_id));
+        assert_eq!(tree.prev_sibling(last_id), None);
+
+        // freed slots are reused under a new generation
+        let new_id = tree.append(root_id, data.clone()).unwrap();
+        assert_ne!(new_id, first_id);
+        assert!(tree.get(first_id).is_none());
+
+        assert!(tree.remove(root_id).is_some());
+        assert!(tree.is_empty());
+        assert_eq!(tree.root_id(), None);
+        assert_eq!(Rc::strong_count(&data), 1);
+    }
+
+    #[test]
+    fn dropping_the_tree_drops_its_data() {
+        let bump = Bump::new();
+        let data = Rc::new(());
+        let mut tree = ArenaTree::new_in(&bump);
+        let root_id = tree.set_root(data.clone());
+        for _ in 0..10 {
+            tree.append(root_id, data.clone());
+        }
+        assert_eq!(Rc::strong_count(&data), 12);
+
+        drop(tree);
+        assert_eq!(Rc::strong_count(&data), 1);
+    }
+
+    #[test]
+    fn ids_from_other_trees_are_rejected() {
+        let bump = Bump::new();
+        let mut first = sample(&bump);
+        let mut second = sample(&bump);
+        let second_root_id = second.root_id().unwrap();
+
+        assert!(first.get(second_root_id).is_none());
+        assert!(first.append(second_root_id, 5).is_none());
+        assert!(first.remove(second_root_id).is_none());
+        assert_eq!(first.len(), 5);
+        assert!(second.get_mut(second_root_id).is_some());
+    }
+
+    #[test]
+    fn into_tree_keeps_the_shape() {
+        let bump = Bump::new();
+        let tree = sample(&bump);
+        let expected = pre_order(&tree);
+
+        let tree = tree.into_tree();
+        let actual: Vec<(i32, usize)> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (*node.data(), node.ancestors().count()))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn deep_trees() {
+        let bump = Bump::new();
+        let mut tree = ArenaTree::new_in(&bump);
+        let mut node_id = tree.set_root(0);
+        for i in 1..100_000 {
+            node_id = tree.append(node_id, i).unwrap();
+        }
+
+        let tree = tree.into_tree();
+        assert_eq!(tree.root().unwrap().traverse_pre_order().count(), 100_000);
+
+        let mut tree = ArenaTree::new_in(&bump);
+        let mut node_id = tree.set_root(0);
+        for i in 1..100_000 {
+            node_id = tree.append(node_id, i).unwrap();
+        }
+        let root_id = tree.root_id().unwrap();
+        assert_eq!(tree.remove(root_id), Some(0));
+        assert!(tree.is_empty());
+    }
+}
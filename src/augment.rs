@@ -0,0 +1,364 @@
+//!
+//! Aggregates of each `Node`'s sub-tree (such as its size, the sum of its data, or a bounding
+//! box) which the `Tree` keeps up to date as it changes.
+//!
+//! An aggregate is set up with `Tree::with_aggregate`, from a function combining a `Node`'s data
+//! with the aggregates of its children.  Whenever a `Node` is inserted, removed, moved, or has its
+//! data replaced, only the aggregates along the path from the change up to the root are
+//! recomputed.
+//!
+
+use crate::observer::TreeIndex;
+use crate::observer::TreeObserver;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::FusedIterator;
+
+type CombineFn<T, A> = Box<dyn Fn(&T, ChildAggregates<'_, T, A>) -> A + Send + Sync>;
+
+///
+/// An iterator over the aggregates of a `Node`'s children, in order, handed to the function
+/// passed to `Tree::with_aggregate`.
+///
+pub struct ChildAggregates<'a, T, A> {
+    tree: &'a Tree<T>,
+    aggregates: &'a HashMap<NodeId, A>,
+    next: Option<NodeId>,
+    // a child which is about to be removed, and so no longer counts
+    skip: Option<NodeId>,
+}
+
+impl<'a, T, A> Iterator for ChildAggregates<'a, T, A> {
+    type Item = &'a A;
+
+    fn next(&mut self) -> Option<&'a A> {
+        loop {
+            let node_id = self.next?;
+            self.next = self.tree.get_node_relatives(node_id).next_sibling;
+            if Some(node_id) == self.skip {
+                continue;
+            }
+            if let Some(aggregate) = self.aggregates.get(&node_id) {
+                return Some(aggregate);
+            }
+        }
+    }
+}
+
+impl<'a, T, A> FusedIterator for ChildAggregates<'a, T, A> {}
+
+impl<'a, T, A> fmt::Debug for ChildAggregates<'a, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChildAggregates")
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+/// Keeps the aggregate of every `Node` in a `Tree` (including orphans).
+struct AggregateIndex<T, A> {
+    combine: CombineFn<T, A>,
+    aggregates: HashMap<NodeId, A>,
+    // the parent each node had when it was last seen, to find the old path after a move
+    parents: HashMap<NodeId, Option<NodeId>>,
+}
+
+impl<T, A> AggregateIndex<T, A> {
+    fn compute(&self, tree: &Tree<T>, node_id: NodeId, skip: Option<NodeId>) -> Option<A> {
+        let node = tree.get_node(node_id)?;
+        let children = ChildAggregates {
+            tree,
+            aggregates: &self.aggregates,
+            next: node.relatives.first_child,
+            skip,
+        };
+        Some((self.combine)(&node.data, children))
+    }
+
+    /// Recomputes the aggregates from `start` up to the root, leaving out `skip` (a child of
+    /// `start`).  Stops at `Node`s which aren't tracked, such as ones being removed.
+    fn update_path(&mut self, tree: &Tree<T>, start: Option<NodeId>, skip: Option<NodeId>) {
+        let mut current = start;
+        let mut skip = skip;
+        while let Some(node_id) = current {
+            if !self.aggregates.contains_key(&node_id) {
+                return;
+            }
+            if let Some(aggregate) = self.compute(tree, node_id, skip.take()) {
+                self.aggregates.insert(node_id, aggregate);
+            }
+            current = tree.get_node_relatives(node_id).parent;
+        }
+    }
+}
+
+impl<T, A> TreeObserver<T> for AggregateIndex<T, A> {
+    fn on_insert(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        if let Some(aggregate) = self.compute(tree, node_id, None) {
+            self.aggregates.insert(node_id, aggregate);
+        }
+        let parent = tree.get_node_relatives(node_id).parent;
+        self.parents.insert(node_id, parent);
+        self.update_path(tree, parent, None);
+    }
+
+    fn on_remove(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        // descendants removed along with a node are reported after it, by which time their
+        // parent is no longer tracked and there is nothing left to update
+        self.aggregates.remove(&node_id);
+        self.parents.remove(&node_id);
+        let parent = tree.get_node_relatives(node_id).parent;
+        self.update_path(tree, parent, Some(node_id));
+    }
+
+    fn on_move(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        let parent = tree.get_node_relatives(node_id).parent;
+        let old_parent = self.parents.insert(node_id, parent).flatten();
+        if old_parent != parent {
+            self.update_path(tree, old_parent, None);
+        }
+        self.update_path(tree, parent, None);
+    }
+
+    fn on_data_replaced(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        self.update_path(tree, Some(node_id), None);
+    }
+}
+
+impl<T, A> TreeIndex<T> for AggregateIndex<T, A>
+where
+    T: 'static,
+    A: Send + Sync + 'static,
+{
+    fn rebuild(&mut self, tree: &Tree<T>) {
+        self.aggregates.clear();
+        self.parents.clear();
+
+        // every node's children come before it in post-order, starting from each parentless node
+        let node_ids = tree.all_node_ids();
+        for &top_id in node_ids.iter() {
+            if tree.get_node_relatives(top_id).parent.is_some() {
+                continue;
+            }
+            let top = tree.get(top_id).expect("node must exist");
+            for node in top.traverse_post_order() {
+                let aggregate = self
+                    .compute(tree, node.node_id(), None)
+                    .expect("node must exist");
+                self.aggregates.insert(node.node_id(), aggregate);
+            }
+        }
+        for node_id in node_ids {
+            self.parents
+                .insert(node_id, tree.get_node_relatives(node_id).parent);
+        }
+    }
+
+    fn remap(&mut self, node_ids: &HashMap<NodeId, NodeId>) {
+        let remap = |node_id: NodeId| node_ids.get(&node_id).copied().unwrap_or(node_id);
+        self.aggregates = self
+            .aggregates
+            .drain()
+            .map(|(node_id, aggregate)| (remap(node_id), aggregate))
+            .collect();
+        self.parents = self
+            .parents
+            .drain()
+            .map(|(node_id, parent)| (remap(node_id), parent.map(remap)))
+            .collect();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T: 'static> Tree<T> {
+    ///
+    /// Makes the `Tree` keep an aggregate of type `A` for every `Node`, computed by `combine`
+    /// from the `Node`'s data and the aggregates of its children, for reading with `aggregate`.
+    ///
+    /// Aggregates are recomputed along the path to the root whenever a `Node` is inserted,
+    /// removed, moved, or has its data replaced with `NodeMut::replace_data`; changes made to
+    /// the data through `NodeMut::data` aren't picked up.  Setting up a second aggregate of the
+    /// same type replaces the first.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// // the size of each sub-tree
+    /// let mut tree = TreeBuilder::new()
+    ///     .with_root("root")
+    ///     .build()
+    ///     .with_aggregate(|_, children| 1 + children.sum::<usize>());
+    ///
+    /// let mut root = tree.root_mut().unwrap();
+    /// let a_id = root.append("a").node_id();
+    /// root.append("b").append("c");
+    ///
+    /// assert_eq!(tree.aggregate::<usize>(tree.root_id().unwrap()), Some(&4));
+    /// assert_eq!(tree.aggregate::<usize>(a_id), Some(&1));
+    /// ```
+    ///
+    pub fn with_aggregate<A, F>(mut self, combine: F) -> Tree<T>
+    where
+        A: Send + Sync + 'static,
+        F: Fn(&T, ChildAggregates<'_, T, A>) -> A + Send + Sync + 'static,
+    {
+        let mut index = AggregateIndex {
+            combine: Box::new(combine),
+            aggregates: HashMap::new(),
+            parents: HashMap::new(),
+        };
+        index.rebuild(&self);
+
+        let existing = self
+            .hooks
+            .indexes_mut()
+            .find_map(|existing| existing.as_any_mut().downcast_mut::<AggregateIndex<T, A>>());
+        match existing {
+            Some(existing) => *existing = index,
+            None => self.hooks.add_index(Box::new(index)),
+        }
+        self
+    }
+
+    ///
+    /// Returns the aggregate of type `A` of the `Node` with the given `NodeId`, as set up with
+    /// `with_aggregate`.  Returns `None` if the `Node` doesn't exist, or if no aggregate of this
+    /// type has been set up.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// // the sum of each sub-tree
+    /// let mut tree = TreeBuilder::new()
+    ///     .with_root(1)
+    ///     .build()
+    ///     .with_aggregate(|&n: &i64, children| n + children.sum::<i64>());
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// let mut root = tree.root_mut().unwrap();
+    /// let ten_id = root.append(10).node_id();
+    /// root.append(100).append(1000);
+    /// assert_eq!(tree.aggregate::<i64>(root_id), Some(&1111));
+    ///
+    /// tree.get_mut(ten_id).unwrap().replace_data(20);
+    /// assert_eq!(tree.aggregate::<i64>(root_id), Some(&1121));
+    ///
+    /// tree.remove(ten_id, RemoveBehavior::DropChildren);
+    /// assert_eq!(tree.aggregate::<i64>(root_id), Some(&1101));
+    /// assert_eq!(tree.aggregate::<u8>(root_id), None);
+    /// ```
+    ///
+    pub fn aggregate<A>(&self, node_id: NodeId) -> Option<&A>
+    where
+        A: Send + Sync + 'static,
+    {
+        self.hooks
+            .indexes()
+            .find_map(|index| index.as_any().downcast_ref::<AggregateIndex<T, A>>())?
+            .aggregates
+            .get(&node_id)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod augment_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::reparent::InsertPosition;
+
+    // the data of each sub-tree in pre-order, which any order-sensitive mistake would show up in
+    fn sample() -> Tree<i32> {
+        Tree::unfold(3, |n| (n, (0..n).collect()))
+            .with_aggregate(|&n: &i32, children| -> Vec<i32> {
+                let mut all = vec![n];
+                children.for_each(|child: &Vec<i32>| all.extend(child));
+                all
+            })
+            .with_aggregate(|_, children| 1 + children.sum::<usize>())
+    }
+
+    fn check(tree: &Tree<i32>) {
+        for node_id in tree.all_node_ids() {
+            let node = tree.get(node_id).unwrap();
+            let expected: Vec<i32> = node.traverse_pre_order().map(|n| *n.data()).collect();
+            assert_eq!(tree.aggregate::<Vec<i32>>(node_id), Some(&expected));
+            assert_eq!(tree.aggregate::<usize>(node_id), Some(&expected.len()));
+        }
+    }
+
+    #[test]
+    fn inserts_and_removals() {
+        let mut tree = sample();
+        check(&tree);
+
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root().unwrap().last_child().unwrap().node_id();
+        tree.get_mut(two_id).unwrap().prepend(7);
+        tree.get_mut(two_id).unwrap().append(8);
+        check(&tree);
+
+        let one_id = tree
+            .get(two_id)
+            .unwrap()
+            .children()
+            .nth(2)
+            .unwrap()
+            .node_id();
+        tree.remove(one_id, OrphanChildren);
+        check(&tree);
+        tree.remove(two_id, DropChildren);
+        check(&tree);
+
+        tree.set_root(9);
+        check(&tree);
+        assert_eq!(tree.aggregate::<usize>(root_id), Some(&4));
+        assert_eq!(tree.aggregate::<usize>(two_id), None);
+    }
+
+    #[test]
+    fn moves_and_replacements() {
+        let mut tree = sample();
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root().unwrap().last_child().unwrap().node_id();
+        let zero_id = tree.root().unwrap().first_child().unwrap().node_id();
+
+        tree.move_subtree(two_id, zero_id, InsertPosition::First)
+            .unwrap();
+        check(&tree);
+        tree.get_mut(two_id).unwrap().replace_data(20);
+        check(&tree);
+        tree.get_mut(zero_id).unwrap().make_last_sibling();
+        check(&tree);
+        tree.move_subtree(two_id, root_id, InsertPosition::At(0))
+            .unwrap();
+        check(&tree);
+    }
+
+    #[test]
+    fn wholesale_changes() {
+        let mut tree = sample();
+        let two_id = tree.root().unwrap().last_child().unwrap().node_id();
+        tree.remove(two_id, OrphanChildren);
+        tree.compact();
+        check(&tree);
+
+        let mut tree = sample();
+        let two_id = tree.root().unwrap().last_child().unwrap().node_id();
+        let snapshot = tree.snapshot();
+        tree.remove(two_id, DropChildren);
+        assert!(tree.restore(snapshot));
+        check(&tree);
+    }
+}
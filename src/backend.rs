@@ -0,0 +1,373 @@
+//!
+//! Traits describing what can be read from and written to a tree, so that algorithms can be
+//! written once and run against `Tree`s, other tree types, or mocks in tests.
+//!
+//! `TreeRead` covers looking up data and following the links between `Node`s; `TreeWrite` adds
+//! inserting and removing `Node`s.  Both are implemented by `Tree<T>`, and each names its own
+//! `Id` type so that other implementations aren't tied to this crate's `NodeId`.
+//!
+//! ```
+//! use slab_tree::backend::TreeRead;
+//! use slab_tree::tree::TreeBuilder;
+//!
+//! // counts the leaves below `node` in any tree
+//! fn leaves<T, R: TreeRead<T>>(tree: &R, node: R::Id) -> usize {
+//!     let mut stack = vec![node];
+//!     let mut leaves = 0;
+//!     while let Some(node) = stack.pop() {
+//!         let before = stack.len();
+//!         stack.extend(tree.children(node));
+//!         if stack.len() == before {
+//!             leaves += 1;
+//!         }
+//!     }
+//!     leaves
+//! }
+//!
+//! let mut tree = TreeBuilder::new().with_root(0).build();
+//! let mut root = tree.root_mut().unwrap();
+//! root.append(1).append(2);
+//! root.append(3);
+//!
+//! assert_eq!(leaves(&tree, tree.root_id().unwrap()), 2);
+//! ```
+//!
+
+use crate::behaviors::RemoveBehavior;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+
+///
+/// Read access to a tree: its root, the data of each `Node`, and the links between them.
+///
+/// Every method taking an `Id` returns `None` if the `Id` doesn't refer to a `Node` of the tree.
+///
+pub trait TreeRead<T> {
+    ///
+    /// The type identifying `Node`s of the tree.
+    ///
+    type Id: Copy + Eq;
+
+    ///
+    /// Returns the `Id` of the root, or `None` if the tree is empty.
+    ///
+    fn root_id(&self) -> Option<Self::Id>;
+
+    ///
+    /// Returns a reference to the data of `node`.
+    ///
+    fn data(&self, node: Self::Id) -> Option<&T>;
+
+    ///
+    /// Returns the `Id` of the parent of `node`.
+    ///
+    fn parent(&self, node: Self::Id) -> Option<Self::Id>;
+
+    ///
+    /// Returns the `Id` of the sibling before `node`.
+    ///
+    fn prev_sibling(&self, node: Self::Id) -> Option<Self::Id>;
+
+    ///
+    /// Returns the `Id` of the sibling after `node`.
+    ///
+    fn next_sibling(&self, node: Self::Id) -> Option<Self::Id>;
+
+    ///
+    /// Returns the `Id` of the first child of `node`.
+    ///
+    fn first_child(&self, node: Self::Id) -> Option<Self::Id>;
+
+    ///
+    /// Returns the `Id` of the last child of `node`.
+    ///
+    fn last_child(&self, node: Self::Id) -> Option<Self::Id>;
+
+    ///
+    /// Returns an iterator over the `Id`s of the children of `node`, in order.  The iterator is
+    /// empty if `node` has no children or isn't a `Node` of the tree.
+    ///
+    /// ```
+    /// use slab_tree::backend::TreeRead;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append(2).node_id();
+    /// let three_id = root.append(3).node_id();
+    ///
+    /// let root_id = tree.root_id().unwrap();
+    /// let children: Vec<_> = TreeRead::children(&tree, root_id).collect();
+    /// assert_eq!(children, vec![two_id, three_id]);
+    /// ```
+    ///
+    fn children(&self, node: Self::Id) -> ChildIds<'_, T, Self>
+    where
+        Self: Sized,
+    {
+        ChildIds {
+            tree: self,
+            next: self.first_child(node),
+            data: PhantomData,
+        }
+    }
+}
+
+///
+/// Write access to a tree, on top of `TreeRead`.
+///
+pub trait TreeWrite<T>: TreeRead<T> {
+    ///
+    /// Returns a mutable reference to the data of `node`.
+    ///
+    fn data_mut(&mut self, node: Self::Id) -> Option<&mut T>;
+
+    ///
+    /// Makes a new `Node` holding `data` the root of the tree, with any old root as its only
+    /// child, and returns its `Id`.
+    ///
+    fn set_root(&mut self, data: T) -> Self::Id;
+
+    ///
+    /// Appends a new `Node` holding `data` as the last child of `parent` and returns its `Id`.
+    /// Returns `None` (and drops `data`) if `parent` isn't a `Node` of the tree.
+    ///
+    fn append(&mut self, parent: Self::Id, data: T) -> Option<Self::Id>;
+
+    ///
+    /// Removes `node` from the tree, handling its children according to `behavior`, and
+    /// returns its data.
+    ///
+    fn remove(&mut self, node: Self::Id, behavior: RemoveBehavior) -> Option<T>;
+}
+
+///
+/// An iterator over the `Id`s of the children of a `Node`, returned by `TreeRead::children`.
+///
+pub struct ChildIds<'a, T, R: TreeRead<T>> {
+    tree: &'a R,
+    next: Option<R::Id>,
+    data: PhantomData<fn() -> T>,
+}
+
+impl<'a, T, R: TreeRead<T>> Iterator for ChildIds<'a, T, R> {
+    type Item = R::Id;
+
+    fn next(&mut self) -> Option<R::Id> {
+        let node = self.next.take()?;
+        self.next = self.tree.next_sibling(node);
+        Some(node)
+    }
+}
+
+impl<'a, T, R: TreeRead<T>> FusedIterator for ChildIds<'a, T, R> {}
+
+impl<'a, T, R: TreeRead<T>> Clone for ChildIds<'a, T, R> {
+    fn clone(&self) -> Self {
+        ChildIds {
+            tree: self.tree,
+            next: self.next,
+            data: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, R: TreeRead<T>> fmt::Debug for ChildIds<'a, T, R>
+where
+    R::Id: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChildIds")
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+impl<T> TreeRead<T> for Tree<T> {
+    type Id = NodeId;
+
+    fn root_id(&self) -> Option<NodeId> {
+        self.root_id
+    }
+
+    fn data(&self, node: NodeId) -> Option<&T> {
+        self.get_node(node).map(|node| &node.data)
+    }
+
+    fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.get_node(node)?.relatives.parent
+    }
+
+    fn prev_sibling(&self, node: NodeId) -> Option<NodeId> {
+        self.get_node(node)?.relatives.prev_sibling
+    }
+
+    fn next_sibling(&self, node: NodeId) -> Option<NodeId> {
+        self.get_node(node)?.relatives.next_sibling
+    }
+
+    fn first_child(&self, node: NodeId) -> Option<NodeId> {
+        self.get_node(node)?.relatives.first_child
+    }
+
+    fn last_child(&self, node: NodeId) -> Option<NodeId> {
+        self.get_node(node)?.relatives.last_child
+    }
+}
+
+impl<T> TreeWrite<T> for Tree<T> {
+    fn data_mut(&mut self, node: NodeId) -> Option<&mut T> {
+        self.get_node_mut(node).map(|node| &mut node.data)
+    }
+
+    fn set_root(&mut self, data: T) -> NodeId {
+        Tree::set_root(self, data)
+    }
+
+    fn append(&mut self, parent: NodeId, data: T) -> Option<NodeId> {
+        Some(self.get_mut(parent)?.append(data).node_id())
+    }
+
+    fn remove(&mut self, node: NodeId, behavior: RemoveBehavior) -> Option<T> {
+        Tree::remove(self, node, behavior)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod backend_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    // a minimal tree kept as parent links, to check that algorithms written against the traits
+    // don't depend on anything `Tree`-specific
+    struct ParentList {
+        nodes: Vec<Option<(i32, Option<usize>)>>,
+    }
+
+    impl TreeRead<i32> for ParentList {
+        type Id = usize;
+
+        fn root_id(&self) -> Option<usize> {
+            self.nodes
+                .iter()
+                .position(|node| matches!(node, Some((_, None))))
+        }
+
+        fn data(&self, node: usize) -> Option<&i32> {
+            self.nodes.get(node)?.as_ref().map(|(data, _)| data)
+        }
+
+        fn parent(&self, node: usize) -> Option<usize> {
+            self.nodes.get(node)?.as_ref()?.1
+        }
+
+        fn prev_sibling(&self, node: usize) -> Option<usize> {
+            let parent = self.parent(node)?;
+            self.siblings(parent).take_while(|&n| n != node).last()
+        }
+
+        fn next_sibling(&self, node: usize) -> Option<usize> {
+            let parent = self.parent(node)?;
+            self.siblings(parent).skip_while(|&n| n != node).nth(1)
+        }
+
+        fn first_child(&self, node: usize) -> Option<usize> {
+            self.siblings(node).next()
+        }
+
+        fn last_child(&self, node: usize) -> Option<usize> {
+            self.siblings(node).last()
+        }
+    }
+
+    impl ParentList {
+        fn siblings(&self, parent: usize) -> impl Iterator<Item = usize> + '_ {
+            self.nodes
+                .iter()
+                .enumerate()
+                .filter(move |(_, node)| matches!(node, Some((_, Some(p)))  if *p == parent))
+                .map(|(index, _)| index)
+        }
+    }
+
+    impl TreeWrite<i32> for ParentList {
+        fn data_mut(&mut self, node: usize) -> Option<&mut i32> {
+            self.nodes.get_mut(node)?.as_mut().map(|(data, _)| data)
+        }
+
+        fn set_root(&mut self, data: i32) -> usize {
+            let new_root = self.nodes.len();
+            if let Some(old_root) = self.root_id() {
+                self.nodes[old_root].as_mut().unwrap().1 = Some(new_root);
+            }
+            self.nodes.push(Some((data, None)));
+            new_root
+        }
+
+        fn append(&mut self, parent: usize, data: i32) -> Option<usize> {
+            self.data(parent)?;
+            self.nodes.push(Some((data, Some(parent))));
+            Some(self.nodes.len() - 1)
+        }
+
+        fn remove(&mut self, node: usize, _behavior: RemoveBehavior) -> Option<i32> {
+            self.nodes.get_mut(node)?.take().map(|(data, _)| data)
+        }
+    }
+
+    fn build<W: TreeWrite<i32>>(tree: &mut W) {
+        let root = tree.set_root(1);
+        let two = tree.append(root, 2).unwrap();
+        tree.append(two, 4).unwrap();
+        tree.append(root, 3).unwrap();
+        *tree.data_mut(two).unwrap() *= 10;
+    }
+
+    fn pre_order<T: Copy, R: TreeRead<T>>(tree: &R) -> Vec<T> {
+        let mut out = Vec::new();
+        let mut stack: Vec<R::Id> = tree.root_id().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            out.push(*tree.data(node).unwrap());
+            let children: Vec<R::Id> = tree.children(node).collect();
+            stack.extend(children.into_iter().rev());
+        }
+        out
+    }
+
+    #[test]
+    fn same_results_on_both_backends() {
+        let mut tree = TreeBuilder::new().build();
+        build(&mut tree);
+        let mut list = ParentList { nodes: Vec::new() };
+        build(&mut list);
+
+        assert_eq!(pre_order(&tree), vec![1, 20, 4, 3]);
+        assert_eq!(pre_order(&list), vec![1, 20, 4, 3]);
+    }
+
+    #[test]
+    fn tree_relatives() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let two_id = TreeWrite::append(&mut tree, root_id, 2).unwrap();
+        let three_id = TreeWrite::append(&mut tree, root_id, 3).unwrap();
+
+        assert_eq!(TreeRead::parent(&tree, two_id), Some(root_id));
+        assert_eq!(TreeRead::next_sibling(&tree, two_id), Some(three_id));
+        assert_eq!(TreeRead::prev_sibling(&tree, three_id), Some(two_id));
+        assert_eq!(TreeRead::first_child(&tree, root_id), Some(two_id));
+        assert_eq!(TreeRead::last_child(&tree, root_id), Some(three_id));
+
+        assert_eq!(
+            TreeWrite::remove(&mut tree, two_id, RemoveBehavior::DropChildren),
+            Some(2)
+        );
+        assert_eq!(TreeRead::data(&tree, two_id), None);
+        assert_eq!(TreeWrite::append(&mut tree, two_id, 4), None);
+        assert_eq!(TreeRead::children(&tree, two_id).count(), 0);
+    }
+}
@@ -20,3 +20,28 @@ pub enum RemoveBehavior {
     ///
     OrphanChildren,
 }
+
+///
+/// Describes what happens to the children of each Node removed by `Tree::retain_with`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RetainBehavior {
+    ///
+    /// Children of a removed Node are dropped along with it, as with
+    /// `RemoveBehavior::DropChildren`.
+    ///
+    DropChildren,
+
+    ///
+    /// Children of a removed Node are left in the Tree, disconnected from it, as with
+    /// `RemoveBehavior::OrphanChildren`.  They aren't checked against the predicate.
+    ///
+    OrphanChildren,
+
+    ///
+    /// Children of a removed Node take its place among its siblings, in order, and are then
+    /// checked against the predicate themselves.  Children of a removed root have nowhere to go
+    /// and are orphaned instead.
+    ///
+    LiftChildren,
+}
@@ -0,0 +1,372 @@
+//!
+//! A compact binary encoding for `Tree`s.
+//!
+//! The encoding starts with a 4 byte magic number (`SLBT`) and a 1 byte format version, followed
+//! by the number of `Node`s and then each `Node` in pre-order.  Each `Node` is written as its
+//! number of children, the length of its encoded data and finally the encoded data itself.  All
+//! counts and lengths are written as LEB128 variable-length integers.
+//!
+//! `NodeId`s are not written; decoding a `Tree` produces fresh ones.
+//!
+
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+const MAGIC: &[u8; 4] = b"SLBT";
+const VERSION: u8 = 1;
+
+///
+/// Describes the ways in which decoding a `Tree` with `Tree::from_bytes` can fail.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryError {
+    ///
+    /// The input does not start with the expected magic number.
+    ///
+    BadMagic,
+
+    ///
+    /// The input was written with a format version this crate doesn't understand.
+    ///
+    UnsupportedVersion(u8),
+
+    ///
+    /// The input ended in the middle of the encoded `Tree`.
+    ///
+    UnexpectedEof,
+
+    ///
+    /// A length or count in the input doesn't fit in a `usize`.
+    ///
+    Overflow,
+
+    ///
+    /// The child counts in the input don't describe a single `Tree` with the given number of
+    /// `Node`s.
+    ///
+    InvalidStructure,
+
+    ///
+    /// The user-provided decoder rejected the data of the `Node` at the given pre-order position.
+    ///
+    InvalidData(usize),
+
+    ///
+    /// There were bytes left over after the encoded `Tree`.
+    ///
+    TrailingBytes,
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryError::BadMagic => write!(f, "input is not an encoded tree"),
+            BinaryError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {}", version)
+            }
+            BinaryError::UnexpectedEof => write!(f, "unexpected end of input"),
+            BinaryError::Overflow => write!(f, "length or count is too large"),
+            BinaryError::InvalidStructure => write!(f, "child counts do not describe a tree"),
+            BinaryError::InvalidData(position) => {
+                write!(f, "invalid data for node at position {}", position)
+            }
+            BinaryError::TrailingBytes => write!(f, "trailing bytes after the encoded tree"),
+        }
+    }
+}
+
+impl Error for BinaryError {}
+
+impl<T> Tree<T> {
+    ///
+    /// Encodes the `Tree` into the binary format described in the `binary` module, using
+    /// `encode` to write the data of each `Node` into the provided buffer.
+    ///
+    /// ```
+    /// use slab_tree::tree::{Tree, TreeBuilder};
+    /// use std::convert::TryInto;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u32).build();
+    /// tree.root_mut().unwrap().append(2);
+    ///
+    /// let bytes = tree.to_bytes(|data, buf| buf.extend_from_slice(&data.to_le_bytes()));
+    /// let decoded = Tree::from_bytes(&bytes, |buf| Some(u32::from_le_bytes(buf.try_into().ok()?)));
+    ///
+    /// let root = decoded.unwrap().root().map(|root| *root.data());
+    /// assert_eq!(root, Some(1));
+    /// ```
+    ///
+    pub fn to_bytes<F>(&self, mut encode: F) -> Vec<u8>
+    where
+        F: FnMut(&T, &mut Vec<u8>),
+    {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+
+        let root = match self.root() {
+            Some(root) => root,
+            None => {
+                write_varint(&mut bytes, 0);
+                return bytes;
+            }
+        };
+
+        let mut body = Vec::new();
+        let mut data = Vec::new();
+        let mut node_count = 0;
+
+        for node in root.traverse_pre_order() {
+            data.clear();
+            encode(node.data(), &mut data);
+
+            write_varint(&mut body, node.child_count() as u64);
+            write_varint(&mut body, data.len() as u64);
+            body.extend_from_slice(&data);
+            node_count += 1;
+        }
+
+        write_varint(&mut bytes, node_count);
+        bytes.extend_from_slice(&body);
+        bytes
+    }
+
+    ///
+    /// Decodes a `Tree` written by `Tree::to_bytes`, using `decode` to read the data of each
+    /// `Node`.  If `decode` returns `None`, decoding stops with `BinaryError::InvalidData`.
+    ///
+    /// ```
+    /// use slab_tree::binary::BinaryError;
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let result = Tree::from_bytes(b"not a tree", |buf| Some(buf.to_vec()));
+    /// assert_eq!(result.unwrap_err(), BinaryError::BadMagic);
+    /// ```
+    ///
+    pub fn from_bytes<F>(bytes: &[u8], mut decode: F) -> Result<Tree<T>, BinaryError>
+    where
+        F: FnMut(&[u8]) -> Option<T>,
+    {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(BinaryError::BadMagic);
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(BinaryError::UnsupportedVersion(version));
+        }
+
+        let node_count = reader.read_usize()?;
+        // every node takes at least two bytes, so don't trust counts larger than that
+        let capacity = node_count.min(reader.bytes.len() / 2);
+        let mut tree = TreeBuilder::new().with_capacity(capacity).build();
+
+        // nodes which are still waiting for children, along with how many they're waiting for
+        let mut open: Vec<(NodeId, usize)> = Vec::new();
+
+        for position in 0..node_count {
+            let child_count = reader.read_usize()?;
+            let len = reader.read_usize()?;
+            let data = decode(reader.take(len)?).ok_or(BinaryError::InvalidData(position))?;
+
+            let node_id = if position == 0 {
+                tree.set_root(data)
+            } else {
+                let (parent_id, remaining) =
+                    open.last_mut().ok_or(BinaryError::InvalidStructure)?;
+                let parent_id = *parent_id;
+                *remaining -= 1;
+                if *remaining == 0 {
+                    open.pop();
+                }
+                tree.get_mut(parent_id)
+                    .expect("open node must exist")
+                    .append(data)
+                    .node_id()
+            };
+
+            if child_count > 0 {
+                open.push((node_id, child_count));
+            }
+        }
+
+        if !open.is_empty() {
+            return Err(BinaryError::InvalidStructure);
+        }
+        if !reader.bytes.is_empty() {
+            return Err(BinaryError::TrailingBytes);
+        }
+
+        Ok(tree)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BinaryError> {
+        if self.bytes.len() < len {
+            return Err(BinaryError::UnexpectedEof);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn read_usize(&mut self) -> Result<usize, BinaryError> {
+        let mut value: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.take(1)?[0];
+            if shift >= 64 || (shift == 63 && byte > 1) {
+                return Err(BinaryError::Overflow);
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        usize::try_from(value).map_err(|_| BinaryError::Overflow)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod binary_tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    fn encode(data: &i32, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&data.to_le_bytes());
+    }
+
+    fn decode(buf: &[u8]) -> Option<i32> {
+        Some(i32::from_le_bytes(buf.try_into().ok()?))
+    }
+
+    fn pre_order(tree: &Tree<i32>) -> Vec<(i32, usize)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| (*node.data(), node.ancestors().count()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn sample() -> Tree<i32> {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        one.append(2);
+        one.append(3).append(4);
+        root.append(5);
+        root.append(6).append(7);
+        tree
+    }
+
+    #[test]
+    fn round_trip() {
+        let tree = sample();
+        let bytes = tree.to_bytes(encode);
+        let decoded = Tree::from_bytes(&bytes, decode).unwrap();
+        assert_eq!(pre_order(&decoded), pre_order(&tree));
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        let tree: Tree<i32> = Tree::new();
+        let bytes = tree.to_bytes(encode);
+        assert_eq!(bytes, b"SLBT\x01\x00");
+
+        let decoded = Tree::from_bytes(&bytes, decode).unwrap();
+        assert!(decoded.root().is_none());
+    }
+
+    #[test]
+    fn layout() {
+        let mut tree = TreeBuilder::new().with_root(1u8).build();
+        tree.root_mut().unwrap().append(2);
+
+        let bytes = tree.to_bytes(|data, buf| buf.push(*data));
+        assert_eq!(bytes, b"SLBT\x01\x02\x01\x01\x01\x00\x01\x02");
+    }
+
+    #[test]
+    fn varints() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, value);
+            let mut reader = Reader { bytes: &bytes };
+            if let Ok(read) = reader.read_usize() {
+                assert_eq!(read as u64, value);
+            } else {
+                assert!(value > usize::MAX as u64);
+            }
+            assert!(reader.bytes.is_empty());
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let bytes = sample().to_bytes(encode);
+
+        assert_eq!(
+            Tree::from_bytes(b"SLBX\x01\x00", decode).unwrap_err(),
+            BinaryError::BadMagic
+        );
+        assert_eq!(
+            Tree::from_bytes(b"SLBT\x02\x00", decode).unwrap_err(),
+            BinaryError::UnsupportedVersion(2)
+        );
+        assert_eq!(
+            Tree::from_bytes(&bytes[..bytes.len() - 1], decode).unwrap_err(),
+            BinaryError::UnexpectedEof
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Tree::from_bytes(&trailing, decode).unwrap_err(),
+            BinaryError::TrailingBytes
+        );
+
+        assert_eq!(
+            Tree::from_bytes(&bytes, |_| None::<i32>).unwrap_err(),
+            BinaryError::InvalidData(0)
+        );
+    }
+
+    #[test]
+    fn invalid_structure() {
+        // a root which claims two children, followed by only one
+        let missing_child = b"SLBT\x01\x02\x02\x00\x00\x00";
+        assert_eq!(
+            Tree::from_bytes(missing_child, |_| Some(())).unwrap_err(),
+            BinaryError::InvalidStructure
+        );
+
+        // two nodes without any children between them
+        let two_roots = b"SLBT\x01\x02\x00\x00\x00\x00";
+        assert_eq!(
+            Tree::from_bytes(two_roots, |_| Some(())).unwrap_err(),
+            BinaryError::InvalidStructure
+        );
+    }
+}
@@ -0,0 +1,548 @@
+//!
+//! A C-compatible interface for driving a `Tree` from C, C++, or any language with a C FFI
+//! (such as Python through `ctypes` or `cffi`).
+//!
+//! Trees are created with `slab_tree_new` and handed out as opaque `CTree` pointers, which must
+//! be released with `slab_tree_free`.  Each `Node` holds a `uint64_t` of data, which is enough
+//! for a number, a key into a table kept by the caller, or a pointer.  `Node`s are identified
+//! by `CNodeId`s, plain pairs of integers built from `NodeId::to_raw`.
+//!
+//! Every function taking a `CTree` pointer returns `false` (or 0) when given a null pointer, a
+//! `CNodeId` that doesn't refer to a `Node` of the `Tree`, or a null output pointer, instead of
+//! crashing.  Pointers which aren't null must be valid and, for `CTree`s, come from
+//! `slab_tree_new` and not have been freed.
+//!
+//! The functions are exported unmangled, so linking this crate into a `staticlib` or `cdylib`
+//! makes them available to C.  A matching header looks like:
+//!
+//! ```c
+//! #include <stdbool.h>
+//! #include <stddef.h>
+//! #include <stdint.h>
+//!
+//! typedef struct CTree CTree;
+//! typedef struct { uint64_t index; uint64_t generation; } CNodeId;
+//! typedef bool (*CVisit)(CNodeId node, uint64_t data, void *user_data);
+//!
+//! CTree *slab_tree_new(void);
+//! void slab_tree_free(CTree *tree);
+//! bool slab_tree_set_root(CTree *tree, uint64_t data, CNodeId *out);
+//! bool slab_tree_root(const CTree *tree, CNodeId *out);
+//! bool slab_tree_append(CTree *tree, CNodeId parent, uint64_t data, CNodeId *out);
+//! bool slab_tree_remove(CTree *tree, CNodeId node, bool drop_children, uint64_t *out);
+//! bool slab_tree_get(const CTree *tree, CNodeId node, uint64_t *out);
+//! bool slab_tree_set(CTree *tree, CNodeId node, uint64_t data);
+//! bool slab_tree_parent(const CTree *tree, CNodeId node, CNodeId *out);
+//! bool slab_tree_first_child(const CTree *tree, CNodeId node, CNodeId *out);
+//! bool slab_tree_next_sibling(const CTree *tree, CNodeId node, CNodeId *out);
+//! size_t slab_tree_traverse_pre_order(const CTree *tree, CNodeId start, CVisit visit,
+//!                                     void *user_data);
+//! ```
+//!
+
+#![allow(unsafe_code)] // `#[no_mangle]` counts as unsafe code; nothing here dereferences a pointer
+
+use crate::behaviors::RemoveBehavior;
+use crate::node::NodeRef;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::ffi::c_void;
+
+///
+/// An opaque handle to a `Tree<u64>`, created by `slab_tree_new`.
+///
+#[derive(Debug, Default)]
+pub struct CTree {
+    tree: Tree<u64>,
+}
+
+///
+/// A `NodeId` in a form which can cross the C boundary.
+///
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CNodeId {
+    ///
+    /// The slot of the `Node` in its `Tree`.
+    ///
+    pub index: u64,
+
+    ///
+    /// The generation of the slot when the `Node` was inserted.
+    ///
+    pub generation: u64,
+}
+
+impl From<NodeId> for CNodeId {
+    fn from(node_id: NodeId) -> CNodeId {
+        let (index, generation) = node_id.to_raw();
+        CNodeId { index, generation }
+    }
+}
+
+impl CTree {
+    fn node_id(&self, node: CNodeId) -> Option<NodeId> {
+        self.tree.id_from_raw((node.index, node.generation))
+    }
+
+    fn node(&self, node: CNodeId) -> Option<NodeRef<'_, u64>> {
+        self.node_id(node)
+            .and_then(|node_id| self.tree.get(node_id))
+    }
+}
+
+///
+/// A callback for `slab_tree_traverse_pre_order`, receiving each `Node`'s id and data along
+/// with the caller's `user_data`.  Returning `false` stops the traversal.
+///
+pub type CVisit = extern "C" fn(node: CNodeId, data: u64, user_data: *mut c_void) -> bool;
+
+fn write_out<T>(out: Option<&mut T>, value: Option<T>) -> bool {
+    match (out, value) {
+        (Some(out), Some(value)) => {
+            *out = value;
+            true
+        }
+        _ => false,
+    }
+}
+
+///
+/// Creates a new, empty `Tree`.  The returned pointer must be released with `slab_tree_free`.
+///
+/// ```
+/// use slab_tree::capi::*;
+///
+/// let tree = slab_tree_new();
+/// let mut root = CNodeId { index: 0, generation: 0 };
+/// assert!(!slab_tree_root(Some(&tree), Some(&mut root)));
+/// slab_tree_free(Some(tree));
+/// ```
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_new() -> Box<CTree> {
+    Box::default()
+}
+
+///
+/// Releases a `Tree` created by `slab_tree_new`, along with all of its `Node`s.  Does nothing
+/// if `tree` is null.
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_free(tree: Option<Box<CTree>>) {
+    drop(tree);
+}
+
+///
+/// Sets the root of the `Tree`, as `Tree::set_root` does, writing its id to `out`.  Returns
+/// `false` if `tree` or `out` is null.
+///
+/// ```
+/// use slab_tree::capi::*;
+///
+/// let mut tree = slab_tree_new();
+/// let mut root = CNodeId { index: 0, generation: 0 };
+/// assert!(slab_tree_set_root(Some(&mut tree), 7, Some(&mut root)));
+///
+/// let mut data = 0;
+/// assert!(slab_tree_get(Some(&tree), root, Some(&mut data)));
+/// assert_eq!(data, 7);
+/// slab_tree_free(Some(tree));
+/// ```
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_set_root(
+    tree: Option<&mut CTree>,
+    data: u64,
+    out: Option<&mut CNodeId>,
+) -> bool {
+    match (tree, out) {
+        (Some(tree), Some(out)) => {
+            *out = tree.tree.set_root(data).into();
+            true
+        }
+        _ => false,
+    }
+}
+
+///
+/// Writes the id of the `Tree`'s root to `out`.  Returns `false` if the `Tree` is empty.
+///
+/// ```
+/// use slab_tree::capi::*;
+///
+/// let mut tree = slab_tree_new();
+/// let mut root = CNodeId { index: 0, generation: 0 };
+/// slab_tree_set_root(Some(&mut tree), 1, Some(&mut root));
+///
+/// let mut found = CNodeId { index: 0, generation: 0 };
+/// assert!(slab_tree_root(Some(&tree), Some(&mut found)));
+/// assert_eq!(found, root);
+/// slab_tree_free(Some(tree));
+/// ```
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_root(tree: Option<&CTree>, out: Option<&mut CNodeId>) -> bool {
+    let root_id = tree.and_then(|tree| tree.tree.root_id());
+    write_out(out, root_id.map(CNodeId::from))
+}
+
+///
+/// Appends a new `Node` holding `data` as the last child of `parent`, writing its id to `out`.
+/// Returns `false` (and adds nothing) if `parent` isn't a `Node` of the `Tree`.
+///
+/// ```
+/// use slab_tree::capi::*;
+///
+/// let mut tree = slab_tree_new();
+/// let mut root = CNodeId { index: 0, generation: 0 };
+/// slab_tree_set_root(Some(&mut tree), 1, Some(&mut root));
+///
+/// let mut child = CNodeId { index: 0, generation: 0 };
+/// assert!(slab_tree_append(Some(&mut tree), root, 2, Some(&mut child)));
+///
+/// let mut parent = CNodeId { index: 0, generation: 0 };
+/// assert!(slab_tree_parent(Some(&tree), child, Some(&mut parent)));
+/// assert_eq!(parent, root);
+/// slab_tree_free(Some(tree));
+/// ```
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_append(
+    tree: Option<&mut CTree>,
+    parent: CNodeId,
+    data: u64,
+    out: Option<&mut CNodeId>,
+) -> bool {
+    let (tree, out) = match (tree, out) {
+        (Some(tree), Some(out)) => (tree, out),
+        _ => return false,
+    };
+    let parent_id = tree.node_id(parent);
+    match parent_id.and_then(|parent_id| tree.tree.get_mut(parent_id)) {
+        Some(mut parent) => {
+            *out = parent.append(data).node_id().into();
+            true
+        }
+        None => false,
+    }
+}
+
+///
+/// Removes `node` from the `Tree`, dropping its children if `drop_children` is `true` and
+/// orphaning them otherwise, and writes its data to `out` if `out` isn't null.  Returns `false`
+/// if `node` isn't a `Node` of the `Tree`.
+///
+/// ```
+/// use slab_tree::capi::*;
+///
+/// let mut tree = slab_tree_new();
+/// let mut root = CNodeId { index: 0, generation: 0 };
+/// slab_tree_set_root(Some(&mut tree), 1, Some(&mut root));
+/// let mut child = CNodeId { index: 0, generation: 0 };
+/// slab_tree_append(Some(&mut tree), root, 2, Some(&mut child));
+///
+/// let mut data = 0;
+/// assert!(slab_tree_remove(Some(&mut tree), child, true, Some(&mut data)));
+/// assert_eq!(data, 2);
+/// assert!(!slab_tree_remove(Some(&mut tree), child, true, None));
+/// slab_tree_free(Some(tree));
+/// ```
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_remove(
+    tree: Option<&mut CTree>,
+    node: CNodeId,
+    drop_children: bool,
+    out: Option<&mut u64>,
+) -> bool {
+    let tree = match tree {
+        Some(tree) => tree,
+        None => return false,
+    };
+    let behavior = if drop_children {
+        RemoveBehavior::DropChildren
+    } else {
+        RemoveBehavior::OrphanChildren
+    };
+    let data = tree
+        .node_id(node)
+        .and_then(|node_id| tree.tree.remove(node_id, behavior));
+    match (data, out) {
+        (Some(data), Some(out)) => {
+            *out = data;
+            true
+        }
+        (data, _) => data.is_some(),
+    }
+}
+
+///
+/// Writes the data of `node` to `out`.  Returns `false` if `node` isn't a `Node` of the `Tree`.
+///
+/// ```
+/// use slab_tree::capi::*;
+///
+/// let mut tree = slab_tree_new();
+/// let mut root = CNodeId { index: 0, generation: 0 };
+/// slab_tree_set_root(Some(&mut tree), 5, Some(&mut root));
+///
+/// let mut data = 0;
+/// assert!(slab_tree_get(Some(&tree), root, Some(&mut data)));
+/// assert_eq!(data, 5);
+/// slab_tree_free(Some(tree));
+/// ```
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_get(
+    tree: Option<&CTree>,
+    node: CNodeId,
+    out: Option<&mut u64>,
+) -> bool {
+    let data = tree
+        .and_then(|tree| tree.node(node))
+        .map(|node| *node.data());
+    write_out(out, data)
+}
+
+///
+/// Replaces the data of `node` with `data`.  Returns `false` if `node` isn't a `Node` of the
+/// `Tree`.
+///
+/// ```
+/// use slab_tree::capi::*;
+///
+/// let mut tree = slab_tree_new();
+/// let mut root = CNodeId { index: 0, generation: 0 };
+/// slab_tree_set_root(Some(&mut tree), 5, Some(&mut root));
+///
+/// assert!(slab_tree_set(Some(&mut tree), root, 6));
+/// let mut data = 0;
+/// slab_tree_get(Some(&tree), root, Some(&mut data));
+/// assert_eq!(data, 6);
+/// slab_tree_free(Some(tree));
+/// ```
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_set(tree: Option<&mut CTree>, node: CNodeId, data: u64) -> bool {
+    let tree = match tree {
+        Some(tree) => tree,
+        None => return false,
+    };
+    let node_id = tree.node_id(node);
+    match node_id.and_then(|node_id| tree.tree.get_mut(node_id)) {
+        Some(mut node) => {
+            node.replace_data(data);
+            true
+        }
+        None => false,
+    }
+}
+
+///
+/// Writes the id of the parent of `node` to `out`.  Returns `false` if `node` isn't a `Node`
+/// of the `Tree` or has no parent.
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_parent(
+    tree: Option<&CTree>,
+    node: CNodeId,
+    out: Option<&mut CNodeId>,
+) -> bool {
+    let parent = tree
+        .and_then(|tree| tree.node(node))
+        .and_then(|node| node.parent().map(|parent| parent.node_id().into()));
+    write_out(out, parent)
+}
+
+///
+/// Writes the id of the first child of `node` to `out`.  Returns `false` if `node` isn't a
+/// `Node` of the `Tree` or has no children.
+///
+/// ```
+/// use slab_tree::capi::*;
+///
+/// let mut tree = slab_tree_new();
+/// let mut root = CNodeId { index: 0, generation: 0 };
+/// slab_tree_set_root(Some(&mut tree), 1, Some(&mut root));
+/// let mut child = CNodeId { index: 0, generation: 0 };
+/// slab_tree_append(Some(&mut tree), root, 2, Some(&mut child));
+///
+/// let mut first = CNodeId { index: 0, generation: 0 };
+/// assert!(slab_tree_first_child(Some(&tree), root, Some(&mut first)));
+/// assert_eq!(first, child);
+/// assert!(!slab_tree_first_child(Some(&tree), child, Some(&mut first)));
+/// slab_tree_free(Some(tree));
+/// ```
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_first_child(
+    tree: Option<&CTree>,
+    node: CNodeId,
+    out: Option<&mut CNodeId>,
+) -> bool {
+    let first_child = tree
+        .and_then(|tree| tree.node(node))
+        .and_then(|node| node.first_child().map(|child| child.node_id().into()));
+    write_out(out, first_child)
+}
+
+///
+/// Writes the id of the next sibling of `node` to `out`.  Returns `false` if `node` isn't a
+/// `Node` of the `Tree` or is the last of its siblings.
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_next_sibling(
+    tree: Option<&CTree>,
+    node: CNodeId,
+    out: Option<&mut CNodeId>,
+) -> bool {
+    let next_sibling = tree
+        .and_then(|tree| tree.node(node))
+        .and_then(|node| node.next_sibling().map(|sibling| sibling.node_id().into()));
+    write_out(out, next_sibling)
+}
+
+///
+/// Calls `visit` with each `Node` of the sub-tree rooted at `start`, in pre-order, passing
+/// `user_data` through untouched.  Stops early if `visit` returns `false`.  Returns the number
+/// of `Node`s visited, which is 0 if `start` isn't a `Node` of the `Tree` or `visit` is null.
+///
+/// ```
+/// use slab_tree::capi::*;
+/// use std::ffi::c_void;
+///
+/// extern "C" fn visit(_node: CNodeId, data: u64, user_data: *mut c_void) -> bool {
+///     data != 2
+/// }
+///
+/// let mut tree = slab_tree_new();
+/// let mut root = CNodeId { index: 0, generation: 0 };
+/// slab_tree_set_root(Some(&mut tree), 1, Some(&mut root));
+/// let mut child = CNodeId { index: 0, generation: 0 };
+/// for data in 2..5 {
+///     slab_tree_append(Some(&mut tree), root, data, Some(&mut child));
+/// }
+///
+/// let visited = slab_tree_traverse_pre_order(Some(&tree), root, Some(visit), std::ptr::null_mut());
+/// assert_eq!(visited, 2);
+/// slab_tree_free(Some(tree));
+/// ```
+///
+#[no_mangle]
+pub extern "C" fn slab_tree_traverse_pre_order(
+    tree: Option<&CTree>,
+    start: CNodeId,
+    visit: Option<CVisit>,
+    user_data: *mut c_void,
+) -> usize {
+    let (start, visit) = match (tree.and_then(|tree| tree.node(start)), visit) {
+        (Some(start), Some(visit)) => (start, visit),
+        _ => return 0,
+    };
+    let mut visited = 0;
+    for node in start.traverse_pre_order() {
+        visited += 1;
+        if !visit(node.node_id().into(), *node.data(), user_data) {
+            break;
+        }
+    }
+    visited
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod capi_tests {
+    use super::*;
+
+    const NONE: CNodeId = CNodeId {
+        index: 0,
+        generation: 0,
+    };
+
+    thread_local! {
+        static SEEN: std::cell::RefCell<Vec<(u64, bool)>> = const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    // reading `user_data` would need `unsafe`, so this only records whether it was passed through
+    extern "C" fn collect(_node: CNodeId, data: u64, user_data: *mut c_void) -> bool {
+        SEEN.with(|seen| seen.borrow_mut().push((data, user_data.is_null())));
+        true
+    }
+
+    #[test]
+    fn build_and_traverse() {
+        let mut tree = slab_tree_new();
+        let mut root = NONE;
+        assert!(slab_tree_set_root(Some(&mut tree), 1, Some(&mut root)));
+        let mut two = NONE;
+        assert!(slab_tree_append(Some(&mut tree), root, 2, Some(&mut two)));
+        let mut three = NONE;
+        assert!(slab_tree_append(Some(&mut tree), two, 3, Some(&mut three)));
+        let mut four = NONE;
+        assert!(slab_tree_append(Some(&mut tree), root, 4, Some(&mut four)));
+
+        let mut sibling = NONE;
+        assert!(slab_tree_next_sibling(Some(&tree), two, Some(&mut sibling)));
+        assert_eq!(sibling, four);
+        assert!(!slab_tree_next_sibling(
+            Some(&tree),
+            four,
+            Some(&mut sibling)
+        ));
+
+        let visited =
+            slab_tree_traverse_pre_order(Some(&tree), root, Some(collect), std::ptr::null_mut());
+        assert_eq!(visited, 4);
+        let seen = SEEN.with(|seen| seen.borrow_mut().split_off(0));
+        assert_eq!(seen, vec![(1, true), (2, true), (3, true), (4, true)]);
+
+        assert!(slab_tree_remove(Some(&mut tree), two, false, None));
+        let mut data = 0;
+        assert!(slab_tree_get(Some(&tree), three, Some(&mut data)));
+        assert_eq!(data, 3);
+        let mut parent = NONE;
+        assert!(!slab_tree_parent(Some(&tree), three, Some(&mut parent)));
+        slab_tree_free(Some(tree));
+    }
+
+    #[test]
+    fn rejects_null_and_stale() {
+        let mut tree = slab_tree_new();
+        let mut root = NONE;
+        let mut data = 0;
+        assert!(!slab_tree_set_root(None, 1, Some(&mut root)));
+        assert!(!slab_tree_set_root(Some(&mut tree), 1, None));
+        assert!(!slab_tree_root(None, Some(&mut root)));
+        assert!(!slab_tree_get(None, root, Some(&mut data)));
+
+        slab_tree_set_root(Some(&mut tree), 1, Some(&mut root));
+        let mut child = NONE;
+        slab_tree_append(Some(&mut tree), root, 2, Some(&mut child));
+        assert!(slab_tree_remove(Some(&mut tree), child, true, None));
+
+        assert!(!slab_tree_get(Some(&tree), child, Some(&mut data)));
+        assert!(!slab_tree_set(Some(&mut tree), child, 3));
+        assert!(!slab_tree_append(
+            Some(&mut tree),
+            child,
+            3,
+            Some(&mut root)
+        ));
+        assert_eq!(
+            slab_tree_traverse_pre_order(Some(&tree), child, Some(collect), std::ptr::null_mut()),
+            0
+        );
+        assert_eq!(
+            slab_tree_traverse_pre_order(Some(&tree), root, None, std::ptr::null_mut()),
+            0
+        );
+        let stale = CNodeId {
+            index: u64::MAX,
+            generation: u64::MAX,
+        };
+        assert!(!slab_tree_get(Some(&tree), stale, Some(&mut data)));
+        slab_tree_free(None);
+        slab_tree_free(Some(tree));
+    }
+}
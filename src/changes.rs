@@ -0,0 +1,389 @@
+//!
+//! An opt-in log of the changes made to a `Tree`, for keeping views of it (such as a rendered
+//! UI) up to date incrementally.
+//!
+//! Once enabled with `Tree::with_change_log`, the `Tree` records a `ChangeEvent` for every
+//! change it would report to an observer, which can be collected with `Tree::drain_changes`
+//! after a batch of mutations.
+//!
+
+use crate::observer::TreeIndex;
+use crate::observer::TreeObserver;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::any::Any;
+use std::collections::HashMap;
+use std::mem;
+
+///
+/// A single change recorded by a `Tree`'s change log.
+///
+/// Positions are given as the `NodeId` of the parent (`None` for the root and for orphans) and
+/// the index among that parent's children, as they were just after the change.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChangeEvent {
+    ///
+    /// A new `Node` was inserted.
+    ///
+    NodeInserted {
+        ///
+        /// The parent of the new `Node`.
+        ///
+        parent: Option<NodeId>,
+
+        ///
+        /// The position of the new `Node` among its siblings.
+        ///
+        index: usize,
+
+        ///
+        /// The `NodeId` of the new `Node`.
+        ///
+        id: NodeId,
+    },
+
+    ///
+    /// A `Node` was removed.  When a `Node` is removed along with its children, each of them
+    /// gets its own event, parents before children.
+    ///
+    NodeRemoved {
+        ///
+        /// The `NodeId` of the removed `Node`.
+        ///
+        id: NodeId,
+    },
+
+    ///
+    /// A `Node` (along with its sub-tree) changed position.
+    ///
+    NodeMoved {
+        ///
+        /// The `NodeId` of the moved `Node`.
+        ///
+        id: NodeId,
+
+        ///
+        /// The new parent of the `Node`.
+        ///
+        parent: Option<NodeId>,
+
+        ///
+        /// The new position of the `Node` among its siblings.
+        ///
+        index: usize,
+    },
+
+    ///
+    /// The data of a `Node` was replaced (with `NodeMut::replace_data` or similar).  Changes
+    /// made through `NodeMut::data` aren't recorded.
+    ///
+    DataChanged {
+        ///
+        /// The `NodeId` of the changed `Node`.
+        ///
+        id: NodeId,
+    },
+
+    ///
+    /// The `Tree` changed wholesale (e.g. it was compacted, defragmented or restored from a
+    /// snapshot), so `NodeId`s held by views may no longer be valid and they should be rebuilt
+    /// from scratch.
+    /// Any events recorded before this one are discarded.
+    ///
+    Reset,
+}
+
+/// Records a `ChangeEvent` for each notification the `Tree` sends.
+#[derive(Default)]
+struct ChangeLog {
+    events: Vec<ChangeEvent>,
+}
+
+fn position<T>(tree: &Tree<T>, node_id: NodeId) -> (Option<NodeId>, usize) {
+    let relatives = tree.get_node_relatives(node_id);
+    let mut index = 0;
+    let mut prev = relatives.prev_sibling;
+    while let Some(prev_id) = prev {
+        index += 1;
+        prev = tree.get_node_relatives(prev_id).prev_sibling;
+    }
+    (relatives.parent, index)
+}
+
+impl<T> TreeObserver<T> for ChangeLog {
+    fn on_insert(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        let (parent, index) = position(tree, node_id);
+        self.events.push(ChangeEvent::NodeInserted {
+            parent,
+            index,
+            id: node_id,
+        });
+    }
+
+    fn on_remove(&mut self, _tree: &Tree<T>, node_id: NodeId) {
+        self.events.push(ChangeEvent::NodeRemoved { id: node_id });
+    }
+
+    fn on_move(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        let (parent, index) = position(tree, node_id);
+        self.events.push(ChangeEvent::NodeMoved {
+            id: node_id,
+            parent,
+            index,
+        });
+    }
+
+    fn on_data_replaced(&mut self, _tree: &Tree<T>, node_id: NodeId) {
+        self.events.push(ChangeEvent::DataChanged { id: node_id });
+    }
+}
+
+impl<T> TreeIndex<T> for ChangeLog {
+    fn rebuild(&mut self, _tree: &Tree<T>) {
+        self.events.clear();
+        self.events.push(ChangeEvent::Reset);
+    }
+
+    fn remap(&mut self, _node_ids: &HashMap<NodeId, NodeId>) {
+        self.events.clear();
+        self.events.push(ChangeEvent::Reset);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Makes the `Tree` record a `ChangeEvent` for every subsequent change, to be collected with
+    /// `drain_changes`.  Enabling the log on a `Tree` which already has one does nothing.
+    ///
+    /// ```
+    /// use slab_tree::changes::ChangeEvent;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build().with_change_log();
+    /// let root_id = tree.root_id().unwrap();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    ///
+    /// assert_eq!(
+    ///     tree.drain_changes(),
+    ///     vec![ChangeEvent::NodeInserted { parent: Some(root_id), index: 0, id: two_id }]
+    /// );
+    /// ```
+    ///
+    pub fn with_change_log(mut self) -> Tree<T> {
+        if self.change_log_mut().is_none() {
+            self.hooks.add_index(Box::new(ChangeLog::default()));
+        }
+        self
+    }
+
+    ///
+    /// Returns (and forgets) the `ChangeEvent`s recorded since the last call, oldest first.
+    /// Returns an empty `Vec` if the change log hasn't been enabled with `with_change_log`.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::changes::ChangeEvent;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    /// let three_id = tree.get_mut(two_id).unwrap().append(3).node_id();
+    /// assert!(tree.drain_changes().is_empty());
+    ///
+    /// let mut tree = tree.with_change_log();
+    /// tree.get_mut(two_id).unwrap().replace_data(20);
+    /// tree.remove(two_id, RemoveBehavior::DropChildren);
+    ///
+    /// assert_eq!(
+    ///     tree.drain_changes(),
+    ///     vec![
+    ///         ChangeEvent::DataChanged { id: two_id },
+    ///         ChangeEvent::NodeRemoved { id: two_id },
+    ///         ChangeEvent::NodeRemoved { id: three_id },
+    ///     ]
+    /// );
+    /// assert!(tree.drain_changes().is_empty());
+    /// ```
+    ///
+    pub fn drain_changes(&mut self) -> Vec<ChangeEvent> {
+        self.change_log_mut()
+            .map(|log| mem::take(&mut log.events))
+            .unwrap_or_default()
+    }
+
+    fn change_log_mut(&mut self) -> Option<&mut ChangeLog> {
+        self.hooks
+            .indexes_mut()
+            .find_map(|index| index.as_any_mut().downcast_mut::<ChangeLog>())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod changes_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::OrphanChildren;
+    use crate::reparent::InsertPosition;
+    use crate::tree::TreeBuilder;
+
+    #[test]
+    fn records_positions() {
+        let mut tree = TreeBuilder::new().with_root(0).build().with_change_log();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append(1).node_id();
+        let two_id = root.append(2).node_id();
+        let zero_id = root.prepend(-1).node_id();
+        tree.move_subtree(one_id, two_id, InsertPosition::Last)
+            .unwrap();
+
+        assert_eq!(
+            tree.drain_changes(),
+            vec![
+                ChangeEvent::NodeInserted {
+                    parent: Some(root_id),
+                    index: 0,
+                    id: one_id
+                },
+                ChangeEvent::NodeInserted {
+                    parent: Some(root_id),
+                    index: 1,
+                    id: two_id
+                },
+                ChangeEvent::NodeInserted {
+                    parent: Some(root_id),
+                    index: 0,
+                    id: zero_id
+                },
+                ChangeEvent::NodeMoved {
+                    id: one_id,
+                    parent: Some(two_id),
+                    index: 0
+                },
+            ]
+        );
+
+        tree.remove(two_id, OrphanChildren);
+        let new_root_id = tree.set_root(10);
+        assert_eq!(
+            tree.drain_changes(),
+            vec![
+                ChangeEvent::NodeRemoved { id: two_id },
+                ChangeEvent::NodeMoved {
+                    id: one_id,
+                    parent: None,
+                    index: 0
+                },
+                ChangeEvent::NodeInserted {
+                    parent: None,
+                    index: 0,
+                    id: new_root_id
+                },
+                ChangeEvent::NodeMoved {
+                    id: root_id,
+                    parent: Some(new_root_id),
+                    index: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn enabling_twice_keeps_one_log() {
+        let mut tree = TreeBuilder::new()
+            .with_root(0)
+            .build()
+            .with_change_log()
+            .with_change_log();
+        tree.root_mut().unwrap().replace_data(1);
+        assert_eq!(tree.drain_changes().len(), 1);
+    }
+
+    #[test]
+    fn compaction_resets() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        tree.root_mut().unwrap().append(2);
+        let mut tree = tree.with_change_log();
+        tree.remove(one_id, OrphanChildren);
+        tree.compact();
+        assert_eq!(tree.drain_changes(), vec![ChangeEvent::Reset]);
+    }
+
+    #[test]
+    fn reorderings_replay() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        for n in [3, 5, 10, 12, 15] {
+            tree.root_mut().unwrap().append(n);
+        }
+        let mut tree = tree.with_change_log();
+        let mut mirror: Vec<NodeId> = tree
+            .root()
+            .unwrap()
+            .children()
+            .map(|n| n.node_id())
+            .collect();
+        let child_id = |tree: &Tree<i32>, n: i32| {
+            tree.root()
+                .unwrap()
+                .children()
+                .find(|child| *child.data() == n)
+                .unwrap()
+                .node_id()
+        };
+
+        let ops: Vec<fn(&mut Tree<i32>, NodeId)> = vec![
+            |tree, _| {
+                let order = [5, 12, 10, 15, 3];
+                tree.root_mut()
+                    .unwrap()
+                    .sort_children_by_cached_key(|child| {
+                        order.iter().position(|n| n == child.data())
+                    })
+            },
+            |tree, _| {
+                tree.root_mut()
+                    .unwrap()
+                    .sort_children_by_cached_key(|child| *child.data())
+            },
+            |tree, _| tree.root_mut().unwrap().rotate_children_left(2),
+            |tree, _| tree.root_mut().unwrap().rotate_children_right(1),
+            |tree, id| assert!(tree.get_mut(id).unwrap().make_nth_sibling(3)),
+            |tree, id| assert!(tree.get_mut(id).unwrap().make_first_sibling()),
+            |tree, id| assert!(tree.get_mut(id).unwrap().make_last_sibling()),
+            |tree, id| assert!(tree.get_mut(id).unwrap().swap_prev_sibling()),
+            |tree, id| assert!(tree.get_mut(id).unwrap().swap_next_sibling()),
+        ];
+        for op in ops {
+            let ten_id = child_id(&tree, 10);
+            op(&mut tree, ten_id);
+            for event in tree.drain_changes() {
+                match event {
+                    ChangeEvent::NodeMoved { id, parent, index } => {
+                        assert_eq!(parent, Some(root_id));
+                        mirror.retain(|&node_id| node_id != id);
+                        mirror.insert(index, id);
+                    }
+                    event => panic!("unexpected {:?}", event),
+                }
+            }
+            let children: Vec<NodeId> = tree
+                .root()
+                .unwrap()
+                .children()
+                .map(|n| n.node_id())
+                .collect();
+            assert_eq!(mirror, children);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//!
+//! Structural hashes of each `Node`'s sub-tree which the `Tree` keeps up to date as it changes,
+//! for comparing `Tree`s (or parts of them) Merkle-style without walking them.
+//!
+//! Two sub-trees get the same hash when their data and shape are the same, regardless of the
+//! `NodeId`s involved.  Hashes are computed with FNV-1a rather than the standard library's
+//! randomly seeded hasher, so they agree between processes; they rely on the data's `Hash`
+//! implementation, so (as for anything hashing `usize`s) they may differ between platforms.
+//!
+
+use crate::node::NodeRef;
+use crate::tree::Tree;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+/// The 64-bit FNV-1a hash.
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Keeps the hash aggregate apart from any `u64` aggregate the caller sets up.
+struct SubtreeHash(u64);
+
+impl<T: Hash + 'static> Tree<T> {
+    ///
+    /// Makes the `Tree` keep a hash of every `Node`'s sub-tree (covering the data of each `Node`
+    /// in it and the order and nesting of its children), for reading with
+    /// `NodeRef::cached_hash`.  Each change updates only the hashes along the path to the root.
+    ///
+    /// As with `with_aggregate`, changes made to the data through `NodeMut::data` aren't picked
+    /// up; use `NodeMut::replace_data` instead.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut a = TreeBuilder::new().with_root("root").build().with_subtree_hashes();
+    /// a.root_mut().unwrap().append("x").append("y");
+    ///
+    /// let mut b = TreeBuilder::new().with_root("root").build().with_subtree_hashes();
+    /// let x_id = b.root_mut().unwrap().append("x").node_id();
+    ///
+    /// let hash = |tree: &slab_tree::Tree<&'static str>| tree.root().unwrap().cached_hash();
+    /// assert_ne!(hash(&a), hash(&b));
+    ///
+    /// b.get_mut(x_id).unwrap().append("y");
+    /// assert_eq!(hash(&a), hash(&b));
+    /// ```
+    ///
+    pub fn with_subtree_hashes(self) -> Tree<T> {
+        self.with_aggregate(|data: &T, children| {
+            let mut hasher = Fnv::default();
+            data.hash(&mut hasher);
+            let mut count = 0usize;
+            for child in children {
+                let SubtreeHash(hash) = child;
+                hasher.write_u64(*hash);
+                count += 1;
+            }
+            hasher.write_usize(count);
+            SubtreeHash(hasher.finish())
+        })
+    }
+}
+
+impl<'a, T: 'static> NodeRef<'a, T> {
+    ///
+    /// Returns the hash of this `Node`'s sub-tree, as kept by a `Tree` set up with
+    /// `Tree::with_subtree_hashes`.  Returns `None` if the `Tree` doesn't keep hashes.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build().with_subtree_hashes();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append(1).node_id();
+    /// let other_one_id = root.append(1).node_id();
+    ///
+    /// let hash = |node_id| tree.get(node_id).unwrap().cached_hash();
+    /// assert!(hash(one_id).is_some());
+    /// assert_eq!(hash(one_id), hash(other_one_id));
+    /// assert_ne!(hash(one_id), hash(tree.root_id().unwrap()));
+    ///
+    /// let plain = TreeBuilder::new().with_root(0).build();
+    /// assert_eq!(plain.root().unwrap().cached_hash(), None);
+    /// ```
+    ///
+    pub fn cached_hash(&self) -> Option<u64> {
+        self.tree()
+            .aggregate::<SubtreeHash>(self.node_id())
+            .map(|SubtreeHash(hash)| *hash)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod checksum_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::DropChildren;
+    use crate::reparent::InsertPosition;
+    use crate::tree::TreeBuilder;
+
+    fn root_hash(tree: &Tree<i32>) -> Option<u64> {
+        tree.root().unwrap().cached_hash()
+    }
+
+    #[test]
+    fn fnv_reference_values() {
+        let hash = |bytes: &[u8]| {
+            let mut hasher = Fnv::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn shape_matters() {
+        // 0 -> [1, 2] versus 0 -> [1 -> [2]]
+        let mut wide = TreeBuilder::new()
+            .with_root(0)
+            .build()
+            .with_subtree_hashes();
+        wide.root_mut().unwrap().append(1);
+        wide.root_mut().unwrap().append(2);
+        let mut deep = TreeBuilder::new()
+            .with_root(0)
+            .build()
+            .with_subtree_hashes();
+        deep.root_mut().unwrap().append(1).append(2);
+        assert_ne!(root_hash(&wide), root_hash(&deep));
+
+        // and so does order
+        let mut swapped = TreeBuilder::new()
+            .with_root(0)
+            .build()
+            .with_subtree_hashes();
+        swapped.root_mut().unwrap().append(2);
+        swapped.root_mut().unwrap().append(1);
+        assert_ne!(root_hash(&wide), root_hash(&swapped));
+    }
+
+    #[test]
+    fn follows_edits() {
+        let build = || Tree::unfold(3, |n| (n, (0..n).collect())).with_subtree_hashes();
+        let mut tree = build();
+        let original = root_hash(&tree);
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root().unwrap().last_child().unwrap().node_id();
+
+        let four_id = tree.get_mut(two_id).unwrap().append(4).node_id();
+        let with_four = root_hash(&tree);
+        assert_ne!(with_four, original);
+        tree.remove(four_id, DropChildren);
+        assert_eq!(root_hash(&tree), original);
+
+        let zero_id = tree.root().unwrap().first_child().unwrap().node_id();
+        tree.move_subtree(zero_id, root_id, InsertPosition::Last)
+            .unwrap();
+        assert_ne!(root_hash(&tree), original);
+        tree.move_subtree(zero_id, root_id, InsertPosition::First)
+            .unwrap();
+        assert_eq!(root_hash(&tree), original);
+
+        tree.get_mut(two_id).unwrap().replace_data(5);
+        assert_ne!(root_hash(&tree), original);
+        tree.get_mut(two_id).unwrap().replace_data(2);
+        assert_eq!(root_hash(&tree), original);
+        assert_eq!(root_hash(&build()), original);
+    }
+}
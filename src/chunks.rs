@@ -0,0 +1,304 @@
+use std::collections::TryReserveError;
+use std::convert::Infallible;
+use std::fmt;
+use std::ops::Index;
+use std::ops::IndexMut;
+
+/// The most items a single chunk holds.
+#[cfg(not(test))]
+const CHUNK_LEN: usize = 1 << 12;
+
+// small enough for the crate's own tests to cross chunk boundaries all the time
+#[cfg(test)]
+const CHUNK_LEN: usize = 4;
+
+/// A growable sequence of items stored in separately allocated chunks of at most `CHUNK_LEN`
+/// items each, with the parts of `Vec`'s interface that `Slab` uses.
+///
+/// Every chunk but the last one in use is full.  Growing never moves items out of a full chunk,
+/// so (unlike a `Vec`) making room for more items copies at most one chunk, and memory can be
+/// handed back chunk by chunk when the sequence shrinks.
+pub(crate) struct Chunks<T> {
+    chunks: Vec<Vec<T>>,
+    len: usize,
+}
+
+impl<T> Chunks<T> {
+    pub(crate) fn with_capacity(capacity: usize) -> Chunks<T> {
+        let mut chunks = Chunks {
+            chunks: Vec::new(),
+            len: 0,
+        };
+        chunks.reserve(capacity);
+        chunks
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.chunks.iter().map(Vec::capacity).sum()
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+        self.chunks
+            .reserve(chunk_count(needed).saturating_sub(self.chunks.len()));
+        let grown = self.grow(needed, |chunk, additional| {
+            chunk.reserve_exact(additional);
+            Ok::<(), Infallible>(())
+        });
+        match grown {
+            Ok(()) => {}
+            Err(never) => match never {},
+        }
+    }
+
+    pub(crate) fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        // an overflowing length asks for more chunks than can exist, which fails below
+        let needed = self.len.saturating_add(additional);
+        self.chunks
+            .try_reserve(chunk_count(needed).saturating_sub(self.chunks.len()))?;
+        self.grow(needed, Vec::try_reserve_exact)
+    }
+
+    /// Makes room for `needed` items in total, growing each chunk with `reserve_exact`.
+    fn grow<E, F>(&mut self, needed: usize, mut reserve_exact: F) -> Result<(), E>
+    where
+        F: FnMut(&mut Vec<T>, usize) -> Result<(), E>,
+    {
+        // the chunks before the one holding the last item are full already
+        let mut chunk_index = self.len / CHUNK_LEN;
+        while chunk_index * CHUNK_LEN < needed {
+            if chunk_index == self.chunks.len() {
+                self.chunks.push(Vec::new());
+            }
+            let chunk = &mut self.chunks[chunk_index];
+            let wanted = (needed - chunk_index * CHUNK_LEN).min(CHUNK_LEN);
+            if chunk.capacity() < wanted {
+                // at least double a chunk that has to grow, so repeatedly reserving one more
+                // item doesn't reallocate every time
+                let wanted = wanted.max(chunk.capacity() * 2).min(CHUNK_LEN);
+                reserve_exact(chunk, wanted - chunk.len())?;
+            }
+            chunk_index += 1;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        let chunk_index = self.len / CHUNK_LEN;
+        if chunk_index == self.chunks.len() {
+            self.chunks.push(Vec::new());
+        }
+        let chunk = &mut self.chunks[chunk_index];
+        if chunk.len() == chunk.capacity() {
+            // grow like a `Vec` would, but never past the end of the chunk
+            let additional = chunk.capacity().max(4).min(CHUNK_LEN - chunk.len());
+            chunk.reserve_exact(additional);
+        }
+        chunk.push(item);
+        self.len += 1;
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<&T> {
+        self.chunks
+            .get(index / CHUNK_LEN)
+            .and_then(|chunk| chunk.get(index % CHUNK_LEN))
+    }
+
+    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.chunks
+            .get_mut(index / CHUNK_LEN)
+            .and_then(|chunk| chunk.get_mut(index % CHUNK_LEN))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub(crate) fn swap(&mut self, a: usize, b: usize) {
+        let (low, high) = (a.min(b), a.max(b));
+        let (low_chunk, high_chunk) = (low / CHUNK_LEN, high / CHUNK_LEN);
+        if low_chunk == high_chunk {
+            self.chunks[low_chunk].swap(low % CHUNK_LEN, high % CHUNK_LEN);
+        } else {
+            let (before, after) = self.chunks.split_at_mut(high_chunk);
+            std::mem::swap(
+                &mut before[low_chunk][low % CHUNK_LEN],
+                &mut after[0][high % CHUNK_LEN],
+            );
+        }
+    }
+
+    /// Drops the items from `len` on, keeping the chunks they were in.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        for (chunk_index, chunk) in self.chunks.iter_mut().enumerate().skip(len / CHUNK_LEN) {
+            chunk.truncate(len.saturating_sub(chunk_index * CHUNK_LEN));
+        }
+        self.len = len;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Frees every chunk past the last item and shrinks the chunk holding it.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.chunks.truncate(chunk_count(self.len));
+        if let Some(last) = self.chunks.last_mut() {
+            last.shrink_to_fit();
+        }
+        self.chunks.shrink_to_fit();
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.chunks.iter().flatten()
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.chunks.iter_mut().flatten()
+    }
+
+    /// Consumes the chunks, mapping each item with `f` in parallel, chunk by chunk.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn par_map<U, F>(self, f: F) -> Chunks<U>
+    where
+        T: Send,
+        U: Send,
+        F: Fn(T) -> U + Sync + Send,
+    {
+        use rayon::prelude::*;
+
+        let chunks = self
+            .chunks
+            .into_par_iter()
+            .map(|chunk| {
+                let mut mapped = Vec::with_capacity(chunk.capacity());
+                mapped.extend(chunk.into_iter().map(&f));
+                mapped
+            })
+            .collect();
+        Chunks {
+            chunks,
+            len: self.len,
+        }
+    }
+}
+
+fn chunk_count(len: usize) -> usize {
+    len.div_ceil(CHUNK_LEN)
+}
+
+impl<T> Index<usize> for Chunks<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.chunks[index / CHUNK_LEN][index % CHUNK_LEN]
+    }
+}
+
+impl<T> IndexMut<usize> for Chunks<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.chunks[index / CHUNK_LEN][index % CHUNK_LEN]
+    }
+}
+
+impl<T> Extend<T> for Chunks<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        for item in items {
+            self.push(item);
+        }
+    }
+}
+
+/// Chunks compare by their items alone, whatever room they have left.
+impl<T: PartialEq> PartialEq for Chunks<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Chunks<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod chunks_tests {
+    use super::Chunks;
+    use super::CHUNK_LEN;
+
+    #[test]
+    fn with_capacity_is_exact() {
+        let chunks = Chunks::<i32>::with_capacity(CHUNK_LEN + 1);
+        assert_eq!(chunks.capacity(), CHUNK_LEN + 1);
+        assert_eq!(chunks.chunks.len(), 2);
+        assert_eq!(chunks.len(), 0);
+    }
+
+    #[test]
+    fn push_never_grows_past_a_chunk() {
+        let mut chunks = Chunks::new_filled(3 * CHUNK_LEN + 1);
+        assert!(chunks
+            .chunks
+            .iter()
+            .all(|chunk| chunk.capacity() <= CHUNK_LEN));
+        assert_eq!(chunks.chunks.len(), 4);
+        assert_eq!(chunks[2 * CHUNK_LEN], 2 * CHUNK_LEN);
+        assert_eq!(chunks.get(3 * CHUNK_LEN + 1), None);
+
+        chunks.swap(1, 2 * CHUNK_LEN + 1);
+        assert_eq!(chunks[1], 2 * CHUNK_LEN + 1);
+        assert_eq!(chunks[2 * CHUNK_LEN + 1], 1);
+        chunks.swap(2, 3);
+        assert_eq!(
+            chunks.iter().take(4).copied().collect::<Vec<_>>(),
+            vec![0, 2 * CHUNK_LEN + 1, 3, 2]
+        );
+    }
+
+    #[test]
+    fn shrinking_frees_chunks() {
+        let mut chunks = Chunks::new_filled(3 * CHUNK_LEN);
+        chunks.truncate(CHUNK_LEN + 1);
+        assert_eq!(chunks.len(), CHUNK_LEN + 1);
+        assert_eq!(chunks.capacity(), 3 * CHUNK_LEN);
+        assert_eq!(chunks.iter().count(), CHUNK_LEN + 1);
+
+        chunks.shrink_to_fit();
+        assert_eq!(chunks.capacity(), CHUNK_LEN + 1);
+        assert_eq!(chunks.chunks.len(), 2);
+
+        chunks.push(7);
+        assert_eq!(chunks[CHUNK_LEN + 1], 7);
+        chunks.clear();
+        assert_eq!(chunks.iter().count(), 0);
+        chunks.shrink_to_fit();
+        assert_eq!(chunks.capacity(), 0);
+    }
+
+    #[test]
+    fn reserve_fills_chunks_in_order() {
+        let mut chunks = Chunks::new_filled(1);
+        chunks.reserve(2 * CHUNK_LEN);
+        assert!(chunks.capacity() > 2 * CHUNK_LEN);
+        assert_eq!(chunks.chunks[0].capacity(), CHUNK_LEN);
+        assert!(chunks.try_reserve(usize::MAX).is_err());
+    }
+
+    impl Chunks<usize> {
+        fn new_filled(len: usize) -> Chunks<usize> {
+            let mut chunks = Chunks::with_capacity(0);
+            chunks.extend(0..len);
+            chunks
+        }
+    }
+}
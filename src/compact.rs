@@ -0,0 +1,282 @@
+//!
+//! Compaction and defragmentation of a `Tree`'s storage.
+//!
+
+use crate::tree::Tree;
+use crate::NodeId;
+use std::collections::HashMap;
+
+///
+/// What compacting a `Tree` would reclaim, as reported by `Tree::compact_dry_run`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CompactStats {
+    /// The number of `Node`s stored in the `Tree`, including any orphans.
+    pub node_count: usize,
+    /// The number of slots (filled or not) the `Tree` has room for beyond its `Node`s.
+    pub reclaimable_slots: usize,
+    /// The memory taken up by the reclaimable slots, in bytes.
+    pub reclaimable_bytes: usize,
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Reports how much space `compact` would reclaim, without changing the `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).with_capacity(4).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    /// tree.remove(two_id, RemoveBehavior::DropChildren);
+    ///
+    /// let stats = tree.compact_dry_run();
+    /// assert_eq!(stats.node_count, 1);
+    /// assert_eq!(stats.reclaimable_slots, 3);
+    /// assert!(stats.reclaimable_bytes > 0);
+    /// ```
+    ///
+    pub fn compact_dry_run(&self) -> CompactStats {
+        let node_count = self.core_tree.len();
+        let reclaimable_slots = self.capacity() - node_count;
+        CompactStats {
+            node_count,
+            reclaimable_slots,
+            reclaimable_bytes: reclaimable_slots * self.core_tree.slot_size(),
+        }
+    }
+
+    ///
+    /// Moves every `Node` (including any orphans) down over the gaps left by removed `Node`s and
+    /// releases the `Tree`'s unused capacity.  Returns a map from the old `NodeId` of every
+    /// `Node` to its new `NodeId`; `NodeId`s of `Node`s that didn't move map to themselves.
+    ///
+    /// Old `NodeId`s of moved `Node`s no longer refer to anything once the `Tree` is compacted,
+    /// and all snapshots of the `Tree` are discarded.  Observers are not notified, but the
+    /// `Tree`'s own indexes (such as the one set up by `with_key`) are updated.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    /// let three_id = tree.root_mut().unwrap().append(3).node_id();
+    /// tree.remove(two_id, RemoveBehavior::DropChildren);
+    ///
+    /// let remap = tree.compact();
+    ///
+    /// assert_eq!(remap.len(), 2);
+    /// assert!(tree.get(three_id).is_none());
+    /// assert_eq!(tree.get(remap[&three_id]).unwrap().data(), &3);
+    /// assert_eq!(tree.capacity(), 2);
+    /// ```
+    ///
+    pub fn compact(&mut self) -> HashMap<NodeId, NodeId> {
+        let node_ids = self.core_tree.compact();
+        self.root_id = self.root_id.map(|root_id| node_ids[&root_id]);
+        for index in self.hooks.indexes_mut() {
+            index.remap(&node_ids);
+        }
+        node_ids
+    }
+
+    ///
+    /// Rearranges the `Tree`'s storage so its `Node`s are laid out in depth-first pre-order:
+    /// the root's sub-tree first, followed by the sub-tree of each orphaned `Node`, in the order
+    /// `iter_all_roots_first` visits them.  Traversing a `Tree` which has been heavily edited
+    /// jumps around its storage; defragmenting it makes traversals read the storage front to
+    /// back again, as they do on a freshly built `Tree`.
+    ///
+    /// Returns a map from the old `NodeId` of every `Node` to its new `NodeId`, just like
+    /// `compact`, and the same caveats apply: old `NodeId`s of moved `Node`s no longer refer to
+    /// anything, all snapshots of the `Tree` are discarded, and observers are not notified
+    /// (though the `Tree`'s own indexes are updated).  Unlike `compact`, the `Tree`'s capacity
+    /// is kept.
+    ///
+    /// ```
+    /// use slab_tree::reparent::InsertPosition;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append(1).node_id();
+    /// let two_id = root.append(2).node_id();
+    /// tree.get_mut(one_id).unwrap().append(3);
+    /// let root_id = tree.root_id().unwrap();
+    /// tree.move_subtree(one_id, root_id, InsertPosition::Last).unwrap();
+    ///
+    /// let remap = tree.defragment();
+    ///
+    /// assert_eq!(remap.len(), 4);
+    /// assert_eq!(tree.get(remap[&two_id]).unwrap().data(), &2);
+    /// let ids: Vec<_> = tree.root().unwrap().traverse_pre_order().map(|n| n.node_id()).collect();
+    /// let mut sorted = ids.clone();
+    /// sorted.sort();
+    /// assert_eq!(ids, sorted);
+    /// ```
+    ///
+    pub fn defragment(&mut self) -> HashMap<NodeId, NodeId> {
+        let order: Vec<NodeId> = self
+            .iter_all_roots_first()
+            .map(|node| node.node_id())
+            .collect();
+        let node_ids = self.core_tree.reorder(&order);
+        self.root_id = self.root_id.map(|root_id| node_ids[&root_id]);
+        for index in self.hooks.indexes_mut() {
+            index.remap(&node_ids);
+        }
+        node_ids
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod compact_tests {
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::reparent::InsertPosition;
+    use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+    use crate::NodeId;
+
+    fn pre_order(tree: &Tree<i32>) -> Vec<(i32, usize)> {
+        tree.root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (*node.data(), node.ancestors().count()))
+            .collect()
+    }
+
+    #[test]
+    fn compact_keeps_structure() {
+        let mut tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let root_id = tree.root_id().unwrap();
+        let removed_id = tree.root().unwrap().first_child().unwrap().node_id();
+        tree.remove(removed_id, DropChildren);
+        let ids: Vec<NodeId> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| node.node_id())
+            .collect();
+        let expected = pre_order(&tree);
+
+        let remap = tree.compact();
+        assert_eq!(pre_order(&tree), expected);
+        assert_eq!(remap.len(), ids.len());
+        assert_eq!(remap[&root_id], root_id);
+        assert_eq!(tree.root_id(), Some(root_id));
+        for id in ids {
+            let new_id = remap[&id];
+            assert_eq!(tree.get(new_id).unwrap().node_id(), new_id);
+            if new_id != id {
+                assert!(tree.get(id).is_none());
+            }
+        }
+        assert_eq!(tree.capacity(), expected.len());
+        assert_eq!(tree.compact_dry_run().reclaimable_slots, 0);
+    }
+
+    #[test]
+    fn compact_keeps_orphans() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        let three_id = tree.get_mut(two_id).unwrap().append(3).node_id();
+        let four_id = tree.get_mut(two_id).unwrap().append(4).node_id();
+        tree.remove(two_id, OrphanChildren);
+
+        let remap = tree.compact();
+        assert_eq!(remap.len(), 3);
+        let three = tree.get(remap[&three_id]).unwrap();
+        assert!(three.parent().is_none());
+        assert_eq!(three.next_sibling().unwrap().node_id(), remap[&four_id]);
+        assert_eq!(tree.root().unwrap().subtree_size(), 1);
+    }
+
+    #[test]
+    fn stale_ids_stay_stale() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        tree.remove(one_id, DropChildren);
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        let three_id = tree.root_mut().unwrap().append(3).node_id();
+        tree.remove(two_id, DropChildren);
+
+        // 3 moves into the slot that 1 and 2 used to occupy
+        let remap = tree.compact();
+        assert!(tree.get(one_id).is_none());
+        assert!(tree.get(two_id).is_none());
+        assert!(tree.get(three_id).is_none());
+        assert_eq!(tree.get(remap[&three_id]).unwrap().data(), &3);
+    }
+
+    #[test]
+    fn compact_discards_snapshots() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        tree.root_mut().unwrap().append(2);
+        let snapshot = tree.snapshot();
+        tree.remove(one_id, DropChildren);
+
+        tree.compact();
+        assert!(!tree.restore(snapshot));
+        assert_eq!(pre_order(&tree), vec![(0, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn defragment_lays_out_pre_order() {
+        let mut tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let root_id = tree.root_id().unwrap();
+        let last_id = tree.root().unwrap().last_child().unwrap().node_id();
+        tree.move_subtree(last_id, root_id, InsertPosition::First)
+            .unwrap();
+        let first_id = tree.root().unwrap().children().nth(1).unwrap().node_id();
+        tree.remove(first_id, OrphanChildren);
+        let orphan_id = tree.root_mut().unwrap().append(9).node_id();
+        tree.remove(orphan_id, OrphanChildren);
+        tree.root_mut().unwrap().append(5).append(6);
+        let expected = pre_order(&tree);
+        let old_ids: Vec<NodeId> = tree.iter_all_roots_first().map(|n| n.node_id()).collect();
+        let capacity = tree.capacity();
+
+        let remap = tree.defragment();
+        assert_eq!(pre_order(&tree), expected);
+        assert_eq!(remap.len(), old_ids.len());
+        let new_ids: Vec<NodeId> = tree.iter_all_roots_first().map(|n| n.node_id()).collect();
+        let remapped: Vec<NodeId> = old_ids.iter().map(|id| remap[id]).collect();
+        assert_eq!(new_ids, remapped);
+        assert_eq!(tree.all_node_ids(), new_ids);
+        assert_eq!(tree.capacity(), capacity);
+
+        // a defragmented tree is already in order
+        let remap = tree.defragment();
+        assert!(remap.iter().all(|(old, new)| old == new));
+    }
+
+    #[test]
+    fn defragment_keeps_indexes() {
+        let mut tree = TreeBuilder::new()
+            .with_root(0)
+            .build()
+            .with_depths()
+            .with_key(|n: &i32| *n);
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        tree.get_mut(one_id).unwrap().append(3);
+        tree.move_subtree(one_id, two_id, InsertPosition::Last)
+            .unwrap();
+
+        tree.defragment();
+        let three = tree.get_by_key(&3).unwrap();
+        assert_eq!(three.depth(), 3);
+        assert_eq!(three.parent().unwrap().data(), &1);
+    }
+
+    #[test]
+    fn compact_empty() {
+        let mut tree: Tree<i32> = Tree::new();
+        assert!(tree.compact().is_empty());
+        assert!(tree.root_id().is_none());
+    }
+}
@@ -0,0 +1,502 @@
+//!
+//! Comparisons between `Tree`s beyond what `PartialEq` offers.
+//!
+
+use crate::node::NodeRef;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+impl<T: Eq + Hash> Tree<T> {
+    ///
+    /// Returns `true` if the two `Tree`s hold equal data in the same shape, ignoring the order of
+    /// each `Node`'s children.  Orphaned `Node`s are ignored, and two empty `Tree`s are equal.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut a = TreeBuilder::new().with_root("deps").build();
+    /// a.root_mut().unwrap().append("serde").append("serde_derive");
+    /// a.root_mut().unwrap().append("rand");
+    ///
+    /// let mut b = TreeBuilder::new().with_root("deps").build();
+    /// b.root_mut().unwrap().append("rand");
+    /// b.root_mut().unwrap().append("serde").append("serde_derive");
+    ///
+    /// assert!(a.eq_unordered(&b));
+    ///
+    /// b.root_mut().unwrap().append("rand");
+    /// assert!(!a.eq_unordered(&b));
+    /// ```
+    ///
+    pub fn eq_unordered(&self, other: &Tree<T>) -> bool {
+        self.eq_unordered_by_key(other, |data| data)
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Returns `true` if the two `Tree`s have the same shape and their `Node`s have equal keys
+    /// (as derived by `key`), ignoring the order of each `Node`'s children.  Orphaned `Node`s are
+    /// ignored, and two empty `Tree`s are equal.
+    ///
+    /// Each `Node` is given a class shared by all `Node`s with the same key and the same classes
+    /// of children, so this takes roughly linear time rather than trying every pairing of
+    /// children.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut a = TreeBuilder::new().with_root(("config", 1)).build();
+    /// a.root_mut().unwrap().append(("port", 80));
+    /// a.root_mut().unwrap().append(("host", 2));
+    ///
+    /// let mut b = TreeBuilder::new().with_root(("config", 5)).build();
+    /// b.root_mut().unwrap().append(("host", 6));
+    /// b.root_mut().unwrap().append(("port", 7));
+    ///
+    /// assert!(a.eq_unordered_by_key(&b, |&(name, _)| name));
+    /// assert!(!a.eq_unordered_by_key(&b, |&(_, value)| value));
+    /// ```
+    ///
+    pub fn eq_unordered_by_key<'a, K, F>(&'a self, other: &'a Tree<T>, key: F) -> bool
+    where
+        K: Eq + Hash,
+        F: Fn(&'a T) -> K,
+    {
+        let mut classes = HashMap::new();
+        let class = self.unordered_class(&key, &mut classes);
+        class == other.unordered_class(&key, &mut classes)
+    }
+
+    ///
+    /// Finds every group of two or more `Node`s whose subtrees are identical: equal data in the
+    /// same shape, with children in the same order.  Each group lists its `Node`s in post-order,
+    /// and the groups are ordered by their first `Node`.  Orphaned `Node`s are ignored.
+    ///
+    /// Every subtree is hashed bottom-up once, so this takes roughly linear time.  Note that when
+    /// two larger subtrees are identical, the `Node`s within them form groups of their own.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// //         "+"
+    /// //        /   \
+    /// //     "*"     "*"
+    /// //     / \     / \
+    /// //   "a" "b" "a" "b"
+    /// let mut tree = TreeBuilder::new().with_root("+").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let mut left = root.append("*");
+    /// left.append("a");
+    /// left.append("b");
+    /// let left_id = left.node_id();
+    /// let mut right = root.append("*");
+    /// right.append("a");
+    /// right.append("b");
+    /// let right_id = right.node_id();
+    ///
+    /// let groups = tree.duplicate_subtrees();
+    /// assert_eq!(groups.len(), 3);
+    /// assert!(groups.contains(&vec![left_id, right_id]));
+    /// ```
+    ///
+    pub fn duplicate_subtrees(&self) -> Vec<Vec<NodeId>>
+    where
+        T: Eq + Hash,
+    {
+        self.duplicate_subtrees_by_key(|data| data)
+    }
+
+    ///
+    /// Like `duplicate_subtrees`, but compares the keys `key` derives from each `Node`'s data
+    /// instead of the data itself.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(("root", 0)).build();
+    /// let a_id = tree.root_mut().unwrap().append(("leaf", 1)).node_id();
+    /// let b_id = tree.root_mut().unwrap().append(("leaf", 2)).node_id();
+    ///
+    /// assert!(tree.duplicate_subtrees().is_empty());
+    /// assert_eq!(tree.duplicate_subtrees_by_key(|&(name, _)| name), vec![vec![a_id, b_id]]);
+    /// ```
+    ///
+    pub fn duplicate_subtrees_by_key<'a, K, F>(&'a self, key: F) -> Vec<Vec<NodeId>>
+    where
+        K: Eq + Hash,
+        F: Fn(&'a T) -> K,
+    {
+        let mut classes = HashMap::new();
+        let mut groups: Vec<Vec<NodeId>> = Vec::new();
+        // the position in `groups` of each class
+        let mut group_of: HashMap<usize, usize> = HashMap::new();
+
+        for (node_id, class) in self.classify(&key, false, &mut classes) {
+            let next_group = groups.len();
+            let group = *group_of.entry(class).or_insert(next_group);
+            if group == next_group {
+                groups.push(Vec::new());
+            }
+            groups[group].push(node_id);
+        }
+
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    // the class of the root, given the classes handed out so far
+    fn unordered_class<'a, K, F>(
+        &'a self,
+        key: &F,
+        classes: &mut HashMap<(K, Vec<usize>), usize>,
+    ) -> Option<usize>
+    where
+        K: Eq + Hash,
+        F: Fn(&'a T) -> K,
+    {
+        // the root comes last in post-order
+        let classified = self.classify(key, true, classes);
+        classified.last().map(|&(_, class)| class)
+    }
+
+    // gives every node under the root a class shared by all nodes with the same key and the same
+    // classes of children (in any order, if `unordered`), listing the nodes in post-order
+    fn classify<'a, K, F>(
+        &'a self,
+        key: &F,
+        unordered: bool,
+        classes: &mut HashMap<(K, Vec<usize>), usize>,
+    ) -> Vec<(NodeId, usize)>
+    where
+        K: Eq + Hash,
+        F: Fn(&'a T) -> K,
+    {
+        let root = match self.root() {
+            Some(root) => root,
+            None => return Vec::new(),
+        };
+        let mut classified = Vec::new();
+        // the classes of nodes whose parent hasn't been visited yet
+        let mut pending: HashMap<NodeId, usize> = HashMap::new();
+
+        for node in root.traverse_post_order() {
+            let mut children: Vec<usize> = node
+                .children()
+                .map(|child| {
+                    pending
+                        .remove(&child.node_id())
+                        .expect("children are visited first")
+                })
+                .collect();
+            if unordered {
+                children.sort_unstable();
+            }
+
+            let next_class = classes.len();
+            let class = *classes
+                .entry((key(node.data()), children))
+                .or_insert(next_class);
+            pending.insert(node.node_id(), class);
+            classified.push((node.node_id(), class));
+        }
+
+        classified
+    }
+}
+
+impl<'a, T> NodeRef<'a, T> {
+    ///
+    /// Returns `true` if the subtree rooted at this `Node` has exactly the same shape as the one
+    /// rooted at `other` (with children in the same order), and `eq` holds for the data of every
+    /// pair of corresponding `Node`s.  Pass `|_, _| true` to compare only the shapes.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut numbers = TreeBuilder::new().with_root(1).build();
+    /// numbers.root_mut().unwrap().append(2).append(3);
+    /// numbers.root_mut().unwrap().append(4);
+    ///
+    /// let mut names = TreeBuilder::new().with_root("1").build();
+    /// names.root_mut().unwrap().append("2").append("3");
+    /// names.root_mut().unwrap().append("4");
+    ///
+    /// let numbers_root = numbers.root().unwrap();
+    /// let names_root = names.root().unwrap();
+    /// assert!(numbers_root.is_isomorphic_to(&names_root, |n, name| n.to_string() == *name));
+    ///
+    /// // the same shape, but not the same data
+    /// names.root_mut().unwrap().first_child().unwrap().replace_data("two");
+    /// let names_root = names.root().unwrap();
+    /// assert!(numbers_root.is_isomorphic_to(&names_root, |_, _| true));
+    /// assert!(!numbers_root.is_isomorphic_to(&names_root, |n, name| n.to_string() == *name));
+    /// ```
+    ///
+    pub fn is_isomorphic_to<'b, U, F>(&self, other: &NodeRef<'b, U>, eq: F) -> bool
+    where
+        F: Fn(&T, &U) -> bool,
+    {
+        let mut stack = vec![(self.node_id(), other.node_id())];
+
+        while let Some((node_id, other_id)) = stack.pop() {
+            let node = self.tree().get(node_id).expect("node doesn't exist?");
+            let other_node = other.tree().get(other_id).expect("node doesn't exist?");
+            if !eq(node.data(), other_node.data()) {
+                return false;
+            }
+
+            let start = stack.len();
+            let mut children = node.children();
+            let mut other_children = other_node.children();
+            loop {
+                match (children.next(), other_children.next()) {
+                    (Some(child), Some(other_child)) => {
+                        stack.push((child.node_id(), other_child.node_id()))
+                    }
+                    (None, None) => break,
+                    _ => return false,
+                }
+            }
+            // visit the children in order
+            stack[start..].reverse();
+        }
+
+        true
+    }
+}
+
+impl<'a, T: PartialEq> NodeRef<'a, T> {
+    ///
+    /// Returns `true` if some `Node` in the subtree rooted at this `Node` (including this `Node`
+    /// itself) has a subtree equal to the one rooted at `pattern`: the same shape, with children
+    /// in the same order, and equal data at every position.  See `find_subtree`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut expr = TreeBuilder::new().with_root("+").build();
+    /// let mut root = expr.root_mut().unwrap();
+    /// root.append("1");
+    /// let mut product = root.append("*");
+    /// product.append("x");
+    /// product.append("2");
+    ///
+    /// let mut pattern = TreeBuilder::new().with_root("*").build();
+    /// pattern.root_mut().unwrap().append("x");
+    /// pattern.root_mut().unwrap().append("2");
+    ///
+    /// let expr_root = expr.root().unwrap();
+    /// assert!(expr_root.contains_subtree(&pattern.root().unwrap()));
+    ///
+    /// pattern.root_mut().unwrap().append("y");
+    /// assert!(!expr_root.contains_subtree(&pattern.root().unwrap()));
+    /// ```
+    ///
+    pub fn contains_subtree<'b>(&self, pattern: &NodeRef<'b, T>) -> bool {
+        self.find_subtree(pattern).is_some()
+    }
+
+    ///
+    /// Returns the first `Node` (in pre-order) in the subtree rooted at this `Node` (including
+    /// this `Node` itself) whose subtree is equal to the one rooted at `pattern`: the same shape,
+    /// with children in the same order, and equal data at every position.  Returns `None` if
+    /// there is no such `Node`.
+    ///
+    /// This takes time linear in the size of both subtrees: only `Node`s whose subtrees have as
+    /// many `Node`s as the pattern are compared against it, and since none of those can be
+    /// nested inside another, no `Node` takes part in more than one comparison.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(1).append(2);
+    /// let second_id = root.append(1).node_id();
+    /// tree.get_mut(second_id).unwrap().append(3);
+    ///
+    /// let mut pattern = TreeBuilder::new().with_root(1).build();
+    /// pattern.root_mut().unwrap().append(3);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let found = root.find_subtree(&pattern.root().unwrap()).unwrap();
+    /// assert_eq!(found.node_id(), second_id);
+    /// ```
+    ///
+    pub fn find_subtree<'b>(&self, pattern: &NodeRef<'b, T>) -> Option<NodeRef<'a, T>> {
+        let pattern_size = pattern.subtree_size();
+
+        // the sizes of the subtrees of the visited nodes whose parents haven't been visited yet;
+        // each node's children are the last of them when it's visited
+        let mut sizes: Vec<usize> = Vec::new();
+        for node in self.traverse_post_order() {
+            let children_start = sizes.len() - node.child_count();
+            let size = 1 + sizes.drain(children_start..).sum::<usize>();
+            if size == pattern_size && node.is_isomorphic_to(pattern, |a, b| a == b) {
+                return Some(node);
+            }
+            sizes.push(size);
+        }
+        None
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod compare_tests {
+    use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+
+    #[test]
+    fn eq_unordered_deep_reordering() {
+        let a = Tree::unfold(4, |n| (n, (0..n).collect()));
+        let b = Tree::unfold(4, |n| (n, (0..n).rev().collect()));
+        assert!(a.eq_unordered(&b));
+        assert!(b.eq_unordered(&a));
+        assert!(a.eq_unordered(&a));
+    }
+
+    #[test]
+    fn eq_unordered_multisets() {
+        // root -> [1 -> [2], 1] vs root -> [1, 1 -> [2]] are equal
+        let mut a = TreeBuilder::new().with_root(0).build();
+        a.root_mut().unwrap().append(1).append(2);
+        a.root_mut().unwrap().append(1);
+
+        let mut b = TreeBuilder::new().with_root(0).build();
+        b.root_mut().unwrap().append(1);
+        b.root_mut().unwrap().append(1).append(2);
+        assert!(a.eq_unordered(&b));
+
+        // root -> [1 -> [2], 1 -> [2]] differs from both
+        let mut c = TreeBuilder::new().with_root(0).build();
+        c.root_mut().unwrap().append(1).append(2);
+        c.root_mut().unwrap().append(1).append(2);
+        assert!(!a.eq_unordered(&c));
+
+        // moving a grandchild to another parent matters
+        let mut d = TreeBuilder::new().with_root(0).build();
+        d.root_mut().unwrap().append(1);
+        d.root_mut().unwrap().append(1);
+        d.root_mut().unwrap().append(2);
+        assert!(!a.eq_unordered(&d));
+    }
+
+    #[test]
+    fn eq_unordered_empty() {
+        let empty: Tree<i32> = Tree::new();
+        let single = TreeBuilder::new().with_root(0).build();
+        assert!(empty.eq_unordered(&Tree::new()));
+        assert!(!empty.eq_unordered(&single));
+        assert!(!single.eq_unordered(&empty));
+    }
+
+    #[test]
+    fn isomorphism() {
+        let a = Tree::unfold(4, |n| (n, (0..n).collect()));
+        let b = Tree::unfold(4, |n| (n as u64 * 2, (0..n).collect()));
+        let c = Tree::unfold(4, |n| (n, (0..n).rev().collect()));
+        let (a_root, b_root, c_root) = (a.root().unwrap(), b.root().unwrap(), c.root().unwrap());
+
+        assert!(a_root.is_isomorphic_to(&a_root, |x, y| x == y));
+        assert!(a_root.is_isomorphic_to(&b_root, |&x, &y| x as u64 * 2 == y));
+        assert!(!a_root.is_isomorphic_to(&b_root, |&x, &y| x as u64 == y));
+        // the same multiset of children in another order has another shape
+        assert!(!a_root.is_isomorphic_to(&c_root, |_, _| true));
+
+        // subtrees of the same tree
+        let children: Vec<_> = a_root.children().collect();
+        let last_of_two = children[2].last_child().unwrap();
+        assert!(children[1].is_isomorphic_to(&last_of_two, |x, y| x == y));
+        assert!(!children[1].is_isomorphic_to(&children[2], |_, _| true));
+    }
+
+    #[test]
+    fn isomorphism_extra_children() {
+        let mut a = TreeBuilder::new().with_root(0).build();
+        a.root_mut().unwrap().append(1);
+        let mut b = TreeBuilder::new().with_root(0).build();
+        b.root_mut().unwrap().append(1);
+        b.root_mut().unwrap().append(2);
+
+        let (a_root, b_root) = (a.root().unwrap(), b.root().unwrap());
+        assert!(!a_root.is_isomorphic_to(&b_root, |_, _| true));
+        assert!(!b_root.is_isomorphic_to(&a_root, |_, _| true));
+    }
+
+    #[test]
+    fn duplicate_subtrees() {
+        let tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let ids = |data: i32| -> Vec<_> {
+            tree.root()
+                .unwrap()
+                .traverse_post_order()
+                .filter(|node| *node.data() == data)
+                .map(|node| node.node_id())
+                .collect()
+        };
+
+        // 3 -> [0, 1 -> [0], 2 -> [0, 1 -> [0]]]
+        let groups = tree.duplicate_subtrees();
+        assert_eq!(groups, vec![ids(0), ids(1)]);
+        assert_eq!(groups[0].len(), 4);
+        assert_eq!(groups[1].len(), 2);
+
+        // order matters
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut a = root.append(1);
+        a.append(2);
+        a.append(3);
+        let mut b = root.append(1);
+        b.append(3);
+        b.append(2);
+        assert_eq!(tree.duplicate_subtrees().len(), 2);
+        assert!(tree
+            .duplicate_subtrees()
+            .iter()
+            .all(|group| group.len() == 2));
+
+        let empty: Tree<i32> = Tree::new();
+        assert!(empty.duplicate_subtrees().is_empty());
+    }
+
+    #[test]
+    fn find_subtree() {
+        // 3 -> [0, 1 -> [0], 2 -> [0, 1 -> [0]]]
+        let tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let root = tree.root().unwrap();
+        let find = |pattern: &Tree<i32>| {
+            root.find_subtree(&pattern.root().unwrap())
+                .map(|node| node.node_id())
+        };
+
+        let one = Tree::unfold(1, |n| (n, (0..n).collect()));
+        let first_one = root.children().nth(1).unwrap().node_id();
+        assert_eq!(find(&one), Some(first_one));
+        assert_eq!(find(&tree), Some(root.node_id()));
+
+        // a matching node must match with its whole subtree
+        let leaf_one = TreeBuilder::new().with_root(1).build();
+        assert_eq!(find(&leaf_one), None);
+
+        // and with its children in the same order
+        let mut two = TreeBuilder::new().with_root(2).build();
+        two.root_mut().unwrap().append(1).append(0);
+        two.root_mut().unwrap().append(0);
+        assert_eq!(find(&two), None);
+        assert!(!root.contains_subtree(&two.root().unwrap()));
+
+        // searching only covers the subtree below the node searched from
+        let last = root.last_child().unwrap();
+        let zero = TreeBuilder::new().with_root(0).build();
+        assert_eq!(
+            last.find_subtree(&zero.root().unwrap()).unwrap().node_id(),
+            last.first_child().unwrap().node_id()
+        );
+        assert!(!last.contains_subtree(&tree.root().unwrap()));
+    }
+}
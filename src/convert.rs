@@ -0,0 +1,23 @@
+//!
+//! Conversions between `Tree`s and the tree types of other crates.
+//!
+//! Each conversion lives behind an optional feature named after the crate it converts to and
+//! from.  All conversions preserve the structure of the tree (including the order of each
+//! `Node`'s children).  Conversions which consume a tree move its data rather than cloning it.
+//!
+
+#[cfg(feature = "indextree")]
+mod arena;
+#[cfg(feature = "ego-tree")]
+mod ego;
+#[cfg(feature = "petgraph")]
+mod graph;
+
+#[cfg(feature = "indextree")]
+pub use self::arena::from_arena;
+#[cfg(feature = "indextree")]
+pub use self::arena::into_arena;
+#[cfg(feature = "ego-tree")]
+pub use self::ego::from_ego_tree;
+#[cfg(feature = "ego-tree")]
+pub use self::ego::into_ego_tree;
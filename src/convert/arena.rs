@@ -0,0 +1,210 @@
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use indextree::Arena;
+
+///
+/// Converts a `Tree` into an `indextree::Arena`, returning the `Arena` along with the id of the
+/// `Node` that was the root of the `Tree` (or `None` if the `Tree` was empty).
+///
+/// ```
+/// use slab_tree::convert::into_arena;
+/// use slab_tree::tree::TreeBuilder;
+///
+/// let mut tree = TreeBuilder::new().with_root(1).build();
+/// let mut root = tree.root_mut().expect("root doesn't exist?");
+/// root.append(2);
+/// root.append(3);
+///
+/// let (arena, root_id) = into_arena(tree);
+/// let root_id = root_id.expect("root doesn't exist?");
+///
+/// let children: Vec<i32> = root_id.children(&arena).map(|id| *arena[id].get()).collect();
+/// assert_eq!(children, vec![2, 3]);
+/// ```
+///
+pub fn into_arena<T>(mut tree: Tree<T>) -> (Arena<T>, Option<indextree::NodeId>) {
+    let mut arena = Arena::with_capacity(tree.capacity());
+
+    let root_id = match tree.root_id() {
+        Some(root_id) => root_id,
+        None => return (arena, None),
+    };
+
+    let mut new_root_id = None;
+    let mut stack: Vec<(NodeId, Option<indextree::NodeId>)> = vec![(root_id, None)];
+
+    while let Some((node_id, parent_id)) = stack.pop() {
+        let relatives = tree.get_node_relatives(node_id);
+        let data = tree
+            .core_tree
+            .remove(node_id)
+            .expect("reachable node must exist");
+
+        let new_id = match parent_id {
+            Some(parent_id) => parent_id.append_value(data, &mut arena),
+            None => {
+                let new_id = arena.new_node(data);
+                new_root_id = Some(new_id);
+                new_id
+            }
+        };
+
+        // push the next sibling first so that this node's children are handled before it
+        if let (Some(next_sibling), Some(_)) = (relatives.next_sibling, parent_id) {
+            stack.push((next_sibling, parent_id));
+        }
+        if let Some(first_child) = relatives.first_child {
+            stack.push((first_child, Some(new_id)));
+        }
+    }
+
+    (arena, new_root_id)
+}
+
+///
+/// Converts the sub-tree of an `indextree::Arena` starting at `root` into a `Tree`.
+///
+/// `root` does not need to be one of the `Arena`'s roots; if it has a parent, only `root` and
+/// its descendants are converted.  Any other `Node`s in the `Arena` are dropped.
+///
+/// Returns `None` if `root` does not refer to a `Node` in the `Arena` (including `Node`s which
+/// have been removed).
+///
+/// ```
+/// use indextree::Arena;
+/// use slab_tree::convert::from_arena;
+///
+/// let mut arena = Arena::new();
+/// let root_id = arena.new_node(1);
+/// root_id.append_value(2, &mut arena);
+/// root_id.append_value(3, &mut arena);
+///
+/// let tree = from_arena(arena, root_id).expect("root doesn't exist?");
+/// let root = tree.root().expect("root doesn't exist?");
+///
+/// let children: Vec<i32> = root.children().map(|node| *node.data()).collect();
+/// assert_eq!(children, vec![2, 3]);
+/// ```
+///
+pub fn from_arena<T>(arena: Arena<T>, root: indextree::NodeId) -> Option<Tree<T>> {
+    if root.is_removed(&arena) {
+        return None;
+    }
+
+    // (arena index, arena index of the parent) in pre-order; the root's parent is ignored
+    let layout: Vec<(usize, Option<usize>)> = root
+        .descendants(&arena)
+        .map(|id| {
+            let parent = if id == root {
+                None
+            } else {
+                arena[id].parent().map(index)
+            };
+            (index(id), parent)
+        })
+        .collect();
+
+    let mut data: Vec<Option<T>> = arena.into_iter().map(|node| node.into_data()).collect();
+    let mut new_ids: Vec<Option<NodeId>> = vec![None; data.len()];
+    let mut tree = TreeBuilder::new().with_capacity(layout.len()).build();
+
+    for (index, parent) in layout {
+        let value = data[index].take().expect("live node must have data");
+        let new_id = match parent {
+            Some(parent) => {
+                let parent_id = new_ids[parent].expect("parent was inserted before its children");
+                tree.get_mut(parent_id)
+                    .expect("parent was inserted before its children")
+                    .append(value)
+                    .node_id()
+            }
+            None => tree.set_root(value),
+        };
+        new_ids[index] = Some(new_id);
+    }
+
+    Some(tree)
+}
+
+fn index(node_id: indextree::NodeId) -> usize {
+    usize::from(node_id) - 1
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod arena_tests {
+    use super::*;
+
+    fn pre_order<T: Copy>(tree: &Tree<T>) -> Vec<(T, usize)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| (*node.data(), node.ancestors().count()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn sample() -> Tree<i32> {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        one.append(2);
+        one.append(3).append(4);
+        root.append(5);
+        root.append(6).append(7);
+        tree
+    }
+
+    #[test]
+    fn round_trip() {
+        let tree = sample();
+        let expected = pre_order(&tree);
+
+        let (arena, root_id) = into_arena(tree);
+        assert_eq!(arena.len(), 8);
+
+        let tree = from_arena(arena, root_id.unwrap()).unwrap();
+        assert_eq!(pre_order(&tree), expected);
+    }
+
+    #[test]
+    fn into_arena_empty() {
+        let (arena, root_id) = into_arena(Tree::<i32>::new());
+        assert!(arena.is_empty());
+        assert!(root_id.is_none());
+    }
+
+    #[test]
+    fn into_arena_preserves_child_order() {
+        let (arena, root_id) = into_arena(sample());
+        let data: Vec<i32> = root_id
+            .unwrap()
+            .descendants(&arena)
+            .map(|id| *arena[id].get())
+            .collect();
+        assert_eq!(data, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn from_arena_sub_tree() {
+        let mut arena = Arena::new();
+        let root = arena.new_node(0);
+        let one = root.append_value(1, &mut arena);
+        one.append_value(2, &mut arena);
+        root.append_value(3, &mut arena);
+        arena.new_node(99);
+
+        let tree = from_arena(arena, one).unwrap();
+        assert_eq!(pre_order(&tree), vec![(1, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn from_arena_removed_root() {
+        let mut arena = Arena::new();
+        let root = arena.new_node(0);
+        root.remove(&mut arena);
+        assert!(from_arena(arena, root).is_none());
+    }
+}
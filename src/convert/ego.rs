@@ -0,0 +1,169 @@
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+
+///
+/// Converts a `Tree` into an `ego_tree::Tree`.
+///
+/// Returns `None` if the `Tree` is empty, since an `ego_tree::Tree` always has a root.
+///
+/// ```
+/// use slab_tree::convert::into_ego_tree;
+/// use slab_tree::tree::TreeBuilder;
+///
+/// let mut tree = TreeBuilder::new().with_root(1).build();
+/// let mut root = tree.root_mut().expect("root doesn't exist?");
+/// root.append(2);
+/// root.append(3);
+///
+/// let ego = into_ego_tree(tree).expect("root doesn't exist?");
+/// let children: Vec<i32> = ego.root().children().map(|node| *node.value()).collect();
+/// assert_eq!(children, vec![2, 3]);
+/// ```
+///
+pub fn into_ego_tree<T>(mut tree: Tree<T>) -> Option<ego_tree::Tree<T>> {
+    let root_id = tree.root_id()?;
+    let relatives = tree.get_node_relatives(root_id);
+    let data = tree
+        .core_tree
+        .remove(root_id)
+        .expect("root node must exist");
+
+    let mut ego = ego_tree::Tree::with_capacity(data, tree.capacity());
+    let mut stack: Vec<(NodeId, ego_tree::NodeId)> = Vec::new();
+    if let Some(first_child) = relatives.first_child {
+        stack.push((first_child, ego.root().id()));
+    }
+
+    while let Some((node_id, parent_id)) = stack.pop() {
+        let relatives = tree.get_node_relatives(node_id);
+        let data = tree
+            .core_tree
+            .remove(node_id)
+            .expect("reachable node must exist");
+
+        let new_id = ego
+            .get_mut(parent_id)
+            .expect("parent was inserted before its children")
+            .append(data)
+            .id();
+
+        // push the next sibling first so that this node's children are handled before it
+        if let Some(next_sibling) = relatives.next_sibling {
+            stack.push((next_sibling, parent_id));
+        }
+        if let Some(first_child) = relatives.first_child {
+            stack.push((first_child, new_id));
+        }
+    }
+
+    Some(ego)
+}
+
+///
+/// Converts an `ego_tree::Tree` into a `Tree`.
+///
+/// Only the root of the `ego_tree::Tree` and its descendants are converted; orphaned `Node`s
+/// are dropped.
+///
+/// ```
+/// use slab_tree::convert::from_ego_tree;
+///
+/// let mut ego = ego_tree::Tree::new(1);
+/// let mut root = ego.root_mut();
+/// root.append(2);
+/// root.append(3);
+///
+/// let tree = from_ego_tree(ego);
+/// let root = tree.root().expect("root doesn't exist?");
+///
+/// let children: Vec<i32> = root.children().map(|node| *node.data()).collect();
+/// assert_eq!(children, vec![2, 3]);
+/// ```
+///
+pub fn from_ego_tree<T>(ego: ego_tree::Tree<T>) -> Tree<T> {
+    // move the values out, leaving behind a tree of positions in `values`
+    let mut values: Vec<Option<T>> = Vec::new();
+    let positions = ego.map(|value| {
+        values.push(Some(value));
+        values.len() - 1
+    });
+
+    let root = positions.root();
+    let mut tree = TreeBuilder::new().with_capacity(values.len()).build();
+    let mut new_ids: Vec<Option<NodeId>> = vec![None; values.len()];
+
+    for node in root.descendants() {
+        let position = *node.value();
+        let value = values[position].take().expect("each node is visited once");
+        let new_id = match node.parent() {
+            Some(parent) => {
+                let parent_id =
+                    new_ids[*parent.value()].expect("parent was inserted before its children");
+                tree.get_mut(parent_id)
+                    .expect("parent was inserted before its children")
+                    .append(value)
+                    .node_id()
+            }
+            None => tree.set_root(value),
+        };
+        new_ids[position] = Some(new_id);
+    }
+
+    tree
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod ego_tests {
+    use super::*;
+
+    fn pre_order<T: Copy>(tree: &Tree<T>) -> Vec<(T, usize)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| (*node.data(), node.ancestors().count()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn sample() -> Tree<i32> {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        one.append(2);
+        one.append(3).append(4);
+        root.append(5);
+        root.append(6).append(7);
+        tree
+    }
+
+    #[test]
+    fn round_trip() {
+        let tree = sample();
+        let expected = pre_order(&tree);
+
+        let ego = into_ego_tree(tree).unwrap();
+        let data: Vec<i32> = ego.root().descendants().map(|n| *n.value()).collect();
+        assert_eq!(data, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+
+        let tree = from_ego_tree(ego);
+        assert_eq!(pre_order(&tree), expected);
+    }
+
+    #[test]
+    fn into_ego_tree_empty() {
+        assert!(into_ego_tree(Tree::<i32>::new()).is_none());
+    }
+
+    #[test]
+    fn from_ego_tree_drops_orphans() {
+        let mut ego = ego_tree::Tree::new(0);
+        ego.root_mut().append(1);
+        ego.orphan(99).append(100);
+
+        let tree = from_ego_tree(ego);
+        assert_eq!(pre_order(&tree), vec![(0, 0), (1, 1)]);
+    }
+}
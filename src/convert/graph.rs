@@ -0,0 +1,89 @@
+use crate::tree::Tree;
+use crate::NodeId;
+use petgraph::graph::DiGraph;
+use petgraph::graph::NodeIndex;
+use std::collections::HashMap;
+
+impl<T> Tree<T> {
+    ///
+    /// Exports the `Tree` as a `petgraph::graph::DiGraph` whose node weights borrow the data of
+    /// each `Node` and which has an edge from every `Node` to each of its children.  Also returns
+    /// a map from each `NodeId` in the `Tree` to its `NodeIndex` in the graph.
+    ///
+    /// `Node`s are added to the graph in pre-order, and each `Node`'s outgoing edges are added in
+    /// the same order as its children.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append(2).node_id();
+    /// root.append(3);
+    ///
+    /// let (graph, indices) = tree.to_petgraph();
+    /// assert_eq!(graph.node_count(), 3);
+    /// assert_eq!(graph.edge_count(), 2);
+    /// assert_eq!(*graph[indices[&two_id]], 2);
+    /// ```
+    ///
+    pub fn to_petgraph(&self) -> (DiGraph<&T, ()>, HashMap<NodeId, NodeIndex>) {
+        let mut graph = DiGraph::new();
+        let mut indices = HashMap::new();
+
+        if let Some(root) = self.root() {
+            for node in root.traverse_pre_order() {
+                let index = graph.add_node(node.data());
+                if let Some(parent) = node.parent() {
+                    graph.add_edge(indices[&parent.node_id()], index, ());
+                }
+                indices.insert(node.node_id(), index);
+            }
+        }
+
+        (graph, indices)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod graph_tests {
+    use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+    use petgraph::algo::is_cyclic_directed;
+    use petgraph::Direction;
+
+    #[test]
+    fn to_petgraph_empty() {
+        let tree: Tree<i32> = Tree::new();
+        let (graph, indices) = tree.to_petgraph();
+        assert_eq!(graph.node_count(), 0);
+        assert!(indices.is_empty());
+    }
+
+    #[test]
+    fn to_petgraph() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        let two_id = one.append(2).node_id();
+        let one_id = one.node_id();
+        root.append(3);
+        let root_id = root.node_id();
+
+        let (graph, indices) = tree.to_petgraph();
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 3);
+        assert!(!is_cyclic_directed(&graph));
+
+        for node in tree.root().unwrap().traverse_pre_order() {
+            assert_eq!(*graph[indices[&node.node_id()]], *node.data());
+        }
+
+        let incoming: Vec<_> = graph
+            .neighbors_directed(indices[&root_id], Direction::Incoming)
+            .collect();
+        assert!(incoming.is_empty());
+        assert!(graph.contains_edge(indices[&one_id], indices[&two_id]));
+    }
+}
@@ -2,6 +2,8 @@ use crate::node::Node;
 use crate::slab;
 use crate::NodeId;
 use snowflake::ProcessUniqueId;
+use std::collections::HashMap;
+use std::collections::TryReserveError;
 
 ///
 /// A wrapper around a Slab containing Node<T> values.
@@ -26,6 +28,28 @@ impl<T> CoreTree<T> {
         self.slab.capacity()
     }
 
+    pub(crate) fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.slab.try_reserve(additional)
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.slab.reserve(additional);
+    }
+
+    pub(crate) fn reserve_slot(&mut self) {
+        self.slab.reserve_slot();
+    }
+
+    pub(crate) fn try_reserve_slot(&mut self) -> Result<(), TryReserveError> {
+        self.slab.try_reserve_slot()
+    }
+
+    /// Empties the tree and gives it a fresh id, so no existing `NodeId` can refer into it.
+    pub(crate) fn clear(&mut self) {
+        self.id = ProcessUniqueId::new();
+        self.slab.clear();
+    }
+
     pub(crate) fn insert(&mut self, data: T) -> NodeId {
         let key = self.slab.insert(Node::new(data));
         self.new_node_id(key)
@@ -47,6 +71,149 @@ impl<T> CoreTree<T> {
             .and_then(move |id| self.slab.get_mut(id.index))
     }
 
+    pub(crate) fn len(&self) -> usize {
+        self.slab.len()
+    }
+
+    pub(crate) fn node_ids(&self) -> Vec<NodeId> {
+        self.slab
+            .indices()
+            .map(|index| self.new_node_id(index))
+            .collect()
+    }
+
+    pub(crate) fn slot_size(&self) -> usize {
+        self.slab.slot_size()
+    }
+
+    /// Compacts the slab and returns the new `NodeId` of every `Node`, keyed by its old one.
+    pub(crate) fn compact(&mut self) -> HashMap<NodeId, NodeId> {
+        let moves = self.slab.compact();
+        self.apply_moves(moves)
+    }
+
+    /// Lays the nodes out in the order given, which must list every node exactly once, and
+    /// returns a map from every node's old id to its new one.
+    pub(crate) fn reorder(&mut self, order: &[NodeId]) -> HashMap<NodeId, NodeId> {
+        let order: Vec<slab::Index> = order.iter().map(|node_id| node_id.index).collect();
+        let moves = self.slab.reorder(&order);
+        self.apply_moves(moves)
+    }
+
+    /// Points every node's relatives at the new slots of the nodes that moved, returning a map
+    /// from every node's old id to its new one.
+    fn apply_moves(&mut self, moves: Vec<(slab::Index, slab::Index)>) -> HashMap<NodeId, NodeId> {
+        let moves: HashMap<NodeId, NodeId> = moves
+            .into_iter()
+            .map(|(old, new)| (self.new_node_id(old), self.new_node_id(new)))
+            .collect();
+        let moved: HashMap<NodeId, NodeId> = moves.iter().map(|(&old, &new)| (new, old)).collect();
+        let remap = |node_id: &mut Option<NodeId>| {
+            if let Some(new_id) = node_id.and_then(|id| moves.get(&id)) {
+                *node_id = Some(*new_id);
+            }
+        };
+
+        let tree_id = self.id;
+        let mut node_ids = HashMap::with_capacity(self.slab.capacity());
+        self.slab.for_each_mut(|index, node| {
+            let new_id = NodeId { tree_id, index };
+            node_ids.insert(moved.get(&new_id).copied().unwrap_or(new_id), new_id);
+
+            let relatives = &mut node.relatives;
+            remap(&mut relatives.parent);
+            remap(&mut relatives.prev_sibling);
+            remap(&mut relatives.next_sibling);
+            remap(&mut relatives.first_child);
+            remap(&mut relatives.last_child);
+        });
+        node_ids
+    }
+
+    /// Copies the tree slot for slot under the same id, mapping each node's data with `f`, so
+    /// every `NodeId` refers to the same node in both.
+    pub(crate) fn map<'a, U, F: FnMut(&'a T) -> U>(&'a self, mut f: F) -> CoreTree<U> {
+        CoreTree {
+            id: self.id,
+            slab: self.slab.map(|node| Node {
+                data: f(&node.data),
+                relatives: node.relatives,
+            }),
+        }
+    }
+
+    /// Copies the tree slot for slot into `target` under a fresh id, reusing its allocation,
+    /// with room for `extra` more nodes.  Every node keeps its slot, but `NodeId`s of this tree
+    /// don't work on the copy.
+    pub(crate) fn clone_into(&self, target: &mut CoreTree<T>, extra: usize)
+    where
+        T: Clone,
+    {
+        self.slab.clone_into(&mut target.slab, extra);
+        let tree_id = ProcessUniqueId::new();
+        target.id = tree_id;
+        let retag = |node_id: &mut Option<NodeId>| {
+            if let Some(node_id) = node_id {
+                node_id.tree_id = tree_id;
+            }
+        };
+        target.slab.for_each_mut(|_, node| {
+            let relatives = &mut node.relatives;
+            retag(&mut relatives.parent);
+            retag(&mut relatives.prev_sibling);
+            retag(&mut relatives.next_sibling);
+            retag(&mut relatives.first_child);
+            retag(&mut relatives.last_child);
+        });
+    }
+
+    /// Consumes the tree, mapping each node's data with `f` in parallel while keeping every
+    /// `NodeId` valid.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn par_map<U, F>(self, f: F) -> CoreTree<U>
+    where
+        T: Send,
+        U: Send,
+        F: Fn(T) -> U + Sync + Send,
+    {
+        CoreTree {
+            id: self.id,
+            slab: self.slab.par_map(|node| Node {
+                data: f(node.data),
+                relatives: node.relatives,
+            }),
+        }
+    }
+
+    pub(crate) fn id(&self) -> ProcessUniqueId {
+        self.id
+    }
+
+    pub(crate) fn snapshot(&mut self) -> slab::SnapshotMark
+    where
+        T: Clone,
+    {
+        self.slab.snapshot(Node::clone)
+    }
+
+    pub(crate) fn restore(&mut self, mark: slab::SnapshotMark) -> bool {
+        self.slab.restore(mark)
+    }
+
+    pub(crate) fn clear_snapshots(&mut self) {
+        self.slab.clear_snapshots()
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_slab(id: ProcessUniqueId, slab: slab::Slab<Node<T>>) -> CoreTree<T> {
+        CoreTree { id, slab }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn slab(&self) -> &slab::Slab<Node<T>> {
+        &self.slab
+    }
+
     fn new_node_id(&self, index: slab::Index) -> NodeId {
         NodeId {
             tree_id: self.id,
@@ -0,0 +1,458 @@
+//!
+//! Reading and writing `Tree`s as `id,parent_id,payload` CSV (or TSV) adjacency lists, e.g.
+//!
+//! ```text
+//! id,parent_id,payload
+//! 1,,root
+//! 2,1,a
+//! 3,2,b
+//! 4,1,"c, with a comma"
+//! ```
+//!
+//! Each record holds one `Node`: an id that is unique among the records, the id of its parent
+//! (empty for the root), and its data.  Records can come in any order, and children keep the
+//! order their records have.  Fields containing the delimiter, a `"` or a line break are written
+//! in double quotes (with `"` doubled inside them), as in RFC 4180.
+//!
+
+use crate::rows::RowError;
+use crate::rows::TreeRow;
+use crate::tree::Tree;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+const HEADER: [&str; 3] = ["id", "parent_id", "payload"];
+
+///
+/// The delimiter and header settings used to read and write adjacency lists.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CsvFormat {
+    delimiter: char,
+    header: bool,
+}
+
+impl CsvFormat {
+    ///
+    /// Comma-separated records, with a header record.
+    ///
+    /// ```
+    /// use slab_tree::csv::CsvFormat;
+    ///
+    /// assert_eq!(CsvFormat::csv(), CsvFormat::default());
+    /// ```
+    ///
+    pub fn csv() -> CsvFormat {
+        CsvFormat {
+            delimiter: ',',
+            header: true,
+        }
+    }
+
+    ///
+    /// Tab-separated records, with a header record.
+    ///
+    /// ```
+    /// use slab_tree::csv::CsvFormat;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::new().with_root("root").build();
+    ///
+    /// let mut s = String::new();
+    /// tree.write_csv(&mut s, CsvFormat::tsv(), |data| data.to_string()).unwrap();
+    /// assert_eq!(s, "id\tparent_id\tpayload\n0\t\troot\n");
+    /// ```
+    ///
+    pub fn tsv() -> CsvFormat {
+        CsvFormat {
+            delimiter: '\t',
+            header: true,
+        }
+    }
+
+    ///
+    /// Sets whether the first record is a header.  A header is written as
+    /// `id,parent_id,payload`, and skipped whatever it holds when reading.
+    ///
+    /// ```
+    /// use slab_tree::csv::CsvFormat;
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let tree = Tree::from_csv("1,,root\n", CsvFormat::csv().with_header(false), |payload| {
+    ///     Some(payload.to_string())
+    /// })
+    /// .unwrap();
+    /// assert_eq!(tree.root().unwrap().data(), "root");
+    /// ```
+    ///
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+impl Default for CsvFormat {
+    fn default() -> CsvFormat {
+        CsvFormat::csv()
+    }
+}
+
+///
+/// Describes the ways in which reading an adjacency list can fail.  Lines are numbered from 1,
+/// and a record is referred to by the line it starts on.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvError {
+    ///
+    /// The quoted field starting on the given line is never closed.
+    ///
+    UnterminatedQuote(usize),
+
+    ///
+    /// The record on the given line doesn't have exactly three fields.
+    ///
+    FieldCount {
+        /// The line the record starts on.
+        line: usize,
+        /// The number of fields it has.
+        count: usize,
+    },
+
+    ///
+    /// The user-provided parser rejected the payload of the record on the given line.
+    ///
+    InvalidData(usize),
+
+    ///
+    /// The record on the given line reuses the id of an earlier record.
+    ///
+    DuplicateId(usize),
+
+    ///
+    /// The record on the given line refers to a parent no record has.
+    ///
+    MissingParent(usize),
+
+    ///
+    /// There are records, but none of them is a root (i.e. has an empty parent id).
+    ///
+    NoRoot,
+
+    ///
+    /// The record on the given line is a second root.
+    ///
+    MultipleRoots(usize),
+
+    ///
+    /// The record on the given line can't be reached from the root, because it is part of (or
+    /// below) a cycle of parents.
+    ///
+    Cycle(usize),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::UnterminatedQuote(line) => {
+                write!(f, "the quoted field on line {} is never closed", line)
+            }
+            CsvError::FieldCount { line, count } => write!(
+                f,
+                "the record on line {} has {} fields instead of 3",
+                line, count
+            ),
+            CsvError::InvalidData(line) => write!(f, "invalid payload on line {}", line),
+            CsvError::DuplicateId(line) => write!(f, "the id on line {} is used before", line),
+            CsvError::MissingParent(line) => {
+                write!(f, "the parent on line {} doesn't exist", line)
+            }
+            CsvError::NoRoot => write!(f, "no record is a root"),
+            CsvError::MultipleRoots(line) => {
+                write!(f, "the record on line {} is a second root", line)
+            }
+            CsvError::Cycle(line) => write!(f, "the record on line {} is part of a cycle", line),
+        }
+    }
+}
+
+impl Error for CsvError {}
+
+impl<T> Tree<T> {
+    ///
+    /// Reads a `Tree` from an adjacency list in the given format, as described in the `csv`
+    /// module, using `payload` to parse each `Node`'s data.  If `payload` returns `None`, reading
+    /// stops with `CsvError::InvalidData`.  An input without records produces an empty `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::csv::{CsvError, CsvFormat};
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let input = "id,parent_id,payload\n\
+    ///              b,a,1\n\
+    ///              a,,0\n\
+    ///              c,a,\"2\"\n";
+    /// let tree = Tree::from_csv(input, CsvFormat::csv(), |payload| payload.parse::<u32>().ok());
+    ///
+    /// let tree = tree.unwrap();
+    /// let root = tree.root().expect("root doesn't exist?");
+    /// assert_eq!(root.data(), &0);
+    /// let children: Vec<u32> = root.children().map(|node| *node.data()).collect();
+    /// assert_eq!(children, vec![1, 2]);
+    ///
+    /// let two_roots = "id,parent_id,payload\na,,0\nb,,1\n";
+    /// let error = Tree::from_csv(two_roots, CsvFormat::csv(), |payload| payload.parse::<u32>().ok());
+    /// assert_eq!(error.unwrap_err(), CsvError::MultipleRoots(3));
+    /// ```
+    ///
+    pub fn from_csv<F>(input: &str, format: CsvFormat, mut payload: F) -> Result<Tree<T>, CsvError>
+    where
+        F: FnMut(&str) -> Option<T>,
+    {
+        let mut records = parse_records(input, format.delimiter)?;
+        if format.header && !records.is_empty() {
+            records.remove(0);
+        }
+
+        let mut positions: HashMap<&str, usize> = HashMap::with_capacity(records.len());
+        for (position, (line, fields)) in records.iter().enumerate() {
+            if fields.len() != 3 {
+                return Err(CsvError::FieldCount {
+                    line: *line,
+                    count: fields.len(),
+                });
+            }
+            if positions.insert(&fields[0], position).is_some() {
+                return Err(CsvError::DuplicateId(*line));
+            }
+        }
+
+        let mut rows = Vec::with_capacity(records.len());
+        for (position, (line, fields)) in records.iter().enumerate() {
+            let parent = match fields[1].as_str() {
+                "" => None,
+                parent => Some(
+                    *positions
+                        .get(parent)
+                        .ok_or(CsvError::MissingParent(*line))?,
+                ),
+            };
+            rows.push(TreeRow {
+                id: position,
+                parent,
+                sibling_index: position,
+                data: payload(&fields[2]).ok_or(CsvError::InvalidData(*line))?,
+            });
+        }
+
+        Tree::from_rows(rows).map_err(|error| match error {
+            RowError::NoRoot => CsvError::NoRoot,
+            RowError::MultipleRoots(position) => CsvError::MultipleRoots(records[position].0),
+            RowError::Cycle(position) => CsvError::Cycle(records[position].0),
+            RowError::DuplicateId(_)
+            | RowError::MissingParent { .. }
+            | RowError::DuplicateSiblingIndex { .. } => {
+                unreachable!("ids, parents and sibling indexes were checked above")
+            }
+        })
+    }
+
+    ///
+    /// Writes the root's sub-tree as an adjacency list in the given format, using `payload` to
+    /// describe each `Node`'s data.  The `Node`s are written in pre-order, with their position in
+    /// that order as their id.  Only the header (if any) is written if the `Tree` is empty.
+    ///
+    /// ```
+    /// use slab_tree::csv::CsvFormat;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("a").append("b");
+    /// root.append("say \"hi\", c");
+    ///
+    /// let mut s = String::new();
+    /// tree.write_csv(&mut s, CsvFormat::csv(), |data| data.to_string()).unwrap();
+    /// assert_eq!(s, "\
+    /// id,parent_id,payload
+    /// 0,,root
+    /// 1,0,a
+    /// 2,1,b
+    /// 3,0,\"say \"\"hi\"\", c\"
+    /// ");
+    /// ```
+    ///
+    pub fn write_csv<W, F>(&self, w: &mut W, format: CsvFormat, mut payload: F) -> fmt::Result
+    where
+        W: fmt::Write,
+        F: FnMut(&T) -> String,
+    {
+        if format.header {
+            write_record(w, format.delimiter, &HEADER)?;
+        }
+        let root = match self.root() {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+
+        let mut next_id = 0;
+        // (node, its parent's id); children are pushed last to first so they're numbered in
+        // order
+        let mut stack = vec![(root, None)];
+        while let Some((node, parent)) = stack.pop() {
+            let id = next_id;
+            next_id += 1;
+            let parent = parent.map_or_else(String::new, |parent: usize| parent.to_string());
+            write_record(
+                w,
+                format.delimiter,
+                &[&id.to_string(), &parent, &payload(node.data())],
+            )?;
+            let start = stack.len();
+            stack.extend(node.children().map(|child| (child, Some(id))));
+            stack[start..].reverse();
+        }
+        Ok(())
+    }
+}
+
+fn write_record<W: fmt::Write>(w: &mut W, delimiter: char, fields: &[&str]) -> fmt::Result {
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            w.write_char(delimiter)?;
+        }
+        if field.contains([delimiter, '"', '\n', '\r']) {
+            write!(w, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            w.write_str(field)?;
+        }
+    }
+    w.write_char('\n')
+}
+
+/// Splits `input` into records of fields, along with the line each record starts on.  Empty
+/// lines are skipped.
+fn parse_records(input: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let mut records = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        loop {
+            match chars.next() {
+                Some('"') if field.is_empty() => {
+                    let quote_line = line;
+                    loop {
+                        match chars.next() {
+                            Some('"') if chars.peek() == Some(&'"') => {
+                                chars.next();
+                                field.push('"');
+                            }
+                            Some('"') => break,
+                            Some(c) => {
+                                if c == '\n' {
+                                    line += 1;
+                                }
+                                field.push(c);
+                            }
+                            None => return Err(CsvError::UnterminatedQuote(quote_line)),
+                        }
+                    }
+                }
+                Some(c) if c == delimiter => fields.push(std::mem::take(&mut field)),
+                Some('\r') if chars.peek() == Some(&'\n') => {}
+                Some('\n') | None => {
+                    line += 1;
+                    break;
+                }
+                Some(c) => field.push(c),
+            }
+        }
+        if fields.is_empty() && field.is_empty() {
+            continue;
+        }
+        fields.push(field);
+        records.push((start_line, fields));
+    }
+    Ok(records)
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod csv_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    fn read(input: &str) -> Result<Tree<String>, CsvError> {
+        Tree::from_csv(input, CsvFormat::csv(), |payload| Some(payload.to_string()))
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut tree = TreeBuilder::new().with_root("root".to_string()).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut a = root.append("a,\"quoted\"".to_string());
+        a.append("multi\nline".to_string());
+        a.append(String::new());
+        root.append("tab\there".to_string());
+
+        for format in [CsvFormat::csv(), CsvFormat::tsv().with_header(false)] {
+            let mut s = String::new();
+            tree.write_csv(&mut s, format, |data| data.clone()).unwrap();
+            let read = Tree::from_csv(&s, format, |payload| Some(payload.to_string())).unwrap();
+            assert!(read
+                .root()
+                .unwrap()
+                .is_isomorphic_to(&tree.root().unwrap(), |a, b| a == b));
+        }
+    }
+
+    #[test]
+    fn reads_crlf_and_skips_blank_lines() {
+        let tree =
+            read("id,parent_id,payload\r\n\r\n1,,root\r\n2,1,\"a\r\nb\"\r\n\n3,1,c").unwrap();
+        let root = tree.root().unwrap();
+        let children: Vec<&str> = root.children().map(|node| node.data().as_str()).collect();
+        assert_eq!(children, vec!["a\r\nb", "c"]);
+    }
+
+    #[test]
+    fn empty_inputs() {
+        assert!(read("").unwrap().root().is_none());
+        assert!(read("id,parent_id,payload\n").unwrap().root().is_none());
+
+        let mut s = String::new();
+        Tree::<i32>::new()
+            .write_csv(&mut s, CsvFormat::csv(), |data| data.to_string())
+            .unwrap();
+        assert_eq!(s, "id,parent_id,payload\n");
+    }
+
+    #[test]
+    fn errors() {
+        let header = "id,parent_id,payload\n";
+        let cases = vec![
+            ("1,,\"root\n", CsvError::UnterminatedQuote(2)),
+            (
+                "1,,root,extra\n",
+                CsvError::FieldCount { line: 2, count: 4 },
+            ),
+            ("1,,root\n1,1,again\n", CsvError::DuplicateId(3)),
+            ("1,,root\n2,9,orphan\n", CsvError::MissingParent(3)),
+            ("1,2,a\n2,1,b\n", CsvError::NoRoot),
+            ("1,,root\n\n2,,root\n", CsvError::MultipleRoots(4)),
+            ("1,,root\n2,3,a\n3,2,\"b\nc\"\n4,3,d\n", CsvError::Cycle(3)),
+        ];
+        for (body, error) in cases {
+            assert_eq!(read(&format!("{}{}", header, body)).unwrap_err(), error);
+        }
+
+        let invalid = Tree::from_csv("1,,x\n", CsvFormat::csv().with_header(false), |payload| {
+            payload.parse::<i32>().ok()
+        });
+        assert_eq!(invalid.unwrap_err(), CsvError::InvalidData(1));
+    }
+}
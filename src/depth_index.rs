@@ -0,0 +1,172 @@
+use crate::observer::TreeIndex;
+use crate::observer::TreeObserver;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::any::Any;
+use std::collections::HashMap;
+
+/// Keeps the depth of every `Node`, i.e. the number of ancestors it has.
+#[derive(Default)]
+pub(crate) struct DepthIndex {
+    depths: HashMap<NodeId, usize>,
+}
+
+impl DepthIndex {
+    pub(crate) fn get(&self, node_id: NodeId) -> Option<usize> {
+        self.depths.get(&node_id).copied()
+    }
+
+    fn depth_under<T>(&self, tree: &Tree<T>, node_id: NodeId) -> usize {
+        tree.get_node_relatives(node_id)
+            .parent
+            .and_then(|parent_id| self.get(parent_id))
+            .map_or(0, |depth| depth + 1)
+    }
+
+    /// Recomputes the depths of the sub-tree rooted at `node_id`, parents before children.
+    fn update_subtree<T>(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        let top = tree.get(node_id).expect("updated node must exist");
+        for node in top.traverse_pre_order() {
+            let depth = self.depth_under(tree, node.node_id());
+            self.depths.insert(node.node_id(), depth);
+        }
+    }
+}
+
+impl<T> TreeObserver<T> for DepthIndex {
+    fn on_insert(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        let depth = self.depth_under(tree, node_id);
+        self.depths.insert(node_id, depth);
+    }
+
+    fn on_remove(&mut self, _tree: &Tree<T>, node_id: NodeId) {
+        self.depths.remove(&node_id);
+    }
+
+    fn on_move(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        // moving a sub-tree within the same level (e.g. among its siblings) changes no depths
+        if self.get(node_id) != Some(self.depth_under(tree, node_id)) {
+            self.update_subtree(tree, node_id);
+        }
+    }
+}
+
+impl<T> TreeIndex<T> for DepthIndex {
+    fn rebuild(&mut self, tree: &Tree<T>) {
+        self.depths.clear();
+        for node_id in tree.all_node_ids() {
+            if tree.get_node_relatives(node_id).parent.is_none() {
+                self.update_subtree(tree, node_id);
+            }
+        }
+    }
+
+    fn remap(&mut self, node_ids: &HashMap<NodeId, NodeId>) {
+        self.depths = self
+            .depths
+            .drain()
+            .map(|(node_id, depth)| {
+                let node_id = node_ids.get(&node_id).copied().unwrap_or(node_id);
+                (node_id, depth)
+            })
+            .collect();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Makes the `Tree` keep the depth of every `Node`, so that `NodeRef::depth` can read it in
+    /// constant time instead of walking up through the `Node`'s ancestors.  Inserting a `Node`
+    /// only sets its own depth, while moving a `Node` to a different level updates its whole
+    /// sub-tree.  Setting up the depths on a `Tree` which already keeps them does nothing.
+    ///
+    /// ```
+    /// use slab_tree::reparent::InsertPosition;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build().with_depths();
+    /// let root_id = tree.root_id().unwrap();
+    /// let one_id = tree.root_mut().unwrap().append(1).node_id();
+    /// let two_id = tree.get_mut(one_id).unwrap().append(2).node_id();
+    /// let three_id = tree.get_mut(two_id).unwrap().append(3).node_id();
+    /// assert_eq!(tree.get(three_id).unwrap().depth(), 3);
+    ///
+    /// tree.move_subtree(two_id, root_id, InsertPosition::Last).unwrap();
+    /// assert_eq!(tree.get(two_id).unwrap().depth(), 1);
+    /// assert_eq!(tree.get(three_id).unwrap().depth(), 2);
+    /// ```
+    ///
+    pub fn with_depths(mut self) -> Tree<T> {
+        if self.depth_index().is_none() {
+            let mut depths = DepthIndex::default();
+            depths.rebuild(&self);
+            self.hooks.add_index(Box::new(depths));
+        }
+        self
+    }
+
+    pub(crate) fn depth_index(&self) -> Option<&DepthIndex> {
+        self.hooks
+            .indexes()
+            .find_map(|index| index.as_any().downcast_ref::<DepthIndex>())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod depth_index_tests {
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::reparent::InsertPosition;
+    use crate::tree::Tree;
+
+    // compares every cached depth against a fresh count of the ancestors
+    fn assert_depths(tree: &Tree<i32>) {
+        let depths = tree.depth_index().unwrap();
+        for node_id in tree.all_node_ids() {
+            let node = tree.get(node_id).unwrap();
+            assert_eq!(depths.get(node_id), Some(node.ancestors().count()));
+        }
+    }
+
+    #[test]
+    fn follows_edits() {
+        let mut tree = Tree::unfold(4, |n| (n, (0..n).collect())).with_depths();
+        assert_depths(&tree);
+        let root_id = tree.root_id().unwrap();
+        let three_id = tree.root().unwrap().last_child().unwrap().node_id();
+        let two_id = tree.get(three_id).unwrap().last_child().unwrap().node_id();
+
+        tree.get_mut(two_id).unwrap().prepend(5).append(6);
+        assert_depths(&tree);
+        tree.move_subtree(two_id, root_id, InsertPosition::First)
+            .unwrap();
+        assert_depths(&tree);
+        tree.move_subtree(three_id, two_id, InsertPosition::Last)
+            .unwrap();
+        assert_depths(&tree);
+        tree.remove(two_id, OrphanChildren);
+        assert_depths(&tree);
+        tree.set_root(7);
+        assert_depths(&tree);
+        tree.remove(three_id, DropChildren);
+        assert_depths(&tree);
+    }
+
+    #[test]
+    fn survives_compaction() {
+        let tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let one_id = tree.root().unwrap().children().nth(1).unwrap().node_id();
+        let mut tree = tree.with_depths().with_depths();
+        tree.remove(one_id, DropChildren);
+        tree.compact();
+        assert_depths(&tree);
+    }
+}
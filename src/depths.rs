@@ -0,0 +1,300 @@
+//!
+//! Building `Tree`s from flat `(depth, data)` rows.
+//!
+//! Indentation-based formats and outline exports list the `Node`s of a tree in pre-order along
+//! with their depth, e.g.:
+//!
+//! ```text
+//! 0 root
+//! 1   a
+//! 2     b
+//! 1   c
+//! ```
+//!
+//! The first row is the root (at depth 0) and every later row is a child of the closest earlier
+//! row one level up, so a row can be at most one level deeper than the row before it.
+//!
+//! `NodeMut::append_flattened` attaches rows like these beneath an existing `Node` instead, with
+//! depths relative to it: rows at depth 0 become its new children.
+//!
+
+use crate::node::NodeMut;
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use std::error::Error;
+use std::fmt;
+
+///
+/// Describes the ways in which building a `Tree` with `Tree::from_depths` can fail.  Rows are
+/// numbered from zero.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DepthError {
+    ///
+    /// The given row is more than one level deeper than the row before it (or, for the first
+    /// row, isn't at depth 0).
+    ///
+    DepthJump {
+        /// The row with the unexpected depth.
+        row: usize,
+        /// The depth of the row.
+        depth: usize,
+        /// The deepest the row could have been.
+        max_depth: usize,
+    },
+
+    ///
+    /// The given row is a second row at depth 0.
+    ///
+    MultipleRoots(usize),
+}
+
+impl fmt::Display for DepthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepthError::DepthJump {
+                row,
+                depth,
+                max_depth,
+            } => write!(
+                f,
+                "row {} is at depth {}, but can be at most at depth {}",
+                row, depth, max_depth
+            ),
+            DepthError::MultipleRoots(row) => write!(f, "row {} is a second root", row),
+        }
+    }
+}
+
+impl Error for DepthError {}
+
+impl<T> Tree<T> {
+    ///
+    /// Builds a `Tree` from `(depth, data)` rows listed in pre-order, as described in the
+    /// `depths` module.  No rows produce an empty `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::depths::DepthError;
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let rows = vec![(0, "root"), (1, "a"), (2, "b"), (1, "c")];
+    /// let tree = Tree::from_depths(rows).unwrap();
+    ///
+    /// let root = tree.root().expect("root doesn't exist?");
+    /// let children: Vec<&str> = root.children().map(|node| *node.data()).collect();
+    /// assert_eq!(children, vec!["a", "c"]);
+    ///
+    /// let a = root.first_child().unwrap();
+    /// assert_eq!(a.first_child().unwrap().data(), &"b");
+    ///
+    /// let error = Tree::from_depths(vec![(0, "root"), (2, "too deep")]).unwrap_err();
+    /// assert_eq!(error, DepthError::DepthJump { row: 1, depth: 2, max_depth: 1 });
+    /// ```
+    ///
+    pub fn from_depths<I>(rows: I) -> Result<Tree<T>, DepthError>
+    where
+        I: IntoIterator<Item = (usize, T)>,
+    {
+        let rows = rows.into_iter();
+        let mut tree = TreeBuilder::new().with_capacity(rows.size_hint().0).build();
+
+        // the most recent node at each depth, from the root down to the previous row
+        let mut path: Vec<NodeId> = Vec::new();
+
+        for (row, (depth, data)) in rows.enumerate() {
+            if depth > path.len() {
+                return Err(DepthError::DepthJump {
+                    row,
+                    depth,
+                    max_depth: path.len(),
+                });
+            }
+            if depth == 0 && row > 0 {
+                return Err(DepthError::MultipleRoots(row));
+            }
+
+            path.truncate(depth);
+            let node_id = match path.last() {
+                Some(&parent_id) => tree
+                    .get_mut(parent_id)
+                    .expect("path node must exist")
+                    .append(data)
+                    .node_id(),
+                None => tree.set_root(data),
+            };
+            path.push(node_id);
+        }
+
+        Ok(tree)
+    }
+}
+
+impl<'a, T> NodeMut<'a, T> {
+    ///
+    /// Appends `(depth, data)` rows listed in pre-order beneath this `Node`, as described in the
+    /// `depths` module, with depths relative to this `Node`: rows at depth 0 are appended as
+    /// its children, rows at depth 1 as their children, and so on.
+    ///
+    /// The rows are checked before anything is inserted, so on an error the `Tree` is left
+    /// unchanged; then room is made for all of them at once.  The first row has to be at depth
+    /// 0, and `DepthError::MultipleRoots` is never returned.
+    ///
+    /// ```
+    /// use slab_tree::depths::DepthError;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append("first");
+    /// root.append_flattened(vec![(0, "a"), (1, "b"), (0, "c")]).unwrap();
+    ///
+    /// let root = tree.root().expect("root doesn't exist?");
+    /// let children: Vec<&str> = root.children().map(|node| *node.data()).collect();
+    /// assert_eq!(children, vec!["first", "a", "c"]);
+    /// assert_eq!(root.children().nth(1).unwrap().first_child().unwrap().data(), &"b");
+    ///
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let error = root.append_flattened(vec![(0, "d"), (2, "too deep")]).unwrap_err();
+    /// assert_eq!(error, DepthError::DepthJump { row: 1, depth: 2, max_depth: 1 });
+    /// assert_eq!(tree.root().unwrap().children().count(), 3);
+    /// ```
+    ///
+    pub fn append_flattened<I>(&mut self, rows: I) -> Result<(), DepthError>
+    where
+        I: IntoIterator<Item = (usize, T)>,
+    {
+        let rows: Vec<(usize, T)> = rows.into_iter().collect();
+
+        let mut max_depth = 0;
+        for (row, &(depth, _)) in rows.iter().enumerate() {
+            if depth > max_depth {
+                return Err(DepthError::DepthJump {
+                    row,
+                    depth,
+                    max_depth,
+                });
+            }
+            max_depth = depth + 1;
+        }
+
+        // this node, then the most recent node at each depth down to the previous row
+        let mut path: Vec<NodeId> = vec![self.node_id()];
+        let tree = self.tree_mut();
+        tree.core_tree.reserve(rows.len());
+        for (depth, data) in rows {
+            path.truncate(depth + 1);
+            let parent_id = *path.last().expect("path starts at this node");
+            let node_id = tree.core_tree.insert(data);
+            tree.link_before(node_id, parent_id, None);
+            tree.notify_insert(node_id);
+            path.push(node_id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod depths_tests {
+    use super::*;
+
+    fn pre_order(tree: &Tree<i32>) -> Vec<(usize, i32)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| (node.ancestors().count(), *node.data()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn round_trip() {
+        let rows = vec![
+            (0, 0),
+            (1, 1),
+            (2, 2),
+            (2, 3),
+            (3, 4),
+            (1, 5),
+            (1, 6),
+            (2, 7),
+        ];
+        let tree = Tree::from_depths(rows.clone()).unwrap();
+        assert_eq!(pre_order(&tree), rows);
+        assert_eq!(tree.capacity(), rows.len());
+    }
+
+    #[test]
+    fn empty() {
+        let tree = Tree::<i32>::from_depths(Vec::new()).unwrap();
+        assert!(tree.root().is_none());
+    }
+
+    #[test]
+    fn append_flattened_under_a_node() {
+        let mut tree = Tree::from_depths(vec![(0, 0), (1, 1), (1, 2)]).unwrap();
+        let one_id = tree.root().unwrap().first_child().unwrap().node_id();
+        let rows = vec![(0, 3), (1, 4), (2, 5), (1, 6), (0, 7)];
+        tree.get_mut(one_id)
+            .unwrap()
+            .append_flattened(rows)
+            .unwrap();
+
+        let expected = vec![
+            (0, 0),
+            (1, 1),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (3, 6),
+            (2, 7),
+            (1, 2),
+        ];
+        assert_eq!(pre_order(&tree), expected);
+        assert_eq!(tree.capacity(), expected.len());
+
+        tree.root_mut()
+            .unwrap()
+            .append_flattened(Vec::new())
+            .unwrap();
+        assert_eq!(
+            tree.root_mut()
+                .unwrap()
+                .append_flattened(vec![(1, 8)])
+                .unwrap_err(),
+            DepthError::DepthJump {
+                row: 0,
+                depth: 1,
+                max_depth: 0
+            }
+        );
+        assert_eq!(pre_order(&tree), expected);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Tree::from_depths(vec![(1, 0)]).unwrap_err(),
+            DepthError::DepthJump {
+                row: 0,
+                depth: 1,
+                max_depth: 0
+            }
+        );
+        assert_eq!(
+            Tree::from_depths(vec![(0, 0), (1, 1), (3, 2)]).unwrap_err(),
+            DepthError::DepthJump {
+                row: 2,
+                depth: 3,
+                max_depth: 2
+            }
+        );
+        assert_eq!(
+            Tree::from_depths(vec![(0, 0), (1, 1), (0, 2)]).unwrap_err(),
+            DepthError::MultipleRoots(2)
+        );
+    }
+}
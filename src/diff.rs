@@ -0,0 +1,351 @@
+//!
+//! Structural diffs between two `Tree`s, and a unified text view of them.
+//!
+//! `Node`s are matched by a key: the roots are matched if their keys are equal, and the children
+//! of two matched `Node`s are aligned with a longest common subsequence of their keys, so
+//! inserting or removing a child doesn't make every sibling after it look different.  A matched
+//! pair whose data differs is `Changed`; an unmatched `Node` is `Removed` (or `Added`) along with
+//! its whole sub-tree.  `Tree::write_diff` writes the result as an outline, one line per `Node`:
+//!
+//! ```text
+//!   ("root", 0)
+//! ~     ("a", 1) -> ("a", 2)
+//! -     ("b", 1)
+//! -         ("c", 1)
+//! +     ("d", 1)
+//! ```
+//!
+
+use crate::node::NodeRef;
+use crate::tree::Tree;
+use std::fmt;
+
+///
+/// How a single `Node` differs between the two `Tree`s given to `Tree::diff_by_key`.
+///
+#[derive(Debug)]
+pub enum Change<'a, T> {
+    ///
+    /// The `Node` is in both `Tree`s, with equal data.  Holds the `Node` from the new `Tree`.
+    ///
+    Unchanged(NodeRef<'a, T>),
+
+    ///
+    /// The `Node` is in both `Tree`s, but its data differs.
+    ///
+    Changed {
+        /// The `Node` in the old `Tree`.
+        old: NodeRef<'a, T>,
+        /// The `Node` in the new `Tree`.
+        new: NodeRef<'a, T>,
+    },
+
+    ///
+    /// The `Node` is only in the old `Tree`.
+    ///
+    Removed(NodeRef<'a, T>),
+
+    ///
+    /// The `Node` is only in the new `Tree`.
+    ///
+    Added(NodeRef<'a, T>),
+}
+
+///
+/// One line of a diff: a `Change` and how deep its `Node` is (the root being at depth 0).
+///
+#[derive(Debug)]
+pub struct DiffLine<'a, T> {
+    /// The depth of the `Node` in whichever `Tree` it comes from.
+    pub depth: usize,
+    /// How the `Node` differs between the two `Tree`s.
+    pub change: Change<'a, T>,
+}
+
+impl<'a, T> DiffLine<'a, T> {
+    ///
+    /// Returns the marker `Tree::write_diff` starts the line with: `' '` for `Unchanged`, `'~'`
+    /// for `Changed`, `'-'` for `Removed` and `'+'` for `Added`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let old = TreeBuilder::new().with_root(1).build();
+    /// let new = TreeBuilder::new().with_root(2).build();
+    ///
+    /// let diff = old.diff_by_key(&new, |_| ());
+    /// let markers: Vec<char> = diff.iter().map(|line| line.marker()).collect();
+    /// assert_eq!(markers, vec!['~']);
+    /// ```
+    ///
+    pub fn marker(&self) -> char {
+        match self.change {
+            Change::Unchanged(_) => ' ',
+            Change::Changed { .. } => '~',
+            Change::Removed(_) => '-',
+            Change::Added(_) => '+',
+        }
+    }
+}
+
+enum Work<'a, T> {
+    Matched(NodeRef<'a, T>, NodeRef<'a, T>),
+    Removed(NodeRef<'a, T>),
+    Added(NodeRef<'a, T>),
+}
+
+impl<T: PartialEq> Tree<T> {
+    ///
+    /// Diffs this (old) `Tree` against `other` (the new one), as described in the `diff` module,
+    /// matching `Node`s whose `key`s are equal.  Returns one `DiffLine` per `Node` of either
+    /// `Tree` in pre-order, with each `Removed` `Node` coming before the `Added` ones that take
+    /// its place.  Orphaned `Node`s are ignored.
+    ///
+    /// ```
+    /// use slab_tree::diff::Change;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut old = TreeBuilder::new().with_root(("root", 0)).build();
+    /// old.root_mut().unwrap().append(("a", 1));
+    /// old.root_mut().unwrap().append(("b", 1));
+    ///
+    /// let mut new = TreeBuilder::new().with_root(("root", 0)).build();
+    /// new.root_mut().unwrap().append(("a", 2));
+    /// new.root_mut().unwrap().append(("c", 1));
+    ///
+    /// let diff = old.diff_by_key(&new, |&(name, _)| name);
+    /// assert_eq!(diff.len(), 4);
+    /// assert!(matches!(diff[0].change, Change::Unchanged(_)));
+    /// match diff[1].change {
+    ///     Change::Changed { old, new } => assert_eq!((old.data().1, new.data().1), (1, 2)),
+    ///     _ => panic!("a should have changed"),
+    /// }
+    /// assert!(matches!(diff[2].change, Change::Removed(node) if node.data().0 == "b"));
+    /// assert!(matches!(diff[3].change, Change::Added(node) if node.data().0 == "c"));
+    /// ```
+    ///
+    pub fn diff_by_key<'a, K, F>(&'a self, other: &'a Tree<T>, mut key: F) -> Vec<DiffLine<'a, T>>
+    where
+        K: PartialEq,
+        F: FnMut(&T) -> K,
+    {
+        let mut stack: Vec<(Work<'a, T>, usize)> = Vec::new();
+        match (self.root(), other.root()) {
+            (Some(old), Some(new)) if key(old.data()) == key(new.data()) => {
+                stack.push((Work::Matched(old, new), 0))
+            }
+            (old, new) => {
+                stack.extend(new.map(|new| (Work::Added(new), 0)));
+                stack.extend(old.map(|old| (Work::Removed(old), 0)));
+            }
+        }
+
+        let mut lines = Vec::new();
+        // children are pushed last to first so they come out in order
+        while let Some((work, depth)) = stack.pop() {
+            let start = stack.len();
+            let change = match work {
+                Work::Matched(old, new) => {
+                    align_children(old, new, &mut key, |work| stack.push((work, depth + 1)));
+                    if old.data() == new.data() {
+                        Change::Unchanged(new)
+                    } else {
+                        Change::Changed { old, new }
+                    }
+                }
+                Work::Removed(old) => {
+                    stack.extend(
+                        old.children()
+                            .map(|child| (Work::Removed(child), depth + 1)),
+                    );
+                    Change::Removed(old)
+                }
+                Work::Added(new) => {
+                    stack.extend(new.children().map(|child| (Work::Added(child), depth + 1)));
+                    Change::Added(new)
+                }
+            };
+            stack[start..].reverse();
+            lines.push(DiffLine { depth, change });
+        }
+        lines
+    }
+}
+
+impl<T: PartialEq + fmt::Debug> Tree<T> {
+    ///
+    /// Writes a unified view of how `other` differs from this `Tree` to `w`: the lines of
+    /// `Tree::diff_by_key`, each starting with its `DiffLine::marker` and a space, followed by
+    /// the `Node`'s data (or `old -> new` for a `Changed` `Node`) indented four spaces per
+    /// level.  Writes nothing if both `Tree`s are empty.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut old = TreeBuilder::new().with_root(("root", 0)).build();
+    /// let mut root = old.root_mut().unwrap();
+    /// root.append(("a", 1));
+    /// root.append(("b", 1)).append(("c", 1));
+    ///
+    /// let mut new = TreeBuilder::new().with_root(("root", 0)).build();
+    /// let mut root = new.root_mut().unwrap();
+    /// root.append(("a", 2));
+    /// root.append(("d", 1));
+    ///
+    /// let mut s = String::new();
+    /// old.write_diff(&new, &mut s, |&(name, _)| name).unwrap();
+    /// assert_eq!(&s, r#"  ("root", 0)
+    /// ~     ("a", 1) -> ("a", 2)
+    /// -     ("b", 1)
+    /// -         ("c", 1)
+    /// +     ("d", 1)
+    /// "#);
+    /// ```
+    ///
+    pub fn write_diff<W, K, F>(&self, other: &Tree<T>, w: &mut W, key: F) -> fmt::Result
+    where
+        W: fmt::Write,
+        K: PartialEq,
+        F: FnMut(&T) -> K,
+    {
+        for line in self.diff_by_key(other, key) {
+            write!(
+                w,
+                "{} {:indent$}",
+                line.marker(),
+                "",
+                indent = line.depth * 4
+            )?;
+            match line.change {
+                Change::Changed { old, new } => {
+                    writeln!(w, "{:?} -> {:?}", old.data(), new.data())?
+                }
+                Change::Unchanged(node) | Change::Removed(node) | Change::Added(node) => {
+                    writeln!(w, "{:?}", node.data())?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// Aligns the children of two matched `Node`s by the longest common subsequence of their keys,
+/// handing `push` one `Work` per child in order.
+///
+fn align_children<'a, T, K, F, P>(
+    old: NodeRef<'a, T>,
+    new: NodeRef<'a, T>,
+    key: &mut F,
+    mut push: P,
+) where
+    K: PartialEq,
+    F: FnMut(&T) -> K,
+    P: FnMut(Work<'a, T>),
+{
+    let old: Vec<NodeRef<'a, T>> = old.children().collect();
+    let new: Vec<NodeRef<'a, T>> = new.children().collect();
+    let old_keys: Vec<K> = old.iter().map(|node| key(node.data())).collect();
+    let new_keys: Vec<K> = new.iter().map(|node| key(node.data())).collect();
+
+    // lengths[i][j] is the length of the longest common subsequence of old_keys[i..] and
+    // new_keys[j..]
+    let width = new.len() + 1;
+    let mut lengths = vec![0; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old_keys[i] == new_keys[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old_keys[i] == new_keys[j] {
+            push(Work::Matched(old[i], new[j]));
+            i += 1;
+            j += 1;
+        } else if j == new.len()
+            || (i < old.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            push(Work::Removed(old[i]));
+            i += 1;
+        } else {
+            push(Work::Added(new[j]));
+            j += 1;
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod diff_tests {
+    use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+
+    fn diff(old: &Tree<(char, i32)>, new: &Tree<(char, i32)>) -> String {
+        let mut s = String::new();
+        old.write_diff(new, &mut s, |&(name, _)| name).unwrap();
+        s
+    }
+
+    fn tree(root: char, children: &[(char, &[char])]) -> Tree<(char, i32)> {
+        let mut tree = TreeBuilder::new().with_root((root, 0)).build();
+        let mut root = tree.root_mut().unwrap();
+        for &(child, grandchildren) in children {
+            let mut child = root.append((child, 0));
+            for &grandchild in grandchildren {
+                child.append((grandchild, 0));
+            }
+        }
+        tree
+    }
+
+    #[test]
+    fn identical_trees_are_unchanged() {
+        let old = tree('r', &[('a', &['x', 'y']), ('b', &[])]);
+        assert_eq!(
+            diff(&old, &old),
+            "  ('r', 0)\n      ('a', 0)\n          ('x', 0)\n          ('y', 0)\n      ('b', 0)\n"
+        );
+
+        let empty: Tree<(char, i32)> = Tree::new();
+        assert_eq!(diff(&empty, &empty), "");
+    }
+
+    #[test]
+    fn children_are_aligned() {
+        let old = tree('r', &[('a', &[]), ('b', &['x']), ('c', &[]), ('d', &[])]);
+        let new = tree('r', &[('e', &[]), ('a', &[]), ('c', &[]), ('d', &['y'])]);
+        let markers: String = old
+            .diff_by_key(&new, |&(name, _)| name)
+            .iter()
+            .map(|line| line.marker())
+            .collect();
+        // r, +e, a, -b, -x, c, d, +y
+        assert_eq!(markers, " + --  +");
+
+        let mut changed = tree('r', &[('e', &[]), ('a', &[]), ('c', &[]), ('d', &['y'])]);
+        let d_id = changed.root().unwrap().last_child().unwrap().node_id();
+        changed.get_mut(d_id).unwrap().data().1 = 5;
+        assert_eq!(
+            diff(&new, &changed),
+            "  ('r', 0)\n      ('e', 0)\n      ('a', 0)\n      ('c', 0)\n\
+             ~     ('d', 0) -> ('d', 5)\n          ('y', 0)\n"
+        );
+    }
+
+    #[test]
+    fn different_roots() {
+        let old = tree('r', &[('a', &[])]);
+        let new = tree('s', &[]);
+        assert_eq!(diff(&old, &new), "- ('r', 0)\n-     ('a', 0)\n+ ('s', 0)\n");
+
+        let empty: Tree<(char, i32)> = Tree::new();
+        assert_eq!(diff(&empty, &new), "+ ('s', 0)\n");
+        assert_eq!(diff(&new, &empty), "- ('s', 0)\n");
+    }
+}
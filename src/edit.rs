@@ -0,0 +1,591 @@
+//!
+//! Batched structural edits: queue appends, moves, and removals while reading the `Tree` as it
+//! was, then apply them all at once.
+//!
+//! Interleaving structural changes with a traversal of the same `Tree` isn't possible (the
+//! traversal borrows the `Tree`), and collecting `NodeId`s first and editing afterwards is easy
+//! to get wrong when one edit invalidates another.  `Tree::edit` hands out a `TreeTransaction`
+//! which can read the `Tree` freely, checks each edit against it as the edit is queued, and
+//! applies the queue only once the closure returns.
+//!
+//! Since nothing is applied before the closure returns, `Tree::try_edit` can abandon every
+//! queued edit when the closure fails, leaving the `Tree` untouched.
+//!
+
+use crate::behaviors::RemoveBehavior;
+use crate::reparent::InsertPosition;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+
+///
+/// Identifies a `Node` which has been queued for insertion by a `TreeTransaction` but doesn't
+/// exist yet.  `NewIds::get` gives its `NodeId` once the transaction has been applied.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PendingId(usize);
+
+///
+/// A `Node` which edits can refer to: either one already in the `Tree` or one queued for
+/// insertion earlier in the same transaction.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EditTarget {
+    ///
+    /// A `Node` already in the `Tree`.
+    ///
+    Node(NodeId),
+
+    ///
+    /// A `Node` queued for insertion earlier in the same transaction.
+    ///
+    Pending(PendingId),
+}
+
+impl From<NodeId> for EditTarget {
+    fn from(node_id: NodeId) -> EditTarget {
+        EditTarget::Node(node_id)
+    }
+}
+
+impl From<PendingId> for EditTarget {
+    fn from(pending_id: PendingId) -> EditTarget {
+        EditTarget::Pending(pending_id)
+    }
+}
+
+///
+/// Describes why a `TreeTransaction` refused to queue an edit.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditError {
+    ///
+    /// The `NodeId` doesn't refer to a `Node` in the `Tree` as it was before the edit began.
+    ///
+    MissingNode(NodeId),
+
+    ///
+    /// The `PendingId` wasn't handed out by this transaction.
+    ///
+    UnknownPending(PendingId),
+
+    ///
+    /// The `Node` to move is the root of the `Tree`, which can't be given a parent.
+    ///
+    Root,
+
+    ///
+    /// The new parent would be the `Node` being moved or one of its descendants (taking the moves
+    /// queued so far into account), so moving it would create a cycle.
+    ///
+    Cycle,
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::MissingNode(node_id) => {
+                write!(f, "{:?} is not a node of the tree", node_id)
+            }
+            EditError::UnknownPending(pending_id) => {
+                write!(f, "{:?} was not queued by this transaction", pending_id)
+            }
+            EditError::Root => write!(f, "the root of the tree can't be moved"),
+            EditError::Cycle => {
+                write!(f, "the new parent is inside the sub-tree being moved")
+            }
+        }
+    }
+}
+
+impl Error for EditError {}
+
+enum Edit<T> {
+    Append {
+        parent: EditTarget,
+        data: T,
+    },
+    Move {
+        node: NodeId,
+        new_parent: EditTarget,
+        position: InsertPosition,
+    },
+}
+
+///
+/// A queue of structural edits to a `Tree`, handed to the closure passed to `Tree::edit`.
+///
+/// The `Tree` itself can be read through `TreeTransaction::tree` while edits are queued; it
+/// doesn't change until the closure returns.  Each edit is checked when it is queued, so an edit
+/// which is accepted is guaranteed to apply.
+///
+pub struct TreeTransaction<'a, T> {
+    tree: &'a Tree<T>,
+    edits: Vec<Edit<T>>,
+    removals: Vec<(NodeId, RemoveBehavior)>,
+    pending_parents: Vec<EditTarget>,
+    moved_parents: HashMap<NodeId, EditTarget>,
+}
+
+impl<'a, T> TreeTransaction<'a, T> {
+    ///
+    /// Returns the `Tree` as it was before the edit began.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// tree.edit(|txn| {
+    ///     txn.append(root_id, 2).unwrap();
+    ///     // the append hasn't happened yet
+    ///     assert!(txn.tree().get(root_id).unwrap().first_child().is_none());
+    /// });
+    /// assert!(tree.root().unwrap().first_child().is_some());
+    /// ```
+    ///
+    pub fn tree(&self) -> &'a Tree<T> {
+        self.tree
+    }
+
+    ///
+    /// Queues a new `Node` holding `data` to be appended as the last child of `parent`, and
+    /// returns a `PendingId` which later edits can use to refer to it.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// let (three, new_ids) = tree.edit(|txn| {
+    ///     let two = txn.append(root_id, 2).unwrap();
+    ///     txn.append(two, 3).unwrap()
+    /// });
+    ///
+    /// let three = tree.get(new_ids.get(three)).unwrap();
+    /// assert_eq!(*three.parent().unwrap().data(), 2);
+    /// ```
+    ///
+    pub fn append<P: Into<EditTarget>>(
+        &mut self,
+        parent: P,
+        data: T,
+    ) -> Result<PendingId, EditError> {
+        let parent = parent.into();
+        self.check_target(parent)?;
+        self.edits.push(Edit::Append { parent, data });
+        self.pending_parents.push(parent);
+        Ok(PendingId(self.pending_parents.len() - 1))
+    }
+
+    ///
+    /// Queues the `Node` `node` (along with its sub-tree) to be moved below `new_parent`, as
+    /// `Tree::move_subtree` does.  Moves which would create a cycle are refused, taking the moves
+    /// queued before this one into account.
+    ///
+    /// ```
+    /// use slab_tree::reparent::InsertPosition;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append(1).node_id();
+    /// let two_id = root.append(2).node_id();
+    ///
+    /// tree.edit(|txn| {
+    ///     txn.move_subtree(one_id, two_id, InsertPosition::Last).unwrap();
+    ///     // 2 is about to become 1's parent, so 2 can't also go below 1
+    ///     assert!(txn.move_subtree(two_id, one_id, InsertPosition::Last).is_err());
+    /// });
+    /// ```
+    ///
+    pub fn move_subtree<P: Into<EditTarget>>(
+        &mut self,
+        node: NodeId,
+        new_parent: P,
+        position: InsertPosition,
+    ) -> Result<(), EditError> {
+        let new_parent = new_parent.into();
+        if self.tree.get_node(node).is_none() {
+            return Err(EditError::MissingNode(node));
+        }
+        self.check_target(new_parent)?;
+        if self.tree.root_id == Some(node) {
+            return Err(EditError::Root);
+        }
+
+        let mut ancestor = Some(new_parent);
+        while let Some(target) = ancestor {
+            ancestor = match target {
+                EditTarget::Node(node_id) if node_id == node => return Err(EditError::Cycle),
+                EditTarget::Node(node_id) => match self.moved_parents.get(&node_id) {
+                    Some(&parent) => Some(parent),
+                    None => self
+                        .tree
+                        .get_node_relatives(node_id)
+                        .parent
+                        .map(EditTarget::Node),
+                },
+                EditTarget::Pending(PendingId(index)) => Some(self.pending_parents[index]),
+            };
+        }
+
+        self.moved_parents.insert(node, new_parent);
+        self.edits.push(Edit::Move {
+            node,
+            new_parent,
+            position,
+        });
+        Ok(())
+    }
+
+    ///
+    /// Queues the `Node` `node` to be removed, as `Tree::remove` does.  Removals are applied
+    /// after every append and move, so `Node`s appended or moved below `node` in the same
+    /// transaction are dropped or orphaned along with its other children.  Removing a `Node`
+    /// which an earlier removal already took out of the `Tree` does nothing.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let ids: Vec<_> = (1..=4).map(|n| root.append(n).node_id()).collect();
+    ///
+    /// // remove the odd children while looking at the tree
+    /// tree.edit(|txn| {
+    ///     for child in txn.tree().root().unwrap().children() {
+    ///         if child.data() % 2 == 1 {
+    ///             txn.remove(child.node_id(), RemoveBehavior::DropChildren).unwrap();
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// let children: Vec<i32> = tree.root().unwrap().children().map(|n| *n.data()).collect();
+    /// assert_eq!(children, vec![2, 4]);
+    /// ```
+    ///
+    pub fn remove(&mut self, node: NodeId, behavior: RemoveBehavior) -> Result<(), EditError> {
+        if self.tree.get_node(node).is_none() {
+            return Err(EditError::MissingNode(node));
+        }
+        self.removals.push((node, behavior));
+        Ok(())
+    }
+
+    fn check_target(&self, target: EditTarget) -> Result<(), EditError> {
+        match target {
+            EditTarget::Node(node_id) if self.tree.get_node(node_id).is_none() => {
+                Err(EditError::MissingNode(node_id))
+            }
+            EditTarget::Pending(pending_id) if pending_id.0 >= self.pending_parents.len() => {
+                Err(EditError::UnknownPending(pending_id))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+///
+/// The `NodeId`s of the `Node`s inserted by a transaction, returned by `Tree::edit`.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewIds(Vec<NodeId>);
+
+impl NewIds {
+    ///
+    /// Returns the `NodeId` of the `Node` queued as `pending_id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pending_id` wasn't handed out by the transaction these ids came from.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// let (two, new_ids) = tree.edit(|txn| txn.append(root_id, 2).unwrap());
+    /// assert_eq!(*tree.get(new_ids.get(two)).unwrap().data(), 2);
+    /// ```
+    ///
+    pub fn get(&self, pending_id: PendingId) -> NodeId {
+        self.0[pending_id.0]
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Runs `f` with a `TreeTransaction` for queuing structural edits, then applies the queued
+    /// edits: appends and moves in the order they were queued, followed by removals.  Returns
+    /// what `f` returned along with the `NodeId`s of the appended `Node`s.
+    ///
+    /// Observers are notified of each change as it is applied.
+    ///
+    /// ```
+    /// use slab_tree::reparent::InsertPosition;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("/").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("a.txt");
+    /// root.append("b.rs");
+    /// root.append("c.txt");
+    ///
+    /// // gather the text files into a new directory
+    /// tree.edit(|txn| {
+    ///     let root = txn.tree().root().unwrap();
+    ///     let docs = txn.append(root.node_id(), "docs").unwrap();
+    ///     for child in root.children().filter(|child| child.data().ends_with(".txt")) {
+    ///         txn.move_subtree(child.node_id(), docs, InsertPosition::Last).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// \"/\"
+    /// ├── \"b.rs\"
+    /// └── \"docs\"
+    ///     ├── \"a.txt\"
+    ///     └── \"c.txt\"
+    /// ");
+    /// ```
+    ///
+    pub fn edit<R, F>(&mut self, f: F) -> (R, NewIds)
+    where
+        F: FnOnce(&mut TreeTransaction<'_, T>) -> R,
+    {
+        match self.try_edit(|txn| Ok::<R, Infallible>(f(txn))) {
+            Ok(edited) => edited,
+            Err(never) => match never {},
+        }
+    }
+
+    ///
+    /// Like `Tree::edit`, but `f` returns a `Result`.  If it returns `Err`, none of the queued
+    /// edits are applied and the `Tree` is left exactly as it was; otherwise the edits are applied
+    /// and the `Ok` value is returned along with the `NodeId`s of the appended `Node`s.
+    ///
+    /// Any error type works; implementing `From<EditError>` for it lets `f` use `?` on the
+    /// results of `TreeTransaction`'s methods.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::edit::EditError;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let root_id = tree.root_id().unwrap();
+    /// let one_id = tree.root_mut().unwrap().append(1).node_id();
+    ///
+    /// let other = TreeBuilder::new().with_root(0).build();
+    /// let other_id = other.root_id().unwrap();
+    ///
+    /// let result = tree.try_edit(|txn| {
+    ///     txn.append(root_id, 2)?;
+    ///     txn.remove(one_id, RemoveBehavior::DropChildren)?;
+    ///     // `other_id` belongs to a different tree, so this fails
+    ///     txn.append(other_id, 3)?;
+    ///     Ok(())
+    /// });
+    ///
+    /// // the closure failed, so neither the append nor the removal happened
+    /// assert_eq!(result, Err(EditError::MissingNode(other_id)));
+    /// let children: Vec<i32> = tree.root().unwrap().children().map(|n| *n.data()).collect();
+    /// assert_eq!(children, vec![1]);
+    /// ```
+    ///
+    pub fn try_edit<R, E, F>(&mut self, f: F) -> Result<(R, NewIds), E>
+    where
+        F: FnOnce(&mut TreeTransaction<'_, T>) -> Result<R, E>,
+    {
+        let mut txn = TreeTransaction {
+            tree: self,
+            edits: Vec::new(),
+            removals: Vec::new(),
+            pending_parents: Vec::new(),
+            moved_parents: HashMap::new(),
+        };
+        // nothing has touched the tree yet, so dropping the queue is all it takes to roll back
+        let value = f(&mut txn)?;
+        let TreeTransaction {
+            edits, removals, ..
+        } = txn;
+        Ok((value, self.apply_edits(edits, removals)))
+    }
+
+    fn apply_edits(
+        &mut self,
+        edits: Vec<Edit<T>>,
+        removals: Vec<(NodeId, RemoveBehavior)>,
+    ) -> NewIds {
+        let mut new_ids: Vec<NodeId> = Vec::new();
+        let resolve = |new_ids: &[NodeId], target: EditTarget| match target {
+            EditTarget::Node(node_id) => node_id,
+            EditTarget::Pending(PendingId(index)) => new_ids[index],
+        };
+
+        for edit in edits {
+            match edit {
+                Edit::Append { parent, data } => {
+                    let parent_id = resolve(&new_ids, parent);
+                    let node_id = self
+                        .get_mut(parent_id)
+                        .expect("parent was checked when queued")
+                        .append(data)
+                        .node_id();
+                    new_ids.push(node_id);
+                }
+                Edit::Move {
+                    node,
+                    new_parent,
+                    position,
+                } => {
+                    let parent_id = resolve(&new_ids, new_parent);
+                    self.move_subtree(node, parent_id, position)
+                        .expect("move was checked when queued");
+                }
+            }
+        }
+        for (node_id, behavior) in removals {
+            self.remove(node_id, behavior);
+        }
+
+        NewIds(new_ids)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod edit_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    fn outline(tree: &Tree<i32>) -> Vec<(i32, usize)> {
+        tree.root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (*node.data(), node.ancestors().count()))
+            .collect()
+    }
+
+    #[test]
+    fn edits_apply_in_order() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+
+        let ((), new_ids) = tree.edit(|txn| {
+            let three = txn.append(one_id, 3).unwrap();
+            let four = txn.append(three, 4).unwrap();
+            txn.move_subtree(two_id, four, InsertPosition::First)
+                .unwrap();
+            txn.append(root_id, 5).unwrap();
+            assert_eq!(outline(txn.tree()), vec![(0, 0), (1, 1), (2, 1)]);
+        });
+
+        assert_eq!(
+            outline(&tree),
+            vec![(0, 0), (1, 1), (3, 2), (4, 3), (2, 4), (5, 1)]
+        );
+        assert_eq!(*tree.get(new_ids.get(PendingId(1))).unwrap().data(), 4);
+    }
+
+    #[test]
+    fn removals_apply_last() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+
+        let (three, new_ids) = tree.edit(|txn| {
+            txn.remove(one_id, RemoveBehavior::DropChildren).unwrap();
+            txn.remove(two_id, RemoveBehavior::OrphanChildren).unwrap();
+            txn.remove(one_id, RemoveBehavior::DropChildren).unwrap();
+            txn.append(one_id, 10).unwrap();
+            txn.append(two_id, 3).unwrap()
+        });
+
+        assert_eq!(outline(&tree), vec![(0, 0)]);
+        assert_eq!(tree.root_id(), Some(root_id));
+        let three = tree.get(new_ids.get(three)).unwrap();
+        assert!(three.parent().is_none());
+        assert_eq!(tree.get(new_ids.get(PendingId(0))).map(|n| *n.data()), None);
+    }
+
+    #[test]
+    fn failed_edit_rolls_back() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        let before = outline(&tree);
+
+        let result: Result<((), NewIds), &str> = tree.try_edit(|txn| {
+            txn.append(root_id, 3).unwrap();
+            txn.move_subtree(two_id, one_id, InsertPosition::First)
+                .unwrap();
+            txn.remove(one_id, RemoveBehavior::DropChildren).unwrap();
+            Err("changed my mind")
+        });
+        assert_eq!(result, Err("changed my mind"));
+        assert_eq!(outline(&tree), before);
+
+        let (count, _) = tree
+            .try_edit(|txn| -> Result<usize, EditError> {
+                txn.move_subtree(two_id, one_id, InsertPosition::First)?;
+                Ok(txn.tree().root().unwrap().children().count())
+            })
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(outline(&tree), vec![(0, 0), (1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn errors_leave_queue_unchanged() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        let other = TreeBuilder::new().with_root(0).build();
+        let other_id = other.root_id().unwrap();
+
+        tree.edit(|txn| {
+            assert_eq!(
+                txn.append(other_id, 1),
+                Err(EditError::MissingNode(other_id))
+            );
+            assert_eq!(
+                txn.append(PendingId(0), 1),
+                Err(EditError::UnknownPending(PendingId(0)))
+            );
+            assert_eq!(
+                txn.remove(other_id, RemoveBehavior::DropChildren),
+                Err(EditError::MissingNode(other_id))
+            );
+            assert_eq!(
+                txn.move_subtree(root_id, one_id, InsertPosition::Last),
+                Err(EditError::Root)
+            );
+            assert_eq!(
+                txn.move_subtree(one_id, one_id, InsertPosition::Last),
+                Err(EditError::Cycle)
+            );
+            let two = txn.append(one_id, 2).unwrap();
+            assert_eq!(
+                txn.move_subtree(one_id, two, InsertPosition::Last),
+                Err(EditError::Cycle)
+            );
+        });
+
+        assert_eq!(outline(&tree), vec![(0, 0), (1, 1), (2, 2)]);
+    }
+}
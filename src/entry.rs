@@ -0,0 +1,271 @@
+//!
+//! An entry-style API for finding or creating a path of `Node`s below a given `Node`.
+//!
+
+use crate::node::NodeMut;
+use crate::tree::Tree;
+use crate::NodeId;
+
+///
+/// A path of keys below a `Node`, as far as it already exists in the `Tree`.  Created with
+/// `Tree::path_entry` or `NodeMut::path_entry`.
+///
+/// Each key of the path is matched against the children of the previous `Node` (starting with
+/// the `Node` the entry was created from) by comparing the children's data to the key; the first
+/// matching child is used.
+///
+pub struct PathEntry<'a, T, I: Iterator> {
+    tree: &'a mut Tree<T>,
+    node_id: NodeId,
+    missing: Option<I::Item>,
+    rest: I,
+}
+
+impl<'a, T, I> PathEntry<'a, T, I>
+where
+    I: Iterator,
+    T: PartialEq<I::Item>,
+{
+    pub(crate) fn new(tree: &'a mut Tree<T>, node_id: NodeId, mut rest: I) -> Self {
+        let mut node_id = node_id;
+        let mut missing = None;
+
+        for key in rest.by_ref() {
+            let child_id = tree
+                .get(node_id)
+                .expect("entry node must exist")
+                .children()
+                .find(|child| *child.data() == key)
+                .map(|child| child.node_id());
+            match child_id {
+                Some(child_id) => node_id = child_id,
+                None => {
+                    missing = Some(key);
+                    break;
+                }
+            }
+        }
+
+        PathEntry {
+            tree,
+            node_id,
+            missing,
+            rest,
+        }
+    }
+
+    ///
+    /// Returns a `NodeMut` pointing to the `Node` at the end of the path if the whole path
+    /// exists; otherwise returns `None`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    /// tree.root_mut().unwrap().append("a").append("b");
+    ///
+    /// assert!(tree.path_entry(vec!["a", "b"]).unwrap().get().is_some());
+    /// assert!(tree.path_entry(vec!["a", "c"]).unwrap().get().is_none());
+    /// ```
+    ///
+    pub fn get(self) -> Option<NodeMut<'a, T>> {
+        match self.missing {
+            Some(_) => None,
+            None => Some(NodeMut::new(self.node_id, self.tree)),
+        }
+    }
+
+    ///
+    /// Applies `f` to the data of the `Node` at the end of the path, if the whole path exists.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(1);
+    ///
+    /// let mut node = tree.path_entry(vec![1]).unwrap().and_modify(|data| *data += 10).get();
+    /// assert_eq!(node.unwrap().data(), &mut 11);
+    /// ```
+    ///
+    pub fn and_modify<F: FnOnce(&mut T)>(self, f: F) -> Self {
+        if self.missing.is_none() {
+            let node = self
+                .tree
+                .get_node_mut(self.node_id)
+                .expect("entry node must exist");
+            f(&mut node.data);
+        }
+        self
+    }
+
+    ///
+    /// Returns a `NodeMut` pointing to the `Node` at the end of the path, first creating any
+    /// missing `Node`s along it.  `f` is called with each missing key (in order) to produce the
+    /// data for the `Node` created for it; new `Node`s are appended after any existing children.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("all".to_string()).build();
+    ///
+    /// for path in &[["fruit", "apple"], ["fruit", "pear"], ["veg", "leek"]] {
+    ///     tree.path_entry(path.iter().copied())
+    ///         .unwrap()
+    ///         .or_insert_with(|key| key.to_string());
+    /// }
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// \"all\"
+    /// ├── \"fruit\"
+    /// │   ├── \"apple\"
+    /// │   └── \"pear\"
+    /// └── \"veg\"
+    ///     └── \"leek\"
+    /// ");
+    /// ```
+    ///
+    pub fn or_insert_with<F: FnMut(I::Item) -> T>(self, mut f: F) -> NodeMut<'a, T> {
+        let PathEntry {
+            tree,
+            mut node_id,
+            missing,
+            rest,
+        } = self;
+
+        if let Some(key) = missing {
+            for key in Some(key).into_iter().chain(rest) {
+                node_id = tree
+                    .get_mut(node_id)
+                    .expect("entry node must exist")
+                    .append(f(key))
+                    .node_id();
+            }
+        }
+
+        NodeMut::new(node_id, tree)
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Returns a `PathEntry` for the given path of keys below the root of the `Tree`, or `None`
+    /// if the `Tree` is empty.  The root itself is not part of the path.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    ///
+    /// let leaf_id = tree.path_entry(vec!["a", "b"]).unwrap().or_insert_with(|key| key).node_id();
+    /// let again = tree.path_entry(vec!["a", "b"]).unwrap().or_insert_with(|key| key).node_id();
+    ///
+    /// assert_eq!(leaf_id, again);
+    /// ```
+    ///
+    pub fn path_entry<I>(&mut self, path: I) -> Option<PathEntry<'_, T, I::IntoIter>>
+    where
+        I: IntoIterator,
+        T: PartialEq<I::Item>,
+    {
+        let root_id = self.root_id()?;
+        Some(PathEntry::new(self, root_id, path.into_iter()))
+    }
+}
+
+impl<'a, T> NodeMut<'a, T> {
+    ///
+    /// Returns a `PathEntry` for the given path of keys below this `Node`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let one_id = tree.root_mut().unwrap().append(1).node_id();
+    ///
+    /// let node = tree.get_mut(one_id).unwrap().path_entry(vec![2, 3]).or_insert_with(|key| key);
+    /// assert_eq!(node.as_ref().ancestors().count(), 3);
+    /// ```
+    ///
+    pub fn path_entry<I>(self, path: I) -> PathEntry<'a, T, I::IntoIter>
+    where
+        I: IntoIterator,
+        T: PartialEq<I::Item>,
+    {
+        let (node_id, tree) = self.into_parts();
+        PathEntry::new(tree, node_id, path.into_iter())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod entry_tests {
+    use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+
+    fn outline(tree: &Tree<String>) -> Vec<(String, usize)> {
+        tree.root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (node.data().clone(), node.ancestors().count()))
+            .collect()
+    }
+
+    #[test]
+    fn path_entry_empty_tree() {
+        let mut tree: Tree<String> = Tree::new();
+        assert!(tree.path_entry(vec!["a"]).is_none());
+    }
+
+    #[test]
+    fn path_entry_empty_path_is_root() {
+        let mut tree = TreeBuilder::new().with_root("root".to_string()).build();
+        let root_id = tree.root_id().unwrap();
+        let node = tree.path_entry(Vec::<&str>::new()).unwrap().get().unwrap();
+        assert_eq!(node.node_id(), root_id);
+    }
+
+    #[test]
+    fn or_insert_with_creates_only_missing_nodes() {
+        let mut tree = TreeBuilder::new().with_root("root".to_string()).build();
+        tree.root_mut().unwrap().append("a".to_string());
+
+        let mut created = Vec::new();
+        tree.path_entry(vec!["a", "b", "c"])
+            .unwrap()
+            .or_insert_with(|key| {
+                created.push(key);
+                key.to_string()
+            });
+        assert_eq!(created, vec!["b", "c"]);
+
+        tree.path_entry(vec!["a", "d"])
+            .unwrap()
+            .or_insert_with(|key| key.to_string());
+
+        let expected: Vec<(String, usize)> = vec![
+            ("root".to_string(), 0),
+            ("a".to_string(), 1),
+            ("b".to_string(), 2),
+            ("c".to_string(), 3),
+            ("d".to_string(), 2),
+        ];
+        assert_eq!(outline(&tree), expected);
+    }
+
+    #[test]
+    fn and_modify_only_applies_to_existing_nodes() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        tree.root_mut().unwrap().append(1);
+
+        let mut calls = 0;
+        tree.path_entry(vec![2])
+            .unwrap()
+            .and_modify(|_| calls += 1)
+            .or_insert_with(|key| key);
+        tree.path_entry(vec![1]).unwrap().and_modify(|_| calls += 1);
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,386 @@
+//!
+//! Building `Tree`s from (and turning them into) depth-first streams of open/close events, the
+//! shape in which most streaming parsers and serializers see hierarchical data.
+//!
+
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use std::error::Error;
+use std::fmt;
+
+///
+/// Describes the ways in which an event stream can fail to describe a single `Tree`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventError {
+    ///
+    /// A `Node` was opened after the root `Node` had already been closed.
+    ///
+    MultipleRoots,
+
+    ///
+    /// `close` was called without a matching `open`.
+    ///
+    UnmatchedClose,
+
+    ///
+    /// `finish` was called while the given number of `Node`s were still open.
+    ///
+    Unclosed(usize),
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventError::MultipleRoots => write!(f, "opened a node after the root was closed"),
+            EventError::UnmatchedClose => write!(f, "closed a node that was never opened"),
+            EventError::Unclosed(count) => write!(f, "{} node(s) were never closed", count),
+        }
+    }
+}
+
+impl Error for EventError {}
+
+///
+/// Builds a `Tree` from a depth-first stream of `open` and `close` events.
+///
+/// Each `open` adds a new `Node` as the last child of the innermost open `Node` (or as the root,
+/// for the first `open`), and each `close` finishes the innermost open `Node`.
+///
+/// ```
+/// use slab_tree::events::EventBuilder;
+///
+/// let mut builder = EventBuilder::new();
+/// builder.open("html").unwrap();
+/// builder.open("head").unwrap();
+/// builder.close().unwrap();
+/// builder.open("body").unwrap();
+/// builder.close().unwrap();
+/// builder.close().unwrap();
+///
+/// let tree = builder.finish().unwrap();
+/// let root = tree.root().expect("root doesn't exist?");
+/// assert_eq!(*root.data(), "html");
+/// assert_eq!(*root.last_child().unwrap().data(), "body");
+/// ```
+///
+#[derive(Debug)]
+pub struct EventBuilder<T> {
+    tree: Tree<T>,
+    open: Vec<NodeId>,
+}
+
+impl<T> EventBuilder<T> {
+    ///
+    /// Creates a new `EventBuilder` with an empty `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::events::EventBuilder;
+    ///
+    /// let builder: EventBuilder<i32> = EventBuilder::new();
+    /// # assert!(builder.finish().unwrap().root().is_none());
+    /// ```
+    ///
+    pub fn new() -> EventBuilder<T> {
+        EventBuilder::with_capacity(0)
+    }
+
+    ///
+    /// Creates a new `EventBuilder` whose `Tree` has room for `capacity` `Node`s.
+    ///
+    /// ```
+    /// use slab_tree::events::EventBuilder;
+    ///
+    /// let builder: EventBuilder<i32> = EventBuilder::with_capacity(10);
+    /// # assert_eq!(builder.finish().unwrap().capacity(), 10);
+    /// ```
+    ///
+    pub fn with_capacity(capacity: usize) -> EventBuilder<T> {
+        EventBuilder {
+            tree: TreeBuilder::new().with_capacity(capacity).build(),
+            open: Vec::new(),
+        }
+    }
+
+    ///
+    /// Opens a new `Node` holding `data` and returns its `NodeId`.
+    ///
+    /// Returns `EventError::MultipleRoots` if the root `Node` has already been closed.
+    ///
+    /// ```
+    /// use slab_tree::events::{EventBuilder, EventError};
+    ///
+    /// let mut builder = EventBuilder::new();
+    /// builder.open(1).unwrap();
+    /// builder.close().unwrap();
+    ///
+    /// assert_eq!(builder.open(2), Err(EventError::MultipleRoots));
+    /// ```
+    ///
+    pub fn open(&mut self, data: T) -> Result<NodeId, EventError> {
+        let node_id = match self.open.last() {
+            Some(&parent_id) => self
+                .tree
+                .get_mut(parent_id)
+                .expect("open node must exist")
+                .append(data)
+                .node_id(),
+            None if self.tree.root_id().is_some() => return Err(EventError::MultipleRoots),
+            None => self.tree.set_root(data),
+        };
+        self.open.push(node_id);
+        Ok(node_id)
+    }
+
+    ///
+    /// Closes the innermost open `Node` and returns its `NodeId`.
+    ///
+    /// Returns `EventError::UnmatchedClose` if no `Node` is open.
+    ///
+    /// ```
+    /// use slab_tree::events::{EventBuilder, EventError};
+    ///
+    /// let mut builder = EventBuilder::new();
+    /// let node_id = builder.open(1).unwrap();
+    ///
+    /// assert_eq!(builder.close(), Ok(node_id));
+    /// assert_eq!(builder.close(), Err(EventError::UnmatchedClose));
+    /// ```
+    ///
+    pub fn close(&mut self) -> Result<NodeId, EventError> {
+        self.open.pop().ok_or(EventError::UnmatchedClose)
+    }
+
+    ///
+    /// Finishes building and returns the `Tree`.  If no `Node`s were opened, the `Tree` is empty.
+    ///
+    /// Returns `EventError::Unclosed` if any `Node`s are still open.
+    ///
+    /// ```
+    /// use slab_tree::events::{EventBuilder, EventError};
+    ///
+    /// let mut builder = EventBuilder::new();
+    /// builder.open(1).unwrap();
+    ///
+    /// assert_eq!(builder.finish().unwrap_err(), EventError::Unclosed(1));
+    /// ```
+    ///
+    pub fn finish(self) -> Result<Tree<T>, EventError> {
+        if self.open.is_empty() {
+            Ok(self.tree)
+        } else {
+            Err(EventError::Unclosed(self.open.len()))
+        }
+    }
+}
+
+impl<T> Default for EventBuilder<T> {
+    fn default() -> Self {
+        EventBuilder::new()
+    }
+}
+
+///
+/// A single event in the depth-first event stream of a `Tree`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event<'a, T> {
+    ///
+    /// A `Node` (holding the given data) is entered; its children follow, then its `Close`.
+    ///
+    Open(&'a T),
+
+    ///
+    /// The most recently opened `Node` which hasn't been closed yet is finished.
+    ///
+    Close,
+}
+
+///
+/// An `Iterator` over the depth-first event stream of a `Tree`, created by `Tree::events`.
+///
+pub struct Events<'a, T> {
+    tree: &'a Tree<T>,
+    next: Option<NodeId>,
+    open: Vec<NodeId>,
+}
+
+impl<'a, T> Iterator for Events<'a, T> {
+    type Item = Event<'a, T>;
+
+    fn next(&mut self) -> Option<Event<'a, T>> {
+        if let Some(node_id) = self.next.take() {
+            let node = self
+                .tree
+                .get_node(node_id)
+                .expect("reachable node must exist");
+            self.next = node.relatives.first_child;
+            self.open.push(node_id);
+            return Some(Event::Open(&node.data));
+        }
+
+        let node_id = self.open.pop()?;
+        if !self.open.is_empty() {
+            self.next = self.tree.get_node_next_sibling_id(node_id);
+        }
+        Some(Event::Close)
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Returns an `Iterator` over the `Tree` as a depth-first stream of events: each `Node`
+    /// produces an `Event::Open` with its data, followed by the events of its children and then
+    /// an `Event::Close`.  The stream is empty if the `Tree` is empty.
+    ///
+    /// This is the dual of `EventBuilder`, and doesn't use recursion.
+    ///
+    /// ```
+    /// use slab_tree::events::Event;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(1);
+    /// root.append(2);
+    ///
+    /// let events: Vec<Event<i32>> = tree.events().collect();
+    /// assert_eq!(
+    ///     events,
+    ///     vec![
+    ///         Event::Open(&0),
+    ///         Event::Open(&1),
+    ///         Event::Close,
+    ///         Event::Open(&2),
+    ///         Event::Close,
+    ///         Event::Close,
+    ///     ]
+    /// );
+    /// ```
+    ///
+    pub fn events(&self) -> Events<'_, T> {
+        Events {
+            tree: self,
+            next: self.root_id(),
+            open: Vec::new(),
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod events_tests {
+    use super::*;
+
+    fn pre_order(tree: &Tree<i32>) -> Vec<(i32, usize)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| (*node.data(), node.ancestors().count()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn builds_nested_tree() {
+        let mut builder = EventBuilder::new();
+        builder.open(0).unwrap();
+        builder.open(1).unwrap();
+        builder.open(2).unwrap();
+        builder.close().unwrap();
+        builder.open(3).unwrap();
+        builder.close().unwrap();
+        builder.close().unwrap();
+        builder.open(4).unwrap();
+        builder.close().unwrap();
+        builder.close().unwrap();
+
+        let tree = builder.finish().unwrap();
+        assert_eq!(
+            pre_order(&tree),
+            vec![(0, 0), (1, 1), (2, 2), (3, 2), (4, 1)]
+        );
+    }
+
+    #[test]
+    fn finish_empty() {
+        let builder: EventBuilder<i32> = EventBuilder::default();
+        assert!(builder.finish().unwrap().root().is_none());
+    }
+
+    #[test]
+    fn close_returns_node_ids() {
+        let mut builder = EventBuilder::new();
+        let root_id = builder.open(0).unwrap();
+        let child_id = builder.open(1).unwrap();
+        assert_eq!(builder.close(), Ok(child_id));
+        assert_eq!(builder.close(), Ok(root_id));
+
+        let tree = builder.finish().unwrap();
+        assert_eq!(tree.root_id(), Some(root_id));
+        assert_eq!(*tree.get(child_id).unwrap().data(), 1);
+    }
+
+    #[test]
+    fn errors() {
+        let mut builder = EventBuilder::new();
+        assert_eq!(builder.close(), Err(EventError::UnmatchedClose));
+
+        builder.open(0).unwrap();
+        builder.open(1).unwrap();
+        builder.close().unwrap();
+        builder.close().unwrap();
+        assert_eq!(builder.open(2), Err(EventError::MultipleRoots));
+        assert!(builder.finish().is_ok());
+
+        let mut builder = EventBuilder::new();
+        builder.open(0).unwrap();
+        builder.open(1).unwrap();
+        assert_eq!(builder.finish().unwrap_err(), EventError::Unclosed(2));
+    }
+
+    #[test]
+    fn events_empty() {
+        let tree: Tree<i32> = Tree::new();
+        assert_eq!(tree.events().count(), 0);
+    }
+
+    #[test]
+    fn events_round_trip() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        one.append(2);
+        one.append(3).append(4);
+        root.append(5);
+
+        let mut builder = EventBuilder::new();
+        for event in tree.events() {
+            match event {
+                Event::Open(data) => {
+                    builder.open(*data).unwrap();
+                }
+                Event::Close => {
+                    builder.close().unwrap();
+                }
+            }
+        }
+
+        let rebuilt = builder.finish().unwrap();
+        assert_eq!(pre_order(&rebuilt), pre_order(&tree));
+    }
+
+    #[test]
+    fn events_deep_tree() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut node_id = tree.root_id().unwrap();
+        for i in 1..100_000 {
+            node_id = tree.get_mut(node_id).unwrap().append(i).node_id();
+        }
+
+        let closes = tree.events().filter(|e| *e == Event::Close).count();
+        assert_eq!(closes, 100_000);
+    }
+}
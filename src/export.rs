@@ -0,0 +1,287 @@
+//!
+//! Writers which export `Tree`s to text formats meant for documentation.
+//!
+
+use crate::tree::Tree;
+use crate::NodeId;
+use std::collections::HashMap;
+use std::fmt;
+
+///
+/// Describes how a single `Node` is rendered by `Tree::write_html_list`: the text inside its
+/// `<li>` element along with any attributes for that element.  Both are HTML-escaped when
+/// written.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HtmlItem {
+    text: String,
+    attributes: Vec<(String, String)>,
+}
+
+impl HtmlItem {
+    ///
+    /// Creates an `HtmlItem` with the given text and no attributes.
+    ///
+    /// ```
+    /// use slab_tree::export::HtmlItem;
+    ///
+    /// let item = HtmlItem::new("hello");
+    /// # assert_eq!(item, HtmlItem::new("hello".to_string()));
+    /// ```
+    ///
+    pub fn new<S: Into<String>>(text: S) -> HtmlItem {
+        HtmlItem {
+            text: text.into(),
+            attributes: Vec::new(),
+        }
+    }
+
+    ///
+    /// Adds an attribute to the `<li>` element of the `HtmlItem`.
+    ///
+    /// ```
+    /// use slab_tree::export::HtmlItem;
+    ///
+    /// let item = HtmlItem::new("hello").with_attribute("class", "greeting");
+    /// # assert_ne!(item, HtmlItem::new("hello"));
+    /// ```
+    ///
+    pub fn with_attribute<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.attributes.push((name.into(), value.into()));
+        self
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Writes the `Tree` as a top-down [Mermaid](https://mermaid.js.org) flowchart, using `label`
+    /// to produce the text shown for each `Node`.  The output can be pasted straight into a
+    /// ` ```mermaid ` block in Markdown.
+    ///
+    /// `Node`s are named `n0`, `n1`, ... in pre-order.  Characters which Mermaid would otherwise
+    /// interpret (`"` and `#`) are written as entity codes.  An empty `Tree` is written as just
+    /// the `graph TD` header.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("hello").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("world");
+    /// root.append("trees");
+    ///
+    /// let mut s = String::new();
+    /// tree.write_mermaid(&mut s, |data| data.to_string()).unwrap();
+    /// assert_eq!(&s, "\
+    /// graph TD
+    ///     n0[\"hello\"]
+    ///     n1[\"world\"]
+    ///     n0 --> n1
+    ///     n2[\"trees\"]
+    ///     n0 --> n2
+    /// ");
+    /// ```
+    ///
+    pub fn write_mermaid<W, F>(&self, w: &mut W, mut label: F) -> fmt::Result
+    where
+        W: fmt::Write,
+        F: FnMut(&T) -> String,
+    {
+        writeln!(w, "graph TD")?;
+
+        let root = match self.root() {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+
+        let mut names: HashMap<NodeId, usize> = HashMap::new();
+        for (name, node) in root.traverse_pre_order().enumerate() {
+            write!(w, "    n{}[\"", name)?;
+            for c in label(node.data()).chars() {
+                match c {
+                    '"' => w.write_str("#quot;")?,
+                    '#' => w.write_str("#35;")?,
+                    c => w.write_char(c)?,
+                }
+            }
+            writeln!(w, "\"]")?;
+
+            if let Some(parent) = node.parent() {
+                writeln!(w, "    n{} --> n{}", names[&parent.node_id()], name)?;
+            }
+            names.insert(node.node_id(), name);
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Writes the `Tree` as nested HTML `<ul>`/`<li>` lists, using `render_node` to produce the
+    /// text and attributes of each `Node`'s `<li>` element.  Writes nothing if the `Tree` is
+    /// empty.
+    ///
+    /// ```
+    /// use slab_tree::export::HtmlItem;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("fish & chips").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("cod");
+    /// root.append("chips");
+    ///
+    /// let mut s = String::new();
+    /// tree.write_html_list(&mut s, |data| HtmlItem::new(*data).with_attribute("class", "food"))
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     &s,
+    ///     "<ul><li class=\"food\">fish &amp; chips<ul>\
+    ///      <li class=\"food\">cod</li>\
+    ///      <li class=\"food\">chips</li>\
+    ///      </ul></li></ul>"
+    /// );
+    /// ```
+    ///
+    pub fn write_html_list<W, F>(&self, w: &mut W, mut render_node: F) -> fmt::Result
+    where
+        W: fmt::Write,
+        F: FnMut(&T) -> HtmlItem,
+    {
+        let root_id = match self.root_id() {
+            Some(root_id) => root_id,
+            None => return Ok(()),
+        };
+
+        w.write_str("<ul>")?;
+
+        // `true` once a node's children have all been written
+        let mut stack = vec![(root_id, false)];
+        while let Some((node_id, exiting)) = stack.pop() {
+            if exiting {
+                w.write_str("</ul></li>")?;
+                continue;
+            }
+
+            let node = self.get(node_id).expect("reachable node must exist");
+            let item = render_node(node.data());
+
+            w.write_str("<li")?;
+            for (name, value) in &item.attributes {
+                w.write_char(' ')?;
+                write_escaped(w, name)?;
+                w.write_str("=\"")?;
+                write_escaped(w, value)?;
+                w.write_char('"')?;
+            }
+            w.write_char('>')?;
+            write_escaped(w, &item.text)?;
+
+            match node.last_child() {
+                Some(last_child) => {
+                    w.write_str("<ul>")?;
+                    stack.push((node_id, true));
+                    let mut child_id = Some(last_child.node_id());
+                    while let Some(node_id) = child_id {
+                        stack.push((node_id, false));
+                        child_id = self.get_node_prev_sibling_id(node_id);
+                    }
+                }
+                None => w.write_str("</li>")?,
+            }
+        }
+
+        w.write_str("</ul>")
+    }
+}
+
+fn write_escaped<W: fmt::Write>(w: &mut W, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        match c {
+            '&' => w.write_str("&amp;")?,
+            '<' => w.write_str("&lt;")?,
+            '>' => w.write_str("&gt;")?,
+            '"' => w.write_str("&quot;")?,
+            '\'' => w.write_str("&#39;")?,
+            c => w.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+    use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+
+    #[test]
+    fn write_mermaid_empty() {
+        let tree: Tree<i32> = Tree::new();
+        let mut s = String::new();
+        tree.write_mermaid(&mut s, |data| data.to_string()).unwrap();
+        assert_eq!(s, "graph TD\n");
+    }
+
+    #[test]
+    fn write_mermaid_nested() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        root.append(1).append(2);
+        root.append(3);
+
+        let mut s = String::new();
+        tree.write_mermaid(&mut s, |data| format!("node {}", data))
+            .unwrap();
+        assert_eq!(
+            s,
+            "graph TD\n    n0[\"node 0\"]\n    n1[\"node 1\"]\n    n0 --> n1\n    n2[\"node 2\"]\n    n1 --> n2\n    n3[\"node 3\"]\n    n0 --> n3\n"
+        );
+    }
+
+    #[test]
+    fn write_mermaid_escapes_labels() {
+        let tree = TreeBuilder::new().with_root("say \"#1\"").build();
+        let mut s = String::new();
+        tree.write_mermaid(&mut s, |data| data.to_string()).unwrap();
+        assert_eq!(s, "graph TD\n    n0[\"say #quot;#35;1#quot;\"]\n");
+    }
+
+    #[test]
+    fn write_html_list_empty() {
+        let tree: Tree<i32> = Tree::new();
+        let mut s = String::new();
+        tree.write_html_list(&mut s, |data| HtmlItem::new(data.to_string()))
+            .unwrap();
+        assert_eq!(s, "");
+    }
+
+    #[test]
+    fn write_html_list_nested() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        root.append(1).append(2);
+        root.append(3);
+
+        let mut s = String::new();
+        tree.write_html_list(&mut s, |data| HtmlItem::new(data.to_string()))
+            .unwrap();
+        assert_eq!(
+            s,
+            "<ul><li>0<ul><li>1<ul><li>2</li></ul></li><li>3</li></ul></li></ul>"
+        );
+    }
+
+    #[test]
+    fn write_html_list_escapes() {
+        let tree = TreeBuilder::new().with_root("<b>'hi'</b>").build();
+        let mut s = String::new();
+        tree.write_html_list(&mut s, |data| {
+            HtmlItem::new(*data).with_attribute("data-x", "\"&\"")
+        })
+        .unwrap();
+        assert_eq!(
+            s,
+            "<ul><li data-x=\"&quot;&amp;&quot;\">&lt;b&gt;&#39;hi&#39;&lt;/b&gt;</li></ul>"
+        );
+    }
+}
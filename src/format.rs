@@ -0,0 +1,615 @@
+//!
+//! Writing `Tree`s as text diagrams: an indented outline (`Tree::write_formatted_with`, or
+//! `Tree::write_formatted_styled` for colored output) or a top-down drawing with each parent
+//! centered above its children (`Tree::write_top_down`).
+//!
+
+use crate::node::NodeRef;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::collections::HashMap;
+use std::fmt;
+
+///
+/// Chooses which annotations `Tree::write_formatted_with` writes after each `Node`'s data.
+///
+/// Annotations are written in parentheses, in the order `NodeId`, depth, child count.  With
+/// none of them turned on (the default) the output matches `Tree::write_formatted`.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    node_ids: bool,
+    depth: bool,
+    child_count: bool,
+}
+
+impl FormatOptions {
+    ///
+    /// Creates a `FormatOptions` with every annotation turned off.
+    ///
+    /// ```
+    /// use slab_tree::format::FormatOptions;
+    ///
+    /// let options = FormatOptions::new();
+    /// # assert_eq!(options, FormatOptions::default());
+    /// ```
+    ///
+    pub fn new() -> FormatOptions {
+        FormatOptions::default()
+    }
+
+    ///
+    /// Sets whether each `Node`'s `NodeId` is written (in its `Debug` form).
+    ///
+    /// ```
+    /// use slab_tree::format::FormatOptions;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::new().with_root(0).build();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// let mut s = String::new();
+    /// let options = FormatOptions::new().with_node_ids(true);
+    /// tree.write_formatted_with(&mut s, &options).unwrap();
+    /// assert_eq!(s, format!("0 ({:?})\n", root_id));
+    /// ```
+    ///
+    pub fn with_node_ids(mut self, node_ids: bool) -> Self {
+        self.node_ids = node_ids;
+        self
+    }
+
+    ///
+    /// Sets whether each `Node`'s depth is written; the root is at depth 0.
+    ///
+    /// ```
+    /// use slab_tree::format::FormatOptions;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(1);
+    ///
+    /// let mut s = String::new();
+    /// let options = FormatOptions::new().with_depth(true);
+    /// tree.write_formatted_with(&mut s, &options).unwrap();
+    /// assert_eq!(&s, "\
+    /// 0 (depth 0)
+    /// └── 1 (depth 1)
+    /// ");
+    /// ```
+    ///
+    pub fn with_depth(mut self, depth: bool) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    ///
+    /// Sets whether the number of children of each `Node` is written.
+    ///
+    /// ```
+    /// use slab_tree::format::FormatOptions;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(1);
+    ///
+    /// let mut s = String::new();
+    /// let options = FormatOptions::new().with_child_count(true);
+    /// tree.write_formatted_with(&mut s, &options).unwrap();
+    /// assert_eq!(&s, "\
+    /// 0 (1 child)
+    /// └── 1 (0 children)
+    /// ");
+    /// ```
+    ///
+    pub fn with_child_count(mut self, child_count: bool) -> Self {
+        self.child_count = child_count;
+        self
+    }
+
+    fn write_annotations<T, W: fmt::Write>(
+        &self,
+        w: &mut W,
+        tree: &Tree<T>,
+        node_id: NodeId,
+        depth: usize,
+    ) -> fmt::Result {
+        if !(self.node_ids || self.depth || self.child_count) {
+            return Ok(());
+        }
+        let mut separator = " (";
+        if self.node_ids {
+            write!(w, "{}{:?}", separator, node_id)?;
+            separator = ", ";
+        }
+        if self.depth {
+            write!(w, "{}depth {}", separator, depth)?;
+            separator = ", ";
+        }
+        if self.child_count {
+            let node = tree.get(node_id).expect("annotated node doesn't exist?");
+            match node.child_count() {
+                1 => write!(w, "{}1 child", separator)?,
+                count => write!(w, "{}{} children", separator, count)?,
+            }
+        }
+        write!(w, ")")
+    }
+}
+
+///
+/// Text written around a `Node` by `Tree::write_formatted_styled`, such as ANSI escape codes for
+/// coloring it.  The prefix and suffix wrap the `Node`'s data and annotations, and the branch
+/// prefix and suffix wrap the `├── ` or `└── ` glyph leading to it.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    prefix: String,
+    suffix: String,
+    branch_prefix: String,
+    branch_suffix: String,
+}
+
+impl Style {
+    ///
+    /// Creates a `Style` which writes nothing around the `Node`.
+    ///
+    /// ```
+    /// use slab_tree::format::Style;
+    ///
+    /// let style = Style::new();
+    /// # assert_eq!(style, Style::default());
+    /// ```
+    ///
+    pub fn new() -> Style {
+        Style::default()
+    }
+
+    ///
+    /// Sets the text written around the `Node`'s data and annotations.
+    ///
+    /// ```
+    /// use slab_tree::format::Style;
+    ///
+    /// let red = Style::new().with_node("\x1b[31m", "\x1b[0m");
+    /// # assert_ne!(red, Style::new());
+    /// ```
+    ///
+    pub fn with_node<P: Into<String>, S: Into<String>>(mut self, prefix: P, suffix: S) -> Self {
+        self.prefix = prefix.into();
+        self.suffix = suffix.into();
+        self
+    }
+
+    ///
+    /// Sets the text written around the branch glyph leading to the `Node`.  The root has no
+    /// branch glyph, so this has no effect on it.
+    ///
+    /// ```
+    /// use slab_tree::format::Style;
+    ///
+    /// let red = Style::new().with_branch("\x1b[31m", "\x1b[0m");
+    /// # assert_ne!(red, Style::new());
+    /// ```
+    ///
+    pub fn with_branch<P: Into<String>, S: Into<String>>(mut self, prefix: P, suffix: S) -> Self {
+        self.branch_prefix = prefix.into();
+        self.branch_suffix = suffix.into();
+        self
+    }
+}
+
+// where a subtree sits inside its own block of columns in a top-down drawing
+struct Layout {
+    label: Vec<char>,
+    width: usize,
+    label_start: usize,
+    child_starts: Vec<usize>,
+}
+
+impl Layout {
+    fn center(&self) -> usize {
+        self.label_start + self.label.len() / 2
+    }
+}
+
+impl<T: fmt::Debug> Tree<T> {
+    ///
+    /// Writes the `Tree` the same way as `Tree::write_formatted`, adding the annotations chosen
+    /// in `options` after each `Node`'s data.  Useful when chasing `NodeId`-related bugs, where
+    /// the plain outline doesn't say which line belongs to which `NodeId`.
+    ///
+    /// ```
+    /// use slab_tree::format::FormatOptions;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(1).append(2);
+    /// root.append(3);
+    ///
+    /// let mut s = String::new();
+    /// let options = FormatOptions::new().with_depth(true).with_child_count(true);
+    /// tree.write_formatted_with(&mut s, &options).unwrap();
+    /// assert_eq!(&s, "\
+    /// 0 (depth 0, 2 children)
+    /// ├── 1 (depth 1, 1 child)
+    /// │   └── 2 (depth 2, 0 children)
+    /// └── 3 (depth 1, 0 children)
+    /// ");
+    /// ```
+    ///
+    pub fn write_formatted_with<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        options: &FormatOptions,
+    ) -> fmt::Result {
+        self.write_formatted_styled(w, options, |_| Style::new())
+    }
+
+    ///
+    /// Writes the `Tree` the same way as `Tree::write_formatted_with`, wrapping each `Node` (and
+    /// the branch glyph leading to it) in the text given by the `Style` that `style` returns for
+    /// it.  Handy for highlighting the interesting `Node`s of a dump with terminal colors.
+    ///
+    /// ```
+    /// use slab_tree::format::{FormatOptions, Style};
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(1);
+    /// root.append(2);
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted_styled(&mut s, &FormatOptions::new(), |node| {
+    ///     if *node.data() == 1 {
+    ///         Style::new().with_node("<", ">").with_branch("[", "]")
+    ///     } else {
+    ///         Style::new()
+    ///     }
+    /// })
+    /// .unwrap();
+    /// assert_eq!(&s, "\
+    /// 0
+    /// [├── ]<1>
+    /// └── 2
+    /// ");
+    /// ```
+    ///
+    pub fn write_formatted_styled<W, F>(
+        &self,
+        w: &mut W,
+        options: &FormatOptions,
+        mut style: F,
+    ) -> fmt::Result
+    where
+        W: fmt::Write,
+        F: FnMut(&NodeRef<T>) -> Style,
+    {
+        let root_id = match self.root_id() {
+            Some(root_id) => root_id,
+            None => return Ok(()),
+        };
+
+        // whether the node (or ancestor) on each level below the root is the last of its
+        // siblings; pushed when descending and popped when climbing back up
+        let mut last: Vec<bool> = Vec::new();
+        let mut node_id = root_id;
+        loop {
+            let node = NodeRef::new(node_id, self);
+            let node_style = style(&node);
+            let level = last.len();
+            for &is_last in last.iter().take(level.saturating_sub(1)) {
+                if is_last {
+                    write!(w, "    ")?;
+                } else {
+                    write!(w, "│   ")?;
+                }
+            }
+            if let Some(&is_last) = last.last() {
+                write!(w, "{}", node_style.branch_prefix)?;
+                if is_last {
+                    write!(w, "└── ")?;
+                } else {
+                    write!(w, "├── ")?;
+                }
+                write!(w, "{}", node_style.branch_suffix)?;
+            }
+            write!(w, "{}{:?}", node_style.prefix, node.data())?;
+            options.write_annotations(w, self, node_id, level)?;
+            writeln!(w, "{}", node_style.suffix)?;
+
+            let relatives = self.get_node_relatives(node_id);
+            if let Some(child_id) = relatives.first_child {
+                last.push(self.get_node_relatives(child_id).next_sibling.is_none());
+                node_id = child_id;
+                continue;
+            }
+
+            // climb until some ancestor (or the node itself) has a next sibling to move on to
+            loop {
+                if last.is_empty() {
+                    return Ok(());
+                }
+                let relatives = self.get_node_relatives(node_id);
+                last.pop();
+                match relatives.next_sibling {
+                    Some(next_id) => {
+                        last.push(self.get_node_relatives(next_id).next_sibling.is_none());
+                        node_id = next_id;
+                        break;
+                    }
+                    None => node_id = relatives.parent.expect("non-root must have a parent"),
+                }
+            }
+        }
+    }
+
+    ///
+    /// Writes the `Tree` top-down, with each `Node`'s data (in its `Debug` form) centered above
+    /// its children and box-drawing lines connecting them.  Sibling subtrees are separated by a
+    /// single column.  Widths are counted in `char`s, so data containing wide or combining
+    /// characters may not line up.  Writes nothing if the tree is empty.
+    ///
+    /// This is usually easier to read than `Tree::write_formatted` for wide, shallow trees.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("a").append("c");
+    /// root.append("b");
+    ///
+    /// let mut s = String::new();
+    /// tree.write_top_down(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// \"root\"
+    ///  ┌─┴─┐
+    /// \"a\" \"b\"
+    ///  │
+    /// \"c\"
+    /// ");
+    /// ```
+    ///
+    pub fn write_top_down<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        let root = match self.root() {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+
+        // lay out every subtree in post-order, so children are done before their parent
+        let mut layouts: HashMap<NodeId, Layout> = HashMap::new();
+        for node in root.traverse_post_order() {
+            let label: Vec<char> = format!("{:?}", node.data()).chars().collect();
+            let mut child_starts = Vec::new();
+            let mut next_start = 0;
+            let mut first_center = None;
+            let mut last_center = 0;
+            for child in node.children() {
+                let child_layout = &layouts[&child.node_id()];
+                child_starts.push(next_start);
+                first_center.get_or_insert(next_start + child_layout.center());
+                last_center = next_start + child_layout.center();
+                next_start += child_layout.width + 1;
+            }
+
+            let layout = match first_center {
+                None => Layout {
+                    width: label.len(),
+                    label,
+                    label_start: 0,
+                    child_starts,
+                },
+                Some(first_center) => {
+                    let center = (first_center + last_center) / 2;
+                    // shift the children right if the label would stick out to the left
+                    let shift = (label.len() / 2).saturating_sub(center);
+                    for start in child_starts.iter_mut() {
+                        *start += shift;
+                    }
+                    let label_start = center + shift - label.len() / 2;
+                    Layout {
+                        width: (next_start - 1 + shift).max(label_start + label.len()),
+                        label,
+                        label_start,
+                        child_starts,
+                    }
+                }
+            };
+            layouts.insert(node.node_id(), layout);
+        }
+
+        // place every subtree's block, drawing labels and the lines down to their children
+        let width = layouts[&root.node_id()].width;
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut stack = vec![(root.node_id(), 0, 0)];
+        while let Some((node_id, block_start, depth)) = stack.pop() {
+            let layout = &layouts[&node_id];
+            while rows.len() < depth * 2 + 2 {
+                rows.push(vec![' '; width]);
+            }
+
+            let label_start = block_start + layout.label_start;
+            rows[depth * 2][label_start..label_start + layout.label.len()]
+                .copy_from_slice(&layout.label);
+
+            if layout.child_starts.is_empty() {
+                continue;
+            }
+            let node = self.get(node_id).expect("laid out node doesn't exist?");
+            let child_centers: Vec<usize> = node
+                .children()
+                .zip(layout.child_starts.iter())
+                .map(|(child, start)| block_start + start + layouts[&child.node_id()].center())
+                .collect();
+            let first = child_centers[0];
+            let last = child_centers[child_centers.len() - 1];
+            let lines = &mut rows[depth * 2 + 1];
+            for column in lines[first..=last].iter_mut() {
+                *column = '─';
+            }
+            for &center in child_centers.iter() {
+                lines[center] = '┬';
+            }
+            lines[first] = '┌';
+            lines[last] = '┐';
+            let center = block_start + layout.center();
+            lines[center] = match lines[center] {
+                _ if first == last => '│',
+                '┌' => '├',
+                '┐' => '┤',
+                '┬' => '┼',
+                _ => '┴',
+            };
+
+            for (child, start) in node.children().zip(layout.child_starts.iter()) {
+                stack.push((child.node_id(), block_start + start, depth + 1));
+            }
+        }
+
+        for row in rows.iter() {
+            let line: String = row.iter().collect();
+            let line = line.trim_end();
+            if !line.is_empty() {
+                writeln!(w, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod format_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    #[test]
+    fn all_annotations() {
+        let tree = Tree::unfold(2, |n: i32| (n, (0..n).collect()));
+        let root = tree.root().unwrap();
+        let ids: Vec<_> = root.traverse_pre_order().map(|n| n.node_id()).collect();
+
+        let mut s = String::new();
+        let options = FormatOptions::new()
+            .with_node_ids(true)
+            .with_depth(true)
+            .with_child_count(true);
+        tree.write_formatted_with(&mut s, &options).unwrap();
+
+        let expected = vec![
+            format!("2 ({:?}, depth 0, 2 children)", ids[0]),
+            format!("├── 0 ({:?}, depth 1, 0 children)", ids[1]),
+            format!("└── 1 ({:?}, depth 1, 1 child)", ids[2]),
+            format!("    └── 0 ({:?}, depth 2, 0 children)", ids[3]),
+        ];
+        assert_eq!(s.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn default_matches_write_formatted() {
+        let tree = Tree::unfold(3, |n: i32| (n, (0..n).collect()));
+        let mut plain = String::new();
+        tree.write_formatted(&mut plain).unwrap();
+        let mut with = String::new();
+        tree.write_formatted_with(&mut with, &FormatOptions::default())
+            .unwrap();
+        assert_eq!(with, plain);
+    }
+
+    #[test]
+    fn outline_matches_shape() {
+        let tree = Tree::unfold(4, |n: i32| (n, (0..n).collect()));
+        let mut s = String::new();
+        tree.write_formatted(&mut s).unwrap();
+        let lines: Vec<&str> = s.lines().collect();
+        let expected: Vec<(i32, usize)> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (*node.data(), node.ancestors().count()))
+            .collect();
+        assert_eq!(lines.len(), expected.len());
+        for (line, (data, depth)) in lines.iter().zip(expected) {
+            let indent = if depth == 0 { 0 } else { depth * 4 };
+            assert_eq!(line.chars().count(), indent + 1);
+            assert!(line.ends_with(&data.to_string()));
+        }
+        assert_eq!(lines[lines.len() - 1], "            └── 0");
+    }
+
+    #[test]
+    fn empty() {
+        let tree: Tree<i32> = Tree::new();
+        let mut s = String::new();
+        let options = FormatOptions::new().with_node_ids(true);
+        tree.write_formatted_with(&mut s, &options).unwrap();
+        assert_eq!(s, "");
+    }
+
+    #[test]
+    fn styled() {
+        let tree = Tree::unfold(2, |n: i32| (n, (0..n).collect()));
+        let mut s = String::new();
+        let options = FormatOptions::new().with_child_count(true);
+        tree.write_formatted_styled(&mut s, &options, |node| match node.data() {
+            0 => Style::new().with_node("*", "*"),
+            _ => Style::new().with_branch("(", ")"),
+        })
+        .unwrap();
+        assert_eq!(
+            s,
+            "\
+2 (2 children)
+├── *0 (0 children)*
+(└── )1 (1 child)
+    └── *0 (0 children)*
+"
+        );
+    }
+
+    #[test]
+    fn top_down_centers_parents() {
+        let tree = Tree::unfold(3, |n: i32| (n, (0..n).collect()));
+        let mut s = String::new();
+        tree.write_top_down(&mut s).unwrap();
+        assert_eq!(
+            s,
+            "  3
+┌─┼──┐
+0 1  2
+  │ ┌┴┐
+  0 0 1
+      │
+      0
+"
+        );
+    }
+
+    #[test]
+    fn top_down_wide_label() {
+        let mut tree = TreeBuilder::new().with_root("parent").build();
+        let mut root = tree.root_mut().unwrap();
+        root.append("a");
+        root.append("b");
+        let mut s = String::new();
+        tree.write_top_down(&mut s).unwrap();
+        assert_eq!(
+            s,
+            "\"parent\"
+  ┌─┴─┐
+ \"a\" \"b\"
+"
+        );
+    }
+
+    #[test]
+    fn top_down_empty() {
+        let tree: Tree<i32> = Tree::new();
+        let mut s = String::new();
+        tree.write_top_down(&mut s).unwrap();
+        assert_eq!(s, "");
+    }
+}
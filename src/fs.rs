@@ -0,0 +1,358 @@
+//!
+//! Loading directory trees from the filesystem.
+//!
+//! `Tree::from_dir` walks a directory into a `Tree` with one `Node` per entry, describing each
+//! entry with a `DirEntryData`.  The entries of each directory are sorted by name, so loading
+//! the same directory twice gives the same `Tree`.  Symbolic links are never followed: they are
+//! loaded as leaves, which also keeps links that point back up the tree from looping forever.
+//!
+
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+///
+/// The kinds of filesystem entries a `DirEntryData` can describe.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EntryKind {
+    /// A regular file.
+    File,
+    /// A directory.
+    Dir,
+    /// A symbolic link, which isn't followed.
+    Symlink,
+    /// Anything else, e.g. a socket or a device.
+    Other,
+}
+
+///
+/// A single entry of a directory tree loaded by `Tree::from_dir`.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirEntryData {
+    ///
+    /// The entry's file name.  For the root, this is the last component of the path it was
+    /// loaded from (or the whole path, if it has none, e.g. `/` or `..`).
+    ///
+    pub name: OsString,
+
+    ///
+    /// The entry's path: the path the root was loaded from, joined with the names of the entries
+    /// leading to this one.
+    ///
+    pub path: PathBuf,
+
+    ///
+    /// What kind of entry this is.
+    ///
+    pub kind: EntryKind,
+
+    ///
+    /// The entry's size in bytes, as reported by its metadata.
+    ///
+    pub len: u64,
+}
+
+impl DirEntryData {
+    fn load(path: PathBuf, name: OsString) -> io::Result<DirEntryData> {
+        let metadata = fs::symlink_metadata(&path)?;
+        let file_type = metadata.file_type();
+        let kind = if file_type.is_symlink() {
+            EntryKind::Symlink
+        } else if file_type.is_dir() {
+            EntryKind::Dir
+        } else if file_type.is_file() {
+            EntryKind::File
+        } else {
+            EntryKind::Other
+        };
+        Ok(DirEntryData {
+            name,
+            path,
+            kind,
+            len: metadata.len(),
+        })
+    }
+}
+
+///
+/// Limits on which entries `Tree::from_dir` loads.  By default, every entry is loaded, however
+/// deep it is.
+///
+#[derive(Default)]
+pub struct DirOptions {
+    max_depth: Option<usize>,
+    filter: Option<Box<Filter>>,
+}
+
+type Filter = dyn Fn(&DirEntryData) -> bool;
+
+impl DirOptions {
+    ///
+    /// Creates `DirOptions` that load every entry.
+    ///
+    /// ```
+    /// use slab_tree::fs::DirOptions;
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let src = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
+    /// let tree = Tree::from_dir(src, DirOptions::new()).unwrap();
+    /// let root = tree.root().unwrap();
+    /// assert!(root.traverse_pre_order().any(|node| node.data().name == "lib.rs"));
+    /// ```
+    ///
+    pub fn new() -> DirOptions {
+        DirOptions::default()
+    }
+
+    ///
+    /// Only loads entries at most `max_depth` levels below the root (which is at depth 0).
+    /// Directories at `max_depth` are loaded as leaves, without reading their entries.
+    ///
+    /// ```
+    /// use slab_tree::fs::{DirOptions, EntryKind};
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let options = DirOptions::new().with_max_depth(1);
+    /// let tree = Tree::from_dir(env!("CARGO_MANIFEST_DIR"), options).unwrap();
+    ///
+    /// let root = tree.root().unwrap();
+    /// let src = root.children().find(|node| node.data().name == "src").unwrap();
+    /// assert_eq!(src.data().kind, EntryKind::Dir);
+    /// assert!(src.is_leaf());
+    /// ```
+    ///
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    ///
+    /// Only loads the entries below the root for which `filter` returns `true`.  Directories the
+    /// filter rejects are skipped along with everything in them.
+    ///
+    /// ```
+    /// use slab_tree::fs::{DirOptions, EntryKind};
+    /// use slab_tree::tree::Tree;
+    ///
+    /// // directories and Rust sources, but nothing from the build output
+    /// let options = DirOptions::new().with_filter(|entry| match entry.kind {
+    ///     EntryKind::Dir => entry.name != "target" && entry.name != ".git",
+    ///     _ => entry.path.extension().map_or(false, |extension| extension == "rs"),
+    /// });
+    /// let tree = Tree::from_dir(env!("CARGO_MANIFEST_DIR"), options).unwrap();
+    ///
+    /// let root = tree.root().unwrap();
+    /// assert!(root.traverse_pre_order().skip(1).all(|node| {
+    ///     let entry = node.data();
+    ///     entry.kind == EntryKind::Dir || entry.name.to_string_lossy().ends_with(".rs")
+    /// }));
+    /// ```
+    ///
+    pub fn with_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&DirEntryData) -> bool + 'static,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+}
+
+impl fmt::Debug for DirOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirOptions")
+            .field("max_depth", &self.max_depth)
+            .field("filter", &self.filter.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl Tree<DirEntryData> {
+    ///
+    /// Loads the directory at `path` (and everything in it, subject to `options`) into a `Tree`
+    /// rooted at `path`, as described in the `fs` module.  If `path` isn't a directory, the
+    /// `Tree` only has a root.  Fails with the first error that comes up reading an entry.
+    ///
+    /// ```
+    /// use slab_tree::fs::{DirOptions, EntryKind};
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let tree = Tree::from_dir(env!("CARGO_MANIFEST_DIR"), DirOptions::new().with_max_depth(1))
+    ///     .unwrap();
+    ///
+    /// let root = tree.root().unwrap();
+    /// assert_eq!(root.data().kind, EntryKind::Dir);
+    /// let manifest = root.children().find(|node| node.data().name == "Cargo.toml").unwrap();
+    /// assert_eq!(manifest.data().kind, EntryKind::File);
+    /// assert!(manifest.data().len > 0);
+    /// ```
+    ///
+    pub fn from_dir<P: AsRef<Path>>(path: P, options: DirOptions) -> io::Result<Self> {
+        let path = path.as_ref();
+        let name = path.file_name().unwrap_or(path.as_os_str()).to_os_string();
+        let root = DirEntryData::load(path.to_path_buf(), name)?;
+        let is_dir = root.kind == EntryKind::Dir;
+        let mut tree = TreeBuilder::new().with_root(root).build();
+        if !is_dir {
+            return Ok(tree);
+        }
+
+        // directories still to read, along with their depth
+        let mut stack: Vec<(NodeId, usize)> = vec![(tree.root_id().expect("root was just set"), 0)];
+        while let Some((dir_id, depth)) = stack.pop() {
+            if matches!(options.max_depth, Some(max_depth) if depth >= max_depth) {
+                continue;
+            }
+
+            let dir_path = &tree.get(dir_id).expect("directory must exist").data().path;
+            let mut entries = Vec::new();
+            for entry in fs::read_dir(dir_path)? {
+                let entry = entry?;
+                let entry = DirEntryData::load(entry.path(), entry.file_name())?;
+                if options.filter.iter().all(|filter| filter(&entry)) {
+                    entries.push(entry);
+                }
+            }
+            entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+            let mut dir = tree.get_mut(dir_id).expect("directory must exist");
+            let start = stack.len();
+            for entry in entries {
+                let is_dir = entry.kind == EntryKind::Dir;
+                let entry_id = dir.append(entry).node_id();
+                if is_dir {
+                    stack.push((entry_id, depth + 1));
+                }
+            }
+            // read directories in order, so errors come up in the order they would in a listing
+            stack[start..].reverse();
+        }
+
+        Ok(tree)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod fs_tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A scratch directory that is removed again when dropped.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new() -> Scratch {
+            static COUNT: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "slab_tree_fs_tests_{}_{}",
+                std::process::id(),
+                COUNT.fetch_add(1, Ordering::SeqCst)
+            ));
+            fs::create_dir_all(path.join("b/deep/deeper")).unwrap();
+            fs::create_dir_all(path.join("a")).unwrap();
+            fs::write(path.join("b/deep/deeper/leaf.txt"), "leaf").unwrap();
+            fs::write(path.join("b/one.txt"), "1").unwrap();
+            fs::write(path.join("c.txt"), "three").unwrap();
+            Scratch(path)
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn listing(tree: &Tree<DirEntryData>) -> Vec<(String, usize)> {
+        tree.root()
+            .unwrap()
+            .traverse_pre_order()
+            .skip(1)
+            .map(|node| {
+                let name = node.data().name.to_string_lossy().into_owned();
+                (name, node.depth())
+            })
+            .collect()
+    }
+
+    fn owned(listing: &[(&str, usize)]) -> Vec<(String, usize)> {
+        listing
+            .iter()
+            .map(|&(name, depth)| (name.to_string(), depth))
+            .collect()
+    }
+
+    #[test]
+    fn loads_sorted_entries() {
+        let scratch = Scratch::new();
+        let tree = Tree::from_dir(&scratch.0, DirOptions::new()).unwrap();
+        assert_eq!(
+            listing(&tree),
+            owned(&[
+                ("a", 1),
+                ("b", 1),
+                ("deep", 2),
+                ("deeper", 3),
+                ("leaf.txt", 4),
+                ("one.txt", 2),
+                ("c.txt", 1),
+            ])
+        );
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.data().path, scratch.0);
+        assert_eq!(root.data().name, scratch.0.file_name().unwrap());
+        let c = root.last_child().unwrap();
+        assert_eq!(c.data().kind, EntryKind::File);
+        assert_eq!(c.data().len, 5);
+        assert_eq!(c.data().path, scratch.0.join("c.txt"));
+    }
+
+    #[test]
+    fn options_limit_entries() {
+        let scratch = Scratch::new();
+        let tree = Tree::from_dir(&scratch.0, DirOptions::new().with_max_depth(2)).unwrap();
+        assert_eq!(
+            listing(&tree),
+            owned(&[
+                ("a", 1),
+                ("b", 1),
+                ("deep", 2),
+                ("one.txt", 2),
+                ("c.txt", 1)
+            ])
+        );
+
+        let options = DirOptions::new().with_filter(|entry| entry.name != "deep");
+        let tree = Tree::from_dir(&scratch.0, options).unwrap();
+        assert_eq!(
+            listing(&tree),
+            owned(&[("a", 1), ("b", 1), ("one.txt", 2), ("c.txt", 1)])
+        );
+
+        let tree = Tree::from_dir(scratch.0.join("c.txt"), DirOptions::new()).unwrap();
+        assert!(tree.root().unwrap().is_leaf());
+        assert!(Tree::from_dir(scratch.0.join("missing"), DirOptions::new()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_leaves() {
+        let scratch = Scratch::new();
+        std::os::unix::fs::symlink(&scratch.0, scratch.0.join("a/loop")).unwrap();
+        let tree = Tree::from_dir(&scratch.0, DirOptions::new()).unwrap();
+        let root = tree.root().unwrap();
+        let a = root.first_child().unwrap();
+        let link = a.first_child().unwrap();
+        assert_eq!(link.data().kind, EntryKind::Symlink);
+        assert!(link.is_leaf());
+    }
+}
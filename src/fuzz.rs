@@ -0,0 +1,168 @@
+use crate::behaviors::RemoveBehavior;
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use arbitrary::Arbitrary;
+use arbitrary::Unstructured;
+
+///
+/// Generates a `Tree` with an arbitrary shape and arbitrary data.
+///
+/// Each new `Node` is appended beneath a randomly chosen, previously generated `Node`, so every
+/// possible shape can be produced.
+///
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Tree<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let len = u.arbitrary_len::<T>()?;
+        let mut tree = TreeBuilder::new().with_capacity(len).build();
+        let mut ids: Vec<NodeId> = Vec::with_capacity(len);
+
+        for _ in 0..len {
+            let data = T::arbitrary(u)?;
+            let id = if ids.is_empty() {
+                tree.set_root(data)
+            } else {
+                let parent_id = ids[u.choose_index(ids.len())?];
+                tree.get_mut(parent_id)
+                    .expect("generated node must exist")
+                    .append(data)
+                    .node_id()
+            };
+            ids.push(id);
+        }
+
+        Ok(tree)
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Applies a single, randomly chosen mutation to the `Tree`.  The mutation (and any data it
+    /// needs) is drawn from `u`, which makes this a convenient building block for fuzzing code
+    /// that has to keep working while a `Tree` changes underneath it.
+    ///
+    /// ```
+    /// use arbitrary::{Arbitrary, Unstructured};
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let bytes = [7u8; 64];
+    /// let mut u = Unstructured::new(&bytes);
+    ///
+    /// let mut tree = Tree::<u8>::arbitrary(&mut u).unwrap();
+    /// while !u.is_empty() {
+    ///     tree.arbitrary_mutation(&mut u).unwrap();
+    /// }
+    /// ```
+    ///
+    pub fn arbitrary_mutation<'a>(&mut self, u: &mut Unstructured<'a>) -> arbitrary::Result<()>
+    where
+        T: Arbitrary<'a>,
+    {
+        let ids: Vec<NodeId> = match self.root() {
+            Some(root) => root
+                .traverse_pre_order()
+                .map(|node_ref| node_ref.node_id())
+                .collect(),
+            None => {
+                self.set_root(T::arbitrary(u)?);
+                return Ok(());
+            }
+        };
+        let node_id = ids[u.choose_index(ids.len())?];
+        let mut node = self.get_mut(node_id).expect("reachable node must exist");
+
+        match u.int_in_range(0..=7u8)? {
+            0 => {
+                node.append(T::arbitrary(u)?);
+            }
+            1 => {
+                node.prepend(T::arbitrary(u)?);
+            }
+            2 => {
+                let behavior = if u.arbitrary()? {
+                    RemoveBehavior::DropChildren
+                } else {
+                    RemoveBehavior::OrphanChildren
+                };
+                self.remove(node_id, behavior);
+            }
+            3 => {
+                node.swap_next_sibling();
+            }
+            4 => {
+                node.swap_prev_sibling();
+            }
+            5 => {
+                node.make_first_sibling();
+            }
+            6 => {
+                node.make_last_sibling();
+            }
+            _ => {
+                self.set_root(T::arbitrary(u)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod fuzz_tests {
+    use super::*;
+
+    fn bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    fn assert_links_consistent<T>(tree: &Tree<T>) {
+        if let Some(root) = tree.root() {
+            assert!(root.parent().is_none());
+            for node in root.traverse_pre_order() {
+                let children: Vec<NodeId> = node.children().map(|c| c.node_id()).collect();
+                assert_eq!(node.child_count(), children.len());
+                assert_eq!(
+                    node.first_child().map(|c| c.node_id()),
+                    children.first().cloned()
+                );
+                assert_eq!(
+                    node.last_child().map(|c| c.node_id()),
+                    children.last().cloned()
+                );
+                for child in node.children() {
+                    assert_eq!(child.parent().unwrap().node_id(), node.node_id());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn arbitrary_tree() {
+        let data = bytes(512);
+        let mut u = Unstructured::new(&data);
+        let tree = Tree::<u16>::arbitrary(&mut u).unwrap();
+
+        assert!(tree.root().is_some());
+        assert_links_consistent(&tree);
+    }
+
+    #[test]
+    fn arbitrary_tree_from_empty_input() {
+        let mut u = Unstructured::new(&[]);
+        let tree = Tree::<u16>::arbitrary(&mut u).unwrap();
+        assert!(tree.root().is_none());
+    }
+
+    #[test]
+    fn arbitrary_mutations_keep_links_consistent() {
+        let data = bytes(4096);
+        let mut u = Unstructured::new(&data);
+        let mut tree = Tree::<u8>::new();
+
+        while !u.is_empty() {
+            tree.arbitrary_mutation(&mut u).unwrap();
+            assert_links_consistent(&tree);
+        }
+    }
+}
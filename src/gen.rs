@@ -0,0 +1,144 @@
+//!
+//! Random `Tree` generation, mostly useful for benchmarks and examples.
+//!
+
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use rand::Rng;
+
+impl<T> Tree<T> {
+    ///
+    /// Creates a `Tree` with exactly `node_count` `Node`s arranged in a random shape where no
+    /// `Node` has more than `max_branching` children.  The data for each `Node` is produced by
+    /// `data_fn`, in the order the `Node`s are created (the root first).
+    ///
+    /// Each new `Node` is appended to a `Node` chosen uniformly from those that still have room
+    /// for another child, so the same seed always produces the same `Tree`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_branching` is 0 and `node_count` is greater than 1.
+    ///
+    /// ```
+    /// use rand::rngs::StdRng;
+    /// use rand::{Rng, SeedableRng};
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(42);
+    /// let tree = Tree::random(&mut rng, 100, 3, |rng| rng.gen::<u32>());
+    ///
+    /// let root = tree.root().expect("root doesn't exist?");
+    /// assert_eq!(root.traverse_pre_order().count(), 100);
+    /// assert!(root.traverse_pre_order().all(|node| node.children().count() <= 3));
+    /// ```
+    ///
+    pub fn random<R, F>(
+        rng: &mut R,
+        node_count: usize,
+        max_branching: usize,
+        mut data_fn: F,
+    ) -> Tree<T>
+    where
+        R: Rng + ?Sized,
+        F: FnMut(&mut R) -> T,
+    {
+        assert!(
+            max_branching > 0 || node_count <= 1,
+            "a tree with more than one node needs a max_branching of at least 1"
+        );
+
+        let mut tree = TreeBuilder::new().with_capacity(node_count).build();
+        if node_count == 0 {
+            return tree;
+        }
+
+        let root_id = tree.set_root(data_fn(rng));
+
+        // nodes which can still take another child, along with how many children they have
+        let mut open: Vec<(NodeId, usize)> = vec![(root_id, 0)];
+
+        for _ in 1..node_count {
+            let slot = rng.gen_range(0..open.len());
+            let data = data_fn(rng);
+            let (parent_id, child_count) = &mut open[slot];
+
+            let new_id = tree
+                .get_mut(*parent_id)
+                .expect("open node must exist")
+                .append(data)
+                .node_id();
+
+            *child_count += 1;
+            if *child_count == max_branching {
+                open.swap_remove(slot);
+            }
+            open.push((new_id, 0));
+        }
+
+        tree
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod gen_tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn pre_order(tree: &Tree<usize>) -> Vec<usize> {
+        tree.root()
+            .map(|root| root.traverse_pre_order().map(|n| *n.data()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn random_empty() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let tree: Tree<usize> = Tree::random(&mut rng, 0, 0, |_| 0);
+        assert!(tree.root().is_none());
+    }
+
+    #[test]
+    fn random_single_branch_is_a_chain() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut next = 0;
+        let tree = Tree::random(&mut rng, 50, 1, |_| {
+            next += 1;
+            next
+        });
+
+        let leaf = tree.root().unwrap().traverse_pre_order().last().unwrap();
+        assert_eq!(leaf.ancestors().count(), 49);
+        assert_eq!(pre_order(&tree), (1..=50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn random_is_deterministic_for_a_seed() {
+        let build = || {
+            let mut rng = StdRng::seed_from_u64(7);
+            let mut next = 0;
+            Tree::random(&mut rng, 200, 4, |_| {
+                next += 1;
+                next
+            })
+        };
+
+        let first = build();
+        let second = build();
+        assert_eq!(pre_order(&first), pre_order(&second));
+        assert!(first
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .all(|node| node.children().count() <= 4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn random_zero_branching_panics() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let _: Tree<usize> = Tree::random(&mut rng, 2, 0, |_| 0);
+    }
+}
@@ -0,0 +1,237 @@
+//!
+//! Sharing equal `Node` data between `Node`s (and `Tree`s) instead of storing it repeatedly.
+//!
+//! An `Interner` hands out reference-counted `Arc<T>` handles, giving equal values the same
+//! handle, so a `Tree<Arc<T>>` built with one stores each distinct value only once.  This pays
+//! off for trees of strings such as paths or tag names, where the same values repeat many times.
+//!
+
+use crate::tree::Tree;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::Arc;
+
+///
+/// A set of shared values, handing out the same `Arc<T>` for equal values.
+///
+#[derive(Debug)]
+pub struct Interner<T> {
+    values: HashSet<Arc<T>>,
+}
+
+impl<T: Eq + Hash> Interner<T> {
+    ///
+    /// Creates a new, empty `Interner`.
+    ///
+    /// ```
+    /// use slab_tree::intern::Interner;
+    ///
+    /// let interner: Interner<String> = Interner::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    ///
+    pub fn new() -> Interner<T> {
+        Interner {
+            values: HashSet::new(),
+        }
+    }
+
+    ///
+    /// Returns the number of distinct values in the `Interner`.
+    ///
+    /// ```
+    /// use slab_tree::intern::Interner;
+    ///
+    /// let mut interner = Interner::new();
+    /// interner.intern("a");
+    /// interner.intern("b");
+    /// interner.intern("a");
+    ///
+    /// assert_eq!(interner.len(), 2);
+    /// ```
+    ///
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    ///
+    /// Returns `true` if the `Interner` holds no values.
+    ///
+    /// ```
+    /// use slab_tree::intern::Interner;
+    ///
+    /// let mut interner = Interner::new();
+    /// assert!(interner.is_empty());
+    ///
+    /// interner.intern(1);
+    /// assert!(!interner.is_empty());
+    /// ```
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    ///
+    /// Returns the shared handle for `value`, adding it to the `Interner` if no equal value is
+    /// there yet.
+    ///
+    /// ```
+    /// use slab_tree::intern::Interner;
+    /// use std::sync::Arc;
+    ///
+    /// let mut interner = Interner::new();
+    /// let a = interner.intern(String::from("src"));
+    /// let b = interner.intern(String::from("src"));
+    ///
+    /// assert!(Arc::ptr_eq(&a, &b));
+    /// ```
+    ///
+    pub fn intern(&mut self, value: T) -> Arc<T> {
+        if let Some(existing) = self.values.get(&value) {
+            return Arc::clone(existing);
+        }
+        let value = Arc::new(value);
+        self.values.insert(Arc::clone(&value));
+        value
+    }
+
+    ///
+    /// Returns the shared handle for a value equal to `value`, only cloning it if no equal value
+    /// is in the `Interner` yet.
+    ///
+    /// ```
+    /// use slab_tree::intern::Interner;
+    /// use std::sync::Arc;
+    ///
+    /// let mut interner = Interner::new();
+    /// let a = interner.intern(String::from("src"));
+    /// let b = interner.intern_ref(&String::from("src"));
+    ///
+    /// assert!(Arc::ptr_eq(&a, &b));
+    /// ```
+    ///
+    pub fn intern_ref(&mut self, value: &T) -> Arc<T>
+    where
+        T: Clone,
+    {
+        match self.values.get(value) {
+            Some(existing) => Arc::clone(existing),
+            None => self.intern(value.clone()),
+        }
+    }
+
+    ///
+    /// Drops every value which is no longer used outside of the `Interner`.
+    ///
+    /// ```
+    /// use slab_tree::intern::Interner;
+    ///
+    /// let mut interner = Interner::new();
+    /// let kept = interner.intern("kept");
+    /// interner.intern("dropped");
+    ///
+    /// interner.purge();
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    ///
+    pub fn purge(&mut self) {
+        self.values.retain(|value| Arc::strong_count(value) > 1);
+    }
+}
+
+impl<T: Eq + Hash> Default for Interner<T> {
+    fn default() -> Self {
+        Interner::new()
+    }
+}
+
+impl<T: Eq + Hash + Clone> Tree<T> {
+    ///
+    /// Builds a copy of the `Tree` whose `Node`s hold handles from `interner` instead of their
+    /// own copies of the data, so equal data is stored only once.  Orphaned `Node`s are not
+    /// copied, and the copy's `Node`s get new `NodeId`s.
+    ///
+    /// ```
+    /// use slab_tree::intern::Interner;
+    /// use slab_tree::tree::TreeBuilder;
+    /// use std::sync::Arc;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(String::from("div")).build();
+    /// for _ in 0..3 {
+    ///     tree.root_mut().unwrap().append(String::from("span"));
+    /// }
+    ///
+    /// let mut interner = Interner::new();
+    /// let interned = tree.to_interned(&mut interner);
+    ///
+    /// assert_eq!(interner.len(), 2);
+    /// let root = interned.root().unwrap();
+    /// let first = root.first_child().unwrap();
+    /// let last = root.last_child().unwrap();
+    /// assert_eq!(first.data().as_str(), "span");
+    /// assert!(Arc::ptr_eq(first.data(), last.data()));
+    /// ```
+    ///
+    pub fn to_interned(&self, interner: &mut Interner<T>) -> Tree<Arc<T>> {
+        match self.root() {
+            Some(root) => Tree::unfold(root, |node| {
+                (interner.intern_ref(node.data()), node.children().collect())
+            }),
+            None => Tree::new(),
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod intern_tests {
+    use super::*;
+
+    #[test]
+    fn to_interned_keeps_structure() {
+        let tree = Tree::unfold(3, |n: i32| (n % 2, (0..n).collect()));
+        let mut interner = Interner::new();
+        let interned = tree.to_interned(&mut interner);
+        assert_eq!(interner.len(), 2);
+
+        let expected: Vec<(i32, usize)> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (*node.data(), node.ancestors().count()))
+            .collect();
+        let actual: Vec<(i32, usize)> = interned
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (**node.data(), node.ancestors().count()))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn shared_between_trees() {
+        let mut interner = Interner::new();
+        let a = Tree::unfold(2, |n: i32| (n, (0..n).collect())).to_interned(&mut interner);
+        let b = Tree::unfold(1, |n: i32| (n, (0..n).collect())).to_interned(&mut interner);
+        assert_eq!(interner.len(), 3);
+
+        let a_root = a.root().unwrap();
+        let a_one = a_root.last_child().unwrap();
+        let b_one = b.root().unwrap();
+        assert!(Arc::ptr_eq(a_one.data(), b_one.data()));
+
+        drop(a);
+        interner.purge();
+        assert_eq!(interner.len(), 2);
+        drop(b);
+        interner.purge();
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    fn to_interned_empty() {
+        let tree: Tree<i32> = Tree::new();
+        assert!(tree.to_interned(&mut Interner::new()).root().is_none());
+    }
+}
@@ -1,16 +1,36 @@
 use crate::node::*;
 use crate::tree::Tree;
 use crate::NodeId;
+use std::collections::VecDeque;
+use std::fmt;
+use std::iter::FusedIterator;
 
-// todo: document this
-
+/// Iterator over the ancestors of a `Node`, from its parent up to the root, returned by
+/// `NodeRef::ancestors`
 pub struct Ancestors<'a, T> {
     node_id: Option<NodeId>,
     tree: &'a Tree<T>,
 }
 
+impl<'a, T> Clone for Ancestors<'a, T> {
+    fn clone(&self) -> Self {
+        Ancestors {
+            node_id: self.node_id,
+            tree: self.tree,
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for Ancestors<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ancestors")
+            .field("node_id", &self.node_id)
+            .finish()
+    }
+}
+
 impl<'a, T> Ancestors<'a, T> {
-    pub(crate) fn new(node_id: Option<NodeId>, tree: &'a Tree<T>) -> Ancestors<T> {
+    pub(crate) fn new(node_id: Option<NodeId>, tree: &'a Tree<T>) -> Ancestors<'a, T> {
         Ancestors { node_id, tree }
     }
 }
@@ -29,14 +49,77 @@ impl<'a, T> Iterator for Ancestors<'a, T> {
     }
 }
 
-// possibly re-name this, not sure how I feel about it
+impl<'a, T> FusedIterator for Ancestors<'a, T> {}
+
+/// Iterator over a `Node` and then its ancestors, up to the root, returned by
+/// `NodeRef::ancestors_or_self`
+pub struct AncestorsOrSelf<'a, T> {
+    node_id: Option<NodeId>,
+    tree: &'a Tree<T>,
+}
+
+impl<'a, T> Clone for AncestorsOrSelf<'a, T> {
+    fn clone(&self) -> Self {
+        AncestorsOrSelf {
+            node_id: self.node_id,
+            tree: self.tree,
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for AncestorsOrSelf<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AncestorsOrSelf")
+            .field("node_id", &self.node_id)
+            .finish()
+    }
+}
+
+impl<'a, T> AncestorsOrSelf<'a, T> {
+    pub(crate) fn new(node_id: Option<NodeId>, tree: &'a Tree<T>) -> AncestorsOrSelf<'a, T> {
+        AncestorsOrSelf { node_id, tree }
+    }
+}
+
+impl<'a, T> Iterator for AncestorsOrSelf<'a, T> {
+    type Item = NodeRef<'a, T>;
+
+    fn next(&mut self) -> Option<NodeRef<'a, T>> {
+        self.node_id.take().map(|node_id| {
+            self.node_id = self.tree.get_node_relatives(node_id).parent;
+            NodeRef::new(node_id, self.tree)
+        })
+    }
+}
+
+impl<'a, T> FusedIterator for AncestorsOrSelf<'a, T> {}
+
+/// Iterator over a `Node` and the siblings after it, in order; `NodeRef::children` returns one
+/// starting at the first child
 pub struct NextSiblings<'a, T> {
     node_id: Option<NodeId>,
     tree: &'a Tree<T>,
 }
 
+impl<'a, T> Clone for NextSiblings<'a, T> {
+    fn clone(&self) -> Self {
+        NextSiblings {
+            node_id: self.node_id,
+            tree: self.tree,
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for NextSiblings<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NextSiblings")
+            .field("node_id", &self.node_id)
+            .finish()
+    }
+}
+
 impl<'a, T> NextSiblings<'a, T> {
-    pub(crate) fn new(node_id: Option<NodeId>, tree: &'a Tree<T>) -> NextSiblings<T> {
+    pub(crate) fn new(node_id: Option<NodeId>, tree: &'a Tree<T>) -> NextSiblings<'a, T> {
         NextSiblings { node_id, tree }
     }
 }
@@ -52,11 +135,39 @@ impl<'a, T> Iterator for NextSiblings<'a, T> {
     }
 }
 
-/// Depth-first pre-order iterator
-pub struct PreOrder<'a, T> {
+impl<'a, T> FusedIterator for NextSiblings<'a, T> {}
+
+/// Depth-first pre-order iterator, returned by `NodeRef::traverse_pre_order`, which visits each
+/// `Node` before its children.  `with_max_depth` and `prune` limit the subtrees it descends into.
+pub struct PreOrder<'a, T, P = fn(&NodeRef<'a, T>) -> bool> {
     start: Option<NodeRef<'a, T>>,
     children: Vec<NextSiblings<'a, T>>,
     tree: &'a Tree<T>,
+    max_depth: Option<usize>,
+    prune: Option<P>,
+}
+
+impl<'a, T, P: Clone> Clone for PreOrder<'a, T, P> {
+    fn clone(&self) -> Self {
+        PreOrder {
+            start: self.start,
+            children: self.children.clone(),
+            tree: self.tree,
+            max_depth: self.max_depth,
+            prune: self.prune.clone(),
+        }
+    }
+}
+
+impl<'a, T, P> fmt::Debug for PreOrder<'a, T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreOrder")
+            .field("start", &self.start.as_ref().map(NodeRef::node_id))
+            .field("children", &self.children)
+            .field("max_depth", &self.max_depth)
+            .field("pruned", &self.prune.is_some())
+            .finish()
+    }
 }
 
 impl<'a, T> PreOrder<'a, T> {
@@ -67,25 +178,106 @@ impl<'a, T> PreOrder<'a, T> {
             start,
             children,
             tree,
+            max_depth: None,
+            prune: None,
+        }
+    }
+}
+
+impl<'a, T, P> PreOrder<'a, T, P> {
+    ///
+    /// Stops the traversal from descending below `max_depth`, where the starting `Node` is at
+    /// depth 0.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(1).append(2);
+    /// tree.root_mut().unwrap().append(3);
+    ///
+    /// let values: Vec<i32> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_pre_order()
+    ///     .with_max_depth(1)
+    ///     .map(|node| *node.data())
+    ///     .collect();
+    /// assert_eq!(values, vec![0, 1, 3]);
+    /// ```
+    ///
+    pub fn with_max_depth(self, max_depth: usize) -> PreOrder<'a, T, P> {
+        PreOrder {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
+    ///
+    /// Skips every subtree whose root doesn't satisfy `predicate`, without visiting any of the
+    /// `Node`s in it.  `predicate` is called once for each `Node` the traversal reaches
+    /// (including the starting `Node`), and replaces any earlier one.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(1).append(2);
+    /// tree.root_mut().unwrap().append(-3).append(4);
+    ///
+    /// let values: Vec<i32> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_pre_order()
+    ///     .prune(|node| *node.data() >= 0)
+    ///     .map(|node| *node.data())
+    ///     .collect();
+    /// assert_eq!(values, vec![0, 1, 2]);
+    /// ```
+    ///
+    pub fn prune<Q>(self, predicate: Q) -> PreOrder<'a, T, Q>
+    where
+        Q: FnMut(&NodeRef<'a, T>) -> bool,
+    {
+        PreOrder {
+            start: self.start,
+            children: self.children,
+            tree: self.tree,
+            max_depth: self.max_depth,
+            prune: Some(predicate),
         }
     }
 }
 
-impl<'a, T> Iterator for PreOrder<'a, T> {
+impl<'a, T, P> Iterator for PreOrder<'a, T, P>
+where
+    P: FnMut(&NodeRef<'a, T>) -> bool,
+{
     type Item = NodeRef<'a, T>;
 
     fn next(&mut self) -> Option<NodeRef<'a, T>> {
         if let Some(node) = self.start.take() {
-            let first_child_id = node.first_child().map(|child_ref| child_ref.node_id());
-            self.children
-                .push(NextSiblings::new(first_child_id, self.tree));
+            if !keep(&mut self.prune, &node) {
+                return None;
+            }
+            if below_max_depth(self.max_depth, 0) {
+                let first_child_id = node.first_child().map(|child_ref| child_ref.node_id());
+                self.children
+                    .push(NextSiblings::new(first_child_id, self.tree));
+            }
             Some(node)
         } else {
             while !self.children.is_empty() {
                 if let Some(node_ref) = self.children.last_mut().and_then(Iterator::next) {
-                    if let Some(first_child) = node_ref.first_child() {
-                        self.children
-                            .push(NextSiblings::new(Some(first_child.node_id()), self.tree));
+                    if !keep(&mut self.prune, &node_ref) {
+                        continue;
+                    }
+                    // the node is at the depth of the number of sibling iterators above it
+                    if below_max_depth(self.max_depth, self.children.len()) {
+                        if let Some(first_child) = node_ref.first_child() {
+                            self.children
+                                .push(NextSiblings::new(Some(first_child.node_id()), self.tree));
+                        }
                     }
                     return Some(node_ref);
                 }
@@ -96,128 +288,577 @@ impl<'a, T> Iterator for PreOrder<'a, T> {
     }
 }
 
-/// Depth-first post-order iterator
-pub struct PostOrder<'a, T> {
+impl<'a, T, P> FusedIterator for PreOrder<'a, T, P> where P: FnMut(&NodeRef<'a, T>) -> bool {}
+
+/// Depth-first post-order iterator, returned by `NodeRef::traverse_post_order`, which visits
+/// each `Node` after its children.  `with_max_depth` and `prune` limit the subtrees it descends
+/// into.
+pub struct PostOrder<'a, T, P = fn(&NodeRef<'a, T>) -> bool> {
+    start: Option<NodeRef<'a, T>>,
+    // each node on the path down to the current one, with the children it has yet to visit
     nodes: Vec<(NodeRef<'a, T>, NextSiblings<'a, T>)>,
     tree: &'a Tree<T>,
+    max_depth: Option<usize>,
+    prune: Option<P>,
+}
+
+impl<'a, T, P: Clone> Clone for PostOrder<'a, T, P> {
+    fn clone(&self) -> Self {
+        PostOrder {
+            start: self.start,
+            nodes: self
+                .nodes
+                .iter()
+                .map(|(node, children)| (*node, children.clone()))
+                .collect(),
+            tree: self.tree,
+            max_depth: self.max_depth,
+            prune: self.prune.clone(),
+        }
+    }
+}
+
+impl<'a, T, P> fmt::Debug for PostOrder<'a, T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes: Vec<(NodeId, &NextSiblings<'a, T>)> = self
+            .nodes
+            .iter()
+            .map(|(node, children)| (node.node_id(), children))
+            .collect();
+        f.debug_struct("PostOrder")
+            .field("start", &self.start.as_ref().map(NodeRef::node_id))
+            .field("nodes", &nodes)
+            .field("max_depth", &self.max_depth)
+            .field("pruned", &self.prune.is_some())
+            .finish()
+    }
 }
 
 impl<'a, T> PostOrder<'a, T> {
     pub(crate) fn new(node: &NodeRef<'a, T>, tree: &'a Tree<T>) -> PostOrder<'a, T> {
-        let node = tree
-            .get(node.node_id())
-            .expect("getting node of node ref id");
-        let first_child_id = node.first_child().map(|first_child| first_child.node_id());
-        let nodes = vec![(node, NextSiblings::new(first_child_id, tree))];
-        PostOrder { nodes, tree }
+        let start = tree.get(node.node_id());
+        PostOrder {
+            start,
+            nodes: Vec::new(),
+            tree,
+            max_depth: None,
+            prune: None,
+        }
+    }
+}
+
+impl<'a, T, P> PostOrder<'a, T, P> {
+    fn push(&mut self, node: NodeRef<'a, T>) {
+        // the node is at the depth of the number of its ancestors on the stack
+        let first_child_id = if below_max_depth(self.max_depth, self.nodes.len()) {
+            self.tree.get_node_relatives(node.node_id()).first_child
+        } else {
+            None
+        };
+        self.nodes
+            .push((node, NextSiblings::new(first_child_id, self.tree)));
+    }
+}
+
+impl<'a, T, P> PostOrder<'a, T, P> {
+    ///
+    /// Stops the traversal from descending below `max_depth`, where the starting `Node` is at
+    /// depth 0.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(1).append(2);
+    /// tree.root_mut().unwrap().append(3);
+    ///
+    /// let values: Vec<i32> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_post_order()
+    ///     .with_max_depth(1)
+    ///     .map(|node| *node.data())
+    ///     .collect();
+    /// assert_eq!(values, vec![1, 3, 0]);
+    /// ```
+    ///
+    pub fn with_max_depth(self, max_depth: usize) -> PostOrder<'a, T, P> {
+        PostOrder {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
+    ///
+    /// Skips every subtree whose root doesn't satisfy `predicate`, without visiting any of the
+    /// `Node`s in it.  `predicate` is called once for each `Node` the traversal reaches
+    /// (including the starting `Node`) before descending into it, and replaces any earlier one.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(1).append(2);
+    /// tree.root_mut().unwrap().append(-3).append(4);
+    ///
+    /// let values: Vec<i32> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_post_order()
+    ///     .prune(|node| *node.data() >= 0)
+    ///     .map(|node| *node.data())
+    ///     .collect();
+    /// assert_eq!(values, vec![2, 1, 0]);
+    /// ```
+    ///
+    pub fn prune<Q>(self, predicate: Q) -> PostOrder<'a, T, Q>
+    where
+        Q: FnMut(&NodeRef<'a, T>) -> bool,
+    {
+        PostOrder {
+            start: self.start,
+            nodes: self.nodes,
+            tree: self.tree,
+            max_depth: self.max_depth,
+            prune: Some(predicate),
+        }
     }
 }
 
-impl<'a, T> Iterator for PostOrder<'a, T> {
+impl<'a, T, P> Iterator for PostOrder<'a, T, P>
+where
+    P: FnMut(&NodeRef<'a, T>) -> bool,
+{
     type Item = NodeRef<'a, T>;
 
     fn next(&mut self) -> Option<NodeRef<'a, T>> {
-        if let Some((node, mut children)) = self.nodes.pop() {
-            if let Some(next) = children.next() {
-                self.nodes.push((node, children));
-                let mut node_id = next.node_id();
-                loop {
-                    let node = self.tree.get(node_id).expect("getting node of node ref id");
-                    if let Some(first_child) = node.first_child() {
-                        node_id = first_child.node_id();
-                        let mut children = NextSiblings::new(Some(node_id), self.tree);
-                        assert!(children.next().is_some(), "skipping first child");
-                        self.nodes.push((node, children));
-                    } else {
-                        break Some(node);
-                    }
-                }
-            } else {
-                Some(node)
+        if let Some(start) = self.start.take() {
+            if keep(&mut self.prune, &start) {
+                self.push(start);
+            }
+        }
+
+        loop {
+            let (_, children) = self.nodes.last_mut()?;
+            let prune = &mut self.prune;
+            match children.find(|child| keep(prune, child)) {
+                Some(child) => self.push(child),
+                None => return self.nodes.pop().map(|(node, _)| node),
             }
-        } else {
-            None
         }
     }
 }
 
-/// Depth-first level-order iterator
-pub struct LevelOrder<'a, T> {
-    start: NodeRef<'a, T>,
-    levels: Vec<(NodeId, NextSiblings<'a, T>)>,
+impl<'a, T, P> FusedIterator for PostOrder<'a, T, P> where P: FnMut(&NodeRef<'a, T>) -> bool {}
+
+/// Breadth-first level-order iterator, returned by `NodeRef::traverse_level_order`, which visits
+/// the `Node`s one depth at a time, each level from left to right.  `with_max_depth` and `prune`
+/// limit the subtrees it descends into.
+pub struct LevelOrder<'a, T, P = fn(&NodeRef<'a, T>) -> bool> {
+    // nodes reached but not yet handed out, along with their depth below the starting node
+    queue: VecDeque<(NodeRef<'a, T>, usize)>,
     tree: &'a Tree<T>,
+    max_depth: Option<usize>,
+    prune: Option<P>,
+}
+
+impl<'a, T, P: Clone> Clone for LevelOrder<'a, T, P> {
+    fn clone(&self) -> Self {
+        LevelOrder {
+            queue: self.queue.clone(),
+            tree: self.tree,
+            max_depth: self.max_depth,
+            prune: self.prune.clone(),
+        }
+    }
+}
+
+impl<'a, T, P> fmt::Debug for LevelOrder<'a, T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let queue: Vec<(NodeId, usize)> = self
+            .queue
+            .iter()
+            .map(|(node, depth)| (node.node_id(), *depth))
+            .collect();
+        f.debug_struct("LevelOrder")
+            .field("queue", &queue)
+            .field("max_depth", &self.max_depth)
+            .field("pruned", &self.prune.is_some())
+            .finish()
+    }
 }
 
 impl<'a, T> LevelOrder<'a, T> {
     pub(crate) fn new(node: &NodeRef<'a, T>, tree: &'a Tree<T>) -> LevelOrder<'a, T> {
-        let start = tree
+        let queue = tree
             .get(node.node_id())
-            .expect("getting node of node ref id");
-        let levels = Vec::new();
+            .map(|start| (start, 0))
+            .into_iter()
+            .collect();
         LevelOrder {
-            start,
-            levels,
+            queue,
             tree,
+            max_depth: None,
+            prune: None,
         }
     }
 }
 
-impl<'a, T> Iterator for LevelOrder<'a, T> {
+impl<'a, T, P> LevelOrder<'a, T, P> {
+    ///
+    /// Stops the traversal after the level at `max_depth`, where the starting `Node` is at
+    /// depth 0.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(1).append(2);
+    /// tree.root_mut().unwrap().append(3);
+    ///
+    /// let values: Vec<i32> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_level_order()
+    ///     .with_max_depth(1)
+    ///     .map(|node| *node.data())
+    ///     .collect();
+    /// assert_eq!(values, vec![0, 1, 3]);
+    /// ```
+    ///
+    pub fn with_max_depth(self, max_depth: usize) -> LevelOrder<'a, T, P> {
+        LevelOrder {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
+    ///
+    /// Skips every subtree whose root doesn't satisfy `predicate`, without visiting any of the
+    /// `Node`s in it.  `predicate` is called once for each `Node` the traversal reaches
+    /// (including the starting `Node`), and replaces any earlier one.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(1).append(2);
+    /// tree.root_mut().unwrap().append(-3).append(4);
+    ///
+    /// let values: Vec<i32> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_level_order()
+    ///     .prune(|node| *node.data() >= 0)
+    ///     .map(|node| *node.data())
+    ///     .collect();
+    /// assert_eq!(values, vec![0, 1, 2]);
+    /// ```
+    ///
+    pub fn prune<Q>(self, predicate: Q) -> LevelOrder<'a, T, Q>
+    where
+        Q: FnMut(&NodeRef<'a, T>) -> bool,
+    {
+        LevelOrder {
+            queue: self.queue,
+            tree: self.tree,
+            max_depth: self.max_depth,
+            prune: Some(predicate),
+        }
+    }
+}
+
+impl<'a, T, P> Iterator for LevelOrder<'a, T, P>
+where
+    P: FnMut(&NodeRef<'a, T>) -> bool,
+{
     type Item = NodeRef<'a, T>;
 
     fn next(&mut self) -> Option<NodeRef<'a, T>> {
-        if self.levels.is_empty() {
-            let first_child_id = self.start.first_child().map(|child| child.node_id());
-            self.levels.push((
-                self.start.node_id(),
-                NextSiblings::new(first_child_id, self.tree),
-            ));
-            let node = self
-                .tree
-                .get(self.start.node_id())
-                .expect("getting node of existing node ref id");
-            Some(node)
-        } else {
-            let mut on_level = self.levels.len();
-            let next_level = on_level + 1;
-            let mut level = on_level;
-            while level > 0 {
-                if let Some(node) = self.levels.last_mut().expect("non-empty levels").1.next() {
-                    if level >= on_level {
-                        return Some(node);
-                    } else {
-                        let first_child_id = node.first_child().map(|child| child.node_id());
-                        self.levels
-                            .push((node.node_id(), NextSiblings::new(first_child_id, self.tree)));
-                        level += 1;
-                    }
-                } else {
-                    let (node_id, _) = self.levels.pop().expect("on level > 0");
-                    if let Some(next) = self.levels.last_mut().and_then(|level| level.1.next()) {
-                        let first_child_id = next.first_child().map(|child| child.node_id());
-                        self.levels
-                            .push((next.node_id(), NextSiblings::new(first_child_id, self.tree)));
-                    } else if level == 1 {
-                        if on_level < next_level {
-                            on_level += 1;
-                            let node = self
-                                .tree
-                                .get(node_id)
-                                .expect("getting node of existing node ref id");
-                            let first_child_id = node.first_child().map(|child| child.node_id());
-                            self.levels.push((
-                                node.node_id(),
-                                NextSiblings::new(first_child_id, self.tree),
-                            ));
-                        } else {
-                            break;
-                        }
-                    } else {
-                        level -= 1;
-                    }
+        while let Some((node, depth)) = self.queue.pop_front() {
+            if !keep(&mut self.prune, &node) {
+                continue;
+            }
+            if below_max_depth(self.max_depth, depth) {
+                let first_child_id = self.tree.get_node_relatives(node.node_id()).first_child;
+                self.queue.extend(
+                    NextSiblings::new(first_child_id, self.tree).map(|child| (child, depth + 1)),
+                );
+            }
+            return Some(node);
+        }
+        None
+    }
+}
+
+impl<'a, T, P> FusedIterator for LevelOrder<'a, T, P> where P: FnMut(&NodeRef<'a, T>) -> bool {}
+
+/// Depth-first pre-order iterator over every `Node` of a `Tree`, visiting the root's subtree
+/// first and then the subtree of each orphaned `Node` in storage order, returned by
+/// `Tree::iter_all_roots_first`
+pub struct AllRootsFirst<'a, T> {
+    roots: std::vec::IntoIter<NodeId>,
+    current: Option<PreOrder<'a, T>>,
+    tree: &'a Tree<T>,
+}
+
+impl<'a, T> Clone for AllRootsFirst<'a, T> {
+    fn clone(&self) -> Self {
+        AllRootsFirst {
+            roots: self.roots.clone(),
+            current: self.current.clone(),
+            tree: self.tree,
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for AllRootsFirst<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AllRootsFirst")
+            .field("roots", &self.roots.as_slice())
+            .field("current", &self.current)
+            .finish()
+    }
+}
+
+impl<'a, T> AllRootsFirst<'a, T> {
+    pub(crate) fn new(tree: &'a Tree<T>) -> AllRootsFirst<'a, T> {
+        let orphan_roots = tree.all_node_ids().into_iter().filter(|&node_id| {
+            Some(node_id) != tree.root_id() && tree.get_node_relatives(node_id).parent.is_none()
+        });
+        let roots: Vec<NodeId> = tree.root_id().into_iter().chain(orphan_roots).collect();
+        AllRootsFirst {
+            roots: roots.into_iter(),
+            current: None,
+            tree,
+        }
+    }
+}
+
+impl<'a, T> Iterator for AllRootsFirst<'a, T> {
+    type Item = NodeRef<'a, T>;
+
+    fn next(&mut self) -> Option<NodeRef<'a, T>> {
+        loop {
+            if let Some(node_ref) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(node_ref);
+            }
+            let root_id = self.roots.next()?;
+            let root = NodeRef::new(root_id, self.tree);
+            self.current = Some(PreOrder::new(&root, self.tree));
+        }
+    }
+}
+
+impl<'a, T> FusedIterator for AllRootsFirst<'a, T> {}
+
+fn below_max_depth(max_depth: Option<usize>, depth: usize) -> bool {
+    !matches!(max_depth, Some(max_depth) if depth >= max_depth)
+}
+
+fn keep<'a, T, P>(prune: &mut Option<P>, node: &NodeRef<'a, T>) -> bool
+where
+    P: FnMut(&NodeRef<'a, T>) -> bool,
+{
+    match prune {
+        Some(predicate) => predicate(node),
+        None => true,
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod iter_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    const DEPTH: u32 = 10_000;
+
+    // up to 40 nodes, where node `i` holds `i` and hangs off an earlier node picked by a
+    // xorshift generator seeded with `seed`
+    fn random_tree(seed: u32) -> Tree<u32> {
+        let mut state = seed.wrapping_mul(2_654_435_761) | 1;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut ids = vec![tree.root_id().unwrap()];
+        for i in 1..next() % 40 {
+            let parent_id = ids[next() as usize % ids.len()];
+            ids.push(tree.get_mut(parent_id).unwrap().append(i).node_id());
+        }
+        tree
+    }
+
+    // a spine `DEPTH` levels deep, where spine node `i` holds `i * 10` and has a leaf holding
+    // `i * 10 + 1` before the next spine node and, on every third level, a leaf holding
+    // `i * 10 + 2` after it
+    fn deep_tree() -> Tree<u32> {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut spine_id = tree.root_id().unwrap();
+        for i in 0..DEPTH {
+            let mut spine = tree.get_mut(spine_id).unwrap();
+            spine.append(i * 10 + 1);
+            let next_id = spine.append((i + 1) * 10).node_id();
+            if i % 3 == 0 {
+                spine.append(i * 10 + 2);
+            }
+            spine_id = next_id;
+        }
+        tree
+    }
+
+    // prunes some leaves of `deep_tree` but none of its spine
+    fn keep_spine(data: u32) -> bool {
+        data % 10 != 2 && data % 70 != 1
+    }
+
+    // level order built one level at a time, from the kept children of the level before
+    fn reference_level_order<F: Fn(u32) -> bool>(
+        tree: &Tree<u32>,
+        max_depth: usize,
+        keep: F,
+    ) -> Vec<u32> {
+        let mut out = Vec::new();
+        let mut level: Vec<NodeRef<u32>> = vec![tree.root().unwrap()];
+        level.retain(|node| keep(*node.data()));
+        let mut depth = 0;
+        while !level.is_empty() {
+            out.extend(level.iter().map(|node| *node.data()));
+            if depth == max_depth {
+                break;
+            }
+            level = level
+                .iter()
+                .flat_map(|node| node.children())
+                .filter(|node| keep(*node.data()))
+                .collect();
+            depth += 1;
+        }
+        out
+    }
+
+    fn check_level_order<F: Fn(u32) -> bool>(tree: &Tree<u32>, max_depth: usize, keep: F) {
+        let actual: Vec<u32> = tree
+            .root()
+            .unwrap()
+            .traverse_level_order()
+            .with_max_depth(max_depth)
+            .prune(|node| keep(*node.data()))
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(actual, reference_level_order(tree, max_depth, keep));
+    }
+
+    // post order with an explicit stack of nodes still to visit, each marked with whether its
+    // children have been pushed yet
+    fn reference_post_order<F: Fn(u32) -> bool>(
+        tree: &Tree<u32>,
+        max_depth: usize,
+        keep: F,
+    ) -> Vec<u32> {
+        let mut out = Vec::new();
+        let mut stack = vec![(tree.root().unwrap(), 0, false)];
+        while let Some((node, depth, expanded)) = stack.pop() {
+            if expanded {
+                out.push(*node.data());
+                continue;
+            }
+            if !keep(*node.data()) {
+                continue;
+            }
+            let children: Vec<_> = if depth < max_depth {
+                node.children().collect()
+            } else {
+                Vec::new()
+            };
+            stack.push((node, depth, true));
+            for child in children.into_iter().rev() {
+                stack.push((child, depth + 1, false));
+            }
+        }
+        out
+    }
+
+    fn check_post_order<F: Fn(u32) -> bool>(tree: &Tree<u32>, max_depth: usize, keep: F) {
+        let actual: Vec<u32> = tree
+            .root()
+            .unwrap()
+            .traverse_post_order()
+            .with_max_depth(max_depth)
+            .prune(|node| keep(*node.data()))
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(actual, reference_post_order(tree, max_depth, keep));
+    }
+
+    #[test]
+    fn level_order_matches_reference() {
+        for seed in 0..200 {
+            let tree = random_tree(seed);
+            let all: Vec<u32> = tree
+                .root()
+                .unwrap()
+                .traverse_level_order()
+                .map(|node| *node.data())
+                .collect();
+            assert_eq!(all, reference_level_order(&tree, usize::MAX, |_| true));
+
+            for &max_depth in &[0, 1, 2, 3, 5, usize::MAX] {
+                check_level_order(&tree, max_depth, |_| true);
+                for modulus in 2..6 {
+                    check_level_order(&tree, max_depth, |data| data % modulus != modulus - 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn level_order_of_a_deep_tree_matches_reference() {
+        let tree = deep_tree();
+        let root = tree.root().unwrap();
+
+        let all: Vec<u32> = root.traverse_level_order().map(|n| *n.data()).collect();
+        assert_eq!(all.len(), root.traverse_pre_order().count());
+        assert_eq!(all, reference_level_order(&tree, usize::MAX, |_| true));
+
+        check_level_order(&tree, 5_000, keep_spine);
+    }
+
+    #[test]
+    fn post_order_matches_reference() {
+        for seed in 0..200 {
+            let tree = random_tree(seed);
+            let all: Vec<u32> = tree
+                .root()
+                .unwrap()
+                .traverse_post_order()
+                .map(|node| *node.data())
+                .collect();
+            assert_eq!(all, reference_post_order(&tree, usize::MAX, |_| true));
+
+            for &max_depth in &[0, 1, 2, 3, 5, usize::MAX] {
+                check_post_order(&tree, max_depth, |_| true);
+                for modulus in 2..6 {
+                    check_post_order(&tree, max_depth, |data| data % modulus != modulus - 1);
                 }
             }
-            None
         }
     }
+
+    #[test]
+    fn post_order_of_a_deep_tree_matches_reference() {
+        let tree = deep_tree();
+        let root = tree.root().unwrap();
+
+        let all: Vec<u32> = root.traverse_post_order().map(|n| *n.data()).collect();
+        assert_eq!(all.len(), root.traverse_pre_order().count());
+        assert_eq!(all, reference_post_order(&tree, usize::MAX, |_| true));
+
+        check_post_order(&tree, 5_000, keep_spine);
+    }
 }
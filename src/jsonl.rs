@@ -0,0 +1,153 @@
+//!
+//! Streaming `Tree`s out as [JSON Lines](https://jsonlines.org/), one object per `Node`:
+//!
+//! ```text
+//! {"id":0,"parent":null,"depth":0,"data":"root"}
+//! {"id":1,"parent":0,"depth":1,"data":"a"}
+//! {"id":2,"parent":1,"depth":2,"data":"b"}
+//! {"id":3,"parent":0,"depth":1,"data":"c"}
+//! ```
+//!
+//! The `Node`s are written in pre-order (document order), each with its position in that order
+//! as its `id`, so a `Node`'s parent always comes before it.  Unlike `serde_nested`, every line
+//! is written as soon as it is serialized, so the whole `Tree` is never held in memory as JSON.
+//!
+
+use crate::tree::Tree;
+use serde::Serialize;
+use std::io;
+
+#[derive(Serialize)]
+struct Line<'a, T> {
+    id: usize,
+    parent: Option<usize>,
+    depth: usize,
+    data: &'a T,
+}
+
+impl<T: Serialize> Tree<T> {
+    ///
+    /// Writes the root's sub-tree to `w` as JSON Lines, as described in the `jsonl` module.
+    /// Writes nothing if the `Tree` is empty.
+    ///
+    /// Every line is a separate small write, so writing to a file or socket is much faster
+    /// through an `io::BufWriter`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("a").append("b");
+    /// root.append("c");
+    ///
+    /// let mut out = Vec::new();
+    /// tree.write_jsonl(&mut out).unwrap();
+    ///
+    /// let out = String::from_utf8(out).unwrap();
+    /// let lines: Vec<&str> = out.lines().collect();
+    /// assert_eq!(lines.len(), 4);
+    /// assert_eq!(lines[2], r#"{"id":2,"parent":1,"depth":2,"data":"b"}"#);
+    /// ```
+    ///
+    pub fn write_jsonl<W: io::Write>(&self, mut w: W) -> io::Result<()> {
+        let root = match self.root() {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+
+        let mut next_id = 0;
+        // (node, its parent's id, its depth); children are pushed last to first so they're
+        // written in order
+        let mut stack = vec![(root, None, 0)];
+        while let Some((node, parent, depth)) = stack.pop() {
+            let id = next_id;
+            next_id += 1;
+            let line = Line {
+                id,
+                parent,
+                depth,
+                data: node.data(),
+            };
+            serde_json::to_writer(&mut w, &line)?;
+            w.write_all(b"\n")?;
+
+            let start = stack.len();
+            stack.extend(node.children().map(|child| (child, Some(id), depth + 1)));
+            stack[start..].reverse();
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod jsonl_tests {
+    use crate::tree::Tree;
+    use serde::Serialize;
+    use serde_json::Value;
+    use std::io;
+
+    #[test]
+    fn one_object_per_node() {
+        let tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let mut out = Vec::new();
+        tree.write_jsonl(&mut out).unwrap();
+
+        let lines: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let root = tree.root().unwrap();
+        assert_eq!(lines.len(), root.subtree_size());
+        for (id, (line, node)) in lines.iter().zip(root.traverse_pre_order()).enumerate() {
+            assert_eq!(line["id"], id);
+            assert_eq!(line["depth"], node.depth());
+            assert_eq!(line["data"], *node.data());
+            match node.parent() {
+                Some(parent) => {
+                    let parent_id = line["parent"].as_u64().unwrap() as usize;
+                    assert_eq!(lines[parent_id]["data"], *parent.data());
+                }
+                None => assert!(line["parent"].is_null()),
+            }
+        }
+
+        let mut out = Vec::new();
+        Tree::<i32>::new().write_jsonl(&mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn errors_are_passed_on() {
+        struct Unserializable;
+
+        impl Serialize for Unserializable {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("nope"))
+            }
+        }
+
+        let tree = crate::tree::TreeBuilder::new()
+            .with_root(Unserializable)
+            .build();
+        assert!(tree.write_jsonl(Vec::new()).is_err());
+
+        struct Full;
+
+        impl io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let tree = Tree::unfold(1, |n| (n, vec![]));
+        let error = tree.write_jsonl(Full).unwrap_err();
+        assert_eq!(error.to_string(), "full");
+    }
+}
@@ -0,0 +1,332 @@
+use crate::node::NodeMut;
+use crate::node::NodeRef;
+use crate::observer::TreeIndex;
+use crate::observer::TreeObserver;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::any::Any;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+type KeyFn<T, K> = Box<dyn Fn(&T) -> K + Send + Sync>;
+
+/// Maps the key of every `Node` in a `Tree` to its `NodeId`(s).
+struct KeyIndex<T, K> {
+    key: KeyFn<T, K>,
+    // every node with a given key, in the order they were indexed
+    ids: HashMap<K, Vec<NodeId>>,
+    keys: HashMap<NodeId, K>,
+}
+
+impl<T, K: Eq + Hash + Clone> KeyIndex<T, K> {
+    fn insert(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        if let Some(node) = tree.get_node(node_id) {
+            let key = (self.key)(&node.data);
+            self.ids.entry(key.clone()).or_default().push(node_id);
+            self.keys.insert(node_id, key);
+        }
+    }
+
+    fn remove(&mut self, node_id: NodeId) {
+        if let Some(key) = self.keys.remove(&node_id) {
+            if let Some(ids) = self.ids.get_mut(&key) {
+                ids.retain(|&id| id != node_id);
+                if ids.is_empty() {
+                    self.ids.remove(&key);
+                }
+            }
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<NodeId> {
+        self.ids.get(key).and_then(|ids| ids.first()).copied()
+    }
+}
+
+impl<T, K: Eq + Hash + Clone> TreeObserver<T> for KeyIndex<T, K> {
+    fn on_insert(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        self.insert(tree, node_id);
+    }
+
+    fn on_remove(&mut self, _tree: &Tree<T>, node_id: NodeId) {
+        self.remove(node_id);
+    }
+
+    fn on_data_replaced(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        self.remove(node_id);
+        self.insert(tree, node_id);
+    }
+}
+
+impl<T, K> TreeIndex<T> for KeyIndex<T, K>
+where
+    T: 'static,
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+{
+    fn rebuild(&mut self, tree: &Tree<T>) {
+        self.ids.clear();
+        self.keys.clear();
+        for node_id in tree.all_node_ids() {
+            self.insert(tree, node_id);
+        }
+    }
+
+    fn remap(&mut self, node_ids: &HashMap<NodeId, NodeId>) {
+        let remap = |node_id: &mut NodeId| {
+            if let Some(&new_id) = node_ids.get(node_id) {
+                *node_id = new_id;
+            }
+        };
+        for ids in self.ids.values_mut() {
+            ids.iter_mut().for_each(remap);
+        }
+        self.keys = self
+            .keys
+            .drain()
+            .map(|(mut node_id, key)| {
+                remap(&mut node_id);
+                (node_id, key)
+            })
+            .collect();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T: 'static> Tree<T> {
+    ///
+    /// Makes the `Tree` maintain an index from the key `key` derives from each `Node`'s data to
+    /// the `Node`'s `NodeId`, for looking `Node`s up with `get_by_key`.  The index covers every
+    /// `Node` in the `Tree` (including orphans) and is kept up to date as `Node`s are inserted,
+    /// removed, or have their data replaced.
+    ///
+    /// Keys are only derived when a `Node` is inserted or its data is replaced with
+    /// `NodeMut::replace_data`, so changes made to the data through `NodeMut::data` that would
+    /// change its key aren't picked up.  Setting up a second index with the same key type
+    /// replaces the first.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new()
+    ///     .with_root(("crate", 0))
+    ///     .build()
+    ///     .with_key(|&(name, _)| name.to_string());
+    ///
+    /// tree.root_mut().unwrap().append(("main", 1));
+    /// tree.root_mut().unwrap().append(("helper", 2));
+    ///
+    /// let helper = tree.get_by_key(&"helper".to_string()).unwrap();
+    /// assert_eq!(helper.data(), &("helper", 2));
+    /// ```
+    ///
+    pub fn with_key<K, F>(mut self, key: F) -> Tree<T>
+    where
+        K: Eq + Hash + Clone + Send + Sync + 'static,
+        F: Fn(&T) -> K + Send + Sync + 'static,
+    {
+        let mut index = KeyIndex {
+            key: Box::new(key),
+            ids: HashMap::new(),
+            keys: HashMap::new(),
+        };
+        index.rebuild(&self);
+
+        let existing = self
+            .hooks
+            .indexes_mut()
+            .find_map(|existing| existing.as_any_mut().downcast_mut::<KeyIndex<T, K>>());
+        match existing {
+            Some(existing) => *existing = index,
+            None => self.hooks.add_index(Box::new(index)),
+        }
+        self
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to the `Node` with the given key, according to the index set
+    /// up with `with_key`.  If several `Node`s share the key, the one that has been indexed the
+    /// longest is returned.  Returns `None` if no `Node` has the key, or if no index with this
+    /// key type has been set up.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build().with_key(|n: &i32| n % 10);
+    /// let eleven_id = tree.root_mut().unwrap().append(11).node_id();
+    ///
+    /// assert_eq!(tree.get_by_key(&1).unwrap().data(), &1);
+    /// assert!(tree.get_by_key(&2).is_none());
+    ///
+    /// tree.remove(tree.root_id().unwrap(), RemoveBehavior::OrphanChildren);
+    /// assert_eq!(tree.get_by_key(&1).unwrap().node_id(), eleven_id);
+    /// ```
+    ///
+    pub fn get_by_key<K>(&self, key: &K) -> Option<NodeRef<'_, T>>
+    where
+        K: Eq + Hash + Clone + Send + Sync + 'static,
+    {
+        let node_id = self.key_index::<K>()?.get(key)?;
+        self.get(node_id)
+    }
+
+    ///
+    /// Returns a `NodeMut` pointing to the `Node` with the given key; see `get_by_key`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new()
+    ///     .with_root((1, "one"))
+    ///     .build()
+    ///     .with_key(|&(n, _)| n);
+    ///
+    /// tree.get_mut_by_key(&1).unwrap().data().1 = "uno";
+    ///
+    /// assert_eq!(tree.root().unwrap().data(), &(1, "uno"));
+    /// ```
+    ///
+    pub fn get_mut_by_key<K>(&mut self, key: &K) -> Option<NodeMut<'_, T>>
+    where
+        K: Eq + Hash + Clone + Send + Sync + 'static,
+    {
+        let node_id = self.key_index::<K>()?.get(key)?;
+        self.get_mut(node_id)
+    }
+
+    fn key_index<K>(&self) -> Option<&KeyIndex<T, K>>
+    where
+        K: Eq + Hash + Clone + Send + Sync + 'static,
+    {
+        self.hooks
+            .indexes()
+            .find_map(|index| index.as_any().downcast_ref::<KeyIndex<T, K>>())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod key_index_tests {
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+
+    fn named() -> Tree<String> {
+        TreeBuilder::new()
+            .with_root("root".to_string())
+            .build()
+            .with_key(|name: &String| name.clone())
+    }
+
+    fn key(name: &str) -> String {
+        name.to_string()
+    }
+
+    #[test]
+    fn indexes_existing_nodes() {
+        let mut tree = TreeBuilder::new().with_root("root".to_string()).build();
+        let a_id = tree.root_mut().unwrap().append("a".to_string()).node_id();
+        let tree = tree.with_key(|name: &String| name.clone());
+
+        assert_eq!(tree.get_by_key(&key("a")).unwrap().node_id(), a_id);
+        assert!(tree.get_by_key(&key("root")).unwrap().is_root());
+    }
+
+    #[test]
+    fn tracks_inserts_and_removals() {
+        let mut tree = named();
+        let a_id = tree.root_mut().unwrap().append(key("a")).node_id();
+        let b_id = tree.get_mut(a_id).unwrap().append(key("b")).node_id();
+        let c_id = tree.get_mut(b_id).unwrap().prepend(key("c")).node_id();
+        assert_eq!(tree.get_by_key(&key("c")).unwrap().node_id(), c_id);
+
+        tree.remove(b_id, OrphanChildren);
+        assert!(tree.get_by_key(&key("b")).is_none());
+        assert_eq!(tree.get_by_key(&key("c")).unwrap().node_id(), c_id);
+
+        tree.remove(a_id, DropChildren);
+        assert!(tree.get_by_key(&key("a")).is_none());
+        assert_eq!(tree.get_by_key(&key("c")).unwrap().node_id(), c_id);
+
+        tree.set_root(key("new root"));
+        assert!(tree.get_by_key(&key("new root")).unwrap().is_root());
+        assert!(!tree.get_by_key(&key("root")).unwrap().is_root());
+    }
+
+    #[test]
+    fn tracks_replaced_data() {
+        let mut tree = named();
+        let a_id = tree.root_mut().unwrap().append(key("a")).node_id();
+
+        tree.get_mut(a_id).unwrap().replace_data(key("z"));
+        assert!(tree.get_by_key(&key("a")).is_none());
+        assert_eq!(tree.get_by_key(&key("z")).unwrap().node_id(), a_id);
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let mut tree = TreeBuilder::new()
+            .with_root(0)
+            .build()
+            .with_key(|n: &i32| n % 2);
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        let four_id = tree.root_mut().unwrap().append(4).node_id();
+        let root_id = tree.root_id().unwrap();
+
+        assert_eq!(tree.get_by_key(&0).unwrap().node_id(), root_id);
+        tree.remove(root_id, OrphanChildren);
+        assert_eq!(tree.get_by_key(&0).unwrap().node_id(), two_id);
+        tree.remove(two_id, OrphanChildren);
+        assert_eq!(tree.get_by_key(&0).unwrap().node_id(), four_id);
+    }
+
+    #[test]
+    fn several_key_types() {
+        let mut tree = TreeBuilder::new()
+            .with_root((1, 'a'))
+            .build()
+            .with_key(|&(n, _)| n)
+            .with_key(|&(_, c)| c);
+        let two_id = tree.root_mut().unwrap().append((2, 'b')).node_id();
+
+        assert_eq!(tree.get_by_key(&2).unwrap().node_id(), two_id);
+        assert_eq!(tree.get_by_key(&'b').unwrap().node_id(), two_id);
+        assert!(tree.get_by_key(&"b").is_none());
+
+        // replacing the index of a key type
+        let tree = tree.with_key(|&(n, _)| n * 10);
+        assert!(tree.get_by_key(&2).is_none());
+        assert_eq!(tree.get_by_key(&20).unwrap().node_id(), two_id);
+    }
+
+    #[test]
+    fn survives_clear_restore_and_compact() {
+        let mut tree = named();
+        let a_id = tree.root_mut().unwrap().append(key("a")).node_id();
+        let snapshot = tree.snapshot();
+        tree.remove(a_id, DropChildren);
+        assert!(tree.get_by_key(&key("a")).is_none());
+
+        assert!(tree.restore(snapshot));
+        assert_eq!(tree.get_by_key(&key("a")).unwrap().node_id(), a_id);
+
+        let b_id = tree.root_mut().unwrap().prepend(key("b")).node_id();
+        tree.remove(b_id, DropChildren);
+        let c_id = tree.root_mut().unwrap().append(key("c")).node_id();
+        tree.remove(a_id, DropChildren);
+        let remap = tree.compact();
+        assert_eq!(tree.get_by_key(&key("c")).unwrap().node_id(), remap[&c_id]);
+
+        tree.clear();
+        assert!(tree.get_by_key(&key("c")).is_none());
+        tree.set_root(key("d"));
+        assert!(tree.get_by_key(&key("d")).is_some());
+    }
+}
@@ -1,4 +1,5 @@
-#![forbid(unsafe_code)]
+#![cfg_attr(not(any(feature = "capi", feature = "unchecked")), forbid(unsafe_code))]
+#![cfg_attr(any(feature = "capi", feature = "unchecked"), deny(unsafe_code))]
 
 //!
 //! # slab_tree
@@ -27,7 +28,12 @@
 //! this crate to do so; this library will not make those structural decisions for you.
 //!
 //! ## Safety
-//! This crate uses `#![forbid(unsafe_code)]` to prevent any and all `unsafe` code usage.
+//! This crate uses `#![forbid(unsafe_code)]` to prevent any and all `unsafe` code usage.  There
+//! are two exceptions, both behind optional features which make the crate use
+//! `#![deny(unsafe_code)]` instead.  Exporting functions to C with the `capi` feature needs
+//! `#[no_mangle]`, which Rust counts as `unsafe` code; it's allowed only in the `capi` module,
+//! which still never dereferences a raw pointer.  The `unchecked` feature allows `unsafe` only
+//! on `Tree::get_unchecked` and `Tree::get_unchecked_mut`.
 //!
 //! ## Example Usage
 //! ```
@@ -68,16 +74,123 @@
 //! * Arbitrary _Graph_ structure creation and manipulation
 //! * Comparison-based node insertion of any kind
 //!
+//! ## Optional Features
+//! * `arbitrary` - implements `arbitrary::Arbitrary` for `Tree<T>` and adds
+//!   `Tree::arbitrary_mutation` for fuzzing code that works with trees.
+//! * `bumpalo` - adds the `arena` module with `ArenaTree`, a tree whose `Node`s are allocated
+//!   from a caller-provided `bumpalo::Bump`, so a whole batch of trees can be freed at once.
+//! * `capi` - adds the `capi` module, a C-compatible interface for building and traversing
+//!   `Tree<u64>`s from C, C++, or any language with a C FFI.
+//! * `chunked-slab` - stores each `Tree`'s `Node`s in separately allocated chunks of 4096
+//!   slots instead of a single `Vec`, so growing a large `Tree` copies at most one chunk
+//!   rather than every `Node` it holds (and never briefly needs twice the memory), and
+//!   `Tree::compact` frees the chunks it empties.  Looking up a `Node` costs one more
+//!   indirection.
+//! * `csv` - adds the `csv` module for reading and writing `Tree`s as `id,parent_id,payload`
+//!   CSV or TSV adjacency lists.
+//! * `ego-tree` - adds `convert::into_ego_tree` and `convert::from_ego_tree` for converting
+//!   between `Tree`s and `ego_tree::Tree`s.
+//! * `fs` - adds the `fs` module with `Tree::from_dir` for loading directory trees from the
+//!   filesystem.
+//! * `indextree` - adds `convert::into_arena` and `convert::from_arena` for converting between
+//!   `Tree`s and `indextree::Arena`s.
+//! * `jsonl` - adds `Tree::write_jsonl` for streaming a `Tree` out as JSON Lines, one object
+//!   per `Node`.  Enables `serde`.
+//! * `petgraph` - adds `Tree::to_petgraph` for exporting a `Tree` as a `petgraph` graph.
+//! * `proptest` - adds the `strategy` module with `proptest` strategies that generate
+//!   structurally shrinkable `Tree`s.
+//! * `quick-xml` - adds the `xml` module with `Tree::from_xml` and `Tree::write_xml` for
+//!   reading and writing `Tree`s as XML documents, one `Node` per element.
+//! * `rand` - adds the `gen` module with `Tree::random` for generating random trees.
+//! * `rayon` - adds `Tree::par_map` for transforming the data of every `Node` in parallel.
+//! * `serde` - adds the `serde_nested` module for (de)serializing `Tree`s as nested
+//!   `{"data": ..., "children": [...]}` objects, the `serde_outline` module for reading
+//!   nested YAML/JSON documents into `Tree<String>`s, and the `serde_stable` module for
+//!   (de)serializing `Tree`s without changing the positions of their `Node`s, along with
+//!   `Serialize`/`Deserialize` for `NodeId`.
+//! * `u32-index` - stores the slot index and generation inside each `NodeId` as `u32`s
+//!   instead of `usize`/`u64`, shrinking every `NodeId` and slot.  A `Tree` panics if it
+//!   ever needs more than `u32::MAX` slots, and after `u32::MAX` removals the generation
+//!   wraps, so a very old `NodeId` could in principle resolve to a newer `Node`.
+//! * `unchecked` - adds the `unsafe` `Tree::get_unchecked` and `Tree::get_unchecked_mut`,
+//!   which skip validating a `NodeId`.
+//!
 
+#[cfg(feature = "bumpalo")]
+pub mod arena;
+pub mod augment;
+pub mod backend;
 pub mod behaviors;
+pub mod binary;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod changes;
+mod checksum;
+#[cfg(feature = "chunked-slab")]
+mod chunks;
+pub mod compact;
+pub mod compare;
+pub mod convert;
 mod core_tree;
+#[cfg(feature = "csv")]
+pub mod csv;
+mod depth_index;
+pub mod depths;
+pub mod diff;
+pub mod edit;
+pub mod entry;
+pub mod events;
+pub mod export;
+pub mod format;
+#[cfg(feature = "fs")]
+pub mod fs;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+#[cfg(feature = "rand")]
+pub mod gen;
+pub mod intern;
 pub mod iter;
+#[cfg(feature = "jsonl")]
+pub mod jsonl;
+mod key_index;
+pub mod mapped;
+mod merge;
+pub mod navigation;
+pub mod nested;
+pub mod newick;
 pub mod node;
+pub mod observer;
+#[cfg(feature = "rayon")]
+mod par;
+pub mod pattern;
+pub mod pool;
+pub mod reparent;
+mod retain;
+pub mod rewrite;
+pub mod rows;
+#[cfg(feature = "serde")]
+pub mod serde_nested;
+#[cfg(feature = "serde")]
+pub mod serde_outline;
+#[cfg(feature = "serde")]
+pub mod serde_stable;
+mod sizes;
 mod slab;
+pub mod snapshot;
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+pub mod template;
+pub mod transplant;
 pub mod tree;
+mod value_index;
+pub mod walk;
+#[cfg(feature = "quick-xml")]
+pub mod xml;
 
 pub use crate::behaviors::RemoveBehavior;
 pub use crate::iter::Ancestors;
+pub use crate::iter::AncestorsOrSelf;
 pub use crate::iter::NextSiblings;
 pub use crate::node::NodeMut;
 pub use crate::node::NodeRef;
@@ -94,3 +207,28 @@ pub struct NodeId {
     tree_id: ProcessUniqueId,
     index: slab::Index,
 }
+
+impl NodeId {
+    ///
+    /// Returns the `NodeId` as a pair of plain integers (its slot in the `Tree` and that slot's
+    /// generation), for storing it in a database, sending it to another process, or using it as
+    /// a key outside of Rust.  `Tree::id_from_raw` turns the pair back into a `NodeId`.
+    ///
+    /// The pair doesn't say which `Tree` the `NodeId` belongs to, so keep track of that
+    /// separately.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    ///
+    /// let raw = two_id.to_raw();
+    /// assert_eq!(tree.id_from_raw(raw), Some(two_id));
+    /// ```
+    ///
+    pub fn to_raw(&self) -> (u64, u64) {
+        let (index, generation) = self.index.into_parts();
+        (index as u64, generation)
+    }
+}
@@ -0,0 +1,814 @@
+//!
+//! A binary layout for `Tree`s which can be read in place (for example from a memory-mapped file)
+//! without decoding every `Node` up front.
+//!
+//! The layout starts with a 4 byte magic number (`SLMT`), a 1 byte format version and 3 bytes of
+//! padding, followed by the number of `Node`s and the length of the data blob.  Next comes a table
+//! with one fixed-size record per `Node` in pre-order, and finally the data blob holding the
+//! encoded data of every `Node` back to back.  Each record holds the positions of the `Node`'s
+//! parent, previous sibling and next sibling, the position just past the end of its subtree, and
+//! the offset of its data in the blob.  All numbers are little-endian `u64`s, and missing
+//! relatives are written as `u64::MAX`.
+//!
+//! Since `Node`s are numbered in pre-order, every subtree is a contiguous run of records, and
+//! every record is 8-byte aligned relative to the start of the input.
+//!
+//! `MappedTree::new` only checks the header, so opening even a very large input is instant.  The
+//! links of an input that hasn't been checked with `MappedTree::validate` are only trusted as far
+//! as needed to avoid panics and endless loops; a corrupt input may produce wrong answers, but
+//! never crashes.
+//!
+
+use crate::binary::BinaryError;
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::convert::TryInto;
+use std::ops::Range;
+
+const MAGIC: &[u8; 4] = b"SLMT";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 24;
+const RECORD_LEN: usize = 40;
+const NONE: u64 = u64::MAX;
+
+const PARENT: usize = 0;
+const PREV_SIBLING: usize = 1;
+const NEXT_SIBLING: usize = 2;
+const SUBTREE_END: usize = 3;
+const DATA_OFFSET: usize = 4;
+
+impl<T> Tree<T> {
+    ///
+    /// Writes the `Tree` in the layout described in the `mapped` module, using `encode` to write
+    /// the data of each `Node` into the provided buffer.  Orphaned `Node`s are not written.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    /// tree.root_mut().unwrap().append("child");
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.extend_from_slice(data.as_bytes()));
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    ///
+    /// let child = mapped.root().unwrap().first_child().unwrap();
+    /// assert_eq!(child.data(), b"child");
+    /// ```
+    ///
+    pub fn to_mapped_bytes<F>(&self, mut encode: F) -> Vec<u8>
+    where
+        F: FnMut(&T, &mut Vec<u8>),
+    {
+        let node_ids: Vec<NodeId> = match self.root() {
+            Some(root) => root
+                .traverse_pre_order()
+                .map(|node| node.node_id())
+                .collect(),
+            None => Vec::new(),
+        };
+        let positions: HashMap<NodeId, u64> = node_ids
+            .iter()
+            .enumerate()
+            .map(|(position, &node_id)| (node_id, position as u64))
+            .collect();
+        let position =
+            |node_id: Option<NodeId>| node_id.map_or(NONE, |node_id| positions[&node_id]);
+
+        let mut records = Vec::with_capacity(node_ids.len() * RECORD_LEN);
+        let mut blob = Vec::new();
+        let mut subtree_ends = Vec::with_capacity(node_ids.len());
+
+        for (index, &node_id) in node_ids.iter().enumerate() {
+            let node = self.get_node(node_id).expect("node doesn't exist?");
+            // the root's relatives (if it has any) are outside of the written tree
+            let (parent, prev_sibling, next_sibling) = if index == 0 {
+                (None, None, None)
+            } else {
+                let relatives = &node.relatives;
+                (
+                    relatives.parent,
+                    relatives.prev_sibling,
+                    relatives.next_sibling,
+                )
+            };
+
+            let parent = position(parent);
+            let next_sibling = position(next_sibling);
+            let subtree_end = if next_sibling != NONE {
+                next_sibling
+            } else if parent != NONE {
+                subtree_ends[parent as usize]
+            } else {
+                node_ids.len() as u64
+            };
+            subtree_ends.push(subtree_end);
+
+            let fields = [
+                parent,
+                position(prev_sibling),
+                next_sibling,
+                subtree_end,
+                blob.len() as u64,
+            ];
+            for field in &fields {
+                records.extend_from_slice(&field.to_le_bytes());
+            }
+            encode(&node.data, &mut blob);
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + records.len() + blob.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&[0; 3]);
+        bytes.extend_from_slice(&(node_ids.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(blob.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&records);
+        bytes.extend_from_slice(&blob);
+        bytes
+    }
+}
+
+///
+/// A read-only view of a `Tree` written by `Tree::to_mapped_bytes`, which reads `Node`s straight
+/// out of the underlying bytes as they are visited.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct MappedTree<'a> {
+    records: &'a [u8],
+    blob: &'a [u8],
+    len: usize,
+}
+
+impl<'a> MappedTree<'a> {
+    ///
+    /// Opens the `Tree` in `bytes`, checking only the header and the overall length.
+    ///
+    /// ```
+    /// use slab_tree::binary::BinaryError;
+    /// use slab_tree::mapped::MappedTree;
+    ///
+    /// let error = MappedTree::new(b"not a tree").unwrap_err();
+    /// assert_eq!(error, BinaryError::UnexpectedEof);
+    /// ```
+    ///
+    pub fn new(bytes: &'a [u8]) -> Result<MappedTree<'a>, BinaryError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(BinaryError::UnexpectedEof);
+        }
+        let (header, rest) = bytes.split_at(HEADER_LEN);
+        if &header[..4] != MAGIC {
+            return Err(BinaryError::BadMagic);
+        }
+        if header[4] != VERSION {
+            return Err(BinaryError::UnsupportedVersion(header[4]));
+        }
+
+        let len = to_usize(read_u64(header, 8))?;
+        let blob_len = to_usize(read_u64(header, 16))?;
+        let records_len = len.checked_mul(RECORD_LEN).ok_or(BinaryError::Overflow)?;
+        let total = records_len
+            .checked_add(blob_len)
+            .ok_or(BinaryError::Overflow)?;
+        if rest.len() < total {
+            return Err(BinaryError::UnexpectedEof);
+        }
+        if rest.len() > total {
+            return Err(BinaryError::TrailingBytes);
+        }
+
+        let (records, blob) = rest.split_at(records_len);
+        Ok(MappedTree { records, blob, len })
+    }
+
+    ///
+    /// Returns the number of `Node`s in the `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// assert_eq!(MappedTree::new(&bytes).unwrap().len(), 2);
+    /// ```
+    ///
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    ///
+    /// Returns `true` if the `Tree` has no `Node`s.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let bytes = Tree::<u8>::new().to_mapped_bytes(|data, buf| buf.push(*data));
+    /// assert!(MappedTree::new(&bytes).unwrap().is_empty());
+    /// ```
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    ///
+    /// Returns the root `Node`, or `None` if the `Tree` is empty.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::new().with_root(1u8).build();
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    ///
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    /// assert_eq!(mapped.root().unwrap().data(), &[1]);
+    /// ```
+    ///
+    pub fn root(&self) -> Option<MappedNode<'a>> {
+        self.get(0)
+    }
+
+    ///
+    /// Returns the `Node` at the given pre-order position, or `None` if there isn't one.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2).append(3);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    ///
+    /// assert_eq!(mapped.get(2).unwrap().data(), &[3]);
+    /// assert!(mapped.get(3).is_none());
+    /// ```
+    ///
+    pub fn get(&self, position: usize) -> Option<MappedNode<'a>> {
+        if position < self.len {
+            Some(MappedNode {
+                tree: *self,
+                position,
+            })
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Checks that every record describes a single well-formed `Tree` and that every `Node`'s
+    /// data lies within the data blob.  This reads the whole table, so it takes time proportional
+    /// to the number of `Node`s.
+    ///
+    /// ```
+    /// use slab_tree::binary::BinaryError;
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2);
+    ///
+    /// let mut bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// assert!(MappedTree::new(&bytes).unwrap().validate().is_ok());
+    ///
+    /// // point the child's parent link at itself
+    /// bytes[24 + 40] = 1;
+    /// let error = MappedTree::new(&bytes).unwrap().validate().unwrap_err();
+    /// assert_eq!(error, BinaryError::InvalidStructure);
+    /// ```
+    ///
+    pub fn validate(&self) -> Result<(), BinaryError> {
+        let len = self.len as u64;
+        let mut data_offset = 0;
+
+        for position in 0..self.len {
+            let index = position as u64;
+            let parent = self.field(position, PARENT);
+            let prev_sibling = self.field(position, PREV_SIBLING);
+            let next_sibling = self.field(position, NEXT_SIBLING);
+            let subtree_end = self.field(position, SUBTREE_END);
+
+            let offset = self.field(position, DATA_OFFSET);
+            if offset < data_offset || offset > self.blob.len() as u64 {
+                return Err(BinaryError::InvalidData(position));
+            }
+            data_offset = offset;
+
+            if subtree_end <= index || subtree_end > len {
+                return Err(BinaryError::InvalidStructure);
+            }
+
+            if position == 0 {
+                let is_root = parent == NONE
+                    && prev_sibling == NONE
+                    && next_sibling == NONE
+                    && subtree_end == len;
+                if !is_root {
+                    return Err(BinaryError::InvalidStructure);
+                }
+                continue;
+            }
+
+            if parent >= index {
+                return Err(BinaryError::InvalidStructure);
+            }
+            let parent_end = self.field(parent as usize, SUBTREE_END);
+            if subtree_end > parent_end {
+                return Err(BinaryError::InvalidStructure);
+            }
+
+            let expected_next = if subtree_end < parent_end {
+                subtree_end
+            } else {
+                NONE
+            };
+            if next_sibling != expected_next {
+                return Err(BinaryError::InvalidStructure);
+            }
+
+            let linked = if index == parent + 1 {
+                prev_sibling == NONE
+            } else {
+                prev_sibling < index
+                    && self.field(prev_sibling as usize, PARENT) == parent
+                    && self.field(prev_sibling as usize, NEXT_SIBLING) == index
+            };
+            if !linked {
+                return Err(BinaryError::InvalidStructure);
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Validates the `Tree` and decodes it into a regular `Tree`, using `decode` to read the data
+    /// of each `Node`.  If `decode` returns `None`, decoding stops with `BinaryError::InvalidData`.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// let decoded = MappedTree::new(&bytes).unwrap().to_tree(|buf| buf.first().copied()).unwrap();
+    ///
+    /// let root = decoded.root().unwrap();
+    /// assert_eq!(root.first_child().unwrap().data(), &2);
+    /// ```
+    ///
+    pub fn to_tree<T, F>(&self, mut decode: F) -> Result<Tree<T>, BinaryError>
+    where
+        F: FnMut(&[u8]) -> Option<T>,
+    {
+        self.validate()?;
+
+        let mut tree = TreeBuilder::new().with_capacity(self.len).build();
+        let mut node_ids = Vec::with_capacity(self.len);
+
+        for position in 0..self.len {
+            let data = decode(self.data(position)).ok_or(BinaryError::InvalidData(position))?;
+            let node_id = if position == 0 {
+                tree.set_root(data)
+            } else {
+                let parent_id = node_ids[self.field(position, PARENT) as usize];
+                tree.get_mut(parent_id)
+                    .expect("parent must exist")
+                    .append(data)
+                    .node_id()
+            };
+            node_ids.push(node_id);
+        }
+
+        Ok(tree)
+    }
+
+    fn field(&self, position: usize, field: usize) -> u64 {
+        read_u64(self.records, position * RECORD_LEN + field * 8)
+    }
+
+    // the position a link points to, if it points anywhere within `range`
+    fn link(&self, position: usize, field: usize, range: Range<usize>) -> Option<usize> {
+        usize::try_from(self.field(position, field))
+            .ok()
+            .filter(|linked| range.contains(linked))
+    }
+
+    fn data(&self, position: usize) -> &'a [u8] {
+        let start = self.field(position, DATA_OFFSET);
+        let end = if position + 1 < self.len {
+            self.field(position + 1, DATA_OFFSET)
+        } else {
+            self.blob.len() as u64
+        };
+        let blob: &'a [u8] = self.blob;
+        match (usize::try_from(start), usize::try_from(end)) {
+            (Ok(start), Ok(end)) => blob.get(start..end).unwrap_or(&[]),
+            _ => &[],
+        }
+    }
+}
+
+///
+/// A `Node` of a `MappedTree`.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct MappedNode<'a> {
+    tree: MappedTree<'a>,
+    position: usize,
+}
+
+impl<'a> MappedNode<'a> {
+    ///
+    /// Returns the pre-order position of this `Node` in the `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::new().with_root(1u8).build();
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    ///
+    /// assert_eq!(MappedTree::new(&bytes).unwrap().root().unwrap().position(), 0);
+    /// ```
+    ///
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    ///
+    /// Returns the encoded data of this `Node`.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::new().with_root(7u32).build();
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.extend_from_slice(&data.to_le_bytes()));
+    ///
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    /// assert_eq!(mapped.root().unwrap().data(), &7u32.to_le_bytes());
+    /// ```
+    ///
+    pub fn data(&self) -> &'a [u8] {
+        self.tree.data(self.position)
+    }
+
+    ///
+    /// Returns the parent of this `Node`, or `None` if it is the root.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    ///
+    /// assert_eq!(mapped.get(1).unwrap().parent().unwrap().data(), &[1]);
+    /// assert!(mapped.root().unwrap().parent().is_none());
+    /// ```
+    ///
+    pub fn parent(&self) -> Option<MappedNode<'a>> {
+        let position = self.tree.link(self.position, PARENT, 0..self.position)?;
+        self.tree.get(position)
+    }
+
+    ///
+    /// Returns the previous sibling of this `Node`, if it has one.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2);
+    /// tree.root_mut().unwrap().append(3);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    ///
+    /// assert_eq!(mapped.get(2).unwrap().prev_sibling().unwrap().data(), &[2]);
+    /// ```
+    ///
+    pub fn prev_sibling(&self) -> Option<MappedNode<'a>> {
+        let position = self
+            .tree
+            .link(self.position, PREV_SIBLING, 0..self.position)?;
+        self.tree.get(position)
+    }
+
+    ///
+    /// Returns the next sibling of this `Node`, if it has one.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2).append(3);
+    /// tree.root_mut().unwrap().append(4);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    ///
+    /// assert_eq!(mapped.get(1).unwrap().next_sibling().unwrap().data(), &[4]);
+    /// ```
+    ///
+    pub fn next_sibling(&self) -> Option<MappedNode<'a>> {
+        let position = self.tree.link(
+            self.position,
+            NEXT_SIBLING,
+            self.position + 1..self.tree.len,
+        )?;
+        self.tree.get(position)
+    }
+
+    ///
+    /// Returns the first child of this `Node`, if it has one.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    ///
+    /// assert_eq!(mapped.root().unwrap().first_child().unwrap().data(), &[2]);
+    /// assert!(mapped.get(1).unwrap().first_child().is_none());
+    /// ```
+    ///
+    pub fn first_child(&self) -> Option<MappedNode<'a>> {
+        if self.subtree_end() > self.position + 1 {
+            self.tree.get(self.position + 1)
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Returns an iterator over the children of this `Node`.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2).append(3);
+    /// tree.root_mut().unwrap().append(4);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    ///
+    /// let children: Vec<&[u8]> = mapped.root().unwrap().children().map(|c| c.data()).collect();
+    /// assert_eq!(children, vec![&[2], &[4]]);
+    /// ```
+    ///
+    pub fn children(&self) -> impl Iterator<Item = MappedNode<'a>> {
+        // next_sibling only ever moves forward, so this ends even on corrupt input
+        std::iter::successors(self.first_child(), |child| child.next_sibling())
+    }
+
+    ///
+    /// Returns the number of `Node`s in the subtree rooted at this `Node`, including itself.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2).append(3);
+    /// tree.root_mut().unwrap().append(4);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    ///
+    /// assert_eq!(mapped.root().unwrap().subtree_size(), 4);
+    /// assert_eq!(mapped.get(1).unwrap().subtree_size(), 2);
+    /// ```
+    ///
+    pub fn subtree_size(&self) -> usize {
+        self.subtree_end() - self.position
+    }
+
+    ///
+    /// Returns an iterator over this `Node` and its descendants in pre-order.  Since `Node`s are
+    /// stored in pre-order, this simply walks the `Node`'s run of records.
+    ///
+    /// ```
+    /// use slab_tree::mapped::MappedTree;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1u8).build();
+    /// tree.root_mut().unwrap().append(2).append(3);
+    /// tree.root_mut().unwrap().append(4);
+    ///
+    /// let bytes = tree.to_mapped_bytes(|data, buf| buf.push(*data));
+    /// let mapped = MappedTree::new(&bytes).unwrap();
+    ///
+    /// let data: Vec<u8> = mapped.root().unwrap().traverse_pre_order().map(|n| n.data()[0]).collect();
+    /// assert_eq!(data, vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn traverse_pre_order(&self) -> impl Iterator<Item = MappedNode<'a>> {
+        let tree = self.tree;
+        (self.position..self.subtree_end()).map(move |position| MappedNode { tree, position })
+    }
+
+    fn subtree_end(&self) -> usize {
+        let end = self.position + 1..self.tree.len + 1;
+        self.tree
+            .link(self.position, SUBTREE_END, end)
+            .unwrap_or(self.position + 1)
+    }
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    let field = bytes[offset..offset + 8]
+        .try_into()
+        .expect("field is 8 bytes");
+    u64::from_le_bytes(field)
+}
+
+fn to_usize(value: u64) -> Result<usize, BinaryError> {
+    usize::try_from(value).map_err(|_| BinaryError::Overflow)
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod mapped_tests {
+    use super::*;
+
+    fn sample() -> Tree<u8> {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        one.append(2);
+        one.append(3).append(4);
+        root.append(5);
+        root.append(6).append(7);
+        tree
+    }
+
+    fn encode(data: &u8, buf: &mut Vec<u8>) {
+        // variable length data, so that offsets matter
+        buf.resize(buf.len() + *data as usize, *data);
+    }
+
+    fn pre_order(tree: &Tree<u8>) -> Vec<(u8, usize)> {
+        tree.root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (*node.data(), node.ancestors().count()))
+            .collect()
+    }
+
+    #[test]
+    fn navigation_matches_tree() {
+        let tree = sample();
+        let bytes = tree.to_mapped_bytes(encode);
+        let mapped = MappedTree::new(&bytes).unwrap();
+        assert!(mapped.validate().is_ok());
+        assert_eq!(mapped.len(), 8);
+
+        let nodes = tree.root().unwrap().traverse_pre_order();
+        for (node, mapped_node) in nodes.zip(mapped.root().unwrap().traverse_pre_order()) {
+            let value = *node.data();
+            assert_eq!(mapped_node.data(), vec![value; value as usize].as_slice());
+
+            let value_of = |node: Option<MappedNode>| node.map(|node| node.position() as u8);
+            assert_eq!(
+                value_of(mapped_node.parent()),
+                node.parent().map(|n| *n.data())
+            );
+            assert_eq!(
+                value_of(mapped_node.prev_sibling()),
+                node.prev_sibling().map(|n| *n.data())
+            );
+            assert_eq!(
+                value_of(mapped_node.next_sibling()),
+                node.next_sibling().map(|n| *n.data())
+            );
+            assert_eq!(
+                value_of(mapped_node.first_child()),
+                node.first_child().map(|n| *n.data())
+            );
+            assert_eq!(mapped_node.subtree_size(), node.subtree_size());
+            assert_eq!(mapped_node.children().count(), node.children().count());
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let tree = sample();
+        let bytes = tree.to_mapped_bytes(encode);
+        let decoded = MappedTree::new(&bytes)
+            .unwrap()
+            .to_tree(|buf| Some(buf.len() as u8))
+            .unwrap();
+        assert_eq!(pre_order(&decoded), pre_order(&tree));
+    }
+
+    #[test]
+    fn empty() {
+        let tree: Tree<u8> = Tree::new();
+        let bytes = tree.to_mapped_bytes(encode);
+        assert_eq!(bytes.len(), HEADER_LEN);
+
+        let mapped = MappedTree::new(&bytes).unwrap();
+        assert!(mapped.root().is_none());
+        assert!(mapped.validate().is_ok());
+        assert!(mapped.to_tree(|_| Some(0u8)).unwrap().root().is_none());
+    }
+
+    #[test]
+    fn orphans_are_skipped() {
+        let mut tree = sample();
+        let one_id = tree.root().unwrap().first_child().unwrap().node_id();
+        tree.remove(one_id, crate::behaviors::RemoveBehavior::OrphanChildren);
+
+        let bytes = tree.to_mapped_bytes(encode);
+        let mapped = MappedTree::new(&bytes).unwrap();
+        assert!(mapped.validate().is_ok());
+        assert_eq!(mapped.len(), 4);
+    }
+
+    #[test]
+    fn header_errors() {
+        let bytes = sample().to_mapped_bytes(encode);
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[3] = b'X';
+        assert_eq!(
+            MappedTree::new(&bad_magic).unwrap_err(),
+            BinaryError::BadMagic
+        );
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 2;
+        assert_eq!(
+            MappedTree::new(&bad_version).unwrap_err(),
+            BinaryError::UnsupportedVersion(2)
+        );
+
+        assert_eq!(
+            MappedTree::new(&bytes[..bytes.len() - 1]).unwrap_err(),
+            BinaryError::UnexpectedEof
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            MappedTree::new(&trailing).unwrap_err(),
+            BinaryError::TrailingBytes
+        );
+
+        let mut huge = bytes;
+        huge[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(MappedTree::new(&huge).is_err());
+    }
+
+    #[test]
+    fn corrupt_links_never_panic() {
+        let bytes = sample().to_mapped_bytes(encode);
+        let records = HEADER_LEN..HEADER_LEN + 8 * RECORD_LEN;
+
+        for offset in records.step_by(8) {
+            for &value in &[0, 1, 3, 7, 8, 100, NONE] {
+                let mut corrupt = bytes.clone();
+                corrupt[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+                let mapped = MappedTree::new(&corrupt).unwrap();
+
+                for node in mapped.root().unwrap().traverse_pre_order() {
+                    node.data();
+                    node.parent();
+                    node.prev_sibling();
+                    node.next_sibling();
+                    assert!(node.children().count() <= mapped.len());
+                }
+
+                // a moved data offset can still describe a valid (if wrong) tree
+                let is_data_offset = (offset - HEADER_LEN) % RECORD_LEN == DATA_OFFSET * 8;
+                if corrupt != bytes && !is_data_offset {
+                    assert!(mapped.validate().is_err());
+                    assert!(mapped.to_tree(|_| Some(0u8)).is_err());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::tree::Tree;
+use crate::NodeId;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+impl<T> Tree<T> {
+    ///
+    /// Overlays `other` onto this `Tree`.
+    ///
+    /// The roots of the two `Tree`s are always merged.  Below that, each child of a merged `Node`
+    /// in `other` is matched by `key` against the children of the corresponding `Node` in this
+    /// `Tree`.  When a match is found, `resolve` merges the data from `other` into the existing
+    /// data and the children of both are matched in turn.  Children without a match are grafted
+    /// (along with their whole sub-tree) after the existing children, in the order they appear
+    /// in `other`.
+    ///
+    /// If this `Tree` is empty, it simply receives all of `other`'s `Node`s.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// // base: config -> [server -> [port=80], logging]
+    /// let mut base = TreeBuilder::new().with_root(("config", 0)).build();
+    /// let mut root = base.root_mut().unwrap();
+    /// root.append(("server", 0)).append(("port", 80));
+    /// root.append(("logging", 0));
+    ///
+    /// // overlay: config -> [server -> [port=443, host=1]]
+    /// let mut overlay = TreeBuilder::new().with_root(("config", 0)).build();
+    /// let server_id = overlay.root_mut().unwrap().append(("server", 0)).node_id();
+    /// let mut server = overlay.get_mut(server_id).unwrap();
+    /// server.append(("port", 443));
+    /// server.append(("host", 1));
+    ///
+    /// base.merge(overlay, |(name, _)| *name, |old, new| *old = new);
+    ///
+    /// let mut s = String::new();
+    /// base.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// (\"config\", 0)
+    /// ├── (\"server\", 0)
+    /// │   ├── (\"port\", 443)
+    /// │   └── (\"host\", 1)
+    /// └── (\"logging\", 0)
+    /// ");
+    /// ```
+    ///
+    pub fn merge<K, F, R>(&mut self, mut other: Tree<T>, key: F, mut resolve: R)
+    where
+        K: Eq + Hash,
+        F: Fn(&T) -> K,
+        R: FnMut(&mut T, T),
+    {
+        let other_root_id = match other.root_id() {
+            Some(root_id) => root_id,
+            None => return,
+        };
+        let root_children = other.child_ids(other_root_id);
+        let root_data = other.take_data(other_root_id);
+
+        // (node in `other`, the node in `self` it was merged into or grafted as, whether its
+        // children still need to be matched)
+        let mut stack: Vec<(Vec<NodeId>, NodeId, bool)> = Vec::new();
+        match self.root_id() {
+            Some(root_id) => {
+                self.resolve_data(root_id, root_data, &mut resolve);
+                stack.push((root_children, root_id, true));
+            }
+            None => {
+                let root_id = self.set_root(root_data);
+                stack.push((root_children, root_id, false));
+            }
+        }
+
+        while let Some((other_children, target_id, matching)) = stack.pop() {
+            let mut existing: HashMap<K, NodeId> = HashMap::new();
+            if matching {
+                for child in self.get(target_id).expect("target must exist").children() {
+                    existing
+                        .entry(key(child.data()))
+                        .or_insert_with(|| child.node_id());
+                }
+            }
+
+            for other_id in other_children {
+                let grandchildren = other.child_ids(other_id);
+                let data = other.take_data(other_id);
+
+                let matched = if matching {
+                    existing.get(&key(&data)).cloned()
+                } else {
+                    None
+                };
+
+                match matched {
+                    Some(node_id) => {
+                        self.resolve_data(node_id, data, &mut resolve);
+                        stack.push((grandchildren, node_id, true));
+                    }
+                    None => {
+                        let node_id = self
+                            .get_mut(target_id)
+                            .expect("target must exist")
+                            .append(data)
+                            .node_id();
+                        stack.push((grandchildren, node_id, false));
+                    }
+                }
+            }
+        }
+    }
+
+    fn child_ids(&self, node_id: NodeId) -> Vec<NodeId> {
+        self.get(node_id)
+            .expect("node must exist")
+            .children()
+            .map(|child| child.node_id())
+            .collect()
+    }
+
+    fn resolve_data<R: FnMut(&mut T, T)>(&mut self, node_id: NodeId, data: T, resolve: &mut R) {
+        let node = self.get_node_mut(node_id).expect("node must exist");
+        resolve(&mut node.data, data);
+        self.notify_data_replaced(node_id);
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod merge_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    fn outline(tree: &Tree<(char, i32)>) -> Vec<(char, i32, usize)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| {
+                        let (name, value) = *node.data();
+                        (name, value, node.ancestors().count())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn add(old: &mut (char, i32), new: (char, i32)) {
+        old.1 += new.1;
+    }
+
+    #[test]
+    fn merge_into_empty() {
+        let mut tree = Tree::new();
+        let mut other = TreeBuilder::new().with_root(('a', 1)).build();
+        other.root_mut().unwrap().append(('b', 2)).append(('c', 3));
+
+        tree.merge(other, |data| data.0, add);
+        assert_eq!(outline(&tree), vec![('a', 1, 0), ('b', 2, 1), ('c', 3, 2)]);
+    }
+
+    #[test]
+    fn merge_empty() {
+        let mut tree = TreeBuilder::new().with_root(('a', 1)).build();
+        tree.merge(Tree::new(), |data| data.0, add);
+        assert_eq!(outline(&tree), vec![('a', 1, 0)]);
+    }
+
+    #[test]
+    fn merge_matches_and_grafts() {
+        let mut tree = TreeBuilder::new().with_root(('r', 1)).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut a = root.append(('a', 1));
+        a.append(('x', 1));
+        root.append(('b', 1));
+
+        let mut other = TreeBuilder::new().with_root(('r', 10)).build();
+        let mut root = other.root_mut().unwrap();
+        root.append(('c', 10)).append(('x', 10));
+        let mut a = root.append(('a', 10));
+        a.append(('x', 10));
+        a.append(('y', 10));
+
+        tree.merge(other, |data| data.0, add);
+        assert_eq!(
+            outline(&tree),
+            vec![
+                ('r', 11, 0),
+                ('a', 11, 1),
+                ('x', 11, 2),
+                ('y', 10, 2),
+                ('b', 1, 1),
+                ('c', 10, 1),
+                ('x', 10, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn grafted_duplicates_are_not_merged() {
+        let mut tree = TreeBuilder::new().with_root(('r', 0)).build();
+        let mut other = TreeBuilder::new().with_root(('r', 0)).build();
+        let mut root = other.root_mut().unwrap();
+        let mut a = root.append(('a', 1));
+        a.append(('x', 1));
+        a.append(('x', 2));
+
+        tree.merge(other, |data| data.0, add);
+        assert_eq!(
+            outline(&tree),
+            vec![('r', 0, 0), ('a', 1, 1), ('x', 1, 2), ('x', 2, 2)]
+        );
+    }
+}
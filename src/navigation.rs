@@ -0,0 +1,395 @@
+//!
+//! Navigation that explains why it failed.
+//!
+//! The `Option`-returning accessors on `Tree`, `NodeRef` and `NodeMut` can't tell a `Node` that
+//! simply has no such relative apart from a `NodeId` that has gone stale.  The `try_*`
+//! counterparts here return a `NavigationError` saying which it was.
+//!
+
+use crate::node::NodeMut;
+use crate::node::NodeRef;
+use crate::node::Relatives;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::error::Error;
+use std::fmt;
+
+///
+/// Describes why a `try_*` navigation method couldn't return a `Node`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationError {
+    ///
+    /// The `NodeId` belongs to a different `Tree` (or to this `Tree` before it was cleared).
+    ///
+    ForeignNode(NodeId),
+
+    ///
+    /// The `NodeId` belongs to this `Tree`, but its `Node` has since been removed.
+    ///
+    RemovedNode(NodeId),
+
+    ///
+    /// The `Node` with the given `NodeId` exists, but doesn't have the requested relative.
+    ///
+    MissingRelative(NodeId),
+}
+
+impl fmt::Display for NavigationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NavigationError::ForeignNode(node_id) => {
+                write!(f, "{:?} belongs to another tree", node_id)
+            }
+            NavigationError::RemovedNode(node_id) => {
+                write!(f, "{:?} has been removed from the tree", node_id)
+            }
+            NavigationError::MissingRelative(node_id) => {
+                write!(f, "{:?} has no such relative", node_id)
+            }
+        }
+    }
+}
+
+impl Error for NavigationError {}
+
+impl<T> Tree<T> {
+    ///
+    /// Returns a `NodeRef` pointing to the `Node` with the given `NodeId`, or an error saying
+    /// whether the `NodeId` belongs to another `Tree` or its `Node` has been removed.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::navigation::NavigationError;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    /// assert_eq!(tree.try_get(two_id).unwrap().data(), &2);
+    ///
+    /// tree.remove(two_id, RemoveBehavior::DropChildren);
+    /// assert_eq!(tree.try_get(two_id).err(), Some(NavigationError::RemovedNode(two_id)));
+    ///
+    /// let other = TreeBuilder::new().with_root(1).build();
+    /// let other_id = other.root_id().unwrap();
+    /// assert_eq!(tree.try_get(other_id).err(), Some(NavigationError::ForeignNode(other_id)));
+    /// ```
+    ///
+    pub fn try_get(&self, node_id: NodeId) -> Result<NodeRef<'_, T>, NavigationError> {
+        self.check_node(node_id)?;
+        Ok(NodeRef::new(node_id, self))
+    }
+
+    ///
+    /// Returns a `NodeMut` pointing to the `Node` with the given `NodeId`, or an error saying
+    /// whether the `NodeId` belongs to another `Tree` or its `Node` has been removed.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::navigation::NavigationError;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().unwrap();
+    /// *tree.try_get_mut(root_id).unwrap().data() = 10;
+    ///
+    /// tree.remove(root_id, RemoveBehavior::DropChildren);
+    /// assert_eq!(tree.try_get_mut(root_id).unwrap_err(), NavigationError::RemovedNode(root_id));
+    /// ```
+    ///
+    pub fn try_get_mut(&mut self, node_id: NodeId) -> Result<NodeMut<'_, T>, NavigationError> {
+        self.check_node(node_id)?;
+        Ok(NodeMut::new(node_id, self))
+    }
+
+    fn check_node(&self, node_id: NodeId) -> Result<(), NavigationError> {
+        if node_id.tree_id != self.core_tree.id() {
+            Err(NavigationError::ForeignNode(node_id))
+        } else if self.get_node(node_id).is_none() {
+            Err(NavigationError::RemovedNode(node_id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn try_relative(
+        &self,
+        node_id: NodeId,
+        relative: fn(&Relatives) -> Option<NodeId>,
+    ) -> Result<NodeId, NavigationError> {
+        self.check_node(node_id)?;
+        relative(&self.get_node_relatives(node_id)).ok_or(NavigationError::MissingRelative(node_id))
+    }
+}
+
+impl<'a, T> NodeRef<'a, T> {
+    ///
+    /// Returns a `NodeRef` pointing to this `Node`'s parent, or an error if it has none.
+    ///
+    /// ```
+    /// use slab_tree::navigation::NavigationError;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    ///
+    /// let two = tree.get(two_id).unwrap();
+    /// assert_eq!(two.try_parent().unwrap().data(), &1);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let error = NavigationError::MissingRelative(root.node_id());
+    /// assert_eq!(root.try_parent().err(), Some(error));
+    /// ```
+    ///
+    pub fn try_parent(&self) -> Result<NodeRef<'a, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.parent)
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to this `Node`'s previous sibling, or an error if it has
+    /// none.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// tree.root_mut().unwrap().append(2);
+    /// let three_id = tree.root_mut().unwrap().append(3).node_id();
+    ///
+    /// let three = tree.get(three_id).unwrap();
+    /// assert_eq!(three.try_prev_sibling().unwrap().data(), &2);
+    /// assert!(tree.root().unwrap().try_prev_sibling().is_err());
+    /// ```
+    ///
+    pub fn try_prev_sibling(&self) -> Result<NodeRef<'a, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.prev_sibling)
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to this `Node`'s next sibling, or an error if it has none.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    /// tree.root_mut().unwrap().append(3);
+    ///
+    /// let two = tree.get(two_id).unwrap();
+    /// assert_eq!(two.try_next_sibling().unwrap().data(), &3);
+    /// ```
+    ///
+    pub fn try_next_sibling(&self) -> Result<NodeRef<'a, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.next_sibling)
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to this `Node`'s first child, or an error if it has none.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// assert!(tree.root().unwrap().try_first_child().is_err());
+    ///
+    /// tree.root_mut().unwrap().append(2);
+    /// assert_eq!(tree.root().unwrap().try_first_child().unwrap().data(), &2);
+    /// ```
+    ///
+    pub fn try_first_child(&self) -> Result<NodeRef<'a, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.first_child)
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to this `Node`'s last child, or an error if it has none.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// tree.root_mut().unwrap().append(2);
+    /// tree.root_mut().unwrap().append(3);
+    ///
+    /// assert_eq!(tree.root().unwrap().try_last_child().unwrap().data(), &3);
+    /// ```
+    ///
+    pub fn try_last_child(&self) -> Result<NodeRef<'a, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.last_child)
+    }
+
+    fn try_relative(
+        &self,
+        relative: fn(&Relatives) -> Option<NodeId>,
+    ) -> Result<NodeRef<'a, T>, NavigationError> {
+        let tree = self.tree();
+        let node_id = tree.try_relative(self.node_id(), relative)?;
+        Ok(NodeRef::new(node_id, tree))
+    }
+}
+
+impl<'a, T> NodeMut<'a, T> {
+    ///
+    /// Returns a `NodeMut` pointing to this `Node`'s parent, or an error if it has none.
+    ///
+    /// ```
+    /// use slab_tree::navigation::NavigationError;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    ///
+    /// let mut two = tree.get_mut(two_id).unwrap();
+    /// *two.try_parent().unwrap().data() = 10;
+    ///
+    /// let mut root = tree.root_mut().unwrap();
+    /// assert_eq!(root.data(), &10);
+    /// assert!(root.try_parent().is_err());
+    /// ```
+    ///
+    pub fn try_parent(&mut self) -> Result<NodeMut<'_, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.parent)
+    }
+
+    ///
+    /// Returns a `NodeMut` pointing to this `Node`'s previous sibling, or an error if it has
+    /// none.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// tree.root_mut().unwrap().append(2);
+    /// let three_id = tree.root_mut().unwrap().append(3).node_id();
+    ///
+    /// let mut three = tree.get_mut(three_id).unwrap();
+    /// assert_eq!(three.try_prev_sibling().unwrap().data(), &2);
+    /// ```
+    ///
+    pub fn try_prev_sibling(&mut self) -> Result<NodeMut<'_, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.prev_sibling)
+    }
+
+    ///
+    /// Returns a `NodeMut` pointing to this `Node`'s next sibling, or an error if it has none.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    /// tree.root_mut().unwrap().append(3);
+    ///
+    /// let mut two = tree.get_mut(two_id).unwrap();
+    /// assert_eq!(two.try_next_sibling().unwrap().data(), &3);
+    /// ```
+    ///
+    pub fn try_next_sibling(&mut self) -> Result<NodeMut<'_, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.next_sibling)
+    }
+
+    ///
+    /// Returns a `NodeMut` pointing to this `Node`'s first child, or an error if it has none.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// assert!(root.try_first_child().is_err());
+    ///
+    /// root.append(2);
+    /// assert_eq!(root.try_first_child().unwrap().data(), &2);
+    /// ```
+    ///
+    pub fn try_first_child(&mut self) -> Result<NodeMut<'_, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.first_child)
+    }
+
+    ///
+    /// Returns a `NodeMut` pointing to this `Node`'s last child, or an error if it has none.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(2);
+    /// root.append(3);
+    ///
+    /// assert_eq!(root.try_last_child().unwrap().data(), &3);
+    /// ```
+    ///
+    pub fn try_last_child(&mut self) -> Result<NodeMut<'_, T>, NavigationError> {
+        self.try_relative(|relatives| relatives.last_child)
+    }
+
+    fn try_relative(
+        &mut self,
+        relative: fn(&Relatives) -> Option<NodeId>,
+    ) -> Result<NodeMut<'_, T>, NavigationError> {
+        let node_id = self.node_id();
+        let tree = self.tree_mut();
+        let relative_id = tree.try_relative(node_id, relative)?;
+        Ok(NodeMut::new(relative_id, tree))
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod navigation_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::DropChildren;
+    use crate::tree::TreeBuilder;
+
+    #[test]
+    fn stale_and_foreign_ids() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        tree.remove(two_id, DropChildren);
+
+        // a new node reusing the slot doesn't revive the old id
+        let three_id = tree.root_mut().unwrap().append(3).node_id();
+        assert_eq!(
+            tree.try_get(two_id).err(),
+            Some(NavigationError::RemovedNode(two_id))
+        );
+        assert_eq!(tree.try_get(three_id).unwrap().data(), &3);
+
+        let root_id = tree.root_id().unwrap();
+        tree.clear();
+        tree.set_root(4);
+        assert_eq!(
+            tree.try_get(root_id).err(),
+            Some(NavigationError::ForeignNode(root_id))
+        );
+    }
+
+    #[test]
+    fn missing_relatives() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let missing = Err(NavigationError::MissingRelative(root_id));
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.try_parent().map(|node| node.node_id()), missing);
+        assert_eq!(root.try_prev_sibling().map(|node| node.node_id()), missing);
+        assert_eq!(root.try_next_sibling().map(|node| node.node_id()), missing);
+        assert_eq!(root.try_first_child().map(|node| node.node_id()), missing);
+        assert_eq!(root.try_last_child().map(|node| node.node_id()), missing);
+
+        let mut root = tree.root_mut().unwrap();
+        assert_eq!(root.try_parent().map(|node| node.node_id()), missing);
+        assert_eq!(root.try_prev_sibling().map(|node| node.node_id()), missing);
+        assert_eq!(root.try_next_sibling().map(|node| node.node_id()), missing);
+        assert_eq!(root.try_first_child().map(|node| node.node_id()), missing);
+        assert_eq!(root.try_last_child().map(|node| node.node_id()), missing);
+    }
+
+    #[test]
+    fn display() {
+        let tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let message = NavigationError::RemovedNode(root_id).to_string();
+        assert!(message.ends_with("has been removed from the tree"));
+    }
+}
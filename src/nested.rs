@@ -0,0 +1,257 @@
+//!
+//! Conversions between `Tree`s and recursive types, such as a struct holding a `Vec` of itself.
+//!
+//! Implementing `NestedNode` for such a type gives `Tree::from_nested` and `Tree::into_nested`
+//! for it, so data can move between the recursive form and a `Tree` without writing the
+//! traversal by hand.  Neither conversion recurses, so deeply nested values are fine.
+//!
+
+use crate::tree::Tree;
+use crate::NodeId;
+
+///
+/// A recursive type which can be taken apart into (and put back together from) its own data
+/// and its children, one level at a time.
+///
+/// Both halves are taken in one call since taking either of them consumes the value.
+///
+/// ```
+/// use slab_tree::nested::NestedNode;
+///
+/// struct Outline {
+///     title: String,
+///     sections: Vec<Outline>,
+/// }
+///
+/// impl NestedNode<String> for Outline {
+///     type Children = Vec<Outline>;
+///
+///     fn into_parts(self) -> (String, Vec<Outline>) {
+///         (self.title, self.sections)
+///     }
+///
+///     fn from_parts(title: String, sections: Vec<Outline>) -> Outline {
+///         Outline { title, sections }
+///     }
+/// }
+/// ```
+///
+pub trait NestedNode<T>: Sized {
+    ///
+    /// The children of a value, in order.
+    ///
+    type Children: IntoIterator<Item = Self>;
+
+    ///
+    /// Splits the value into its data and its children.
+    ///
+    fn into_parts(self) -> (T, Self::Children);
+
+    ///
+    /// Builds a value from its data and its (already built) children.
+    ///
+    fn from_parts(data: T, children: Vec<Self>) -> Self;
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Builds a `Tree` from a recursive value, with one `Node` per level of nesting and the
+    /// children of each `Node` in the order given by `NestedNode::into_parts`.
+    ///
+    /// ```
+    /// use slab_tree::nested::NestedNode;
+    /// use slab_tree::tree::Tree;
+    ///
+    /// enum Expr {
+    ///     Num(i32),
+    ///     Add(Vec<Expr>),
+    /// }
+    ///
+    /// impl NestedNode<String> for Expr {
+    ///     type Children = Vec<Expr>;
+    ///
+    ///     fn into_parts(self) -> (String, Vec<Expr>) {
+    ///         match self {
+    ///             Expr::Num(n) => (n.to_string(), vec![]),
+    ///             Expr::Add(terms) => ("+".to_string(), terms),
+    ///         }
+    ///     }
+    ///
+    ///     fn from_parts(data: String, children: Vec<Expr>) -> Expr {
+    ///         match data.parse() {
+    ///             Ok(n) => Expr::Num(n),
+    ///             Err(_) => Expr::Add(children),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let expr = Expr::Add(vec![Expr::Num(1), Expr::Add(vec![Expr::Num(2), Expr::Num(3)])]);
+    /// let tree = Tree::from_nested(expr);
+    ///
+    /// let pre_order: Vec<&str> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_pre_order()
+    ///     .map(|node| node.data().as_str())
+    ///     .collect();
+    /// assert_eq!(pre_order, vec!["+", "1", "+", "2", "3"]);
+    /// ```
+    ///
+    pub fn from_nested<N: NestedNode<T>>(root: N) -> Tree<T> {
+        Tree::unfold(root, |node| {
+            let (data, children) = node.into_parts();
+            (data, children.into_iter().collect())
+        })
+    }
+
+    ///
+    /// Consumes the `Tree`, rebuilding it as a recursive value with `NestedNode::from_parts`,
+    /// children first.  Returns `None` if the `Tree` is empty.  Orphaned `Node`s are dropped.
+    ///
+    /// ```
+    /// use slab_tree::nested::NestedNode;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Dir {
+    ///     name: &'static str,
+    ///     entries: Vec<Dir>,
+    /// }
+    ///
+    /// impl NestedNode<&'static str> for Dir {
+    ///     type Children = Vec<Dir>;
+    ///
+    ///     fn into_parts(self) -> (&'static str, Vec<Dir>) {
+    ///         (self.name, self.entries)
+    ///     }
+    ///
+    ///     fn from_parts(name: &'static str, entries: Vec<Dir>) -> Dir {
+    ///         Dir { name, entries }
+    ///     }
+    /// }
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("/").build();
+    /// tree.root_mut().unwrap().append("usr").append("bin");
+    ///
+    /// let dir: Dir = tree.into_nested().unwrap();
+    /// assert_eq!(dir.name, "/");
+    /// assert_eq!(dir.entries[0].entries[0].name, "bin");
+    /// ```
+    ///
+    pub fn into_nested<N: NestedNode<T>>(mut self) -> Option<N> {
+        // every `Node`'s children come directly before it in post-order, so building each value
+        // from the top of `built` leaves exactly one value (the root) at the end
+        let order: Vec<(NodeId, usize)> = self
+            .root()?
+            .traverse_post_order()
+            .map(|node| (node.node_id(), node.child_count()))
+            .collect();
+
+        let mut built: Vec<N> = Vec::new();
+        for (node_id, child_count) in order {
+            let children = built.split_off(built.len() - child_count);
+            let data = self.take_data(node_id);
+            built.push(N::from_parts(data, children));
+        }
+        built.pop()
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod nested_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    #[derive(Debug, PartialEq)]
+    struct Node {
+        value: i32,
+        children: Vec<Node>,
+    }
+
+    impl NestedNode<i32> for Node {
+        type Children = Vec<Node>;
+
+        fn into_parts(self) -> (i32, Vec<Node>) {
+            (self.value, self.children)
+        }
+
+        fn from_parts(value: i32, children: Vec<Node>) -> Node {
+            Node { value, children }
+        }
+    }
+
+    fn leaf(value: i32) -> Node {
+        Node {
+            value,
+            children: vec![],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let nested = Node {
+            value: 1,
+            children: vec![
+                Node {
+                    value: 2,
+                    children: vec![leaf(4), leaf(5)],
+                },
+                leaf(3),
+                Node {
+                    value: 6,
+                    children: vec![leaf(7)],
+                },
+            ],
+        };
+
+        let tree = Tree::from_nested(nested);
+        let pre_order: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(pre_order, vec![1, 2, 4, 5, 3, 6, 7]);
+
+        let back: Node = tree.into_nested().unwrap();
+        assert_eq!(back.value, 1);
+        let values: Vec<i32> = back.children.iter().map(|child| child.value).collect();
+        assert_eq!(values, vec![2, 3, 6]);
+        assert_eq!(back.children[0].children, vec![leaf(4), leaf(5)]);
+        assert_eq!(back.children[2].children, vec![leaf(7)]);
+    }
+
+    #[test]
+    fn empty_and_orphans() {
+        let empty: Tree<i32> = TreeBuilder::new().build();
+        assert!(empty.into_nested::<Node>().is_none());
+
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        tree.get_mut(two_id).unwrap().append(3);
+        tree.remove(two_id, crate::behaviors::RemoveBehavior::OrphanChildren);
+        assert_eq!(tree.into_nested::<Node>(), Some(leaf(1)));
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let mut nested = leaf(0);
+        for value in 1..10_000 {
+            nested = Node {
+                value,
+                children: vec![nested],
+            };
+        }
+        let tree = Tree::from_nested(nested);
+        assert_eq!(tree.root().unwrap().traverse_pre_order().count(), 10_000);
+
+        let mut node: Node = tree.into_nested().unwrap();
+        let mut depth = 1;
+        while let Some(child) = node.children.pop() {
+            node = child;
+            depth += 1;
+        }
+        assert_eq!(depth, 10_000);
+    }
+}
@@ -0,0 +1,539 @@
+//!
+//! Reading and writing `Tree`s in the [Newick](https://en.wikipedia.org/wiki/Newick_format)
+//! format, e.g. `(A:0.1,B:0.2,(C,D)E)F;`.
+//!
+//! Each `Node` in a Newick tree has an optional name and an optional branch length, which are
+//! handed to (and requested from) the caller as a `Label`.  Names containing whitespace or any of
+//! the characters `()[]':;,` are written in single quotes (with `'` doubled inside them), and
+//! `[...]` comments are skipped when reading.  Unquoted names are read verbatim; underscores are
+//! not replaced with spaces.
+//!
+//! Both reading and writing use heap-allocated stacks rather than recursion, so deeply nested
+//! trees can't overflow the call stack.
+//!
+
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use std::error::Error;
+use std::fmt;
+
+const SPECIAL: &str = "()[]':;,";
+
+///
+/// The name and branch length of a single `Node` in a Newick tree.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Label {
+    ///
+    /// The name of the `Node`; empty if the `Node` is unnamed.
+    ///
+    pub name: String,
+
+    ///
+    /// The length of the branch leading to the `Node`, if any.
+    ///
+    pub length: Option<f64>,
+}
+
+impl Label {
+    ///
+    /// Creates a `Label` with the given name and no branch length.
+    ///
+    /// ```
+    /// use slab_tree::newick::Label;
+    ///
+    /// let label = Label::new("A");
+    /// assert_eq!(label.name, "A");
+    /// assert_eq!(label.length, None);
+    /// ```
+    ///
+    pub fn new<S: Into<String>>(name: S) -> Label {
+        Label {
+            name: name.into(),
+            length: None,
+        }
+    }
+
+    ///
+    /// Sets the branch length of the `Label`.
+    ///
+    /// ```
+    /// use slab_tree::newick::Label;
+    ///
+    /// let label = Label::new("A").with_length(0.5);
+    /// assert_eq!(label.length, Some(0.5));
+    /// ```
+    ///
+    pub fn with_length(mut self, length: f64) -> Label {
+        self.length = Some(length);
+        self
+    }
+}
+
+///
+/// Describes the ways in which reading a Newick tree can fail.  Positions are byte offsets into
+/// the input.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NewickError {
+    ///
+    /// The input ended before the tree was complete.
+    ///
+    UnexpectedEof,
+
+    ///
+    /// An unexpected character was found at the given position.
+    ///
+    UnexpectedChar(usize, char),
+
+    ///
+    /// The branch length starting at the given position isn't a valid number.
+    ///
+    InvalidLength(usize),
+}
+
+impl fmt::Display for NewickError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NewickError::UnexpectedEof => write!(f, "unexpected end of input"),
+            NewickError::UnexpectedChar(position, found) => {
+                write!(
+                    f,
+                    "unexpected character {:?} at position {}",
+                    found, position
+                )
+            }
+            NewickError::InvalidLength(position) => {
+                write!(f, "invalid branch length at position {}", position)
+            }
+        }
+    }
+}
+
+impl Error for NewickError {}
+
+impl<T> Tree<T> {
+    ///
+    /// Reads a `Tree` from a Newick string, using `data` to turn the `Label` of each `Node` into
+    /// its data.  An empty input (or one containing only `;`) produces an empty `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let tree = Tree::from_newick("(A:0.1,B:0.2,(C,D)E)F;", |label| label.name).unwrap();
+    ///
+    /// let root = tree.root().expect("root doesn't exist?");
+    /// assert_eq!(root.data(), "F");
+    ///
+    /// let children: Vec<&str> = root.children().map(|node| node.data().as_str()).collect();
+    /// assert_eq!(children, vec!["A", "B", "E"]);
+    /// ```
+    ///
+    pub fn from_newick<F>(input: &str, mut data: F) -> Result<Tree<T>, NewickError>
+    where
+        F: FnMut(Label) -> T,
+    {
+        let pending = Parser { input, position: 0 }.parse()?;
+        let mut tree = TreeBuilder::new().with_capacity(pending.len()).build();
+
+        let mut pending: Vec<(Option<Label>, Vec<usize>)> = pending
+            .into_iter()
+            .map(|node| (Some(node.label), node.children))
+            .collect();
+
+        if pending.is_empty() {
+            return Ok(tree);
+        }
+
+        let mut stack: Vec<(Option<NodeId>, usize)> = vec![(None, 0)];
+        while let Some((parent_id, index)) = stack.pop() {
+            let (label, children) = &mut pending[index];
+            let value = data(label.take().expect("each node is visited once"));
+            let node_id = match parent_id {
+                Some(parent_id) => tree
+                    .get_mut(parent_id)
+                    .expect("parent was inserted before its children")
+                    .append(value)
+                    .node_id(),
+                None => tree.set_root(value),
+            };
+            stack.extend(children.iter().rev().map(|&child| (Some(node_id), child)));
+        }
+
+        Ok(tree)
+    }
+
+    ///
+    /// Writes the `Tree` in Newick format (terminated by `;`), using `label` to describe each
+    /// `Node`.  Writes nothing if the `Tree` is empty.
+    ///
+    /// ```
+    /// use slab_tree::newick::Label;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(("root", None)).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(("a b", Some(1.5)));
+    /// root.append(("c", None));
+    ///
+    /// let mut s = String::new();
+    /// tree.write_newick(&mut s, |(name, length)| Label {
+    ///     name: name.to_string(),
+    ///     length: *length,
+    /// })
+    /// .unwrap();
+    /// assert_eq!(s, "('a b':1.5,c)root;");
+    /// ```
+    ///
+    pub fn write_newick<W, F>(&self, w: &mut W, mut label: F) -> fmt::Result
+    where
+        W: fmt::Write,
+        F: FnMut(&T) -> Label,
+    {
+        let root_id = match self.root_id() {
+            Some(root_id) => root_id,
+            None => return Ok(()),
+        };
+
+        // `true` once a node's children have all been written
+        let mut stack = vec![(root_id, false)];
+        while let Some((node_id, exiting)) = stack.pop() {
+            let node = self.get(node_id).expect("reachable node must exist");
+
+            if !exiting && node_id != root_id && node.prev_sibling().is_some() {
+                w.write_char(',')?;
+            }
+
+            if exiting || node.first_child().is_none() {
+                if exiting {
+                    w.write_char(')')?;
+                }
+                write_label(w, &label(node.data()))?;
+            } else {
+                w.write_char('(')?;
+                stack.push((node_id, true));
+                let mut child_id = node.last_child().map(|child| child.node_id());
+                while let Some(node_id) = child_id {
+                    stack.push((node_id, false));
+                    child_id = self.get_node_prev_sibling_id(node_id);
+                }
+            }
+        }
+
+        w.write_char(';')
+    }
+}
+
+fn write_label<W: fmt::Write>(w: &mut W, label: &Label) -> fmt::Result {
+    let needs_quotes = label
+        .name
+        .chars()
+        .any(|c| c.is_whitespace() || SPECIAL.contains(c));
+
+    if needs_quotes {
+        w.write_char('\'')?;
+        for c in label.name.chars() {
+            if c == '\'' {
+                w.write_char('\'')?;
+            }
+            w.write_char(c)?;
+        }
+        w.write_char('\'')?;
+    } else {
+        w.write_str(&label.name)?;
+    }
+
+    if let Some(length) = label.length {
+        write!(w, ":{}", length)?;
+    }
+    Ok(())
+}
+
+struct Pending {
+    label: Label,
+    children: Vec<usize>,
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    /// Parses the whole input into a flat list of nodes; the root (if any) is at index 0.
+    fn parse(mut self) -> Result<Vec<Pending>, NewickError> {
+        let mut nodes: Vec<Pending> = Vec::new();
+
+        self.skip_whitespace()?;
+        if matches!(self.peek(), None | Some(';')) {
+            self.bump();
+            self.expect_end()?;
+            return Ok(nodes);
+        }
+
+        // internal nodes whose children are still being read
+        let mut open: Vec<usize> = Vec::new();
+
+        loop {
+            // read the start of a node
+            self.skip_whitespace()?;
+            let index = nodes.len();
+            nodes.push(Pending {
+                label: Label::default(),
+                children: Vec::new(),
+            });
+            if let Some(&parent) = open.last() {
+                nodes[parent].children.push(index);
+            }
+
+            if self.peek() == Some('(') {
+                self.bump();
+                open.push(index);
+                continue;
+            }
+            nodes[index].label = self.parse_label()?;
+
+            // close any nodes that end here, then move on to the next sibling (if any)
+            loop {
+                self.skip_whitespace()?;
+                match self.peek() {
+                    Some(',') if !open.is_empty() => {
+                        self.bump();
+                        break;
+                    }
+                    Some(')') if !open.is_empty() => {
+                        self.bump();
+                        let closed = open.pop().expect("open is not empty");
+                        nodes[closed].label = self.parse_label()?;
+                    }
+                    None | Some(';') if open.is_empty() => {
+                        self.bump();
+                        self.expect_end()?;
+                        return Ok(nodes);
+                    }
+                    Some(c) => return Err(NewickError::UnexpectedChar(self.position, c)),
+                    None => return Err(NewickError::UnexpectedEof),
+                }
+            }
+        }
+    }
+
+    fn parse_label(&mut self) -> Result<Label, NewickError> {
+        self.skip_whitespace()?;
+        let mut label = Label::default();
+
+        if self.peek() == Some('\'') {
+            self.bump();
+            loop {
+                match self.peek() {
+                    Some('\'') => {
+                        self.bump();
+                        if self.peek() == Some('\'') {
+                            self.bump();
+                            label.name.push('\'');
+                        } else {
+                            break;
+                        }
+                    }
+                    Some(c) => {
+                        self.bump();
+                        label.name.push(c);
+                    }
+                    None => return Err(NewickError::UnexpectedEof),
+                }
+            }
+        } else {
+            let name = self.take_while(|c| !c.is_whitespace() && !SPECIAL.contains(c));
+            label.name.push_str(name);
+        }
+
+        self.skip_whitespace()?;
+        if self.peek() == Some(':') {
+            self.bump();
+            self.skip_whitespace()?;
+            let start = self.position;
+            let length = self.take_while(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+            label.length = Some(
+                length
+                    .parse()
+                    .map_err(|_| NewickError::InvalidLength(start))?,
+            );
+        }
+
+        Ok(label)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), NewickError> {
+        loop {
+            self.take_while(char::is_whitespace);
+            if self.peek() != Some('[') {
+                return Ok(());
+            }
+            self.take_while(|c| c != ']');
+            if self.bump().is_none() {
+                return Err(NewickError::UnexpectedEof);
+            }
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<(), NewickError> {
+        self.skip_whitespace()?;
+        match self.peek() {
+            Some(c) => Err(NewickError::UnexpectedChar(self.position, c)),
+            None => Ok(()),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn take_while<P: Fn(char) -> bool>(&mut self, predicate: P) -> &str {
+        let rest = &self.input[self.position..];
+        let len = rest
+            .char_indices()
+            .find(|&(_, c)| !predicate(c))
+            .map_or(rest.len(), |(i, _)| i);
+        self.position += len;
+        &rest[..len]
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod newick_tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Tree<Label>, NewickError> {
+        Tree::from_newick(input, |label| label)
+    }
+
+    fn write(tree: &Tree<Label>) -> String {
+        let mut s = String::new();
+        tree.write_newick(&mut s, |label| label.clone()).unwrap();
+        s
+    }
+
+    fn names(tree: &Tree<Label>) -> Vec<(String, usize)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| (node.data().name.clone(), node.ancestors().count()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn from_newick_empty() {
+        assert!(parse("").unwrap().root().is_none());
+        assert!(parse(" ; ").unwrap().root().is_none());
+    }
+
+    #[test]
+    fn from_newick_single_node() {
+        let tree = parse("A:1.5;").unwrap();
+        let root = tree.root().unwrap();
+        assert_eq!(*root.data(), Label::new("A").with_length(1.5));
+        assert!(root.first_child().is_none());
+    }
+
+    #[test]
+    fn from_newick_nested() {
+        let tree = parse("((A,B)C,(D)E,F)G;").unwrap();
+        let expected = vec![
+            ("G".to_string(), 0),
+            ("C".to_string(), 1),
+            ("A".to_string(), 2),
+            ("B".to_string(), 2),
+            ("E".to_string(), 1),
+            ("D".to_string(), 2),
+            ("F".to_string(), 1),
+        ];
+        assert_eq!(names(&tree), expected);
+    }
+
+    #[test]
+    fn from_newick_unnamed_nodes() {
+        let tree = parse("(,(,));").unwrap();
+        let names: Vec<usize> = names(&tree).into_iter().map(|(_, depth)| depth).collect();
+        assert_eq!(names, vec![0, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn from_newick_quotes_comments_and_whitespace() {
+        let tree = parse(" ( 'it''s here' : 2 [comment] , b_c:1e-3 ) root ;\n").unwrap();
+        let root = tree.root().unwrap();
+        let children: Vec<Label> = root.children().map(|n| n.data().clone()).collect();
+        assert_eq!(
+            children,
+            vec![
+                Label::new("it's here").with_length(2.0),
+                Label::new("b_c").with_length(0.001),
+            ]
+        );
+        assert_eq!(root.data().name, "root");
+    }
+
+    #[test]
+    fn from_newick_without_semicolon() {
+        assert_eq!(names(&parse("(A)B").unwrap()).len(), 2);
+    }
+
+    #[test]
+    fn from_newick_errors() {
+        assert_eq!(parse("(A,B").unwrap_err(), NewickError::UnexpectedEof);
+        assert_eq!(parse("A:x;").unwrap_err(), NewickError::InvalidLength(2));
+        assert_eq!(
+            parse("(A)B);").unwrap_err(),
+            NewickError::UnexpectedChar(4, ')')
+        );
+        assert_eq!(
+            parse("A,B;").unwrap_err(),
+            NewickError::UnexpectedChar(1, ',')
+        );
+        assert_eq!(
+            parse("A; B").unwrap_err(),
+            NewickError::UnexpectedChar(3, 'B')
+        );
+        assert_eq!(parse("'A").unwrap_err(), NewickError::UnexpectedEof);
+        assert_eq!(parse("A [oops").unwrap_err(), NewickError::UnexpectedEof);
+    }
+
+    #[test]
+    fn write_newick_empty() {
+        assert_eq!(write(&Tree::new()), "");
+    }
+
+    #[test]
+    fn write_newick_quotes_names() {
+        let mut tree = TreeBuilder::new().with_root(Label::new("")).build();
+        let mut root = tree.root_mut().unwrap();
+        root.append(Label::new("it's"));
+        root.append(Label::new("a,b").with_length(0.25));
+        assert_eq!(write(&tree), "('it''s','a,b':0.25);");
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = "((A:1,B:2)C:0.5,(D)E,F,'g h')G;";
+        let tree = parse(input).unwrap();
+        assert_eq!(write(&tree), input);
+        assert_eq!(names(&parse(&write(&tree)).unwrap()), names(&tree));
+    }
+
+    #[test]
+    fn deeply_nested() {
+        let depth = 100_000;
+        let input = format!("{}A{};", "(".repeat(depth), ")".repeat(depth));
+        let tree = parse(&input).unwrap();
+        assert_eq!(write(&tree), input);
+    }
+}
@@ -13,9 +13,10 @@ pub(crate) struct Relatives {
     pub(crate) next_sibling: Option<NodeId>,
     pub(crate) first_child: Option<NodeId>,
     pub(crate) last_child: Option<NodeId>,
+    pub(crate) child_count: usize,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct Node<T> {
     pub(crate) data: T,
     pub(crate) relatives: Relatives,
@@ -31,6 +32,7 @@ impl<T> Node<T> {
                 next_sibling: None,
                 first_child: None,
                 last_child: None,
+                child_count: 0,
             },
         }
     }
@@ -3,6 +3,7 @@ use crate::node::Node;
 use crate::node::NodeRef;
 use crate::tree::Tree;
 use crate::NodeId;
+use std::collections::TryReserveError;
 
 ///
 /// A mutable reference to a given `Node`'s data and its relatives.
@@ -14,10 +15,18 @@ pub struct NodeMut<'a, T> {
 }
 
 impl<'a, T> NodeMut<'a, T> {
-    pub(crate) fn new(node_id: NodeId, tree: &mut Tree<T>) -> NodeMut<T> {
+    pub(crate) fn new(node_id: NodeId, tree: &mut Tree<T>) -> NodeMut<'_, T> {
         NodeMut { node_id, tree }
     }
 
+    pub(crate) fn into_parts(self) -> (NodeId, &'a mut Tree<T>) {
+        (self.node_id, self.tree)
+    }
+
+    pub(crate) fn tree_mut(&mut self) -> &mut Tree<T> {
+        self.tree
+    }
+
     ///
     /// Returns the `NodeId` that identifies this `Node` in the tree.
     ///
@@ -63,6 +72,52 @@ impl<'a, T> NodeMut<'a, T> {
         }
     }
 
+    ///
+    /// Consumes the `NodeMut` and returns a mutable reference to the data contained by the given
+    /// `Node`, which (unlike the one returned by `data`) lives as long as the `NodeMut` would
+    /// have.  This is handy right after inserting a `Node`, to finish initializing it in place.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(vec![1]).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    ///
+    /// let child = root.append(Vec::new()).into_data();
+    /// child.extend(0..3);
+    ///
+    /// assert_eq!(root.first_child().unwrap().data(), &mut vec![0, 1, 2]);
+    /// ```
+    ///
+    pub fn into_data(self) -> &'a mut T {
+        if let Some(node) = self.tree.get_node_mut(self.node_id) {
+            &mut node.data
+        } else {
+            unreachable!()
+        }
+    }
+
+    ///
+    /// Replaces the data contained by the given `Node` with `data` and returns the old data.
+    ///
+    /// Unlike writing through `data`, this notifies any observers registered with the `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    ///
+    /// assert_eq!(root.replace_data(3), 1);
+    /// assert_eq!(root.data(), &mut 3);
+    /// ```
+    ///
+    pub fn replace_data(&mut self, data: T) -> T {
+        let old = std::mem::replace(self.data(), data);
+        self.tree.notify_data_replaced(self.node_id);
+        old
+    }
+
     ///
     /// Returns a `NodeMut` pointing to this `Node`'s parent.  Returns a `Some`-value containing
     /// the `NodeMut` if this `Node` has a parent; otherwise returns a `None`.
@@ -76,7 +131,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.parent().is_none());
     /// ```
     ///
-    pub fn parent(&mut self) -> Option<NodeMut<T>> {
+    pub fn parent(&mut self) -> Option<NodeMut<'_, T>> {
         self.get_self_as_node()
             .relatives
             .parent
@@ -96,7 +151,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.prev_sibling().is_none());
     /// ```
     ///
-    pub fn prev_sibling(&mut self) -> Option<NodeMut<T>> {
+    pub fn prev_sibling(&mut self) -> Option<NodeMut<'_, T>> {
         self.get_self_as_node()
             .relatives
             .prev_sibling
@@ -116,7 +171,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.next_sibling().is_none());
     /// ```
     ///
-    pub fn next_sibling(&mut self) -> Option<NodeMut<T>> {
+    pub fn next_sibling(&mut self) -> Option<NodeMut<'_, T>> {
         self.get_self_as_node()
             .relatives
             .next_sibling
@@ -136,7 +191,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.first_child().is_none());
     /// ```
     ///
-    pub fn first_child(&mut self) -> Option<NodeMut<T>> {
+    pub fn first_child(&mut self) -> Option<NodeMut<'_, T>> {
         self.get_self_as_node()
             .relatives
             .first_child
@@ -156,13 +211,74 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.last_child().is_none());
     /// ```
     ///
-    pub fn last_child(&mut self) -> Option<NodeMut<T>> {
+    pub fn last_child(&mut self) -> Option<NodeMut<'_, T>> {
         self.get_self_as_node()
             .relatives
             .last_child
             .map(move |id| NodeMut::new(id, self.tree))
     }
 
+    ///
+    /// Calls `f` with a mutable reference to the data of each of this `Node`'s children, in
+    /// order.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append(2);
+    /// root.append(3);
+    ///
+    /// root.for_each_child_mut(|data| *data *= 10);
+    ///
+    /// let children: Vec<i32> = root.as_ref().children().map(|child| *child.data()).collect();
+    /// assert_eq!(children, vec![20, 30]);
+    /// ```
+    ///
+    pub fn for_each_child_mut<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        self.for_each_child_node_mut(|mut child| f(child.data()));
+    }
+
+    ///
+    /// Calls `f` with a `NodeMut` pointing to each of this `Node`'s children, in order.  `f` may
+    /// change each child's data and descendants; each next child is looked up after `f` returns,
+    /// so reordering the children from within `f` changes which of them are visited.
+    ///
+    /// If `f` removes the child it was given, the visit goes on with the sibling that followed
+    /// it when `f` was called; if that sibling was removed as well, no further children are
+    /// visited.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append(1);
+    /// root.append(2);
+    ///
+    /// // give every child a child of its own
+    /// root.for_each_child_node_mut(|mut child| {
+    ///     let grandchild = *child.data() * 10;
+    ///     child.append(grandchild);
+    /// });
+    ///
+    /// let data: Vec<i32> = root.as_ref().traverse_pre_order().map(|node| *node.data()).collect();
+    /// assert_eq!(data, vec![0, 1, 10, 2, 20]);
+    /// ```
+    ///
+    pub fn for_each_child_node_mut<F: FnMut(NodeMut<'_, T>)>(&mut self, mut f: F) {
+        let mut next_id = self.get_self_as_node().relatives.first_child;
+        while let Some(child_id) = next_id {
+            let following_id = self.tree.get_node_relatives(child_id).next_sibling;
+            f(NodeMut::new(child_id, self.tree));
+            next_id = match self.tree.get_node(child_id) {
+                Some(child) => child.relatives.next_sibling,
+                None => following_id.filter(|&id| self.tree.get_node(id).is_some()),
+            };
+        }
+    }
+
     ///
     /// Appends a new `Node` as this `Node`'s last child (and first child if it has none).
     /// Returns a `NodeMut` pointing to the newly added `Node`.
@@ -187,7 +303,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert_eq!(child.parent().unwrap().data(), &mut 1);
     /// ```
     ///
-    pub fn append(&mut self, data: T) -> NodeMut<T> {
+    pub fn append(&mut self, data: T) -> NodeMut<'_, T> {
         let new_id = self.tree.core_tree.insert(data);
 
         let relatives = self.tree.get_node_relatives(self.node_id);
@@ -196,7 +312,7 @@ impl<'a, T> NodeMut<'a, T> {
         self.tree.set_parent(new_id, Some(self.node_id));
         self.tree.set_prev_sibling(new_id, prev_sibling);
 
-        let first_child = relatives.first_child.or_else(|| Some(new_id));
+        let first_child = relatives.first_child.or(Some(new_id));
         self.tree.set_first_child(self.node_id, first_child);
         self.tree.set_last_child(self.node_id, Some(new_id));
 
@@ -204,9 +320,54 @@ impl<'a, T> NodeMut<'a, T> {
             self.tree.set_next_sibling(node_id, Some(new_id));
         }
 
+        self.tree.notify_insert(new_id);
         NodeMut::new(new_id, self.tree)
     }
 
+    ///
+    /// Appends a new `Node` holding the data returned by `f`, as `append` does.  `f` is only
+    /// called once the `Node` is about to be inserted, so expensive data isn't built for an
+    /// insertion that never happens (such as one abandoned by a panic while making room for it).
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(vec![0u8; 4]).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    ///
+    /// let buffer = root.append_with(|| vec![0u8; 1024]).into_data();
+    /// buffer[0] = 1;
+    ///
+    /// assert_eq!(root.last_child().unwrap().data()[..2], [1, 0]);
+    /// ```
+    ///
+    pub fn append_with<F: FnOnce() -> T>(&mut self, f: F) -> NodeMut<'_, T> {
+        self.tree.core_tree.reserve_slot();
+        self.append(f())
+    }
+
+    ///
+    /// Like `append`, but returns an error (handing nothing to the `Tree`) if making room for the
+    /// new `Node` fails, instead of aborting the process.  `data` is dropped in that case.
+    ///
+    /// As with `Tree::try_reserve`, only the storage for the `Node` itself is covered: observers,
+    /// indexes and snapshots may still allocate.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    ///
+    /// let two_id = root.try_append(2).expect("out of memory").node_id();
+    /// assert_eq!(root.last_child().unwrap().node_id(), two_id);
+    /// ```
+    ///
+    pub fn try_append(&mut self, data: T) -> Result<NodeMut<'_, T>, TryReserveError> {
+        self.tree.core_tree.try_reserve_slot()?;
+        Ok(self.append(data))
+    }
+
     ///
     /// Prepends a new `Node` as this `Node`'s first child (and last child if it has none).
     /// Returns a `NodeMut` pointing to the newly added `Node`.
@@ -231,7 +392,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert_eq!(child.parent().unwrap().data(), &mut 1);
     /// ```
     ///
-    pub fn prepend(&mut self, data: T) -> NodeMut<T> {
+    pub fn prepend(&mut self, data: T) -> NodeMut<'_, T> {
         let new_id = self.tree.core_tree.insert(data);
 
         let relatives = self.tree.get_node_relatives(self.node_id);
@@ -240,7 +401,7 @@ impl<'a, T> NodeMut<'a, T> {
         self.tree.set_parent(new_id, Some(self.node_id));
         self.tree.set_next_sibling(new_id, next_sibling);
 
-        let last_child = relatives.last_child.or_else(|| Some(new_id));
+        let last_child = relatives.last_child.or(Some(new_id));
         self.tree.set_first_child(self.node_id, Some(new_id));
         self.tree.set_last_child(self.node_id, last_child);
 
@@ -248,6 +409,68 @@ impl<'a, T> NodeMut<'a, T> {
             self.tree.set_prev_sibling(node_id, Some(new_id));
         }
 
+        self.tree.notify_insert(new_id);
+        NodeMut::new(new_id, self.tree)
+    }
+
+    ///
+    /// Inserts a new `Node` as a child of this `Node`, just after the last child whose key (as
+    /// derived by `f`) is less than or equal to the new `Node`'s key.  Returns a `NodeMut`
+    /// pointing to the newly added `Node`.
+    ///
+    /// If the children are already sorted by `f`, they stay sorted, and children with equal keys
+    /// keep the order they were inserted in.  The children are searched from the last one back,
+    /// so inserting children in (mostly) ascending order is cheap.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("/").build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    ///
+    /// for name in &["src", "Cargo.toml", "README.md", "benches"] {
+    ///     root.insert_child_sorted_by_key(*name, |name| name.to_lowercase());
+    /// }
+    ///
+    /// let names: Vec<&str> = root.as_ref().children().map(|child| *child.data()).collect();
+    /// assert_eq!(names, vec!["benches", "Cargo.toml", "README.md", "src"]);
+    /// ```
+    ///
+    pub fn insert_child_sorted_by_key<K, F>(&mut self, data: T, mut f: F) -> NodeMut<'_, T>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let key = f(&data);
+
+        let mut prev_sibling = self.tree.get_node_relatives(self.node_id).last_child;
+        while let Some(node_id) = prev_sibling {
+            let node = self.tree.get_node(node_id).expect("child doesn't exist?");
+            if f(&node.data) <= key {
+                break;
+            }
+            prev_sibling = node.relatives.prev_sibling;
+        }
+
+        let prev_sibling = match prev_sibling {
+            Some(node_id) => node_id,
+            None => return self.prepend(data),
+        };
+        let next_sibling = match self.tree.get_node_relatives(prev_sibling).next_sibling {
+            Some(node_id) => node_id,
+            None => return self.append(data),
+        };
+
+        let new_id = self.tree.core_tree.insert(data);
+
+        self.tree.set_parent(new_id, Some(self.node_id));
+        self.tree.set_prev_sibling(new_id, Some(prev_sibling));
+        self.tree.set_next_sibling(new_id, Some(next_sibling));
+
+        self.tree.set_next_sibling(prev_sibling, Some(new_id));
+        self.tree.set_prev_sibling(next_sibling, Some(new_id));
+
+        self.tree.notify_insert(new_id);
         NodeMut::new(new_id, self.tree)
     }
 
@@ -340,7 +563,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert_eq!(root.data(), &1);
     /// ```
     ///
-    pub fn as_ref(&self) -> NodeRef<T> {
+    pub fn as_ref(&self) -> NodeRef<'_, T> {
         NodeRef::new(self.node_id, self.tree)
     }
 
@@ -434,6 +657,8 @@ impl<'a, T> NodeMut<'a, T> {
                 .set_prev_siblings_next_sibling(node_id, Some(node_id));
             self.tree
                 .set_next_siblings_prev_sibling(node_id, Some(node_id));
+            self.tree.notify_move(node_id);
+            self.tree.notify_move(next_id);
             true
         } else {
             false
@@ -530,6 +755,8 @@ impl<'a, T> NodeMut<'a, T> {
                 .set_prev_siblings_next_sibling(node_id, Some(node_id));
             self.tree
                 .set_next_siblings_prev_sibling(node_id, Some(node_id));
+            self.tree.notify_move(node_id);
+            self.tree.notify_move(prev_id);
             true
         } else {
             false
@@ -611,6 +838,7 @@ impl<'a, T> NodeMut<'a, T> {
                 self.tree.set_next_siblings_prev_sibling(node_id, prev_id);
                 self.tree.set_prev_sibling(node_id, Some(last_id));
                 self.tree.set_next_sibling(node_id, None);
+                self.tree.notify_move(node_id);
                 true
             } else {
                 false
@@ -694,6 +922,7 @@ impl<'a, T> NodeMut<'a, T> {
                 self.tree.set_next_siblings_prev_sibling(node_id, prev_id);
                 self.tree.set_next_sibling(node_id, Some(first_id));
                 self.tree.set_prev_sibling(node_id, None);
+                self.tree.notify_move(node_id);
                 true
             } else {
                 false
@@ -703,9 +932,439 @@ impl<'a, T> NodeMut<'a, T> {
         }
     }
 
+    /// Moves this node to the sibling position `index`, counted from the first sibling (at 0)
+    /// once it has moved.
+    ///
+    /// Returns false if the node was already at `index`, has no parent, or if `index` isn't less
+    /// than the number of siblings (the node included).
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = {
+    ///     let mut root = tree.root_mut().expect("root doesn't exist?");
+    ///     let two_id = root.append(2).node_id();
+    ///     root.append(3);
+    ///     root.append(4);
+    ///     two_id
+    /// };
+    /// assert!(tree.get_mut(two_id).unwrap().make_nth_sibling(1));
+    /// assert_eq!(
+    ///     tree.root().unwrap().children().map(|child_ref| *child_ref.data())
+    ///         .collect::<Vec<i32>>(),
+    ///     vec![3, 2, 4]);
+    /// assert!(tree.get_mut(two_id).unwrap().make_nth_sibling(2));
+    /// assert_eq!(
+    ///     tree.root().unwrap().children().map(|child_ref| *child_ref.data())
+    ///         .collect::<Vec<i32>>(),
+    ///     vec![3, 4, 2]);
+    /// assert!(!tree.get_mut(two_id).unwrap().make_nth_sibling(2));
+    /// assert!(!tree.get_mut(two_id).unwrap().make_nth_sibling(3));
+    /// assert_eq!(
+    ///     tree.root().unwrap().children().map(|child_ref| *child_ref.data())
+    ///         .collect::<Vec<i32>>(),
+    ///     vec![3, 4, 2]);
+    /// ```
+    pub fn make_nth_sibling(&mut self, index: usize) -> bool {
+        let node_id = self.node_id;
+        let parent_id = match self.tree.get_node_relatives(node_id).parent {
+            Some(parent_id) => parent_id,
+            None => return false,
+        };
+        let mut parent = NodeMut::new(parent_id, self.tree);
+        let mut siblings = parent.child_ids();
+        let position = siblings
+            .iter()
+            .position(|&sibling_id| sibling_id == node_id)
+            .expect("node must be among its parent's children");
+        if index >= siblings.len() || index == position {
+            return false;
+        }
+        siblings.remove(position);
+        siblings.insert(index, node_id);
+        parent.relink_children(&siblings);
+        self.tree.notify_move(node_id);
+        true
+    }
+
+    ///
+    /// Sorts this `Node`'s children by the key `f` computes for each of them.  `f` receives a
+    /// `NodeRef` to the child, so the key can depend on the child's whole subtree, and is called
+    /// exactly once per child rather than once per comparison.  The sort is stable.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// // folders holding files of the given sizes
+    /// let mut tree = TreeBuilder::new().with_root(("/", 0)).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let mut docs = root.append(("docs", 0));
+    /// docs.append(("a.md", 30));
+    /// docs.append(("b.md", 30));
+    /// root.append(("src", 0)).append(("lib.rs", 100));
+    /// root.append(("empty", 0));
+    ///
+    /// // biggest folders first
+    /// root.sort_children_by_cached_key(|folder| {
+    ///     let size: u32 = folder.traverse_pre_order().map(|node| node.data().1).sum();
+    ///     std::cmp::Reverse(size)
+    /// });
+    ///
+    /// let names: Vec<&str> = root.as_ref().children().map(|child| child.data().0).collect();
+    /// assert_eq!(names, vec!["src", "docs", "empty"]);
+    /// ```
+    ///
+    pub fn sort_children_by_cached_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(NodeRef<'_, T>) -> K,
+    {
+        let old_order = self.child_ids();
+        if old_order.len() < 2 {
+            return;
+        }
+        let mut children: Vec<(K, NodeId)> = old_order
+            .iter()
+            .map(|&node_id| (f(NodeRef::new(node_id, self.tree)), node_id))
+            .collect();
+        children.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let new_order: Vec<NodeId> = children.iter().map(|&(_, node_id)| node_id).collect();
+        self.relink_children(&new_order);
+
+        // every child from the first one that moved on gets a move, in its final order, so
+        // replaying the moves one after another ends up in the same order
+        let unmoved = new_order
+            .iter()
+            .zip(&old_order)
+            .take_while(|(node_id, old_id)| node_id == old_id)
+            .count();
+        for &node_id in &new_order[unmoved..] {
+            self.tree.notify_move(node_id);
+        }
+    }
+
+    ///
+    /// Groups this `Node`'s children into buckets of `size` consecutive children (the last one
+    /// may hold fewer): a new `Node` is inserted for each bucket, holding the data `make_bucket`
+    /// returns for its index, and the children are moved below it in order.  Returns the
+    /// `NodeId`s of the buckets, which are then this `Node`'s only children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(String::from("list")).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// for n in 0..5 {
+    ///     root.append(n.to_string());
+    /// }
+    ///
+    /// let pages = root.chunk_children(2, |page| format!("page {}", page));
+    /// assert_eq!(pages.len(), 3);
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// \"list\"
+    /// ├── \"page 0\"
+    /// │   ├── \"0\"
+    /// │   └── \"1\"
+    /// ├── \"page 1\"
+    /// │   ├── \"2\"
+    /// │   └── \"3\"
+    /// └── \"page 2\"
+    ///     └── \"4\"
+    /// ");
+    /// ```
+    ///
+    pub fn chunk_children<F>(&mut self, size: usize, mut make_bucket: F) -> Vec<NodeId>
+    where
+        F: FnMut(usize) -> T,
+    {
+        assert!(size != 0, "chunk size must be non-zero");
+        let children = self.child_ids();
+        let mut buckets = Vec::with_capacity(children.len().div_ceil(size));
+        for (index, chunk) in children.chunks(size).enumerate() {
+            let bucket_id = self.tree.core_tree.insert(make_bucket(index));
+            self.tree
+                .link_before(bucket_id, self.node_id, Some(chunk[0]));
+            self.tree.notify_insert(bucket_id);
+            for &node_id in chunk {
+                self.tree.unlink(node_id);
+                self.tree.link_before(node_id, bucket_id, None);
+                self.tree.notify_move(node_id);
+            }
+            buckets.push(bucket_id);
+        }
+        buckets
+    }
+
+    ///
+    /// Rotates this `Node`'s children `n` places to the left, so the first `n` children move to
+    /// the end, in order.  `n` wraps around the number of children, and a `Node` without children
+    /// is left as it is.  Takes time linear in the number of children.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// for n in 1..=5 {
+    ///     root.append(n);
+    /// }
+    ///
+    /// root.rotate_children_left(2);
+    /// let children: Vec<i32> = root.as_ref().children().map(|child| *child.data()).collect();
+    /// assert_eq!(children, vec![3, 4, 5, 1, 2]);
+    ///
+    /// root.rotate_children_left(7);
+    /// let children: Vec<i32> = root.as_ref().children().map(|child| *child.data()).collect();
+    /// assert_eq!(children, vec![5, 1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn rotate_children_left(&mut self, n: usize) {
+        self.rotate_children(|children| {
+            let mid = n % children.len();
+            children.rotate_left(mid);
+        });
+    }
+
+    ///
+    /// Rotates this `Node`'s children `n` places to the right, so the last `n` children move to
+    /// the front, in order.  `n` wraps around the number of children, and a `Node` without
+    /// children is left as it is.  Takes time linear in the number of children.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// for n in 1..=5 {
+    ///     root.append(n);
+    /// }
+    ///
+    /// root.rotate_children_right(2);
+    /// let children: Vec<i32> = root.as_ref().children().map(|child| *child.data()).collect();
+    /// assert_eq!(children, vec![4, 5, 1, 2, 3]);
+    /// ```
+    ///
+    pub fn rotate_children_right(&mut self, n: usize) {
+        self.rotate_children(|children| {
+            let k = n % children.len();
+            children.rotate_right(k);
+        });
+    }
+
+    fn rotate_children<F: FnOnce(&mut [NodeId])>(&mut self, rotate: F) {
+        let mut children = self.child_ids();
+        if children.len() < 2 {
+            return;
+        }
+        let first_id = children[0];
+        rotate(&mut children);
+        if children[0] == first_id {
+            return;
+        }
+        self.relink_children(&children);
+        for &node_id in &children {
+            self.tree.notify_move(node_id);
+        }
+    }
+
+    fn child_ids(&self) -> Vec<NodeId> {
+        let first_child_id = self.tree.get_node_relatives(self.node_id).first_child;
+        std::iter::successors(first_child_id, |&node_id| {
+            self.tree.get_node_relatives(node_id).next_sibling
+        })
+        .collect()
+    }
+
+    /// Links this `Node`'s children (all of them, and nothing else) up in the given order.
+    /// Doesn't notify observers.
+    fn relink_children(&mut self, children: &[NodeId]) {
+        let mut prev_id = None;
+        for &node_id in children {
+            self.tree.set_prev_sibling(node_id, prev_id);
+            if let Some(prev_id) = prev_id {
+                self.tree.set_next_sibling(prev_id, Some(node_id));
+            }
+            prev_id = Some(node_id);
+        }
+        if let Some(last_id) = prev_id {
+            self.tree.set_next_sibling(last_id, None);
+        }
+        self.tree
+            .set_first_child(self.node_id, children.first().copied());
+        self.tree.set_last_child(self.node_id, prev_id);
+    }
+
+    ///
+    /// Splits this `Node`'s children in two: a new `Node` holding `new_data` is inserted as this
+    /// `Node`'s next sibling, and the children from `index` on are moved below it (keeping their
+    /// order and sub-trees).  Returns the `NodeId` of the new `Node`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Node` has no parent (the root or an orphan can't have a sibling), or if
+    /// `index` is greater than the number of children.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("doc").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let mut paragraph = root.append("p1");
+    /// paragraph.append("a");
+    /// paragraph.append("b");
+    /// paragraph.append("c");
+    /// let paragraph_id = paragraph.node_id();
+    /// root.append("p2");
+    ///
+    /// let new_id = tree.get_mut(paragraph_id).unwrap().split_children_at(1, "p1'");
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// \"doc\"
+    /// ├── \"p1\"
+    /// │   └── \"a\"
+    /// ├── \"p1'\"
+    /// │   ├── \"b\"
+    /// │   └── \"c\"
+    /// └── \"p2\"
+    /// ");
+    /// assert_eq!(tree.get(new_id).unwrap().data(), &"p1'");
+    /// ```
+    ///
+    pub fn split_children_at(&mut self, index: usize, new_data: T) -> NodeId {
+        let relatives = self.tree.get_node_relatives(self.node_id);
+        let parent_id = relatives
+            .parent
+            .expect("only a Node with a parent can be split");
+        let moved: Vec<NodeId> = std::iter::successors(relatives.first_child, |&node_id| {
+            self.tree.get_node_relatives(node_id).next_sibling
+        })
+        .skip(index)
+        .collect();
+        let prev_id = match (index, moved.first()) {
+            (0, _) => None,
+            (_, Some(&first_id)) => self.tree.get_node_prev_sibling_id(first_id),
+            (_, None) => {
+                let child_count = self.as_ref().children().count();
+                assert!(
+                    index <= child_count,
+                    "split index (is {}) should be <= number of children (is {})",
+                    index,
+                    child_count
+                );
+                relatives.last_child
+            }
+        };
+
+        let new_id = self.tree.core_tree.insert(new_data);
+        self.tree
+            .link_before(new_id, parent_id, relatives.next_sibling);
+        self.tree.notify_insert(new_id);
+
+        if let (Some(&first_id), Some(&last_id)) = (moved.first(), moved.last()) {
+            match prev_id {
+                Some(prev_id) => self.tree.set_next_sibling(prev_id, None),
+                None => self.tree.set_first_child(self.node_id, None),
+            }
+            self.tree.set_last_child(self.node_id, prev_id);
+            self.tree.set_prev_sibling(first_id, None);
+            self.tree.set_first_child(new_id, Some(first_id));
+            self.tree.set_last_child(new_id, Some(last_id));
+            for &node_id in &moved {
+                self.tree.set_parent(node_id, Some(new_id));
+            }
+            for &node_id in &moved {
+                self.tree.notify_move(node_id);
+            }
+        }
+        new_id
+    }
+
+    ///
+    /// Returns `true` if this `Node` has no children.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?").append(2).node_id();
+    ///
+    /// assert!(tree.get_mut(leaf_id).unwrap().is_leaf());
+    /// assert!(!tree.root_mut().unwrap().is_leaf());
+    /// ```
+    ///
+    pub fn is_leaf(&self) -> bool {
+        self.get_self_as_node().relatives.first_child.is_none()
+    }
+
+    ///
+    /// Returns `true` if this `Node` is the root of the `Tree`.  Orphaned `Node`s have no parent
+    /// but are not the root.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?").append(2).node_id();
+    ///
+    /// assert!(tree.root_mut().unwrap().is_root());
+    /// assert!(!tree.get_mut(leaf_id).unwrap().is_root());
+    /// ```
+    ///
+    pub fn is_root(&self) -> bool {
+        self.tree.root_id() == Some(self.node_id)
+    }
+
+    ///
+    /// Returns `true` if this `Node` has at least one child.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?").append(2).node_id();
+    ///
+    /// assert!(tree.root_mut().unwrap().has_children());
+    /// assert!(!tree.get_mut(leaf_id).unwrap().has_children());
+    /// ```
+    ///
+    pub fn has_children(&self) -> bool {
+        self.get_self_as_node().relatives.first_child.is_some()
+    }
+
+    ///
+    /// Returns `true` if this `Node` has a previous or next sibling.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?").append(2).node_id();
+    ///
+    /// assert!(!tree.get_mut(leaf_id).unwrap().has_siblings());
+    ///
+    /// tree.root_mut().unwrap().append(3);
+    /// assert!(tree.get_mut(leaf_id).unwrap().has_siblings());
+    /// ```
+    ///
+    pub fn has_siblings(&self) -> bool {
+        let relatives = &self.get_self_as_node().relatives;
+        relatives.prev_sibling.is_some() || relatives.next_sibling.is_some()
+    }
+
     fn get_self_as_node(&self) -> &Node<T> {
         if let Some(node) = self.tree.get_node(self.node_id) {
-            &node
+            node
         } else {
             unreachable!()
         }
@@ -717,6 +1376,8 @@ impl<'a, T> NodeMut<'a, T> {
 mod node_mut_tests {
     use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
     use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+    use crate::NodeId;
 
     #[test]
     fn node_id() {
@@ -786,6 +1447,31 @@ mod node_mut_tests {
         assert!(root_mut.last_child().is_none());
     }
 
+    #[test]
+    fn predicates() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        let mut root_mut = tree.root_mut().unwrap();
+        let two_id = root_mut.append(2).node_id();
+        let three_id = root_mut.append(3).node_id();
+        tree.get_mut(two_id).unwrap().append(4);
+
+        let root_mut = tree.root_mut().unwrap();
+        assert!(root_mut.is_root());
+        assert!(root_mut.has_children());
+        assert!(!root_mut.is_leaf());
+        assert!(!root_mut.has_siblings());
+
+        let two = tree.get_mut(two_id).unwrap();
+        assert!(!two.is_root());
+        assert!(two.has_siblings());
+
+        tree.remove(two_id, OrphanChildren);
+        let three = tree.get_mut(three_id).unwrap();
+        assert!(three.is_leaf());
+        assert!(!three.has_siblings());
+    }
+
     #[test]
     fn append_no_children_present() {
         let mut tree = Tree::new();
@@ -1082,6 +1768,291 @@ mod node_mut_tests {
         assert_eq!(new_node_3.data(), &4);
     }
 
+    #[test]
+    fn insert_child_sorted_by_key() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        for n in &[5, 1, 9, 3, 7, 3, 10, 0] {
+            root.insert_child_sorted_by_key(*n, |&n| n);
+        }
+        // equal keys keep insertion order
+        let threes: Vec<_> = root
+            .as_ref()
+            .children()
+            .skip(2)
+            .take(2)
+            .map(|c| c.node_id())
+            .collect();
+        let new_three_id = root.insert_child_sorted_by_key(3, |&n| n).node_id();
+        let all_threes: Vec<_> = root
+            .as_ref()
+            .children()
+            .skip(2)
+            .take(3)
+            .map(|c| c.node_id())
+            .collect();
+        assert_eq!(all_threes, vec![threes[0], threes[1], new_three_id]);
+
+        let root = tree.root().unwrap();
+        let forward: Vec<i32> = root.children().map(|child| *child.data()).collect();
+        assert_eq!(forward, vec![0, 1, 3, 3, 3, 5, 7, 9, 10]);
+
+        let mut backward = Vec::new();
+        let mut node_id = root.last_child().map(|child| child.node_id());
+        while let Some(child_id) = node_id {
+            let child = tree.get(child_id).unwrap();
+            backward.push(*child.data());
+            assert_eq!(child.parent().unwrap().node_id(), root.node_id());
+            node_id = child.prev_sibling().map(|prev| prev.node_id());
+        }
+        backward.reverse();
+        assert_eq!(backward, forward);
+    }
+
+    #[test]
+    fn sort_children_by_cached_key() {
+        let mut tree = Tree::unfold(4, |n| (n, (0..n).collect()));
+        let mut root = tree.root_mut().unwrap();
+        let mut calls = 0;
+        // largest subtree first, which reverses the children
+        root.sort_children_by_cached_key(|child| {
+            calls += 1;
+            std::cmp::Reverse(child.subtree_size())
+        });
+        assert_eq!(calls, 4);
+
+        let root = tree.root().unwrap();
+        let forward: Vec<i32> = root.children().map(|child| *child.data()).collect();
+        assert_eq!(forward, vec![3, 2, 1, 0]);
+        let mut backward: Vec<i32> = Vec::new();
+        let mut node_id = root.last_child().map(|child| child.node_id());
+        while let Some(child_id) = node_id {
+            let child = tree.get(child_id).unwrap();
+            backward.push(*child.data());
+            node_id = child.prev_sibling().map(|prev| prev.node_id());
+        }
+        assert_eq!(backward, vec![0, 1, 2, 3]);
+
+        // stable, and a no-op without children
+        let mut root = tree.root_mut().unwrap();
+        root.sort_children_by_cached_key(|_| 0);
+        let mut only_child = root.last_child().unwrap();
+        only_child.sort_children_by_cached_key(|_| 0);
+        let root = tree.root().unwrap();
+        let unchanged: Vec<i32> = root.children().map(|child| *child.data()).collect();
+        assert_eq!(unchanged, vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn make_nth_sibling() {
+        let mut tree = Tree::unfold(4, |n| (n, (0..n).collect()));
+        let last_id = tree.root().unwrap().last_child().unwrap().node_id();
+        assert!(!tree.root_mut().unwrap().make_nth_sibling(0));
+        assert!(tree.get_mut(last_id).unwrap().make_nth_sibling(0));
+        assert!(tree.get_mut(last_id).unwrap().make_nth_sibling(2));
+
+        let root = tree.root().unwrap();
+        let forward: Vec<i32> = root.children().map(|child| *child.data()).collect();
+        assert_eq!(forward, vec![0, 1, 3, 2]);
+        let mut backward: Vec<i32> = Vec::new();
+        let mut node_id = root.last_child().map(|child| child.node_id());
+        while let Some(child_id) = node_id {
+            let child = tree.get(child_id).unwrap();
+            backward.push(*child.data());
+            node_id = child.prev_sibling().map(|prev| prev.node_id());
+        }
+        assert_eq!(backward, vec![2, 3, 1, 0]);
+    }
+
+    #[test]
+    fn chunk_children() {
+        let mut tree = Tree::unfold(7, |n| (n, (0..n).collect()))
+            .with_depths()
+            .with_subtree_sizes();
+        let root_id = tree.root_id().unwrap();
+        let buckets = tree
+            .root_mut()
+            .unwrap()
+            .chunk_children(3, |index| 100 + index as i32);
+        assert_eq!(buckets.len(), 3);
+
+        let root = tree.root().unwrap();
+        let layout: Vec<(i32, Vec<i32>)> = root
+            .children()
+            .map(|bucket| {
+                assert_eq!(bucket.parent().unwrap().node_id(), root_id);
+                let children = bucket.children().map(|child| *child.data()).collect();
+                (*bucket.data(), children)
+            })
+            .collect();
+        assert_eq!(
+            layout,
+            vec![(100, vec![0, 1, 2]), (101, vec![3, 4, 5]), (102, vec![6])]
+        );
+        for node in root.traverse_pre_order() {
+            assert_eq!(node.depth(), node.ancestors().count());
+            assert_eq!(node.subtree_size(), node.traverse_pre_order().count());
+        }
+
+        // without children there's nothing to chunk
+        let leaf_id = tree
+            .get(buckets[0])
+            .unwrap()
+            .first_child()
+            .unwrap()
+            .node_id();
+        let mut leaf = tree.get_mut(leaf_id).unwrap();
+        assert!(leaf.chunk_children(1, |_| -1).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn chunk_children_of_size_zero() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        tree.root_mut().unwrap().chunk_children(0, |_| 1);
+    }
+
+    #[test]
+    fn rotate_children() {
+        let mut tree = Tree::unfold(4, |n| (n, (0..n).collect()));
+        let mut root = tree.root_mut().unwrap();
+        root.rotate_children_left(1);
+        root.rotate_children_right(6);
+        let root = tree.root().unwrap();
+        let forward: Vec<i32> = root.children().map(|child| *child.data()).collect();
+        assert_eq!(forward, vec![3, 0, 1, 2]);
+        let mut backward: Vec<i32> = Vec::new();
+        let mut node_id = root.last_child().map(|child| child.node_id());
+        while let Some(child_id) = node_id {
+            let child = tree.get(child_id).unwrap();
+            backward.push(*child.data());
+            node_id = child.prev_sibling().map(|prev| prev.node_id());
+        }
+        assert_eq!(backward, vec![2, 1, 0, 3]);
+
+        // full turns and leaves are no-ops
+        let mut root = tree.root_mut().unwrap();
+        root.rotate_children_left(8);
+        root.rotate_children_right(0);
+        root.first_child().unwrap().rotate_children_left(1);
+        let root = tree.root().unwrap();
+        let unchanged: Vec<i32> = root.children().map(|child| *child.data()).collect();
+        assert_eq!(unchanged, vec![3, 0, 1, 2]);
+    }
+
+    #[test]
+    fn split_children_at() {
+        fn children_both_ways(tree: &Tree<i32>, node_id: NodeId) -> Vec<i32> {
+            let node = tree.get(node_id).unwrap();
+            let forward: Vec<i32> = node.children().map(|child| *child.data()).collect();
+            let mut backward = Vec::new();
+            let mut child_id = node.last_child().map(|child| child.node_id());
+            while let Some(id) = child_id {
+                let child = tree.get(id).unwrap();
+                assert_eq!(child.parent().unwrap().node_id(), node_id);
+                backward.push(*child.data());
+                child_id = child.prev_sibling().map(|prev| prev.node_id());
+            }
+            backward.reverse();
+            assert_eq!(forward, backward);
+            forward
+        }
+
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        for n in 10..14 {
+            one.append(n);
+        }
+        let one_id = one.node_id();
+        root.append(2);
+
+        let two_id = tree.get_mut(one_id).unwrap().split_children_at(2, 20);
+        assert_eq!(children_both_ways(&tree, root_id), vec![1, 20, 2]);
+        assert_eq!(children_both_ways(&tree, one_id), vec![10, 11]);
+        assert_eq!(children_both_ways(&tree, two_id), vec![12, 13]);
+
+        // everything, then nothing, moves to the new sibling
+        let three_id = tree.get_mut(one_id).unwrap().split_children_at(0, 30);
+        assert_eq!(children_both_ways(&tree, one_id), Vec::<i32>::new());
+        assert_eq!(children_both_ways(&tree, three_id), vec![10, 11]);
+        let four_id = tree.get_mut(three_id).unwrap().split_children_at(2, 40);
+        assert_eq!(children_both_ways(&tree, three_id), vec![10, 11]);
+        assert_eq!(children_both_ways(&tree, four_id), Vec::<i32>::new());
+        assert_eq!(children_both_ways(&tree, root_id), vec![1, 30, 40, 20, 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_children_at_past_the_end() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        root.append(1).append(10);
+        root.first_child().unwrap().split_children_at(2, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_children_of_the_root() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        tree.root_mut().unwrap().split_children_at(0, 1);
+    }
+
+    #[test]
+    fn for_each_child_mut() {
+        let mut tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let mut root = tree.root_mut().unwrap();
+        root.for_each_child_mut(|data| *data += 10);
+
+        let mut visited = Vec::new();
+        root.for_each_child_node_mut(|mut child| {
+            visited.push(*child.data());
+            child.for_each_child_mut(|data| *data = -1);
+        });
+        assert_eq!(visited, vec![10, 11, 12]);
+
+        let root = tree.root().unwrap();
+        let data: Vec<i32> = root.traverse_pre_order().map(|n| *n.data()).collect();
+        assert_eq!(data, vec![3, 10, 11, -1, 12, -1, -1, 0]);
+
+        // no children
+        let mut root = tree.root_mut().unwrap();
+        let mut leaf = root.first_child().unwrap();
+        leaf.for_each_child_mut(|_| panic!("leaf has no children"));
+    }
+
+    #[test]
+    fn for_each_child_node_mut_removing_children() {
+        let mut tree = Tree::unfold(4, |n| (n, (0..n).collect()));
+        let mut root = tree.root_mut().unwrap();
+        let mut visited = Vec::new();
+        root.for_each_child_node_mut(|mut child| {
+            visited.push(*child.data());
+            if *child.data() % 2 == 0 {
+                child.parent().unwrap().remove_first(DropChildren);
+            }
+        });
+        // 0 removes itself, 2 removes 1
+        assert_eq!(visited, vec![0, 1, 2, 3]);
+        let children: Vec<i32> = tree.root().unwrap().children().map(|n| *n.data()).collect();
+        assert_eq!(children, vec![2, 3]);
+
+        // removing the visited child and the one after it ends the visit
+        let mut tree = Tree::unfold(4, |n| (n, (0..n).collect()));
+        let mut root = tree.root_mut().unwrap();
+        let mut visited = Vec::new();
+        root.for_each_child_node_mut(|mut child| {
+            visited.push(*child.data());
+            let mut parent = child.parent().unwrap();
+            parent.remove_first(DropChildren);
+            parent.remove_first(DropChildren);
+        });
+        assert_eq!(visited, vec![0]);
+        let children: Vec<i32> = tree.root().unwrap().children().map(|n| *n.data()).collect();
+        assert_eq!(children, vec![2, 3]);
+    }
+
     #[test]
     fn remove_first_no_children_present() {
         let mut tree = Tree::new();
@@ -1380,4 +2351,30 @@ mod node_mut_tests {
         let three = three.unwrap();
         assert_eq!(three.relatives.parent, None);
     }
+
+    #[test]
+    fn append_with_builds_lazily() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let mut calls = 0;
+
+        let mut root = tree.get_mut(root_id).unwrap();
+        let two = root
+            .append_with(|| {
+                calls += 1;
+                2
+            })
+            .into_data();
+        *two *= 10;
+        root.append_with(|| 3);
+
+        assert_eq!(calls, 1);
+        let children: Vec<i32> = tree
+            .get(root_id)
+            .unwrap()
+            .children()
+            .map(|child| *child.data())
+            .collect();
+        assert_eq!(children, vec![20, 3]);
+    }
 }
@@ -1,4 +1,5 @@
 use crate::iter::Ancestors;
+use crate::iter::AncestorsOrSelf;
 use crate::iter::LevelOrder;
 use crate::iter::NextSiblings;
 use crate::iter::PostOrder;
@@ -6,6 +7,7 @@ use crate::iter::PreOrder;
 use crate::node::Node;
 use crate::tree::Tree;
 use crate::NodeId;
+use std::fmt;
 
 ///
 /// An immutable reference to a given `Node`'s data and its relatives.
@@ -15,11 +17,32 @@ pub struct NodeRef<'a, T> {
     tree: &'a Tree<T>,
 }
 
+impl<'a, T> Clone for NodeRef<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for NodeRef<'a, T> {}
+
+impl<'a, T: fmt::Debug> fmt::Debug for NodeRef<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeRef")
+            .field("node_id", &self.node_id)
+            .field("data", self.data())
+            .finish()
+    }
+}
+
 impl<'a, T> NodeRef<'a, T> {
-    pub(crate) fn new(node_id: NodeId, tree: &'a Tree<T>) -> NodeRef<T> {
+    pub(crate) fn new(node_id: NodeId, tree: &'a Tree<T>) -> NodeRef<'a, T> {
         NodeRef { node_id, tree }
     }
 
+    pub(crate) fn tree(&self) -> &'a Tree<T> {
+        self.tree
+    }
+
     ///
     /// Returns the `NodeId` that identifies this `Node` in the tree.
     ///
@@ -53,11 +76,7 @@ impl<'a, T> NodeRef<'a, T> {
     /// ```
     ///
     pub fn data(&self) -> &'a T {
-        if let Some(node) = self.tree.get_node(self.node_id) {
-            &node.data
-        } else {
-            unreachable!()
-        }
+        &self.get_self_as_node().data
     }
 
     ///
@@ -74,7 +93,7 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.parent().is_none());
     /// ```
     ///
-    pub fn parent(&self) -> Option<NodeRef<T>> {
+    pub fn parent(&self) -> Option<NodeRef<'_, T>> {
         self.get_self_as_node()
             .relatives
             .parent
@@ -95,7 +114,7 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.prev_sibling().is_none());
     /// ```
     ///
-    pub fn prev_sibling(&self) -> Option<NodeRef<T>> {
+    pub fn prev_sibling(&self) -> Option<NodeRef<'_, T>> {
         self.get_self_as_node()
             .relatives
             .prev_sibling
@@ -116,7 +135,7 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.next_sibling().is_none());
     /// ```
     ///
-    pub fn next_sibling(&self) -> Option<NodeRef<T>> {
+    pub fn next_sibling(&self) -> Option<NodeRef<'_, T>> {
         self.get_self_as_node()
             .relatives
             .next_sibling
@@ -137,7 +156,7 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.first_child().is_none());
     /// ```
     ///
-    pub fn first_child(&self) -> Option<NodeRef<T>> {
+    pub fn first_child(&self) -> Option<NodeRef<'_, T>> {
         self.get_self_as_node()
             .relatives
             .first_child
@@ -158,13 +177,35 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.last_child().is_none());
     /// ```
     ///
-    pub fn last_child(&self) -> Option<NodeRef<T>> {
+    pub fn last_child(&self) -> Option<NodeRef<'_, T>> {
         self.get_self_as_node()
             .relatives
             .last_child
             .map(|id| NodeRef::new(id, self.tree))
     }
 
+    ///
+    /// Returns the number of children this `Node` has.  The count is kept up to date as children
+    /// are added and removed, so this doesn't iterate over them.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append(2).append(3);
+    /// root.append(4);
+    ///
+    /// let root = tree.root().expect("root doesn't exist?");
+    /// assert_eq!(root.child_count(), 2);
+    /// assert_eq!(root.first_child().unwrap().child_count(), 1);
+    /// assert_eq!(root.last_child().unwrap().child_count(), 0);
+    /// ```
+    ///
+    pub fn child_count(&self) -> usize {
+        self.get_self_as_node().relatives.child_count
+    }
+
     ///
     /// Returns a `Iterator` over the given `Node`'s ancestors.  Each call to `Iterator::next()`
     /// returns a `NodeRef` pointing to the current `Node`'s parent.
@@ -192,6 +233,61 @@ impl<'a, T> NodeRef<'a, T> {
         Ancestors::new(Some(self.node_id), self.tree)
     }
 
+    ///
+    /// Returns an `Iterator` over this `Node` followed by each of its ancestors, ending at the
+    /// root.  Each call to `Iterator::next()` returns a `NodeRef` pointing to the next `Node`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    ///
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?")
+    ///     .append(2)
+    ///     .append(3)
+    ///     .node_id();
+    ///
+    /// let leaf = tree.get(leaf_id).unwrap();
+    ///
+    /// let path: Vec<i32> = leaf.ancestors_or_self().map(|node| *node.data()).collect();
+    /// assert_eq!(path, vec![3, 2, 1]);
+    /// ```
+    ///
+    pub fn ancestors_or_self(&self) -> AncestorsOrSelf<'a, T> {
+        AncestorsOrSelf::new(Some(self.node_id), self.tree)
+    }
+
+    ///
+    /// Returns the depth of this `Node`, i.e. the number of ancestors it has; the root (and any
+    /// orphan) is at depth 0.
+    ///
+    /// If the `Tree` keeps depths (see `Tree::with_depths`) this is a lookup; otherwise the
+    /// `Node`'s ancestors are counted.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?")
+    ///     .append(2)
+    ///     .append(3)
+    ///     .node_id();
+    ///
+    /// assert_eq!(tree.root().unwrap().depth(), 0);
+    /// assert_eq!(tree.get(leaf_id).unwrap().depth(), 2);
+    /// ```
+    ///
+    pub fn depth(&self) -> usize {
+        match self
+            .tree
+            .depth_index()
+            .and_then(|depths| depths.get(self.node_id))
+        {
+            Some(depth) => depth,
+            None => self.ancestors().count(),
+        }
+    }
+
     ///
     /// Returns a `Iterator` over the given `Node`'s children.  Each call to `Iterator::next()`
     /// returns a `NodeRef` pointing to the next child of the given `Node`.
@@ -276,9 +372,140 @@ impl<'a, T> NodeRef<'a, T> {
         LevelOrder::new(self, self.tree)
     }
 
-    fn get_self_as_node(&self) -> &Node<T> {
+    ///
+    /// Returns the number of `Node`s in the sub-tree rooted at this `Node` (including this
+    /// `Node` itself).
+    ///
+    /// If the `Tree` keeps sub-tree sizes (see `Tree::with_subtree_sizes`) this is a lookup;
+    /// otherwise the sub-tree is walked through its links without recursion or any extra
+    /// allocation.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let one_id = root.append(1).node_id();
+    /// root.append(2);
+    /// tree.get_mut(one_id).unwrap().append(3);
+    ///
+    /// assert_eq!(tree.root().unwrap().subtree_size(), 4);
+    /// assert_eq!(tree.get(one_id).unwrap().subtree_size(), 2);
+    /// ```
+    ///
+    pub fn subtree_size(&self) -> usize {
+        if let Some(size) = self
+            .tree
+            .subtree_sizes()
+            .and_then(|sizes| sizes.get(self.node_id))
+        {
+            return size;
+        }
+
+        let mut size = 1;
+        let mut current = self.node_id;
+
+        loop {
+            let relatives = self.tree.get_node_relatives(current);
+            if let Some(first_child) = relatives.first_child {
+                current = first_child;
+                size += 1;
+                continue;
+            }
+
+            // climb until we find an unvisited next sibling, without leaving the sub-tree
+            loop {
+                if current == self.node_id {
+                    return size;
+                }
+                let relatives = self.tree.get_node_relatives(current);
+                if let Some(next_sibling) = relatives.next_sibling {
+                    current = next_sibling;
+                    size += 1;
+                    break;
+                }
+                current = relatives
+                    .parent
+                    .expect("non-root sub-tree node must have a parent");
+            }
+        }
+    }
+
+    ///
+    /// Returns `true` if this `Node` has no children.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?").append(2).node_id();
+    ///
+    /// assert!(tree.get(leaf_id).unwrap().is_leaf());
+    /// assert!(!tree.root().unwrap().is_leaf());
+    /// ```
+    ///
+    pub fn is_leaf(&self) -> bool {
+        self.get_self_as_node().relatives.first_child.is_none()
+    }
+
+    ///
+    /// Returns `true` if this `Node` is the root of the `Tree`.  Orphaned `Node`s have no parent
+    /// but are not the root.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?").append(2).node_id();
+    ///
+    /// assert!(tree.root().unwrap().is_root());
+    /// assert!(!tree.get(leaf_id).unwrap().is_root());
+    /// ```
+    ///
+    pub fn is_root(&self) -> bool {
+        self.tree.root_id() == Some(self.node_id)
+    }
+
+    ///
+    /// Returns `true` if this `Node` has at least one child.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?").append(2).node_id();
+    ///
+    /// assert!(tree.root().unwrap().has_children());
+    /// assert!(!tree.get(leaf_id).unwrap().has_children());
+    /// ```
+    ///
+    pub fn has_children(&self) -> bool {
+        self.get_self_as_node().relatives.first_child.is_some()
+    }
+
+    ///
+    /// Returns `true` if this `Node` has a previous or next sibling.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let leaf_id = tree.root_mut().expect("root doesn't exist?").append(2).node_id();
+    ///
+    /// assert!(!tree.get(leaf_id).unwrap().has_siblings());
+    ///
+    /// tree.root_mut().unwrap().append(3);
+    /// assert!(tree.get(leaf_id).unwrap().has_siblings());
+    /// ```
+    ///
+    pub fn has_siblings(&self) -> bool {
+        let relatives = &self.get_self_as_node().relatives;
+        relatives.prev_sibling.is_some() || relatives.next_sibling.is_some()
+    }
+
+    fn get_self_as_node(&self) -> &'a Node<T> {
         if let Some(node) = self.tree.get_node(self.node_id) {
-            &node
+            node
         } else {
             unreachable!()
         }
@@ -288,7 +515,11 @@ impl<'a, T> NodeRef<'a, T> {
 #[cfg_attr(tarpaulin, skip)]
 #[cfg(test)]
 mod node_ref_tests {
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::node::NodeRef;
+    use crate::reparent::InsertPosition;
     use crate::tree::Tree;
+    use crate::NodeId;
 
     #[test]
     fn data() {
@@ -360,6 +591,25 @@ mod node_ref_tests {
         }
     }
 
+    #[test]
+    fn ancestors_or_self() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+
+        let mut root_mut = tree.root_mut().expect("root doesn't exist");
+        let node_id = root_mut.append(2).append(3).node_id();
+
+        let bottom_node = tree.get(node_id).unwrap();
+        let values: Vec<i32> = bottom_node
+            .ancestors_or_self()
+            .map(|node_ref| *node_ref.data())
+            .collect();
+        assert_eq!(values, vec![3, 2, 1]);
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.ancestors_or_self().count(), 1);
+    }
+
     #[test]
     fn children() {
         let mut tree = Tree::new();
@@ -378,4 +628,290 @@ mod node_ref_tests {
             assert_eq!(node_ref.data(), &values[i]);
         }
     }
+
+    #[test]
+    fn subtree_size() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let mut two = root.append(2);
+        let three_id = two.append(3).node_id();
+        two.append(4).append(5);
+        let six_id = root.append(6).node_id();
+
+        assert_eq!(tree.root().unwrap().subtree_size(), 6);
+        assert_eq!(tree.get(three_id).unwrap().subtree_size(), 1);
+        assert_eq!(tree.get(six_id).unwrap().subtree_size(), 1);
+
+        let root = tree.root().unwrap();
+        let two = root.first_child().unwrap();
+        assert_eq!(two.subtree_size(), 4);
+        assert_eq!(two.subtree_size(), two.traverse_pre_order().count());
+    }
+
+    #[test]
+    fn predicates() {
+        use crate::behaviors::RemoveBehavior::OrphanChildren;
+
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append(2).node_id();
+        let four_id = tree.get_mut(two_id).unwrap().append(4).node_id();
+
+        let root = tree.root().unwrap();
+        assert!(root.is_root());
+        assert!(root.has_children());
+        assert!(!root.is_leaf());
+        assert!(!root.has_siblings());
+
+        let four = tree.get(four_id).unwrap();
+        assert!(four.is_leaf());
+        assert!(!four.is_root());
+
+        tree.remove(two_id, OrphanChildren);
+        let orphan = tree.get(four_id).unwrap();
+        assert!(orphan.parent().is_none());
+        assert!(!orphan.is_root());
+    }
+
+    #[test]
+    fn traverse_with_max_depth() {
+        let tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let root = tree.root().unwrap();
+        let depth = |node: &NodeRef<i32>| node.ancestors().count();
+
+        for max_depth in 0..4 {
+            let expected: Vec<NodeId> = root
+                .traverse_pre_order()
+                .filter(|node| depth(node) <= max_depth)
+                .map(|node| node.node_id())
+                .collect();
+            let actual: Vec<NodeId> = root
+                .traverse_pre_order()
+                .with_max_depth(max_depth)
+                .map(|node| node.node_id())
+                .collect();
+            assert_eq!(actual, expected);
+
+            let expected: Vec<NodeId> = root
+                .traverse_post_order()
+                .filter(|node| depth(node) <= max_depth)
+                .map(|node| node.node_id())
+                .collect();
+            let actual: Vec<NodeId> = root
+                .traverse_post_order()
+                .with_max_depth(max_depth)
+                .map(|node| node.node_id())
+                .collect();
+            assert_eq!(actual, expected);
+
+            let expected: Vec<NodeId> = root
+                .traverse_level_order()
+                .filter(|node| depth(node) <= max_depth)
+                .map(|node| node.node_id())
+                .collect();
+            let actual: Vec<NodeId> = root
+                .traverse_level_order()
+                .with_max_depth(max_depth)
+                .map(|node| node.node_id())
+                .collect();
+            assert_eq!(actual, expected);
+        }
+
+        // the subtree 2 -> [0, 1 -> [0]]
+        let two = root.last_child().unwrap();
+        assert_eq!(two.traverse_pre_order().with_max_depth(0).count(), 1);
+        assert_eq!(two.traverse_post_order().with_max_depth(1).count(), 3);
+        assert_eq!(two.traverse_level_order().with_max_depth(5).count(), 4);
+    }
+
+    #[test]
+    fn traverse_with_prune() {
+        let tree = Tree::unfold(4, |n| (n, (0..n).collect()));
+        let root = tree.root().unwrap();
+        // prunes the subtrees rooted at 2s
+        let kept = |node: &NodeRef<i32>| node.ancestors_or_self().all(|node| *node.data() != 2);
+
+        let expected: Vec<NodeId> = root
+            .traverse_pre_order()
+            .filter(kept)
+            .map(|node| node.node_id())
+            .collect();
+        let mut calls = 0;
+        let actual: Vec<NodeId> = root
+            .traverse_pre_order()
+            .prune(|node| {
+                calls += 1;
+                *node.data() != 2
+            })
+            .map(|node| node.node_id())
+            .collect();
+        assert_eq!(actual, expected);
+        // every kept node, plus the root of every pruned subtree
+        assert_eq!(calls, expected.len() + 2);
+
+        let expected: Vec<NodeId> = root
+            .traverse_post_order()
+            .filter(kept)
+            .map(|node| node.node_id())
+            .collect();
+        let mut calls = 0;
+        let actual: Vec<NodeId> = root
+            .traverse_post_order()
+            .prune(|node| {
+                calls += 1;
+                *node.data() != 2
+            })
+            .map(|node| node.node_id())
+            .collect();
+        assert_eq!(actual, expected);
+        assert_eq!(calls, expected.len() + 2);
+
+        let expected: Vec<NodeId> = root
+            .traverse_level_order()
+            .filter(kept)
+            .map(|node| node.node_id())
+            .collect();
+        let mut calls = 0;
+        let actual: Vec<NodeId> = root
+            .traverse_level_order()
+            .prune(|node| {
+                calls += 1;
+                *node.data() != 2
+            })
+            .map(|node| node.node_id())
+            .collect();
+        assert_eq!(actual, expected);
+        assert_eq!(calls, expected.len() + 2);
+
+        let actual: Vec<NodeId> = root
+            .traverse_pre_order()
+            .with_max_depth(1)
+            .prune(|node| *node.data() != 2)
+            .map(|node| node.node_id())
+            .collect();
+        assert_eq!(actual.len(), 4);
+
+        assert_eq!(root.traverse_pre_order().prune(|_| false).count(), 0);
+        assert_eq!(root.traverse_post_order().prune(|_| false).count(), 0);
+        assert_eq!(root.traverse_level_order().prune(|_| false).count(), 0);
+    }
+
+    #[test]
+    fn iterators_clone_and_debug() {
+        fn ids<'a>(iter: impl Iterator<Item = NodeRef<'a, i32>>) -> Vec<NodeId> {
+            iter.map(|node| node.node_id()).collect()
+        }
+
+        let tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let root = tree.root().unwrap();
+        let two = root.last_child().unwrap();
+        let leaf = two.last_child().unwrap();
+
+        let mut pre_order = root.traverse_pre_order().prune(|node| *node.data() != 1);
+        pre_order.next();
+        pre_order.next();
+        assert_eq!(ids(pre_order.clone()), ids(pre_order));
+
+        let mut post_order = root.traverse_post_order().with_max_depth(2);
+        post_order.next();
+        assert_eq!(ids(post_order.clone()), ids(post_order));
+
+        let mut level_order = root.traverse_level_order();
+        level_order.next();
+        level_order.next();
+        assert_eq!(ids(level_order.clone()), ids(level_order));
+
+        let ancestors = leaf.ancestors();
+        assert_eq!(ids(ancestors.clone()), ids(ancestors));
+        let ancestors_or_self = leaf.ancestors_or_self();
+        assert_eq!(ids(ancestors_or_self.clone()), ids(ancestors_or_self));
+        let mut children = root.children();
+        children.next();
+        assert_eq!(ids(children.clone()), ids(children));
+        let mut all = tree.iter_all_roots_first();
+        all.next();
+        assert_eq!(ids(all.clone()), ids(all));
+
+        assert!(format!("{:?}", root.children()).starts_with("NextSiblings { node_id: "));
+        assert!(format!("{:?}", root.traverse_pre_order()).starts_with("PreOrder { start: "));
+        assert!(format!("{:?}", root.traverse_post_order()).starts_with("PostOrder { start: "));
+        assert!(format!("{:?}", root.traverse_level_order()).starts_with("LevelOrder { queue: "));
+        assert!(format!("{:?}", leaf.ancestors()).starts_with("Ancestors { node_id: "));
+    }
+
+    #[test]
+    fn iterators_stay_exhausted() {
+        fn assert_fused<'a, I: std::iter::FusedIterator<Item = NodeRef<'a, i32>>>(mut iter: I) {
+            while iter.next().is_some() {}
+            assert!(iter.next().is_none());
+            assert!(iter.next().is_none());
+        }
+
+        let tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let root = tree.root().unwrap();
+        let two = root.last_child().unwrap();
+        let leaf = two.last_child().unwrap();
+
+        assert_fused(leaf.ancestors());
+        assert_fused(leaf.ancestors_or_self());
+        assert_fused(root.children());
+        assert_fused(root.traverse_pre_order());
+        assert_fused(root.traverse_post_order().prune(|node| *node.data() != 1));
+        assert_fused(root.traverse_level_order().with_max_depth(1));
+        assert_fused(tree.iter_all_roots_first());
+    }
+
+    #[test]
+    fn copy_and_debug() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root("root");
+        let root = tree.root().unwrap();
+
+        let copy = root;
+        let first = move || copy.data().len();
+        let second = move || copy.node_id();
+        assert_eq!(first(), 4);
+        assert_eq!(second(), root_id);
+        assert_eq!(root.clone().node_id(), root_id);
+
+        assert_eq!(
+            format!("{:?}", root),
+            format!("NodeRef {{ node_id: {:?}, data: \"root\" }}", root_id)
+        );
+    }
+    #[test]
+    fn child_count_follows_edits() {
+        let mut tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let counts_match = |tree: &Tree<i32>| {
+            tree.root()
+                .unwrap()
+                .traverse_pre_order()
+                .all(|node| node.child_count() == node.children().count())
+        };
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root().unwrap().last_child().unwrap().node_id();
+        let zero_id = tree.root().unwrap().first_child().unwrap().node_id();
+        assert_eq!(tree.root().unwrap().child_count(), 3);
+        assert!(counts_match(&tree));
+
+        tree.get_mut(zero_id).unwrap().prepend(5);
+        tree.move_subtree(zero_id, two_id, InsertPosition::First)
+            .unwrap();
+        assert_eq!(tree.root().unwrap().child_count(), 2);
+        assert_eq!(tree.get(two_id).unwrap().child_count(), 3);
+        assert!(counts_match(&tree));
+
+        tree.remove(zero_id, OrphanChildren);
+        assert_eq!(tree.get(two_id).unwrap().child_count(), 2);
+        tree.remove(two_id, DropChildren);
+        assert_eq!(tree.get(root_id).unwrap().child_count(), 1);
+        assert!(counts_match(&tree));
+
+        tree.set_root(10);
+        assert_eq!(tree.root().unwrap().child_count(), 1);
+        assert!(counts_match(&tree));
+    }
 }
@@ -0,0 +1,423 @@
+//!
+//! Observers which are notified whenever a `Tree` changes, so that caches, indexes and views
+//! built on top of a `Tree` can stay in sync with it.
+//!
+
+use crate::tree::Tree;
+use crate::NodeId;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+use std::panic::RefUnwindSafe;
+use std::panic::UnwindSafe;
+
+///
+/// Receives notifications about changes to a `Tree`.  Every method has a default (empty)
+/// implementation, so implementors only need to handle the changes they care about.
+///
+/// Each method receives the `Tree` being changed along with the `NodeId` of the affected `Node`.
+/// Observers are only notified of changes made through the `Tree`'s own methods (and those of
+/// its `NodeMut`s); in particular, changes made through `NodeMut::data` are not observed, while
+/// changes made with `NodeMut::replace_data` are.
+///
+/// Observers don't need to be `UnwindSafe` or `RefUnwindSafe`, and registering one doesn't
+/// change whether the `Tree` is.  If a panic unwinds through a `Tree` method (from inside an
+/// observer, say) the `Tree` itself is left consistent, but an observer may have seen only part
+/// of the change; an observer that has to stay exact should be removed and rebuilt after
+/// catching such a panic.
+///
+pub trait TreeObserver<T> {
+    ///
+    /// Called after a new `Node` has been inserted into the `Tree`.
+    ///
+    fn on_insert(&mut self, _tree: &Tree<T>, _node_id: NodeId) {}
+
+    ///
+    /// Called before a `Node` is removed from the `Tree`, while it (and its relatives) can still
+    /// be inspected.  When a `Node` is removed with `DropChildren`, this is called for it and for
+    /// each of its descendants (in pre-order) before any of them are removed.
+    ///
+    fn on_remove(&mut self, _tree: &Tree<T>, _node_id: NodeId) {}
+
+    ///
+    /// Called after a `Node` has changed position in the `Tree` (e.g. it was swapped with a
+    /// sibling, shifted below a new root, or orphaned when its parent was removed).
+    ///
+    fn on_move(&mut self, _tree: &Tree<T>, _node_id: NodeId) {}
+
+    ///
+    /// Called after the data of a `Node` has been replaced.
+    ///
+    fn on_data_replaced(&mut self, _tree: &Tree<T>, _node_id: NodeId) {}
+}
+
+///
+/// Identifies an observer registered with `Tree::add_observer`.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct ObserverId(usize);
+
+type BoxedObserver<T> = Box<dyn TreeObserver<T> + Send + Sync>;
+
+///
+/// A structure the `Tree` maintains for itself (such as the key index), which is notified like
+/// an observer but can also be found again, and is kept in sync through changes that observers
+/// aren't told about.
+///
+pub(crate) trait TreeIndex<T>: TreeObserver<T> + Send + Sync {
+    /// Rebuilds the index from scratch, after the `Tree`'s `Node`s changed wholesale.
+    fn rebuild(&mut self, tree: &Tree<T>);
+
+    /// Replaces the `NodeId`s in the index after the `Tree`'s `Node`s were moved.
+    fn remap(&mut self, node_ids: &HashMap<NodeId, NodeId>);
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// The observers registered with a `Tree`, and the indexes it maintains.
+pub(crate) struct Hooks<T> {
+    observers: Vec<(ObserverId, BoxedObserver<T>)>,
+    indexes: Vec<Box<dyn TreeIndex<T>>>,
+    next_id: usize,
+}
+
+impl<T> Hooks<T> {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.observers.is_empty() && self.indexes.is_empty()
+    }
+
+    pub(crate) fn indexes(&self) -> impl Iterator<Item = &dyn TreeIndex<T>> {
+        self.indexes.iter().map(|index| index.as_ref())
+    }
+
+    pub(crate) fn indexes_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn TreeIndex<T>>> {
+        self.indexes.iter_mut()
+    }
+
+    pub(crate) fn add_index(&mut self, index: Box<dyn TreeIndex<T>>) {
+        self.indexes.push(index);
+    }
+}
+
+impl<T> Default for Hooks<T> {
+    fn default() -> Self {
+        Hooks {
+            observers: Vec::new(),
+            indexes: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Hooks<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("observers", &self.observers.len())
+            .field("indexes", &self.indexes.len())
+            .finish()
+    }
+}
+
+/// Observers and indexes are boxed without `UnwindSafe`/`RefUnwindSafe` bounds, so that
+/// observers sharing state through e.g. boxed closures can still be registered; without these
+/// impls they would make every `Tree` lose both traits.  That's fine because the `Tree` never
+/// relies on an observer for its own consistency.  A panic can leave an observer or index out
+/// of date, which at worst makes it give stale answers (any `NodeId` it hands back still goes
+/// through the `Tree`'s checked lookups), and `TreeObserver` documents that.
+impl<T> UnwindSafe for Hooks<T> {}
+
+impl<T> RefUnwindSafe for Hooks<T> {}
+
+/// Observers don't take part in comparisons between `Tree`s.
+impl<T> PartialEq for Hooks<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Registers an observer which will be notified of every subsequent change to the `Tree`,
+    /// and returns an `ObserverId` which can be used to remove it again.
+    ///
+    /// ```
+    /// use slab_tree::observer::TreeObserver;
+    /// use slab_tree::tree::{Tree, TreeBuilder};
+    /// use slab_tree::NodeId;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// struct CountInserts(Arc<AtomicUsize>);
+    ///
+    /// impl<T> TreeObserver<T> for CountInserts {
+    ///     fn on_insert(&mut self, _tree: &Tree<T>, _node_id: NodeId) {
+    ///         self.0.fetch_add(1, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// let inserts = Arc::new(AtomicUsize::new(0));
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// tree.add_observer(CountInserts(inserts.clone()));
+    ///
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(2);
+    /// root.append(3);
+    ///
+    /// assert_eq!(inserts.load(Ordering::SeqCst), 2);
+    /// ```
+    ///
+    pub fn add_observer<O>(&mut self, observer: O) -> ObserverId
+    where
+        O: TreeObserver<T> + Send + Sync + 'static,
+    {
+        let id = ObserverId(self.hooks.next_id);
+        self.hooks.next_id += 1;
+        self.hooks.observers.push((id, Box::new(observer)));
+        id
+    }
+
+    ///
+    /// Removes a previously registered observer and returns it, or returns `None` if no observer
+    /// with the given `ObserverId` is registered.
+    ///
+    /// ```
+    /// use slab_tree::observer::TreeObserver;
+    /// use slab_tree::tree::Tree;
+    ///
+    /// struct Ignore;
+    /// impl<T> TreeObserver<T> for Ignore {}
+    ///
+    /// let mut tree: Tree<i32> = Tree::new();
+    /// let id = tree.add_observer(Ignore);
+    ///
+    /// assert!(tree.remove_observer(id).is_some());
+    /// assert!(tree.remove_observer(id).is_none());
+    /// ```
+    ///
+    pub fn remove_observer(
+        &mut self,
+        id: ObserverId,
+    ) -> Option<Box<dyn TreeObserver<T> + Send + Sync>> {
+        let position = self
+            .hooks
+            .observers
+            .iter()
+            .position(|(observer_id, _)| *observer_id == id)?;
+        Some(self.hooks.observers.remove(position).1)
+    }
+
+    pub(crate) fn notify_insert(&mut self, node_id: NodeId) {
+        self.notify(|observer, tree| observer.on_insert(tree, node_id));
+    }
+
+    pub(crate) fn notify_remove(&mut self, node_id: NodeId) {
+        self.notify(|observer, tree| observer.on_remove(tree, node_id));
+    }
+
+    pub(crate) fn notify_move(&mut self, node_id: NodeId) {
+        self.notify(|observer, tree| observer.on_move(tree, node_id));
+    }
+
+    pub(crate) fn notify_data_replaced(&mut self, node_id: NodeId) {
+        self.notify(|observer, tree| observer.on_data_replaced(tree, node_id));
+    }
+
+    fn notify<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut dyn TreeObserver<T>, &Tree<T>),
+    {
+        if self.hooks.is_empty() {
+            return;
+        }
+
+        // observers can't register other observers, so nothing is lost while they're taken out
+        let mut indexes = mem::take(&mut self.hooks.indexes);
+        for index in indexes.iter_mut() {
+            f(index.as_mut(), self);
+        }
+        self.hooks.indexes = indexes;
+
+        let mut observers = mem::take(&mut self.hooks.observers);
+        for (_, observer) in observers.iter_mut() {
+            f(observer.as_mut(), self);
+        }
+        self.hooks.observers = observers;
+    }
+
+    /// Rebuilds every index after the `Tree`'s `Node`s changed without notifications.
+    pub(crate) fn rebuild_indexes(&mut self) {
+        let mut indexes = mem::take(&mut self.hooks.indexes);
+        for index in indexes.iter_mut() {
+            index.rebuild(self);
+        }
+        self.hooks.indexes = indexes;
+    }
+
+    /// Returns the `NodeId`s of all of the `Tree`'s `Node`s, including orphans, in storage order.
+    pub(crate) fn all_node_ids(&self) -> Vec<NodeId> {
+        self.core_tree.node_ids()
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod observer_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::tree::TreeBuilder;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    #[derive(Debug, PartialEq)]
+    enum Change {
+        Insert(i32),
+        Remove(i32),
+        Move(i32),
+        Replace(i32),
+    }
+
+    struct Recorder(Arc<Mutex<Vec<Change>>>);
+
+    impl TreeObserver<i32> for Recorder {
+        fn on_insert(&mut self, tree: &Tree<i32>, node_id: NodeId) {
+            let data = *tree.get(node_id).unwrap().data();
+            self.0.lock().unwrap().push(Change::Insert(data));
+        }
+
+        fn on_remove(&mut self, tree: &Tree<i32>, node_id: NodeId) {
+            let data = *tree.get(node_id).unwrap().data();
+            self.0.lock().unwrap().push(Change::Remove(data));
+        }
+
+        fn on_move(&mut self, tree: &Tree<i32>, node_id: NodeId) {
+            let data = *tree.get(node_id).unwrap().data();
+            self.0.lock().unwrap().push(Change::Move(data));
+        }
+
+        fn on_data_replaced(&mut self, tree: &Tree<i32>, node_id: NodeId) {
+            let data = *tree.get(node_id).unwrap().data();
+            self.0.lock().unwrap().push(Change::Replace(data));
+        }
+    }
+
+    fn observed(tree: &mut Tree<i32>) -> Arc<Mutex<Vec<Change>>> {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        tree.add_observer(Recorder(changes.clone()));
+        changes
+    }
+
+    fn take(changes: &Arc<Mutex<Vec<Change>>>) -> Vec<Change> {
+        std::mem::take(&mut *changes.lock().unwrap())
+    }
+
+    #[test]
+    fn inserts() {
+        let mut tree = Tree::new();
+        let changes = observed(&mut tree);
+
+        tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        root.append(2);
+        root.prepend(3);
+        tree.set_root(0);
+
+        assert_eq!(
+            take(&changes),
+            vec![
+                Change::Insert(1),
+                Change::Insert(2),
+                Change::Insert(3),
+                Change::Insert(0),
+                Change::Move(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn removes() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        one.append(2);
+        one.append(3);
+        let mut four = root.append(4);
+        four.append(5);
+        let changes = observed(&mut tree);
+
+        tree.root_mut().unwrap().remove_first(DropChildren);
+        assert_eq!(
+            take(&changes),
+            vec![Change::Remove(1), Change::Remove(2), Change::Remove(3)]
+        );
+
+        tree.root_mut().unwrap().remove_last(OrphanChildren);
+        assert_eq!(take(&changes), vec![Change::Remove(4), Change::Move(5)]);
+    }
+
+    #[test]
+    fn moves() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append(1).node_id();
+        root.append(2);
+        let three_id = root.append(3).node_id();
+        let changes = observed(&mut tree);
+
+        tree.get_mut(one_id).unwrap().swap_next_sibling();
+        tree.get_mut(one_id).unwrap().swap_prev_sibling();
+        tree.get_mut(one_id).unwrap().make_last_sibling();
+        tree.get_mut(one_id).unwrap().make_first_sibling();
+        assert!(!tree.get_mut(three_id).unwrap().make_last_sibling());
+
+        assert_eq!(
+            take(&changes),
+            vec![
+                Change::Move(1),
+                Change::Move(2),
+                Change::Move(1),
+                Change::Move(2),
+                Change::Move(1),
+                Change::Move(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_data() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let changes = observed(&mut tree);
+
+        assert_eq!(tree.root_mut().unwrap().replace_data(7), 0);
+        *tree.root_mut().unwrap().data() = 8;
+
+        assert_eq!(take(&changes), vec![Change::Replace(7)]);
+    }
+
+    #[test]
+    fn remove_observer() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let id = tree.add_observer(Recorder(changes.clone()));
+
+        tree.root_mut().unwrap().append(1);
+        assert!(tree.remove_observer(id).is_some());
+        tree.root_mut().unwrap().append(2);
+
+        assert_eq!(take(&changes), vec![Change::Insert(1)]);
+    }
+
+    #[test]
+    fn trees_stay_unwind_safe() {
+        fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+        assert_unwind_safe::<Tree<i32>>();
+        assert_unwind_safe::<Tree<String>>();
+
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        observed(&mut tree);
+        let result = std::panic::catch_unwind(|| tree.root().map(|root| *root.data()));
+        assert_eq!(result.unwrap(), Some(0));
+    }
+}
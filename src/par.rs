@@ -0,0 +1,85 @@
+use crate::observer::Hooks;
+use crate::tree::Tree;
+
+impl<T: Send> Tree<T> {
+    ///
+    /// Consumes the `Tree` and transforms the data of every `Node` (including any orphans) with
+    /// `f`, calling `f` on many `Node`s at once across rayon's thread pool.  The result has the
+    /// same structure, and every `NodeId` of this `Tree` refers to the corresponding `Node` in
+    /// the result.
+    ///
+    /// Observers, indexes and snapshots of this `Tree` are dropped, since they can't work with
+    /// the new data.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    ///
+    /// let squares = tree.par_map(|n| (n * n).to_string());
+    ///
+    /// assert_eq!(squares.root().unwrap().data(), "1");
+    /// assert_eq!(squares.get(two_id).unwrap().data(), "4");
+    /// ```
+    ///
+    pub fn par_map<U, F>(self, f: F) -> Tree<U>
+    where
+        U: Send,
+        F: Fn(T) -> U + Sync + Send,
+    {
+        Tree {
+            root_id: self.root_id,
+            core_tree: self.core_tree.par_map(f),
+            hooks: Hooks::default(),
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod par_tests {
+    use crate::behaviors::RemoveBehavior::OrphanChildren;
+    use crate::tree::Tree;
+
+    #[test]
+    fn par_map_keeps_structure() {
+        let mut tree = Tree::unfold(5, |n: u32| (n, (0..n).collect()));
+        let removed_id = tree.root().unwrap().last_child().unwrap().node_id();
+        let removed = tree.get(removed_id).unwrap();
+        let orphan_id = removed.first_child().unwrap().node_id();
+        tree.remove(removed_id, OrphanChildren);
+
+        let expected: Vec<(String, usize)> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (node.data().to_string(), node.ancestors().count()))
+            .collect();
+
+        let mapped = tree.par_map(|n| n.to_string());
+        let actual: Vec<(String, usize)> = mapped
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (node.data().clone(), node.ancestors().count()))
+            .collect();
+        assert_eq!(actual, expected);
+        assert!(mapped.get(removed_id).is_none());
+        assert_eq!(mapped.get(orphan_id).unwrap().data(), "0");
+    }
+
+    #[test]
+    fn par_map_then_insert() {
+        let mut tree = Tree::unfold(2, |n: u32| (n, (0..n).collect()));
+        let first_id = tree.root().unwrap().first_child().unwrap().node_id();
+        tree.remove(first_id, OrphanChildren);
+
+        let mut mapped = tree.par_map(u64::from);
+        // the free slot left behind is reused
+        let capacity = mapped.capacity();
+        mapped.root_mut().unwrap().append(10);
+        assert_eq!(mapped.capacity(), capacity);
+        assert_eq!(mapped.root().unwrap().children().count(), 2);
+    }
+}
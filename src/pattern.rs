@@ -0,0 +1,356 @@
+//!
+//! Patterns describing the shape of a subtree, with wildcards and capture slots, for finding the
+//! `Node`s to lint, query, or rewrite.
+//!
+//! A `Pattern` tests a `Node`'s data and, optionally, its children, each of which is tested by a
+//! `Pattern` of its own.  Any `Pattern` can capture the `Node` it matches under a name, and
+//! `Tree::match_at` returns the `NodeId`s of the captured `Node`s when the whole `Pattern`
+//! matches.
+//!
+//! ```
+//! use slab_tree::pattern::Pattern;
+//! use slab_tree::tree::TreeBuilder;
+//!
+//! // (+ (* a b) 0)
+//! let mut tree = TreeBuilder::new().with_root("+").build();
+//! let mut root = tree.root_mut().unwrap();
+//! let mut product = root.append("*");
+//! let a_id = product.append("a").node_id();
+//! product.append("b");
+//! root.append("0");
+//! let root_id = tree.root_id().unwrap();
+//!
+//! // (+ x 0), capturing x
+//! let adds_zero = Pattern::data("+").with_children(vec![
+//!     Pattern::any().capture("x"),
+//!     Pattern::data("0"),
+//! ]);
+//! let captures = tree.match_at(root_id, &adds_zero).unwrap();
+//! assert_eq!(tree.get(captures["x"]).unwrap().data(), &"*");
+//!
+//! // (+ (* y _) _), capturing y
+//! let adds_product = Pattern::data("+").with_children(vec![
+//!     Pattern::data("*").with_children(vec![Pattern::any().capture("y"), Pattern::any()]),
+//!     Pattern::any(),
+//! ]);
+//! assert_eq!(tree.match_at(root_id, &adds_product).unwrap()["y"], a_id);
+//! ```
+//!
+
+use crate::tree::Tree;
+use crate::NodeId;
+use std::collections::HashMap;
+use std::fmt;
+
+///
+/// The `NodeId`s of the `Node`s captured by a `Pattern`, keyed by their capture names.
+///
+pub type Captures = HashMap<String, NodeId>;
+
+/// How a `Pattern` tests the data of a `Node`.
+enum DataTest<T> {
+    Any,
+    Equal(T),
+    Matching(Box<dyn Fn(&T) -> bool>),
+}
+
+///
+/// Describes the `Node`s a subtree must be made of: a test for the data of its top `Node` and,
+/// optionally, a `Pattern` for each of that `Node`'s children.  See the module documentation.
+///
+pub struct Pattern<T> {
+    data: DataTest<T>,
+    children: Option<Vec<Pattern<T>>>,
+    capture: Option<String>,
+}
+
+impl<T> Pattern<T> {
+    ///
+    /// Returns a wildcard `Pattern`, matching any `Node` whatever its data and children.
+    ///
+    /// ```
+    /// use slab_tree::pattern::Pattern;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// tree.root_mut().unwrap().append(2);
+    ///
+    /// assert!(tree.match_at(tree.root_id().unwrap(), &Pattern::any()).is_some());
+    /// ```
+    ///
+    pub fn any() -> Pattern<T> {
+        Pattern {
+            data: DataTest::Any,
+            children: None,
+            capture: None,
+        }
+    }
+
+    ///
+    /// Returns a `Pattern` matching any `Node` whose data is equal to `data`, whatever its
+    /// children.
+    ///
+    /// ```
+    /// use slab_tree::pattern::Pattern;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// assert!(tree.match_at(root_id, &Pattern::data(1)).is_some());
+    /// assert!(tree.match_at(root_id, &Pattern::data(2)).is_none());
+    /// ```
+    ///
+    pub fn data(data: T) -> Pattern<T> {
+        Pattern {
+            data: DataTest::Equal(data),
+            ..Pattern::any()
+        }
+    }
+
+    ///
+    /// Returns a `Pattern` matching any `Node` whose data passes `test`, whatever its children.
+    ///
+    /// ```
+    /// use slab_tree::pattern::Pattern;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::new().with_root(4).build();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// assert!(tree.match_at(root_id, &Pattern::matching(|n| n % 2 == 0)).is_some());
+    /// assert!(tree.match_at(root_id, &Pattern::matching(|n| n % 2 == 1)).is_none());
+    /// ```
+    ///
+    pub fn matching<F>(test: F) -> Pattern<T>
+    where
+        F: Fn(&T) -> bool + 'static,
+    {
+        Pattern {
+            data: DataTest::Matching(Box::new(test)),
+            ..Pattern::any()
+        }
+    }
+
+    ///
+    /// Makes the `Pattern` match only `Node`s with exactly as many children as `children`, each
+    /// matching the `Pattern` at the same position.  Pass no children to match only leaves.
+    ///
+    /// ```
+    /// use slab_tree::pattern::Pattern;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// tree.root_mut().unwrap().append(2);
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// let one_child = Pattern::any().with_children(vec![Pattern::data(2)]);
+    /// assert!(tree.match_at(root_id, &one_child).is_some());
+    ///
+    /// let leaf = Pattern::any().with_children(vec![]);
+    /// assert!(tree.match_at(root_id, &leaf).is_none());
+    /// ```
+    ///
+    pub fn with_children<I>(mut self, children: I) -> Pattern<T>
+    where
+        I: IntoIterator<Item = Pattern<T>>,
+    {
+        self.children = Some(children.into_iter().collect());
+        self
+    }
+
+    ///
+    /// Makes the `Pattern` capture the `Node` it matches under `name`.  If several `Pattern`s
+    /// capture under the same name, the one matching the `Node` which comes last in pre-order
+    /// wins.
+    ///
+    /// ```
+    /// use slab_tree::pattern::Pattern;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// let pattern = Pattern::any()
+    ///     .capture("parent")
+    ///     .with_children(vec![Pattern::any().capture("child")]);
+    /// let captures = tree.match_at(root_id, &pattern).unwrap();
+    /// assert_eq!(captures["parent"], root_id);
+    /// assert_eq!(captures["child"], two_id);
+    /// ```
+    ///
+    pub fn capture<S: Into<String>>(mut self, name: S) -> Pattern<T> {
+        self.capture = Some(name.into());
+        self
+    }
+}
+
+impl<T: PartialEq> Pattern<T> {
+    fn test(&self, data: &T) -> bool {
+        match &self.data {
+            DataTest::Any => true,
+            DataTest::Equal(expected) => data == expected,
+            DataTest::Matching(test) => test(data),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Pattern<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pattern = f.debug_struct("Pattern");
+        match &self.data {
+            DataTest::Any => pattern.field("data", &format_args!("Any")),
+            DataTest::Equal(data) => pattern.field("data", data),
+            DataTest::Matching(_) => pattern.field("data", &format_args!("Matching")),
+        };
+        pattern
+            .field("children", &self.children)
+            .field("capture", &self.capture)
+            .finish()
+    }
+}
+
+impl<T: PartialEq> Tree<T> {
+    ///
+    /// Tests whether the subtree rooted at the `Node` identified by `node_id` matches `pattern`.
+    /// Returns the `NodeId`s of the `Node`s captured by the `Pattern` (keyed by their capture
+    /// names) if it does, or `None` if it doesn't or the `NodeId` doesn't refer to a `Node` of
+    /// this `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::pattern::Pattern;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("call").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("unwrap");
+    /// let arg_id = root.append("x").node_id();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// let unwrap_call = Pattern::data("call")
+    ///     .with_children(vec![Pattern::data("unwrap"), Pattern::any().capture("arg")]);
+    /// let captures = tree.match_at(root_id, &unwrap_call).unwrap();
+    /// assert_eq!(captures["arg"], arg_id);
+    ///
+    /// assert!(tree.match_at(arg_id, &unwrap_call).is_none());
+    /// ```
+    ///
+    pub fn match_at(&self, node_id: NodeId, pattern: &Pattern<T>) -> Option<Captures> {
+        let mut captures = HashMap::new();
+        let mut stack = vec![(pattern, self.get(node_id)?)];
+
+        while let Some((pattern, node)) = stack.pop() {
+            if !pattern.test(node.data()) {
+                return None;
+            }
+            if let Some(name) = &pattern.capture {
+                captures.insert(name.clone(), node.node_id());
+            }
+            if let Some(children) = &pattern.children {
+                if node.child_count() != children.len() {
+                    return None;
+                }
+                let start = stack.len();
+                stack.extend(children.iter().zip(node.children()));
+                // visit the children in order
+                stack[start..].reverse();
+            }
+        }
+
+        Some(captures)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod pattern_tests {
+    use super::Pattern;
+    use crate::behaviors::RemoveBehavior::DropChildren;
+    use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+
+    #[test]
+    fn matches_nested_children() {
+        // 3 -> [0, 1 -> [0], 2 -> [0, 1 -> [0]]]
+        let tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let root = tree.root().unwrap();
+        let two_id = root.last_child().unwrap().node_id();
+        let zero_id = root.first_child().unwrap().node_id();
+
+        let pattern = Pattern::data(3).with_children(vec![
+            Pattern::any().capture("first"),
+            Pattern::any(),
+            Pattern::data(2).capture("last").with_children(vec![
+                Pattern::matching(|n| *n < 1).with_children(vec![]),
+                Pattern::data(1).with_children(vec![Pattern::data(0).capture("deepest")]),
+            ]),
+        ]);
+        let captures = tree.match_at(root.node_id(), &pattern).unwrap();
+        assert_eq!(captures.len(), 3);
+        assert_eq!(captures["first"], zero_id);
+        assert_eq!(captures["last"], two_id);
+        let deepest = tree.get(captures["deepest"]).unwrap();
+        assert_eq!(deepest.ancestors().count(), 3);
+    }
+
+    #[test]
+    fn mismatches() {
+        let tree = Tree::unfold(2, |n| (n, (0..n).collect()));
+        let root_id = tree.root_id().unwrap();
+        let mismatch = |pattern: Pattern<i32>| tree.match_at(root_id, &pattern).is_none();
+
+        // too few, too many, or mismatched children
+        assert!(mismatch(
+            Pattern::data(2).with_children(vec![Pattern::any()])
+        ));
+        assert!(mismatch(
+            Pattern::any().with_children((0..3).map(|_| Pattern::any()))
+        ));
+        assert!(mismatch(
+            Pattern::any().with_children(vec![Pattern::data(1), Pattern::data(0)])
+        ));
+        // a leaf pattern deeper down
+        assert!(mismatch(Pattern::any().with_children(vec![
+            Pattern::any(),
+            Pattern::data(1).with_children(vec![]),
+        ])));
+        assert!(!mismatch(Pattern::any().with_children(vec![
+            Pattern::any(),
+            Pattern::data(1).with_children(vec![Pattern::any()]),
+        ])));
+    }
+
+    #[test]
+    fn later_captures_win() {
+        let tree = Tree::unfold(2, |n| (n, (0..n).collect()));
+        let root = tree.root().unwrap();
+        let pattern = Pattern::any()
+            .capture("x")
+            .with_children(vec![Pattern::any().capture("x"), Pattern::any()]);
+        let captures = tree.match_at(root.node_id(), &pattern).unwrap();
+        assert_eq!(captures["x"], root.first_child().unwrap().node_id());
+    }
+
+    #[test]
+    fn missing_nodes_never_match() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        tree.remove(one_id, DropChildren);
+        assert!(tree.match_at(one_id, &Pattern::any()).is_none());
+
+        let other = TreeBuilder::new().with_root(0).build();
+        assert!(tree
+            .match_at(other.root_id().unwrap(), &Pattern::any())
+            .is_none());
+    }
+
+    #[test]
+    fn debug_output() {
+        let pattern =
+            Pattern::data(1).with_children(vec![Pattern::matching(|n| *n > 0).capture("x")]);
+        let debug = format!("{:?}", pattern);
+        assert!(debug.contains("Matching"));
+        assert!(debug.contains("\"x\""));
+    }
+}
@@ -0,0 +1,233 @@
+//!
+//! A pool of reusable `Tree`s, for programs that build and throw away many small trees.
+//!
+
+use crate::observer::Hooks;
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+
+///
+/// A pool of empty `Tree`s whose storage is reused instead of being reallocated.
+///
+/// `Tree`s are taken out of the pool with `take` and handed back with `give`, which clears them
+/// (keeping their allocated capacity) so the next `take` can reuse them.
+///
+/// ```
+/// use slab_tree::pool::TreePool;
+///
+/// let mut pool = TreePool::new().with_node_capacity(8);
+///
+/// for i in 0..3 {
+///     let mut tree = pool.take();
+///     tree.set_root(i);
+///     assert!(tree.capacity() >= 8);
+///     pool.give(tree);
+/// }
+///
+/// assert_eq!(pool.len(), 1);
+/// ```
+///
+#[derive(Debug)]
+pub struct TreePool<T> {
+    trees: Vec<Tree<T>>,
+    node_capacity: usize,
+    max_pooled: Option<usize>,
+}
+
+impl<T> Default for TreePool<T> {
+    fn default() -> Self {
+        TreePool::new()
+    }
+}
+
+impl<T> TreePool<T> {
+    ///
+    /// Creates a new, empty `TreePool`.
+    ///
+    /// ```
+    /// use slab_tree::pool::TreePool;
+    ///
+    /// let pool: TreePool<i32> = TreePool::new();
+    ///
+    /// assert!(pool.is_empty());
+    /// ```
+    ///
+    pub fn new() -> TreePool<T> {
+        TreePool {
+            trees: Vec::new(),
+            node_capacity: 0,
+            max_pooled: None,
+        }
+    }
+
+    ///
+    /// Sets the capacity of the `Tree`s the pool creates when it has none to hand out.
+    ///
+    /// ```
+    /// use slab_tree::pool::TreePool;
+    ///
+    /// let mut pool: TreePool<i32> = TreePool::new().with_node_capacity(10);
+    ///
+    /// assert_eq!(pool.take().capacity(), 10);
+    /// ```
+    ///
+    pub fn with_node_capacity(self, node_capacity: usize) -> TreePool<T> {
+        TreePool {
+            node_capacity,
+            ..self
+        }
+    }
+
+    ///
+    /// Limits how many `Tree`s the pool holds on to; `Tree`s given back beyond that are dropped.
+    ///
+    /// ```
+    /// use slab_tree::pool::TreePool;
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let mut pool = TreePool::new().with_max_pooled(1);
+    /// pool.give(Tree::<i32>::new());
+    /// pool.give(Tree::new());
+    ///
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    ///
+    pub fn with_max_pooled(self, max_pooled: usize) -> TreePool<T> {
+        TreePool {
+            max_pooled: Some(max_pooled),
+            ..self
+        }
+    }
+
+    ///
+    /// Returns the number of `Tree`s currently held by the pool.
+    ///
+    /// ```
+    /// use slab_tree::pool::TreePool;
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let mut pool = TreePool::new();
+    /// pool.give(Tree::<i32>::new());
+    ///
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    ///
+    pub fn len(&self) -> usize {
+        self.trees.len()
+    }
+
+    ///
+    /// Returns `true` if the pool currently holds no `Tree`s.
+    ///
+    /// ```
+    /// use slab_tree::pool::TreePool;
+    ///
+    /// let pool: TreePool<i32> = TreePool::new();
+    ///
+    /// assert!(pool.is_empty());
+    /// ```
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+
+    ///
+    /// Takes an empty `Tree` out of the pool, creating a new one if the pool has none.
+    ///
+    /// ```
+    /// use slab_tree::pool::TreePool;
+    ///
+    /// let mut pool: TreePool<i32> = TreePool::new();
+    /// let tree = pool.take();
+    ///
+    /// assert!(tree.root().is_none());
+    /// ```
+    ///
+    pub fn take(&mut self) -> Tree<T> {
+        self.trees
+            .pop()
+            .unwrap_or_else(|| TreeBuilder::new().with_capacity(self.node_capacity).build())
+    }
+
+    ///
+    /// Gives a `Tree` back to the pool.  The `Tree` is cleared and its observers are dropped
+    /// (without being notified), but its allocated capacity is kept for the next `take`.
+    ///
+    /// ```
+    /// use slab_tree::pool::TreePool;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut pool = TreePool::new();
+    /// pool.give(TreeBuilder::new().with_root(1).build());
+    ///
+    /// assert!(pool.take().root().is_none());
+    /// ```
+    ///
+    pub fn give(&mut self, mut tree: Tree<T>) {
+        if matches!(self.max_pooled, Some(max) if self.trees.len() >= max) {
+            return;
+        }
+        tree.hooks = Hooks::default();
+        tree.clear();
+        self.trees.push(tree);
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod pool_tests {
+    use super::*;
+    use crate::observer::TreeObserver;
+    use crate::NodeId;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct CountRemoves(Arc<AtomicUsize>);
+
+    impl TreeObserver<i32> for CountRemoves {
+        fn on_remove(&mut self, _tree: &Tree<i32>, _node_id: NodeId) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn reuses_storage() {
+        let mut pool = TreePool::new();
+
+        let mut tree = pool.take();
+        let root_id = tree.set_root(0);
+        for i in 1..100 {
+            tree.root_mut().unwrap().append(i);
+        }
+        let capacity = tree.capacity();
+        pool.give(tree);
+
+        let mut tree = pool.take();
+        assert!(pool.is_empty());
+        assert!(tree.root().is_none());
+        assert!(tree.get(root_id).is_none());
+        assert_eq!(tree.capacity(), capacity);
+
+        tree.set_root(1);
+        for i in 1..99 {
+            tree.root_mut().unwrap().append(i);
+        }
+        assert_eq!(tree.capacity(), capacity);
+    }
+
+    #[test]
+    fn give_drops_observers_silently() {
+        let removed = Arc::new(AtomicUsize::new(0));
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        tree.add_observer(CountRemoves(removed.clone()));
+
+        let mut pool = TreePool::new();
+        pool.give(tree);
+        assert_eq!(removed.load(Ordering::SeqCst), 0);
+
+        let mut tree = pool.take();
+        let root_id = tree.set_root(1);
+        tree.remove(root_id, crate::behaviors::RemoveBehavior::DropChildren);
+        assert_eq!(removed.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,273 @@
+//!
+//! Moving a sub-tree to a different parent within the same `Tree`.
+//!
+
+use crate::tree::Tree;
+use crate::NodeId;
+use std::error::Error;
+use std::fmt;
+
+///
+/// Where `Tree::move_subtree` places a `Node` among the children of its new parent.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InsertPosition {
+    ///
+    /// Before all of the new parent's other children.
+    ///
+    First,
+
+    ///
+    /// After all of the new parent's other children.
+    ///
+    Last,
+
+    ///
+    /// At the given index among the new parent's children, counted once the `Node` has been
+    /// taken out of its old position.  An index past the last child places the `Node` last.
+    ///
+    At(usize),
+}
+
+///
+/// Describes why `Tree::move_subtree` couldn't move a sub-tree.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    ///
+    /// The `NodeId` of the `Node` to move doesn't refer to a `Node` in the `Tree`.
+    ///
+    MissingNode(NodeId),
+
+    ///
+    /// The `NodeId` of the new parent doesn't refer to a `Node` in the `Tree`.
+    ///
+    MissingParent(NodeId),
+
+    ///
+    /// The `Node` to move is the root of the `Tree`, which can't be given a parent.
+    ///
+    Root,
+
+    ///
+    /// The new parent is the `Node` being moved or one of its descendants, so moving it would
+    /// create a cycle.
+    ///
+    Cycle,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::MissingNode(node_id) => {
+                write!(f, "{:?} is not a node of the tree", node_id)
+            }
+            MoveError::MissingParent(node_id) => {
+                write!(f, "{:?} is not a node of the tree", node_id)
+            }
+            MoveError::Root => write!(f, "the root of the tree can't be moved"),
+            MoveError::Cycle => {
+                write!(f, "the new parent is inside the sub-tree being moved")
+            }
+        }
+    }
+}
+
+impl Error for MoveError {}
+
+impl<T> Tree<T> {
+    ///
+    /// Moves the `Node` with the `NodeId` `node` (along with its sub-tree) to be a child of the
+    /// `Node` with the `NodeId` `new_parent`, at the given `position` among its children.  No
+    /// data is moved and every `NodeId` stays valid.  Orphaned `Node`s can be moved back into
+    /// the `Tree` this way.
+    ///
+    /// ```
+    /// use slab_tree::reparent::InsertPosition;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append(1).node_id();
+    /// let two_id = root.append(2).node_id();
+    /// tree.get_mut(one_id).unwrap().append(3);
+    ///
+    /// tree.move_subtree(two_id, one_id, InsertPosition::First).unwrap();
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 0
+    /// └── 1
+    ///     ├── 2
+    ///     └── 3
+    /// ");
+    /// ```
+    ///
+    /// A `Node` can't be moved below itself:
+    ///
+    /// ```
+    /// use slab_tree::reparent::{InsertPosition, MoveError};
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let one_id = tree.root_mut().unwrap().append(1).node_id();
+    /// let two_id = tree.get_mut(one_id).unwrap().append(2).node_id();
+    ///
+    /// assert_eq!(
+    ///     tree.move_subtree(one_id, two_id, InsertPosition::Last),
+    ///     Err(MoveError::Cycle)
+    /// );
+    /// ```
+    ///
+    pub fn move_subtree(
+        &mut self,
+        node: NodeId,
+        new_parent: NodeId,
+        position: InsertPosition,
+    ) -> Result<(), MoveError> {
+        if self.get_node(node).is_none() {
+            return Err(MoveError::MissingNode(node));
+        }
+        if self.get_node(new_parent).is_none() {
+            return Err(MoveError::MissingParent(new_parent));
+        }
+        if self.root_id == Some(node) {
+            return Err(MoveError::Root);
+        }
+        let mut ancestor = Some(new_parent);
+        while let Some(ancestor_id) = ancestor {
+            if ancestor_id == node {
+                return Err(MoveError::Cycle);
+            }
+            ancestor = self.get_node_relatives(ancestor_id).parent;
+        }
+
+        self.unlink(node);
+        let before = match position {
+            InsertPosition::First => self.get_node_relatives(new_parent).first_child,
+            InsertPosition::Last => None,
+            InsertPosition::At(index) => self
+                .get(new_parent)
+                .expect("parent must exist")
+                .children()
+                .nth(index)
+                .map(|child| child.node_id()),
+        };
+        self.link_before(node, new_parent, before);
+        self.notify_move(node);
+        Ok(())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod reparent_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::OrphanChildren;
+    use crate::tree::TreeBuilder;
+
+    fn children(tree: &Tree<i32>, node_id: NodeId) -> Vec<i32> {
+        tree.get(node_id)
+            .unwrap()
+            .children()
+            .map(|child| *child.data())
+            .collect()
+    }
+
+    #[test]
+    fn move_within_siblings() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let ids: Vec<NodeId> = (1..=4)
+            .map(|n| tree.root_mut().unwrap().append(n).node_id())
+            .collect();
+
+        tree.move_subtree(ids[0], root_id, InsertPosition::Last)
+            .unwrap();
+        assert_eq!(children(&tree, root_id), vec![2, 3, 4, 1]);
+        tree.move_subtree(ids[3], root_id, InsertPosition::First)
+            .unwrap();
+        assert_eq!(children(&tree, root_id), vec![4, 2, 3, 1]);
+        tree.move_subtree(ids[3], root_id, InsertPosition::At(2))
+            .unwrap();
+        assert_eq!(children(&tree, root_id), vec![2, 3, 4, 1]);
+        tree.move_subtree(ids[1], root_id, InsertPosition::At(10))
+            .unwrap();
+        assert_eq!(children(&tree, root_id), vec![3, 4, 1, 2]);
+
+        let mut backwards = Vec::new();
+        let mut current = Some(ids[1]);
+        while let Some(node_id) = current {
+            let node = tree.get(node_id).unwrap();
+            backwards.push(*node.data());
+            current = node.prev_sibling().map(|prev| prev.node_id());
+        }
+        assert_eq!(backwards, vec![2, 1, 4, 3]);
+    }
+
+    #[test]
+    fn move_between_parents() {
+        let mut tree = Tree::unfold(3, |n: i32| (n, (0..n).collect()));
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root().unwrap().last_child().unwrap().node_id();
+        let one_id = tree.get(two_id).unwrap().last_child().unwrap().node_id();
+
+        tree.move_subtree(one_id, root_id, InsertPosition::At(1))
+            .unwrap();
+        assert_eq!(children(&tree, root_id), vec![0, 1, 1, 2]);
+        assert_eq!(children(&tree, two_id), vec![0]);
+        assert_eq!(children(&tree, one_id), vec![0]);
+        assert_eq!(
+            tree.get(one_id).unwrap().parent().unwrap().node_id(),
+            root_id
+        );
+
+        let only_id = tree.get(two_id).unwrap().first_child().unwrap().node_id();
+        tree.move_subtree(only_id, one_id, InsertPosition::First)
+            .unwrap();
+        assert!(tree.get(two_id).unwrap().first_child().is_none());
+        assert!(tree.get(two_id).unwrap().last_child().is_none());
+        assert_eq!(children(&tree, one_id), vec![0, 0]);
+    }
+
+    #[test]
+    fn move_orphan() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        let two_id = tree.get_mut(one_id).unwrap().append(2).node_id();
+        tree.remove(one_id, OrphanChildren);
+
+        tree.move_subtree(two_id, root_id, InsertPosition::Last)
+            .unwrap();
+        assert_eq!(children(&tree, root_id), vec![2]);
+    }
+
+    #[test]
+    fn move_errors() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        let other = TreeBuilder::new().with_root(0).build();
+        let other_id = other.root_id().unwrap();
+
+        assert_eq!(
+            tree.move_subtree(other_id, root_id, InsertPosition::Last),
+            Err(MoveError::MissingNode(other_id))
+        );
+        assert_eq!(
+            tree.move_subtree(one_id, other_id, InsertPosition::Last),
+            Err(MoveError::MissingParent(other_id))
+        );
+        assert_eq!(
+            tree.move_subtree(root_id, one_id, InsertPosition::Last),
+            Err(MoveError::Root)
+        );
+        assert_eq!(
+            tree.move_subtree(one_id, one_id, InsertPosition::Last),
+            Err(MoveError::Cycle)
+        );
+        assert_eq!(children(&tree, root_id), vec![1]);
+    }
+}
@@ -0,0 +1,263 @@
+use crate::behaviors::{RemoveBehavior, RetainBehavior};
+use crate::tree::{Tree, TreeBuilder};
+use crate::NodeId;
+
+impl<T> Tree<T> {
+    ///
+    /// Removes every `Node` whose data doesn't satisfy `predicate`, with `behavior` deciding
+    /// what becomes of the children of each removed `Node`.  `predicate` is called once for
+    /// each `Node` reached from the root, in pre-order; `Node`s below a removed one are only
+    /// reached with `RetainBehavior::LiftChildren`.  Orphaned `Node`s already in the `Tree` are
+    /// left alone.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RetainBehavior;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let mut one = root.append(-1);
+    /// one.append(2);
+    /// one.append(-3).append(4);
+    /// root.append(5);
+    ///
+    /// tree.retain_with(|data| *data >= 0, RetainBehavior::LiftChildren);
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 0
+    /// ├── 2
+    /// ├── 4
+    /// └── 5
+    /// ");
+    /// ```
+    ///
+    pub fn retain_with<F>(&mut self, mut predicate: F, behavior: RetainBehavior)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut stack: Vec<NodeId> = self.root_id().into_iter().collect();
+        while let Some(node_id) = stack.pop() {
+            let node = self.get(node_id).expect("retained node doesn't exist?");
+            let child_ids: Vec<NodeId> = node.children().map(|child| child.node_id()).collect();
+            let parent_id = node.parent().map(|parent| parent.node_id());
+
+            if predicate(node.data()) {
+                stack.extend(child_ids.into_iter().rev());
+                continue;
+            }
+
+            match (behavior, parent_id) {
+                (RetainBehavior::DropChildren, _) => {
+                    self.remove(node_id, RemoveBehavior::DropChildren);
+                }
+                (RetainBehavior::LiftChildren, Some(parent_id)) => {
+                    for &child_id in child_ids.iter() {
+                        self.unlink(child_id);
+                        self.link_before(child_id, parent_id, Some(node_id));
+                        self.notify_move(child_id);
+                    }
+                    self.remove(node_id, RemoveBehavior::DropChildren);
+                    stack.extend(child_ids.into_iter().rev());
+                }
+                (RetainBehavior::OrphanChildren, _) | (RetainBehavior::LiftChildren, None) => {
+                    self.remove(node_id, RemoveBehavior::OrphanChildren);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Consumes the `Tree` and builds a new one from the data `f` returns for each `Node`.
+    /// `Node`s for which `f` returns `None` are left out, and their surviving children take
+    /// their place under the nearest surviving ancestor, in order.  `f` is called once for each
+    /// `Node` reached from the root, in pre-order; orphaned `Node`s are dropped.
+    ///
+    /// Returns `None` if the `Tree` is empty or `f` returns `None` for the root, as the
+    /// remaining `Node`s would have no root to hang from.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("block").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let mut group = root.append("(group)");
+    /// group.append("a");
+    /// group.append("b");
+    /// root.append("c");
+    ///
+    /// let lowered = tree
+    ///     .filter_map(|data| if data.starts_with('(') { None } else { Some(data.len()) })
+    ///     .unwrap();
+    ///
+    /// let mut s = String::new();
+    /// lowered.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 5
+    /// ├── 1
+    /// ├── 1
+    /// └── 1
+    /// ");
+    /// ```
+    ///
+    pub fn filter_map<U, F>(mut self, mut f: F) -> Option<Tree<U>>
+    where
+        F: FnMut(T) -> Option<U>,
+    {
+        let root_id = self.root_id()?;
+        let last_child = self.get_node_relatives(root_id).last_child;
+        let root_data = f(self.take_data(root_id))?;
+        let mut tree = TreeBuilder::new()
+            .with_capacity(self.core_tree.len() + 1)
+            .with_root(root_data)
+            .build();
+
+        // (node in `self`, its nearest surviving ancestor in `tree`)
+        let mut stack = Vec::new();
+        let new_root_id = tree.root_id().expect("root must exist");
+        self.push_children(last_child, new_root_id, &mut stack);
+        while let Some((node_id, parent_id)) = stack.pop() {
+            let last_child = self.get_node_relatives(node_id).last_child;
+            let parent_id = match f(self.take_data(node_id)) {
+                Some(data) => tree
+                    .get_mut(parent_id)
+                    .expect("parent must exist")
+                    .append(data)
+                    .node_id(),
+                None => parent_id,
+            };
+            self.push_children(last_child, parent_id, &mut stack);
+        }
+        Some(tree)
+    }
+
+    /// Pushes a `Node`'s children onto `stack` (paired with `parent_id`), starting from its last
+    /// child and working back, so they're popped in order.
+    fn push_children(
+        &self,
+        last_child: Option<NodeId>,
+        parent_id: NodeId,
+        stack: &mut Vec<(NodeId, NodeId)>,
+    ) {
+        let mut next_child = last_child;
+        while let Some(child_id) = next_child {
+            next_child = self.get_node_relatives(child_id).prev_sibling;
+            stack.push((child_id, parent_id));
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod retain_tests {
+    use super::*;
+
+    fn sample() -> Tree<i32> {
+        // 0 -> [-1 -> [2, -3 -> [4]], 5]
+        Tree::unfold(0, |n: i32| {
+            let children = match n {
+                0 => vec![-1, 5],
+                -1 => vec![2, -3],
+                -3 => vec![4],
+                _ => vec![],
+            };
+            (n, children)
+        })
+    }
+
+    fn pre_order(tree: &Tree<i32>) -> Vec<(i32, usize)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| (*node.data(), node.ancestors().count()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn retain_drop_children() {
+        let mut tree = sample();
+        tree.retain_with(|data| *data >= 0, RetainBehavior::DropChildren);
+        assert_eq!(pre_order(&tree), vec![(0, 0), (5, 1)]);
+        assert_eq!(tree.iter_all_roots_first().count(), 2);
+    }
+
+    #[test]
+    fn retain_orphan_children() {
+        let mut tree = sample();
+        tree.retain_with(|data| *data >= 0, RetainBehavior::OrphanChildren);
+        assert_eq!(pre_order(&tree), vec![(0, 0), (5, 1)]);
+        let mut all: Vec<i32> = tree.iter_all_roots_first().map(|n| *n.data()).collect();
+        all.sort();
+        assert_eq!(all, vec![-3, 0, 2, 4, 5]);
+    }
+
+    #[test]
+    fn retain_lift_children() {
+        let mut tree = sample();
+        let mut visited = Vec::new();
+        tree.retain_with(
+            |data| {
+                visited.push(*data);
+                *data >= 0
+            },
+            RetainBehavior::LiftChildren,
+        );
+        assert_eq!(visited, vec![0, -1, 2, -3, 4, 5]);
+        assert_eq!(pre_order(&tree), vec![(0, 0), (2, 1), (4, 1), (5, 1)]);
+
+        let root = tree.root().unwrap();
+        let backwards: Vec<i32> = {
+            let mut values = Vec::new();
+            let mut current = root.last_child().map(|child| child.node_id());
+            while let Some(node_id) = current {
+                let node = tree.get(node_id).unwrap();
+                values.push(*node.data());
+                current = node.prev_sibling().map(|prev| prev.node_id());
+            }
+            values
+        };
+        assert_eq!(backwards, vec![5, 4, 2]);
+    }
+
+    #[test]
+    fn retain_removes_root() {
+        let mut tree = sample();
+        tree.retain_with(|data| *data != 0, RetainBehavior::LiftChildren);
+        assert!(tree.root().is_none());
+        assert_eq!(tree.iter_all_roots_first().count(), 5);
+
+        let mut tree = sample();
+        tree.retain_with(|_| false, RetainBehavior::DropChildren);
+        assert!(tree.root().is_none());
+        assert_eq!(tree.iter_all_roots_first().count(), 0);
+    }
+
+    #[test]
+    fn filter_map_splices_children() {
+        let mut calls = Vec::new();
+        let tree = sample()
+            .filter_map(|data| {
+                calls.push(data);
+                if data >= 0 {
+                    Some(data * 10)
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        assert_eq!(calls, vec![0, -1, 2, -3, 4, 5]);
+        assert_eq!(pre_order(&tree), vec![(0, 0), (20, 1), (40, 1), (50, 1)]);
+        assert_eq!(tree.iter_all_roots_first().count(), 4);
+    }
+
+    #[test]
+    fn filter_map_drops_root() {
+        assert!(sample()
+            .filter_map(|data| if data == 0 { None } else { Some(data) })
+            .is_none());
+        assert!(Tree::<i32>::new().filter_map(Some).is_none());
+    }
+}
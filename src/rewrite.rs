@@ -0,0 +1,739 @@
+//!
+//! Rewriting a `Tree` with rules which replace the sub-trees matching a `Pattern`, until none of
+//! them match anymore.
+//!
+//! Each `Rule` pairs a `Pattern` with a function building a `Replacement` from the `Node`s the
+//! `Pattern` captured.  A `Replacement` is made of new `Node`s and of captured `Node`s, which are
+//! moved into place (along with their sub-trees) rather than cloned.  For one-off substitutions,
+//! `Tree::replace_where` replaces each `Node` passing a predicate with a freshly built `Tree`.
+//!
+//! ```
+//! use slab_tree::pattern::Pattern;
+//! use slab_tree::rewrite::{Replacement, Rule, Strategy};
+//! use slab_tree::tree::TreeBuilder;
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Expr {
+//!     Add,
+//!     Num(i64),
+//!     Var(&'static str),
+//! }
+//!
+//! let is_num = |expr: &Expr| matches!(expr, Expr::Num(_));
+//! let rules = [
+//!     // x + 0 => x
+//!     Rule::new(
+//!         Pattern::data(Expr::Add)
+//!             .with_children(vec![Pattern::any().capture("x"), Pattern::data(Expr::Num(0))]),
+//!         |_, _| Some(Replacement::capture("x")),
+//!     ),
+//!     // a + b => the sum of a and b, when both are numbers
+//!     Rule::new(
+//!         Pattern::data(Expr::Add).with_children(vec![
+//!             Pattern::matching(is_num).capture("a"),
+//!             Pattern::matching(is_num).capture("b"),
+//!         ]),
+//!         |tree, captures| match (tree.get(captures["a"])?.data(), tree.get(captures["b"])?.data()) {
+//!             (Expr::Num(a), Expr::Num(b)) => Some(Replacement::node(Expr::Num(a + b))),
+//!             _ => None,
+//!         },
+//!     ),
+//! ];
+//!
+//! // (y + (1 + -1)) + 2
+//! let mut tree = TreeBuilder::new().with_root(Expr::Add).build();
+//! let mut root = tree.root_mut().unwrap();
+//! let mut inner = root.append(Expr::Add);
+//! inner.append(Expr::Var("y"));
+//! let mut sum = inner.append(Expr::Add);
+//! sum.append(Expr::Num(1));
+//! sum.append(Expr::Num(-1));
+//! root.append(Expr::Num(2));
+//!
+//! assert_eq!(tree.rewrite(&rules, Strategy::BottomUpToFixpoint), Ok(2));
+//!
+//! // y + 2
+//! let root = tree.root().unwrap();
+//! let children: Vec<&Expr> = root.children().map(|child| child.data()).collect();
+//! assert_eq!(children, vec![&Expr::Var("y"), &Expr::Num(2)]);
+//! ```
+//!
+
+use crate::behaviors::RemoveBehavior;
+use crate::node::NodeRef;
+use crate::pattern::Captures;
+use crate::pattern::Pattern;
+use crate::reparent::InsertPosition;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+///
+/// Describes the sub-tree which takes the place of a sub-tree matched by a `Rule`.
+///
+#[derive(Debug)]
+pub struct Replacement<T> {
+    top: Top<T>,
+    children: Vec<Replacement<T>>,
+}
+
+#[derive(Debug)]
+enum Top<T> {
+    Node(T),
+    Capture(String),
+}
+
+impl<T> Replacement<T> {
+    ///
+    /// Returns a `Replacement` made of a new `Node` holding `data`, without any children.
+    ///
+    /// ```
+    /// use slab_tree::rewrite::Replacement;
+    ///
+    /// let replacement = Replacement::node(1).with_children(vec![Replacement::node(2)]);
+    /// ```
+    ///
+    pub fn node(data: T) -> Replacement<T> {
+        Replacement {
+            top: Top::Node(data),
+            children: Vec::new(),
+        }
+    }
+
+    ///
+    /// Returns a `Replacement` made of the `Node` captured under `name`, moved into place
+    /// along with its whole sub-tree.  Each capture can be used once per `Replacement`.
+    ///
+    /// ```
+    /// use slab_tree::rewrite::Replacement;
+    ///
+    /// let replacement = Replacement::node("not").with_children(vec![Replacement::capture("x")]);
+    /// ```
+    ///
+    pub fn capture<S: Into<String>>(name: S) -> Replacement<T> {
+        Replacement {
+            top: Top::Capture(name.into()),
+            children: Vec::new(),
+        }
+    }
+
+    ///
+    /// Gives the new `Node` of this `Replacement` the given children, in order.  A captured
+    /// `Node` keeps its own children, so any children given to a capture are ignored.
+    ///
+    /// ```
+    /// use slab_tree::rewrite::Replacement;
+    ///
+    /// let replacement = Replacement::node("+")
+    ///     .with_children(vec![Replacement::capture("y"), Replacement::capture("x")]);
+    /// ```
+    ///
+    pub fn with_children<I>(mut self, children: I) -> Replacement<T>
+    where
+        I: IntoIterator<Item = Replacement<T>>,
+    {
+        self.children = children.into_iter().collect();
+        self
+    }
+
+    /// Checks that every capture used exists and is used only once.
+    fn check(&self, captures: &Captures) -> Result<(), RewriteError> {
+        let mut used = HashSet::new();
+        let mut stack = vec![self];
+        while let Some(replacement) = stack.pop() {
+            match &replacement.top {
+                Top::Node(_) => stack.extend(&replacement.children),
+                Top::Capture(name) => {
+                    if !captures.contains_key(name) {
+                        return Err(RewriteError::UnknownCapture(name.clone()));
+                    }
+                    if !used.insert(name) {
+                        return Err(RewriteError::CaptureUsedTwice(name.clone()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// Replaces the sub-trees matching a `Pattern` with a `Replacement` built from the `Node`s the
+/// `Pattern` captured.
+///
+pub struct Rule<T> {
+    pattern: Pattern<T>,
+    replace: Box<ReplaceFn<T>>,
+}
+
+type ReplaceFn<T> = dyn Fn(&Tree<T>, &Captures) -> Option<Replacement<T>>;
+
+impl<T> Rule<T> {
+    ///
+    /// Returns a `Rule` rewriting the sub-trees which match `pattern`.  For each match, `replace`
+    /// is given the `Tree` and the captured `Node`s, and returns the `Replacement` for the
+    /// matched sub-tree, or `None` to leave it as it is.
+    ///
+    /// ```
+    /// use slab_tree::pattern::Pattern;
+    /// use slab_tree::rewrite::{Replacement, Rule};
+    ///
+    /// // not(not(x)) => x
+    /// let double_negation = Rule::new(
+    ///     Pattern::data("not").with_children(vec![
+    ///         Pattern::data("not").with_children(vec![Pattern::any().capture("x")]),
+    ///     ]),
+    ///     |_, _| Some(Replacement::capture("x")),
+    /// );
+    /// ```
+    ///
+    pub fn new<F>(pattern: Pattern<T>, replace: F) -> Rule<T>
+    where
+        F: Fn(&Tree<T>, &Captures) -> Option<Replacement<T>> + 'static,
+    {
+        Rule {
+            pattern,
+            replace: Box::new(replace),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Rule<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rule")
+            .field("pattern", &self.pattern)
+            .finish()
+    }
+}
+
+///
+/// The order in which `Tree::rewrite` tries its `Rule`s on the `Tree`'s `Node`s.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    ///
+    /// Visits every `Node` of the root's sub-tree once, children before their parents, and
+    /// rewrites each `Node` at most once.  `Node`s created by a rewrite aren't visited.
+    ///
+    BottomUpOnce,
+
+    ///
+    /// Repeats `BottomUpOnce` until a pass rewrites nothing.  This never ends if the `Rule`s
+    /// can keep rewriting each other's results.
+    ///
+    BottomUpToFixpoint,
+}
+
+///
+/// Describes why `Tree::rewrite` couldn't apply a `Replacement`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteError {
+    ///
+    /// The `Replacement` uses a capture name its `Rule`'s `Pattern` doesn't capture.
+    ///
+    UnknownCapture(String),
+
+    ///
+    /// The `Replacement` uses the same capture more than once, but a captured `Node` can only be
+    /// moved into one place.
+    ///
+    CaptureUsedTwice(String),
+}
+
+impl fmt::Display for RewriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RewriteError::UnknownCapture(name) => {
+                write!(f, "the replacement uses {:?}, which was not captured", name)
+            }
+            RewriteError::CaptureUsedTwice(name) => {
+                write!(f, "the replacement uses {:?} more than once", name)
+            }
+        }
+    }
+}
+
+impl Error for RewriteError {}
+
+impl<T: PartialEq> Tree<T> {
+    ///
+    /// Rewrites the root's sub-tree with `rules`, in the order given by `strategy`.  At each
+    /// visited `Node`, the `Rule`s are tried in order and the first one whose `Pattern` matches
+    /// and which returns a `Replacement` is applied: the `Node` and its sub-tree are replaced by
+    /// the `Replacement`, in the same position among the `Node`'s siblings.  Replacing a `Node`
+    /// with itself (by using the capture of the matched `Node` as the whole `Replacement`) counts
+    /// as no rewrite, so the next `Rule` is tried instead.
+    ///
+    /// Returns the number of rewrites made.  If a `Replacement` uses a capture which doesn't
+    /// exist or uses one twice, an error is returned before that `Replacement` is applied (but
+    /// after any earlier rewrites).  Orphaned `Node`s aren't rewritten.
+    ///
+    /// ```
+    /// use slab_tree::pattern::Pattern;
+    /// use slab_tree::rewrite::{Replacement, Rule, Strategy};
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// // wrap(x) => (x), dropping every wrapper
+    /// let unwrap = Rule::new(
+    ///     Pattern::data("wrap").with_children(vec![Pattern::any().capture("x")]),
+    ///     |_, _| Some(Replacement::capture("x")),
+    /// );
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("wrap").build();
+    /// tree.root_mut().unwrap().append("wrap").append("wrap").append("value");
+    ///
+    /// assert_eq!(tree.rewrite(&[unwrap], Strategy::BottomUpToFixpoint), Ok(3));
+    /// assert_eq!(tree.root().unwrap().data(), &"value");
+    /// assert_eq!(tree.root().unwrap().child_count(), 0);
+    /// ```
+    ///
+    pub fn rewrite(
+        &mut self,
+        rules: &[Rule<T>],
+        strategy: Strategy,
+    ) -> Result<usize, RewriteError> {
+        let mut rewrites = 0;
+        loop {
+            let pass = self.rewrite_pass(rules)?;
+            rewrites += pass;
+            if pass == 0 || strategy == Strategy::BottomUpOnce {
+                return Ok(rewrites);
+            }
+        }
+    }
+
+    fn rewrite_pass(&mut self, rules: &[Rule<T>]) -> Result<usize, RewriteError> {
+        let node_ids: Vec<NodeId> = match self.root() {
+            Some(root) => root
+                .traverse_post_order()
+                .map(|node| node.node_id())
+                .collect(),
+            None => return Ok(0),
+        };
+
+        let mut rewrites = 0;
+        for node_id in node_ids {
+            for rule in rules {
+                // earlier rewrites may have removed the node, in which case nothing matches
+                let captures = match self.match_at(node_id, &rule.pattern) {
+                    Some(captures) => captures,
+                    None => continue,
+                };
+                let replacement = match (rule.replace)(self, &captures) {
+                    Some(replacement) => replacement,
+                    None => continue,
+                };
+                replacement.check(&captures)?;
+                if let Top::Capture(name) = &replacement.top {
+                    if captures[name] == node_id {
+                        continue;
+                    }
+                }
+                self.replace_with(node_id, replacement, &captures);
+                rewrites += 1;
+                break;
+            }
+        }
+        Ok(rewrites)
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Replaces every `Node` of the root's sub-tree for which `predicate` returns `true` (along
+    /// with its sub-tree) with the `Tree` which `build` makes from the `Node`'s data, spliced
+    /// into the same position among the `Node`'s siblings.  The data of the built `Tree` is
+    /// moved, not cloned, and an empty `Tree` simply removes the `Node`.
+    ///
+    /// `Node`s are tested in pre-order, on the `Tree` as it was before any replacement; the
+    /// sub-tree of a replaced `Node` isn't tested, and neither are the `Node`s spliced in.
+    /// Returns the number of `Node`s replaced.  Orphaned `Node`s aren't tested.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("html").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("<header/>");
+    /// root.append("body").append("<header/>");
+    /// root.append("footer");
+    ///
+    /// let replaced = tree.replace_where(
+    ///     |node| node.data().starts_with('<'),
+    ///     |_| {
+    ///         let mut header = TreeBuilder::new().with_root("header").build();
+    ///         header.root_mut().unwrap().append("h1");
+    ///         header
+    ///     },
+    /// );
+    /// assert_eq!(replaced, 2);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let pre_order: Vec<&str> = root.traverse_pre_order().map(|node| *node.data()).collect();
+    /// assert_eq!(
+    ///     pre_order,
+    ///     vec!["html", "header", "h1", "body", "header", "h1", "footer"]
+    /// );
+    /// ```
+    ///
+    pub fn replace_where<P, B>(&mut self, mut predicate: P, mut build: B) -> usize
+    where
+        P: FnMut(&NodeRef<'_, T>) -> bool,
+        B: FnMut(&T) -> Tree<T>,
+    {
+        let mut matched = Vec::new();
+        let mut stack: Vec<NodeRef<'_, T>> = self.root().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if predicate(&node) {
+                matched.push(node.node_id());
+            } else {
+                let start = stack.len();
+                stack.extend(node.children());
+                // visit the children in order
+                stack[start..].reverse();
+            }
+        }
+
+        for &node_id in &matched {
+            let replacement = build(self.get(node_id).expect("matched node must exist").data());
+            self.splice_in_place_of(replacement, node_id);
+        }
+        matched.len()
+    }
+
+    /// Moves the `Node`s of `other`'s root sub-tree into the place of `old_id`, then removes
+    /// `old_id` along with its sub-tree.
+    fn splice_in_place_of(&mut self, mut other: Tree<T>, old_id: NodeId) {
+        // (node in `other`, its new parent in `self`); each node's children are pushed last
+        // to first so they're appended in order
+        let mut stack: Vec<(NodeId, Option<NodeId>)> = other
+            .root_id
+            .map(|root_id| (root_id, None))
+            .into_iter()
+            .collect();
+        while let Some((other_id, parent_id)) = stack.pop() {
+            let mut next_child = other.get_node_relatives(other_id).last_child;
+            let data = other.core_tree.remove(other_id).expect("node must exist");
+            let node_id = match parent_id {
+                Some(parent_id) => self
+                    .get_mut(parent_id)
+                    .expect("parent must exist")
+                    .append(data)
+                    .node_id(),
+                None => {
+                    let node_id = self.core_tree.insert(data);
+                    self.put_in_place_of(node_id, old_id);
+                    self.notify_insert(node_id);
+                    node_id
+                }
+            };
+
+            while let Some(child_id) = next_child {
+                next_child = other.get_node_relatives(child_id).prev_sibling;
+                stack.push((child_id, Some(node_id)));
+            }
+        }
+        self.remove(old_id, RemoveBehavior::DropChildren);
+    }
+
+    /// Puts `replacement` in the place of `node_id`, then removes whatever is left of
+    /// `node_id`'s sub-tree unless the `Node` itself was moved into the replacement.
+    fn replace_with(&mut self, node_id: NodeId, replacement: Replacement<T>, captures: &Captures) {
+        let top_id = match replacement.top {
+            Top::Node(data) => {
+                let top_id = self.core_tree.insert(data);
+                self.put_in_place_of(top_id, node_id);
+                self.notify_insert(top_id);
+                top_id
+            }
+            Top::Capture(name) => {
+                let top_id = captures[&name];
+                self.unlink(top_id);
+                self.put_in_place_of(top_id, node_id);
+                self.notify_move(top_id);
+                top_id
+            }
+        };
+
+        let mut reused = false;
+        // (replacement, its parent); each `Node`'s children are pushed last to first so
+        // they're appended in order
+        let mut stack: Vec<(Replacement<T>, NodeId)> = replacement
+            .children
+            .into_iter()
+            .rev()
+            .map(|child| (child, top_id))
+            .collect();
+        while let Some((replacement, parent_id)) = stack.pop() {
+            match replacement.top {
+                Top::Node(data) => {
+                    let child_id = self
+                        .get_mut(parent_id)
+                        .expect("parent must exist")
+                        .append(data)
+                        .node_id();
+                    stack.extend(
+                        replacement
+                            .children
+                            .into_iter()
+                            .rev()
+                            .map(|child| (child, child_id)),
+                    );
+                }
+                Top::Capture(name) => {
+                    let child_id = captures[&name];
+                    reused |= child_id == node_id;
+                    self.move_subtree(child_id, parent_id, InsertPosition::Last)
+                        .expect("new nodes are never inside a captured sub-tree");
+                }
+            }
+        }
+
+        if !reused {
+            self.remove(node_id, RemoveBehavior::DropChildren);
+        }
+    }
+
+    /// Links the detached `Node` `node_id` where `old_id` is: before it among its siblings, or
+    /// as the root (leaving `old_id` without a parent) if `old_id` is the root.
+    fn put_in_place_of(&mut self, node_id: NodeId, old_id: NodeId) {
+        match self.get_node_relatives(old_id).parent {
+            Some(parent_id) => self.link_before(node_id, parent_id, Some(old_id)),
+            None => self.root_id = Some(node_id),
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod rewrite_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    fn pre_order(tree: &Tree<&'static str>) -> Vec<(&'static str, usize)> {
+        tree.root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (*node.data(), node.ancestors().count()))
+            .collect()
+    }
+
+    // swap(a, b) => pair(b, a)
+    fn swap_rule() -> Rule<&'static str> {
+        Rule::new(
+            Pattern::data("swap").with_children(vec![
+                Pattern::any().capture("a"),
+                Pattern::any().capture("b"),
+            ]),
+            |_, _| {
+                Some(
+                    Replacement::node("pair")
+                        .with_children(vec![Replacement::capture("b"), Replacement::capture("a")]),
+                )
+            },
+        )
+    }
+
+    #[test]
+    fn keeps_sibling_order() {
+        let mut tree = TreeBuilder::new().with_root("list").build();
+        let mut root = tree.root_mut().unwrap();
+        root.append("first");
+        let mut swap = root.append("swap");
+        swap.append("x").append("x1");
+        swap.append("y");
+        root.append("last");
+
+        assert_eq!(tree.rewrite(&[swap_rule()], Strategy::BottomUpOnce), Ok(1));
+        assert_eq!(
+            pre_order(&tree),
+            vec![
+                ("list", 0),
+                ("first", 1),
+                ("pair", 1),
+                ("y", 2),
+                ("x", 2),
+                ("x1", 3),
+                ("last", 1),
+            ]
+        );
+        assert_eq!(tree.all_node_ids().len(), 7);
+    }
+
+    #[test]
+    fn rewrites_the_root() {
+        let mut tree = TreeBuilder::new().with_root("swap").build();
+        tree.root_mut().unwrap().append("x");
+        tree.root_mut().unwrap().append("y");
+        assert_eq!(tree.rewrite(&[swap_rule()], Strategy::BottomUpOnce), Ok(1));
+        assert_eq!(pre_order(&tree), vec![("pair", 0), ("y", 1), ("x", 1)]);
+        assert_eq!(tree.all_node_ids().len(), 3);
+    }
+
+    #[test]
+    fn moves_the_matched_node_itself() {
+        // leaf x => wrapped(x), once per pass
+        let wrap = Rule::new(
+            Pattern::data("x").capture("x").with_children(vec![]),
+            |_, _| {
+                Some(Replacement::node("wrapped").with_children(vec![Replacement::capture("x")]))
+            },
+        );
+        let mut tree = TreeBuilder::new().with_root("x").build();
+        let x_id = tree.root_id().unwrap();
+        assert_eq!(tree.rewrite(&[wrap], Strategy::BottomUpOnce), Ok(1));
+        assert_eq!(pre_order(&tree), vec![("wrapped", 0), ("x", 1)]);
+        assert_eq!(tree.root().unwrap().first_child().unwrap().node_id(), x_id);
+    }
+
+    #[test]
+    fn once_versus_fixpoint() {
+        // a => b, b => c
+        let rules = [
+            Rule::new(Pattern::data("a"), |_, _| Some(Replacement::node("b"))),
+            Rule::new(Pattern::data("b"), |_, _| Some(Replacement::node("c"))),
+        ];
+        let mut tree = TreeBuilder::new().with_root("root").build();
+        tree.root_mut().unwrap().append("a");
+        tree.root_mut().unwrap().append("b");
+
+        let mut once = tree.clone_with_capacity(0);
+        assert_eq!(once.rewrite(&rules, Strategy::BottomUpOnce), Ok(2));
+        assert_eq!(pre_order(&once), vec![("root", 0), ("b", 1), ("c", 1)]);
+
+        assert_eq!(tree.rewrite(&rules, Strategy::BottomUpToFixpoint), Ok(3));
+        assert_eq!(pre_order(&tree), vec![("root", 0), ("c", 1), ("c", 1)]);
+    }
+
+    #[test]
+    fn identity_and_declined_rewrites_fall_through() {
+        let rules = [
+            Rule::new(Pattern::any().capture("me"), |_, _| {
+                Some(Replacement::capture("me"))
+            }),
+            Rule::new(Pattern::data("a"), |_, _| None),
+            Rule::new(Pattern::data("a"), |_, _| Some(Replacement::node("b"))),
+        ];
+        let mut tree = TreeBuilder::new().with_root("a").build();
+        assert_eq!(tree.rewrite(&rules, Strategy::BottomUpToFixpoint), Ok(1));
+        assert_eq!(pre_order(&tree), vec![("b", 0)]);
+    }
+
+    #[test]
+    fn bad_captures_fail_before_rewriting() {
+        let unknown = Rule::new(Pattern::data("a"), |_, _| Some(Replacement::capture("x")));
+        let twice = Rule::new(Pattern::data("a").capture("x"), |_, _| {
+            Some(
+                Replacement::node("b")
+                    .with_children(vec![Replacement::capture("x"), Replacement::capture("x")]),
+            )
+        });
+        let mut tree = TreeBuilder::new().with_root("a").build();
+        assert_eq!(
+            tree.rewrite(&[unknown], Strategy::BottomUpOnce),
+            Err(RewriteError::UnknownCapture("x".to_string()))
+        );
+        assert_eq!(
+            tree.rewrite(&[twice], Strategy::BottomUpOnce),
+            Err(RewriteError::CaptureUsedTwice("x".to_string()))
+        );
+        assert_eq!(pre_order(&tree), vec![("a", 0)]);
+    }
+
+    #[test]
+    fn keeps_indexes_up_to_date() {
+        let mut tree = TreeBuilder::new()
+            .with_root("root")
+            .build()
+            .with_depths()
+            .with_subtree_sizes();
+        let mut root = tree.root_mut().unwrap();
+        let mut swap = root.append("swap");
+        swap.append("x").append("x1");
+        swap.append("y");
+        tree.rewrite(&[swap_rule()], Strategy::BottomUpOnce)
+            .unwrap();
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.subtree_size(), 5);
+        for node in root.traverse_pre_order() {
+            assert_eq!(node.depth(), node.ancestors().count());
+            assert_eq!(node.subtree_size(), node.traverse_pre_order().count());
+        }
+    }
+
+    #[test]
+    fn replace_where_splices_in_order() {
+        let mut tree = TreeBuilder::new().with_root("root").build();
+        let mut root = tree.root_mut().unwrap();
+        root.append("a");
+        root.append("macro").append("ignored");
+        root.append("b");
+        root.append("drop");
+
+        let replaced = tree.replace_where(
+            |node| matches!(*node.data(), "macro" | "drop"),
+            |data| match *data {
+                "macro" => {
+                    let mut expansion = TreeBuilder::new().with_root("expanded").build();
+                    let mut top = expansion.root_mut().unwrap();
+                    top.append("x").append("x1");
+                    top.append("y");
+                    expansion
+                }
+                _ => Tree::new(),
+            },
+        );
+        assert_eq!(replaced, 2);
+        assert_eq!(
+            pre_order(&tree),
+            vec![
+                ("root", 0),
+                ("a", 1),
+                ("expanded", 1),
+                ("x", 2),
+                ("x1", 3),
+                ("y", 2),
+                ("b", 1),
+            ]
+        );
+        assert_eq!(tree.all_node_ids().len(), 7);
+    }
+
+    #[test]
+    fn replace_where_root_and_nested_matches() {
+        let mut tree = TreeBuilder::new().with_root("old").build().with_depths();
+        tree.root_mut().unwrap().append("old");
+
+        // only the outermost match is replaced
+        let replaced = tree.replace_where(
+            |node| *node.data() == "old",
+            |_| {
+                let mut new = TreeBuilder::new().with_root("new").build();
+                new.root_mut().unwrap().append("old");
+                new
+            },
+        );
+        assert_eq!(replaced, 1);
+        assert_eq!(pre_order(&tree), vec![("new", 0), ("old", 1)]);
+        assert_eq!(tree.all_node_ids().len(), 2);
+        let root = tree.root().unwrap();
+        assert_eq!(root.first_child().unwrap().depth(), 1);
+
+        assert_eq!(tree.replace_where(|_| true, |_| Tree::new()), 1);
+        assert!(tree.root().is_none());
+        assert!(tree.all_node_ids().is_empty());
+    }
+
+    #[test]
+    fn empty_tree() {
+        let mut tree: Tree<&'static str> = Tree::new();
+        assert_eq!(
+            tree.rewrite(&[swap_rule()], Strategy::BottomUpToFixpoint),
+            Ok(0)
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//!
+//! Flattening `Tree`s into `(id, parent id, sibling index, data)` rows and building them back.
+//!
+//! This is the adjacency-list form relational tables and dataframes use for hierarchies:
+//!
+//! ```text
+//! id | parent | sibling_index | data
+//! ---+--------+---------------+------
+//!  0 |        |             0 | root
+//!  1 |      0 |             0 | a
+//!  2 |      1 |             0 | b
+//!  3 |      0 |             1 | c
+//! ```
+//!
+//! `Tree::to_rows` numbers the `Node`s by their position in a pre-order traversal, so the ids
+//! are plain integers that don't depend on where the `Node`s happen to be stored.
+//! `Tree::from_rows` accepts any ids, in any order, and only uses the sibling indexes to order
+//! each `Node`'s children, so they may have gaps.
+//!
+
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+///
+/// A single `Node` of a `Tree` flattened into a row.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeRow<T> {
+    /// Identifies the `Node` among the rows.
+    pub id: usize,
+    /// The `id` of the `Node`'s parent, or `None` for the root.
+    pub parent: Option<usize>,
+    /// Where the `Node` goes among its siblings; lower indexes come first.
+    pub sibling_index: usize,
+    /// The `Node`'s data.
+    pub data: T,
+}
+
+///
+/// Describes the ways in which building a `Tree` with `Tree::from_rows` can fail.  Rows are
+/// referred to by their `id`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowError {
+    ///
+    /// More than one row has the given `id`.
+    ///
+    DuplicateId(usize),
+
+    ///
+    /// The row with the given `id` refers to a parent no row has.
+    ///
+    MissingParent {
+        /// The row referring to the parent.
+        id: usize,
+        /// The `id` of the missing parent.
+        parent: usize,
+    },
+
+    ///
+    /// There are rows, but none of them is a root (i.e. has no parent).
+    ///
+    NoRoot,
+
+    ///
+    /// The row with the given `id` is a second root.
+    ///
+    MultipleRoots(usize),
+
+    ///
+    /// Two children of the same parent have the same sibling index.
+    ///
+    DuplicateSiblingIndex {
+        /// The `id` of the parent.
+        parent: usize,
+        /// The sibling index used twice.
+        sibling_index: usize,
+    },
+
+    ///
+    /// The row with the given `id` can't be reached from the root, because it is part of (or
+    /// below) a cycle of parents.
+    ///
+    Cycle(usize),
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowError::DuplicateId(id) => write!(f, "more than one row has id {}", id),
+            RowError::MissingParent { id, parent } => {
+                write!(f, "row {} has parent {}, which doesn't exist", id, parent)
+            }
+            RowError::NoRoot => write!(f, "no row is a root"),
+            RowError::MultipleRoots(id) => write!(f, "row {} is a second root", id),
+            RowError::DuplicateSiblingIndex {
+                parent,
+                sibling_index,
+            } => write!(
+                f,
+                "row {} has more than one child at sibling index {}",
+                parent, sibling_index
+            ),
+            RowError::Cycle(id) => write!(f, "row {} is part of a cycle", id),
+        }
+    }
+}
+
+impl Error for RowError {}
+
+impl<T: Clone> Tree<T> {
+    ///
+    /// Flattens the root's sub-tree into rows, one per `Node` in pre-order.  Each `Node`'s `id`
+    /// is its position in that order, and its `sibling_index` is its position among its
+    /// siblings (0 for the root).  An empty `Tree` produces no rows.
+    ///
+    /// ```
+    /// use slab_tree::rows::TreeRow;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("a").append("b");
+    /// root.append("c");
+    ///
+    /// let rows = tree.to_rows();
+    /// assert_eq!(
+    ///     rows[3],
+    ///     TreeRow { id: 3, parent: Some(0), sibling_index: 1, data: "c" }
+    /// );
+    /// ```
+    ///
+    pub fn to_rows(&self) -> Vec<TreeRow<T>> {
+        let root = match self.root() {
+            Some(root) => root,
+            None => return Vec::new(),
+        };
+
+        let mut rows = Vec::new();
+        // (node, its parent's id, its sibling index); children are pushed last to first so
+        // they're numbered in order
+        let mut stack = vec![(root, None, 0)];
+        while let Some((node, parent, sibling_index)) = stack.pop() {
+            let id = rows.len();
+            rows.push(TreeRow {
+                id,
+                parent,
+                sibling_index,
+                data: node.data().clone(),
+            });
+            let start = stack.len();
+            stack.extend(
+                node.children()
+                    .enumerate()
+                    .map(|(index, child)| (child, Some(id), index)),
+            );
+            stack[start..].reverse();
+        }
+        rows
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Builds a `Tree` from rows, as described in the `rows` module.  The rows can come in any
+    /// order; each `Node`'s children are ordered by their `sibling_index`, and the root's
+    /// `sibling_index` is ignored.  No rows produce an empty `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::rows::{RowError, TreeRow};
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let row = |id, parent, sibling_index, data| TreeRow { id, parent, sibling_index, data };
+    /// let rows = vec![
+    ///     row(30, Some(10), 5, "c"),
+    ///     row(10, None, 0, "root"),
+    ///     row(20, Some(10), 2, "a"),
+    /// ];
+    /// let tree = Tree::from_rows(rows).unwrap();
+    ///
+    /// let root = tree.root().expect("root doesn't exist?");
+    /// let children: Vec<&str> = root.children().map(|node| *node.data()).collect();
+    /// assert_eq!(children, vec!["a", "c"]);
+    ///
+    /// let cycle = vec![row(0, None, 0, "root"), row(1, Some(2), 0, "x"), row(2, Some(1), 0, "y")];
+    /// assert!(matches!(Tree::from_rows(cycle), Err(RowError::Cycle(_))));
+    /// ```
+    ///
+    pub fn from_rows<I>(rows: I) -> Result<Tree<T>, RowError>
+    where
+        I: IntoIterator<Item = TreeRow<T>>,
+    {
+        let rows: Vec<TreeRow<T>> = rows.into_iter().collect();
+
+        let mut positions: HashMap<usize, usize> = HashMap::with_capacity(rows.len());
+        for (position, row) in rows.iter().enumerate() {
+            if positions.insert(row.id, position).is_some() {
+                return Err(RowError::DuplicateId(row.id));
+            }
+        }
+
+        let mut root = None;
+        // (sibling index, position) of each parent's children
+        let mut children: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+        for (position, row) in rows.iter().enumerate() {
+            match row.parent {
+                None if root.is_some() => return Err(RowError::MultipleRoots(row.id)),
+                None => root = Some(position),
+                Some(parent) if !positions.contains_key(&parent) => {
+                    return Err(RowError::MissingParent { id: row.id, parent })
+                }
+                Some(parent) => children
+                    .entry(parent)
+                    .or_default()
+                    .push((row.sibling_index, position)),
+            }
+        }
+        let root = match root {
+            Some(root) => root,
+            None if rows.is_empty() => return Ok(Tree::new()),
+            None => return Err(RowError::NoRoot),
+        };
+        for (&parent, siblings) in children.iter_mut() {
+            siblings.sort_unstable();
+            if let Some(pair) = siblings.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+                return Err(RowError::DuplicateSiblingIndex {
+                    parent,
+                    sibling_index: pair[0].0,
+                });
+            }
+        }
+
+        let ids: Vec<usize> = rows.iter().map(|row| row.id).collect();
+        let mut data: Vec<Option<T>> = rows.into_iter().map(|row| Some(row.data)).collect();
+        let mut take = |position: usize| data[position].take().expect("each row is used once");
+
+        let mut tree = TreeBuilder::new().with_capacity(ids.len()).build();
+        tree.set_root(take(root));
+        let mut built = 1;
+        // (position of a row, the `NodeId` of its parent); children are pushed last to first so
+        // they're appended in order
+        let mut stack: Vec<(usize, NodeId)> = Vec::new();
+        let push_children = |stack: &mut Vec<(usize, NodeId)>, position: usize, node_id| {
+            if let Some(siblings) = children.get(&ids[position]) {
+                stack.extend(
+                    siblings
+                        .iter()
+                        .rev()
+                        .map(|&(_, child_position)| (child_position, node_id)),
+                );
+            }
+        };
+        push_children(&mut stack, root, tree.root_id().expect("root was just set"));
+        while let Some((position, parent_id)) = stack.pop() {
+            let node_id = tree
+                .get_mut(parent_id)
+                .expect("parent must exist")
+                .append(take(position))
+                .node_id();
+            built += 1;
+            push_children(&mut stack, position, node_id);
+        }
+
+        if built < ids.len() {
+            let position = data
+                .iter()
+                .position(Option::is_some)
+                .expect("unbuilt rows still hold their data");
+            return Err(RowError::Cycle(ids[position]));
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod rows_tests {
+    use super::*;
+
+    fn row(id: usize, parent: Option<usize>, sibling_index: usize) -> TreeRow<usize> {
+        TreeRow {
+            id,
+            parent,
+            sibling_index,
+            data: id,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let tree = Tree::unfold(4, |n| (n, (0..n).collect()));
+        let rows = tree.to_rows();
+        assert_eq!(rows.len(), 16);
+        for (position, row) in rows.iter().enumerate() {
+            assert_eq!(row.id, position);
+            assert!(row.parent.iter().all(|&parent| parent < row.id));
+        }
+
+        let mut shuffled = rows.clone();
+        shuffled.reverse();
+        let rebuilt = Tree::from_rows(shuffled).unwrap();
+        assert_eq!(rebuilt.to_rows(), rows);
+        assert!(rebuilt
+            .root()
+            .unwrap()
+            .is_isomorphic_to(&tree.root().unwrap(), |a, b| a == b));
+
+        let empty: Tree<i32> = Tree::new();
+        assert!(empty.to_rows().is_empty());
+        assert!(Tree::<i32>::from_rows(Vec::new()).unwrap().root().is_none());
+    }
+
+    #[test]
+    fn sibling_indexes_order_children() {
+        let rows = vec![
+            row(0, None, 7),
+            row(1, Some(0), 9),
+            row(2, Some(0), 3),
+            row(3, Some(2), 0),
+            row(4, Some(0), 4),
+        ];
+        let tree = Tree::from_rows(rows).unwrap();
+        let pre_order: Vec<usize> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(pre_order, vec![0, 2, 3, 4, 1]);
+    }
+
+    #[test]
+    fn errors() {
+        let cases = vec![
+            (
+                vec![row(0, None, 0), row(0, Some(0), 0)],
+                RowError::DuplicateId(0),
+            ),
+            (
+                vec![row(0, None, 0), row(1, Some(5), 0)],
+                RowError::MissingParent { id: 1, parent: 5 },
+            ),
+            (
+                vec![row(0, Some(1), 0), row(1, Some(0), 0)],
+                RowError::NoRoot,
+            ),
+            (
+                vec![row(0, None, 0), row(1, None, 0)],
+                RowError::MultipleRoots(1),
+            ),
+            (
+                vec![row(0, None, 0), row(1, Some(0), 2), row(2, Some(0), 2)],
+                RowError::DuplicateSiblingIndex {
+                    parent: 0,
+                    sibling_index: 2,
+                },
+            ),
+            (
+                vec![row(0, None, 0), row(1, Some(1), 0), row(2, Some(1), 1)],
+                RowError::Cycle(1),
+            ),
+        ];
+        for (rows, error) in cases {
+            assert_eq!(Tree::from_rows(rows).unwrap_err(), error);
+        }
+    }
+}
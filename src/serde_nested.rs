@@ -0,0 +1,487 @@
+//!
+//! A nested serialization format for `Tree`s, where each `Node` is written as an object with a
+//! `data` field and a `children` field:
+//!
+//! ```json
+//! {"data": 1, "children": [{"data": 2, "children": []}, {"data": 3, "children": []}]}
+//! ```
+//!
+//! `NodeId`s are not written at all; the structure of the `Tree` is carried entirely by the
+//! nesting.  An empty `Tree` is written as a unit (`null` in JSON).  When deserializing, a
+//! missing `children` field is treated as an empty list.
+//!
+//! Deserializing inserts each `Node` into the `Tree` as soon as its data has been read, rather
+//! than reading the whole document into an intermediate value first, so peak memory stays close
+//! to the size of the finished `Tree`.  That needs the `data` field to come before the
+//! `children` field, which is how `serialize` writes them; children listed before the data are
+//! read into memory until the data turns up.
+//!
+//! Since each level of the `Tree` is a level of nesting in the serialized form, serializers and
+//! deserializers recurse once per level and use stack space proportional to the depth of the
+//! `Tree`, and formats may refuse very deep `Tree`s (`serde_json` stops at 128 levels by
+//! default).  The `binary` module has no such limit.
+//!
+//! The functions in this module are meant to be used with serde's `with` attribute:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use slab_tree::tree::{Tree, TreeBuilder};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Document {
+//!     #[serde(with = "slab_tree::serde_nested")]
+//!     outline: Tree<String>,
+//! }
+//!
+//! let mut outline = TreeBuilder::new().with_root("intro".to_string()).build();
+//! outline.root_mut().unwrap().append("details".to_string());
+//!
+//! let json = serde_json::to_string(&Document { outline }).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"outline":{"data":"intro","children":[{"data":"details","children":[]}]}}"#
+//! );
+//!
+//! let document: Document = serde_json::from_str(&json).unwrap();
+//! let root = document.outline.root().unwrap();
+//! assert_eq!(root.first_child().unwrap().data(), "details");
+//! ```
+//!
+//! # Borrowed data
+//!
+//! Data is deserialized with its own `Deserialize` implementation, so a `Tree<&str>` can borrow
+//! every string from the input instead of copying it (as long as the deserializer supports
+//! borrowing, like `serde_json::from_str`, and the strings need no unescaping).  The field
+//! holding the `Tree` needs `#[serde(borrow)]` so that serde ties the lifetimes together:
+//!
+//! ```
+//! use serde::Deserialize;
+//! use slab_tree::tree::Tree;
+//!
+//! #[derive(Deserialize)]
+//! struct Document<'a> {
+//!     #[serde(borrow, with = "slab_tree::serde_nested")]
+//!     outline: Tree<&'a str>,
+//! }
+//!
+//! let json = r#"{"outline": {"data": "intro", "children": [{"data": "details"}]}}"#.to_string();
+//! let document: Document = serde_json::from_str(&json).unwrap();
+//!
+//! let root = document.outline.root().unwrap();
+//! let details = root.first_child().unwrap();
+//! assert_eq!(*details.data(), "details");
+//! assert!(json.as_bytes().as_ptr_range().contains(&details.data().as_ptr()));
+//! ```
+//!
+//! serde always deserializes a bare `Cow<str>` into an owned string, so to borrow where possible
+//! (and fall back to an owned string for escaped input), wrap it in a type with a
+//! `#[serde(borrow)]` field:
+//!
+//! ```
+//! use serde::Deserialize;
+//! use slab_tree::tree::Tree;
+//! use std::borrow::Cow;
+//!
+//! #[derive(Deserialize)]
+//! struct Text<'a>(#[serde(borrow)] Cow<'a, str>);
+//!
+//! let json = r#"{"data": "plain", "children": [{"data": "esc\\aped"}]}"#;
+//! let mut deserializer = serde_json::Deserializer::from_str(json);
+//! let tree: Tree<Text> = slab_tree::serde_nested::deserialize(&mut deserializer).unwrap();
+//!
+//! let root = tree.root().unwrap();
+//! assert!(matches!(root.data().0, Cow::Borrowed("plain")));
+//! assert!(matches!(root.first_child().unwrap().data().0, Cow::Owned(_)));
+//! ```
+//!
+
+use crate::node::NodeRef;
+use crate::tree::Tree;
+use crate::NodeId;
+use serde::de::DeserializeSeed;
+use serde::de::Error;
+use serde::de::IgnoredAny;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::ser::SerializeStruct;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use std::fmt;
+
+///
+/// Serializes `tree` in the nested format.
+///
+/// ```
+/// use slab_tree::tree::TreeBuilder;
+///
+/// let tree = TreeBuilder::new().with_root(1).build();
+///
+/// let mut json = Vec::new();
+/// slab_tree::serde_nested::serialize(&tree, &mut serde_json::Serializer::new(&mut json)).unwrap();
+/// assert_eq!(json, br#"{"data":1,"children":[]}"#);
+/// ```
+///
+pub fn serialize<T, S>(tree: &Tree<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    match tree.root() {
+        Some(root) => NestedRef(root).serialize(serializer),
+        None => serializer.serialize_unit(),
+    }
+}
+
+///
+/// Deserializes a `Tree` from the nested format.
+///
+/// ```
+/// use slab_tree::tree::Tree;
+///
+/// let json = r#"{"data": 1, "children": [{"data": 2}]}"#;
+/// let tree: Tree<i32> =
+///     slab_tree::serde_nested::deserialize(&mut serde_json::Deserializer::from_str(json)).unwrap();
+///
+/// let root = tree.root().expect("root doesn't exist?");
+/// assert_eq!(*root.first_child().unwrap().data(), 2);
+/// ```
+///
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Tree<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let mut tree = Tree::new();
+    deserializer.deserialize_option(Root { tree: &mut tree })?;
+    Ok(tree)
+}
+
+struct NestedRef<'a, T>(NodeRef<'a, T>);
+
+impl<T: Serialize> Serialize for NestedRef<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Node", 2)?;
+        state.serialize_field("data", self.0.data())?;
+        state.serialize_field("children", &Children(&self.0))?;
+        state.end()
+    }
+}
+
+struct Children<'a, 'b, T>(&'b NodeRef<'a, T>);
+
+impl<T: Serialize> Serialize for Children<'_, '_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.children().map(NestedRef))
+    }
+}
+
+const FIELDS: &[&str] = &["data", "children"];
+
+/// Reads the root `Node` of an empty `Tree`, if there is one.
+struct Root<'a, T> {
+    tree: &'a mut Tree<T>,
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for Root<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a node or nothing")
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_none<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        NodeSeed {
+            tree: self.tree,
+            parent: None,
+        }
+        .deserialize(deserializer)
+    }
+}
+
+/// Reads a `Node` (along with its sub-tree) into the `Tree`, as the last child of `parent` or
+/// as the root.
+struct NodeSeed<'a, T> {
+    tree: &'a mut Tree<T>,
+    parent: Option<NodeId>,
+}
+
+impl<T> NodeSeed<'_, T> {
+    fn insert(&mut self, data: T) -> NodeId {
+        match self.parent {
+            Some(parent_id) => self
+                .tree
+                .get_mut(parent_id)
+                .expect("parent must exist")
+                .append(data)
+                .node_id(),
+            None => self.tree.set_root(data),
+        }
+    }
+
+    fn children(&mut self, parent: NodeId) -> ChildrenSeed<'_, T> {
+        ChildrenSeed {
+            tree: &mut *self.tree,
+            parent,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for NodeSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_struct("Node", FIELDS, self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for NodeSeed<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a node")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        let data = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &"a node with data"))?;
+        let node_id = self.insert(data);
+        seq.next_element_seed(self.children(node_id))?;
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        let mut node_id = None;
+        let mut has_children = false;
+        // children which came before the data, and so have no parent to go under yet
+        let mut early_children: Vec<Nested<T>> = Vec::new();
+
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Data if node_id.is_some() => {
+                    return Err(A::Error::duplicate_field("data"));
+                }
+                Field::Data => node_id = Some(self.insert(map.next_value()?)),
+                Field::Children if has_children => {
+                    return Err(A::Error::duplicate_field("children"));
+                }
+                Field::Children => {
+                    has_children = true;
+                    match node_id {
+                        Some(node_id) => map.next_value_seed(self.children(node_id))?,
+                        None => early_children = map.next_value()?,
+                    }
+                }
+                Field::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let node_id = node_id.ok_or_else(|| A::Error::missing_field("data"))?;
+        for child in early_children {
+            child.append_to(self.tree, node_id);
+        }
+        Ok(())
+    }
+}
+
+/// Reads a sequence of `Node`s into the `Tree` as children of `parent`.
+struct ChildrenSeed<'a, T> {
+    tree: &'a mut Tree<T>,
+    parent: NodeId,
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for ChildrenSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for ChildrenSeed<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of nodes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while seq
+            .next_element_seed(NodeSeed {
+                tree: &mut *self.tree,
+                parent: Some(self.parent),
+            })?
+            .is_some()
+        {}
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum Field {
+    Data,
+    Children,
+    #[serde(other)]
+    Other,
+}
+
+/// A `Node` read into memory, for children which can't be inserted into the `Tree` yet.
+#[derive(Deserialize)]
+#[serde(rename = "Node")]
+struct Nested<T> {
+    data: T,
+    #[serde(default = "Vec::new")]
+    children: Vec<Nested<T>>,
+}
+
+impl<T> Nested<T> {
+    fn append_to(self, tree: &mut Tree<T>, parent_id: NodeId) {
+        let mut stack = vec![(parent_id, self)];
+
+        while let Some((parent_id, Nested { data, children })) = stack.pop() {
+            let node_id = tree
+                .get_mut(parent_id)
+                .expect("parent was inserted before its children")
+                .append(data)
+                .node_id();
+            stack.extend(children.into_iter().rev().map(|child| (node_id, child)));
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod serde_nested_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    fn to_json<T: Serialize>(tree: &Tree<T>) -> String {
+        let mut json = Vec::new();
+        serialize(tree, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        String::from_utf8(json).unwrap()
+    }
+
+    fn from_json<'de, T: Deserialize<'de>>(json: &'de str) -> Tree<T> {
+        deserialize(&mut serde_json::Deserializer::from_str(json)).unwrap()
+    }
+
+    fn pre_order(tree: &Tree<i32>) -> Vec<(i32, usize)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| (*node.data(), node.ancestors().count()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn serialize_empty() {
+        assert_eq!(to_json(&Tree::<i32>::new()), "null");
+    }
+
+    #[test]
+    fn deserialize_empty() {
+        let tree: Tree<i32> = from_json("null");
+        assert!(tree.root().is_none());
+    }
+
+    #[test]
+    fn serialize_nested() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        root.append(1).append(2);
+        root.append(3);
+
+        assert_eq!(
+            to_json(&tree),
+            r#"{"data":0,"children":[{"data":1,"children":[{"data":2,"children":[]}]},{"data":3,"children":[]}]}"#
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        one.append(2);
+        one.append(3).append(4);
+        root.append(5);
+
+        let json = to_json(&tree);
+        assert_eq!(pre_order(&from_json(&json)), pre_order(&tree));
+    }
+
+    #[test]
+    fn deserialize_missing_children() {
+        let tree: Tree<i32> = from_json(r#"{"data": 0, "children": [{"data": 1}]}"#);
+        assert_eq!(pre_order(&tree), vec![(0, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn deserialize_missing_data_fails() {
+        let result: Result<Tree<i32>, _> = deserialize(&mut serde_json::Deserializer::from_str(
+            r#"{"children": []}"#,
+        ));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn deserialize_children_before_data() {
+        let tree: Tree<i32> = from_json(
+            r#"{"children": [{"children": [{"data": 2}], "data": 1}, {"data": 3}], "data": 0}"#,
+        );
+        assert_eq!(pre_order(&tree), vec![(0, 0), (1, 1), (2, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn deserialize_borrowed() {
+        // the children come first, so they're read into memory before being inserted
+        let json = r#"{"children": [{"data": "b"}, {"data": "c"}], "data": "a"}"#;
+        let tree: Tree<&str> = from_json(json);
+
+        let root = tree.root().unwrap();
+        let data: Vec<&str> = root.traverse_pre_order().map(|node| *node.data()).collect();
+        assert_eq!(data, vec!["a", "b", "c"]);
+        let input = json.as_bytes().as_ptr_range();
+        assert!(data.iter().all(|data| input.contains(&data.as_ptr())));
+    }
+
+    #[test]
+    fn deserialize_ignores_unknown_fields() {
+        let tree: Tree<i32> = from_json(r#"{"data": 0, "id": 7, "children": [{"data": 1}]}"#);
+        assert_eq!(pre_order(&tree), vec![(0, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn deserialize_sequences() {
+        let tree: Tree<i32> = from_json("[0, [[1, [[2]]], [3]]]");
+        assert_eq!(pre_order(&tree), vec![(0, 0), (1, 1), (2, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn deserialize_duplicate_fields_fails() {
+        for json in &[
+            r#"{"data": 0, "data": 1}"#,
+            r#"{"data": 0, "children": [], "children": []}"#,
+        ] {
+            let result: Result<Tree<i32>, _> =
+                deserialize(&mut serde_json::Deserializer::from_str(json));
+            assert!(result.is_err());
+        }
+    }
+}
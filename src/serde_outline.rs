@@ -0,0 +1,321 @@
+//!
+//! Builds a `Tree<String>` from any self-describing nested document (YAML, JSON, TOML, ...)
+//! where the nesting of the document defines the hierarchy:
+//!
+//! * each key of a map becomes a child `Node`, and the key's value is read beneath it
+//! * each element of a sequence is read beneath the current `Node`
+//! * a scalar (string, number, boolean or char) becomes a leaf `Node` holding its text
+//! * a unit or null adds nothing
+//!
+//! ```
+//! use slab_tree::serde_outline;
+//!
+//! let yaml = "
+//! server:
+//!   host: localhost
+//!   ports: [80, 443]
+//! logging: ~
+//! ";
+//!
+//! let tree = serde_outline::deserialize(serde_yaml::Deserializer::from_str(yaml), "config")
+//!     .unwrap();
+//!
+//! let mut s = String::new();
+//! tree.write_formatted(&mut s).unwrap();
+//! assert_eq!(&s, "\
+//! \"config\"
+//! ├── \"server\"
+//! │   ├── \"host\"
+//! │   │   └── \"localhost\"
+//! │   └── \"ports\"
+//! │       ├── \"80\"
+//! │       └── \"443\"
+//! └── \"logging\"
+//! ");
+//! ```
+//!
+//! Since the input is nested, reading it uses stack space proportional to the depth of the
+//! document.
+//!
+
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use serde::de::DeserializeSeed;
+use serde::de::Deserializer;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use std::fmt;
+
+///
+/// Reads a nested document from `deserializer` into a `Tree<String>` whose root holds `root`.
+///
+/// ```
+/// use slab_tree::serde_outline;
+///
+/// let json = r#"{"a": {"b": "c"}, "d": [1, 2]}"#;
+/// let tree =
+///     serde_outline::deserialize(&mut serde_json::Deserializer::from_str(json), "root").unwrap();
+///
+/// let root = tree.root().expect("root doesn't exist?");
+/// let children: Vec<&str> = root.children().map(|node| node.data().as_str()).collect();
+/// assert_eq!(children, vec!["a", "d"]);
+/// ```
+///
+pub fn deserialize<'de, D, S>(deserializer: D, root: S) -> Result<Tree<String>, D::Error>
+where
+    D: Deserializer<'de>,
+    S: Into<String>,
+{
+    let mut tree = TreeBuilder::new().with_root(root.into()).build();
+    let parent = tree.root_id().expect("root was just set");
+    Children {
+        tree: &mut tree,
+        parent,
+    }
+    .deserialize(deserializer)?;
+    Ok(tree)
+}
+
+/// Reads a value as the children of `parent`.
+struct Children<'a> {
+    tree: &'a mut Tree<String>,
+    parent: NodeId,
+}
+
+impl Children<'_> {
+    fn append(&mut self, data: String) -> NodeId {
+        self.tree
+            .get_mut(self.parent)
+            .expect("parent must exist")
+            .append(data)
+            .node_id()
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Children<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Children<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map, sequence or scalar")
+    }
+
+    fn visit_bool<E>(mut self, v: bool) -> Result<(), E> {
+        self.append(v.to_string());
+        Ok(())
+    }
+
+    fn visit_i64<E>(mut self, v: i64) -> Result<(), E> {
+        self.append(v.to_string());
+        Ok(())
+    }
+
+    fn visit_u64<E>(mut self, v: u64) -> Result<(), E> {
+        self.append(v.to_string());
+        Ok(())
+    }
+
+    fn visit_f64<E>(mut self, v: f64) -> Result<(), E> {
+        self.append(v.to_string());
+        Ok(())
+    }
+
+    fn visit_char<E>(mut self, v: char) -> Result<(), E> {
+        self.append(v.to_string());
+        Ok(())
+    }
+
+    fn visit_str<E>(mut self, v: &str) -> Result<(), E> {
+        self.append(v.to_string());
+        Ok(())
+    }
+
+    fn visit_string<E>(mut self, v: String) -> Result<(), E> {
+        self.append(v);
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_none<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let parent = self.parent;
+        while seq
+            .next_element_seed(Children {
+                tree: &mut *self.tree,
+                parent,
+            })?
+            .is_some()
+        {}
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key_seed(Key)? {
+            let parent = self.append(key);
+            map.next_value_seed(Children {
+                tree: &mut *self.tree,
+                parent,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a scalar map key as text.
+struct Key;
+
+impl<'de> DeserializeSeed<'de> for Key {
+    type Value = String;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<String, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Key {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a scalar map key")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<String, E> {
+        Ok(v)
+    }
+
+    fn visit_unit<E>(self) -> Result<String, E> {
+        Ok(String::new())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod serde_outline_tests {
+    use super::*;
+
+    fn outline(tree: &Tree<String>) -> Vec<(String, usize)> {
+        tree.root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (node.data().clone(), node.ancestors().count()))
+            .collect()
+    }
+
+    fn from_json(json: &str) -> Result<Tree<String>, serde_json::Error> {
+        deserialize(&mut serde_json::Deserializer::from_str(json), "root")
+    }
+
+    fn from_yaml(yaml: &str) -> Result<Tree<String>, serde_yaml::Error> {
+        deserialize(serde_yaml::Deserializer::from_str(yaml), "root")
+    }
+
+    #[test]
+    fn scalars() {
+        let tree = from_json(r#"[true, -1, 2, 1.5, "s", null]"#).unwrap();
+        let expected: Vec<(String, usize)> = vec![
+            ("root".to_string(), 0),
+            ("true".to_string(), 1),
+            ("-1".to_string(), 1),
+            ("2".to_string(), 1),
+            ("1.5".to_string(), 1),
+            ("s".to_string(), 1),
+        ];
+        assert_eq!(outline(&tree), expected);
+    }
+
+    #[test]
+    fn nested_maps_and_sequences() {
+        let tree = from_yaml(
+            "
+a:
+  - b
+  - c: d
+  - [e, f]
+g: {}
+",
+        )
+        .unwrap();
+
+        let expected: Vec<(String, usize)> = vec![
+            ("root".to_string(), 0),
+            ("a".to_string(), 1),
+            ("b".to_string(), 2),
+            ("c".to_string(), 2),
+            ("d".to_string(), 3),
+            ("e".to_string(), 2),
+            ("f".to_string(), 2),
+            ("g".to_string(), 1),
+        ];
+        assert_eq!(outline(&tree), expected);
+    }
+
+    #[test]
+    fn non_string_keys() {
+        let tree = from_yaml("1: one\ntrue: yes\n").unwrap();
+        let keys: Vec<String> = tree
+            .root()
+            .unwrap()
+            .children()
+            .map(|node| node.data().clone())
+            .collect();
+        assert_eq!(keys, vec!["1".to_string(), "true".to_string()]);
+    }
+
+    #[test]
+    fn empty_document() {
+        let tree = from_json("null").unwrap();
+        assert_eq!(outline(&tree), vec![("root".to_string(), 0)]);
+    }
+
+    #[test]
+    fn invalid_key() {
+        assert!(from_yaml("[a]: b\n").is_err());
+    }
+}
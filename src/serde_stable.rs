@@ -0,0 +1,463 @@
+//!
+//! A serialization format for `Tree`s that preserves `NodeId`s.
+//!
+//! Unlike `serde_nested`, this writes out the `Tree`'s underlying storage as it is, including
+//! empty slots and the generation of every `Node`, so a deserialized `Tree` has its `Node`s in
+//! exactly the same places.  A `NodeId` is only ever valid for the `Tree` it came from, though,
+//! and a deserialized `Tree` is a new `Tree`; `Tree::rebind` translates `NodeId`s from the
+//! serialized `Tree` into `NodeId`s for the deserialized one.
+//!
+//! As with `serde_nested`, data is deserialized with its own `Deserialize` implementation, so a
+//! `Tree<&str>` (in a field marked `#[serde(borrow)]`) can borrow its strings from the input.
+//!
+//! This module also implements `Serialize` and `Deserialize` for `NodeId`, so maps keyed by
+//! `NodeId`s can be stored next to the `Tree`.  Only a `NodeId`'s position in its `Tree` is
+//! written; deserialized `NodeId`s don't belong to any `Tree` until they are rebound.
+//!
+//! The functions in this module are meant to be used with serde's `with` attribute:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use slab_tree::tree::{Tree, TreeBuilder};
+//! use slab_tree::NodeId;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Document {
+//!     #[serde(with = "slab_tree::serde_stable")]
+//!     outline: Tree<String>,
+//!     bookmark: NodeId,
+//! }
+//!
+//! let mut outline = TreeBuilder::new().with_root("intro".to_string()).build();
+//! let bookmark = outline.root_mut().unwrap().append("details".to_string()).node_id();
+//!
+//! let json = serde_json::to_string(&Document { outline, bookmark }).unwrap();
+//! let document: Document = serde_json::from_str(&json).unwrap();
+//!
+//! let bookmark = document.outline.rebind(document.bookmark).unwrap();
+//! assert_eq!(document.outline.get(bookmark).unwrap().data(), "details");
+//! ```
+//!
+
+use crate::core_tree::CoreTree;
+use crate::node::Node;
+use crate::node::Relatives;
+use crate::observer::Hooks;
+use crate::slab::Index;
+use crate::slab::RawSlot;
+use crate::slab::Slab;
+use crate::tree::Tree;
+use crate::NodeId;
+use serde::de::Error;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use snowflake::ProcessUniqueId;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// A `NodeId`'s position in its `Tree`: its slot index and generation.
+type Position = (usize, u64);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Tree")]
+struct Stable<D> {
+    root: Option<Position>,
+    generation: u64,
+    first_free_slot: Option<usize>,
+    slots: Vec<StableSlot<D>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Slot", rename_all = "snake_case")]
+enum StableSlot<D> {
+    Empty {
+        next_free_slot: Option<usize>,
+    },
+    Filled {
+        generation: u64,
+        data: D,
+        parent: Option<Position>,
+        prev_sibling: Option<Position>,
+        next_sibling: Option<Position>,
+        first_child: Option<Position>,
+        last_child: Option<Position>,
+    },
+}
+
+///
+/// Serializes a `Tree` along with the positions of all of its `Node`s.
+///
+pub fn serialize<T, S>(tree: &Tree<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    let (raw_slots, first_free_slot, generation) = tree.core_tree.slab().raw_parts();
+    let position = |node_id: Option<NodeId>| node_id.map(|node_id| node_id.index.into_parts());
+
+    let slots = raw_slots
+        .into_iter()
+        .map(|slot| match slot {
+            RawSlot::Empty { next_free_slot } => StableSlot::Empty { next_free_slot },
+            RawSlot::Filled { item, index } => StableSlot::Filled {
+                generation: index.into_parts().1,
+                data: &item.data,
+                parent: position(item.relatives.parent),
+                prev_sibling: position(item.relatives.prev_sibling),
+                next_sibling: position(item.relatives.next_sibling),
+                first_child: position(item.relatives.first_child),
+                last_child: position(item.relatives.last_child),
+            },
+        })
+        .collect();
+
+    Stable {
+        root: position(tree.root_id),
+        generation,
+        first_free_slot,
+        slots,
+    }
+    .serialize(serializer)
+}
+
+///
+/// Deserializes a `Tree` written by `serialize`, with every `Node` at the same position it had
+/// in the serialized `Tree`.
+///
+/// Fails if the input doesn't describe a consistent `Tree`.
+///
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Tree<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let stable = Stable::<T>::deserialize(deserializer)?;
+    stable
+        .into_tree()
+        .ok_or_else(|| D::Error::custom("inconsistent tree structure"))
+}
+
+impl<T> Stable<T> {
+    fn into_tree(self) -> Option<Tree<T>> {
+        let tree_id = ProcessUniqueId::new();
+        let node_id = |position: Option<Position>| -> Option<Option<NodeId>> {
+            match position {
+                Some((index, generation)) => Some(Some(NodeId {
+                    tree_id,
+                    index: Index::from_parts(index, generation)?,
+                })),
+                None => Some(None),
+            }
+        };
+
+        let mut raw_slots = Vec::with_capacity(self.slots.len());
+        for (position, slot) in self.slots.into_iter().enumerate() {
+            raw_slots.push(match slot {
+                StableSlot::Empty { next_free_slot } => RawSlot::Empty { next_free_slot },
+                StableSlot::Filled {
+                    generation,
+                    data,
+                    parent,
+                    prev_sibling,
+                    next_sibling,
+                    first_child,
+                    last_child,
+                } => RawSlot::Filled {
+                    item: Node {
+                        data,
+                        relatives: Relatives {
+                            parent: node_id(parent)?,
+                            prev_sibling: node_id(prev_sibling)?,
+                            next_sibling: node_id(next_sibling)?,
+                            first_child: node_id(first_child)?,
+                            last_child: node_id(last_child)?,
+                            child_count: 0,
+                        },
+                    },
+                    index: Index::from_parts(position, generation)?,
+                },
+            });
+        }
+
+        let slab = Slab::from_raw_parts(raw_slots, self.first_free_slot, self.generation)?;
+        let mut tree = Tree {
+            root_id: node_id(self.root)?,
+            core_tree: CoreTree::from_slab(tree_id, slab),
+            hooks: Hooks::default(),
+        };
+
+        if !is_consistent(&tree) {
+            return None;
+        }
+        // child counts aren't serialized, since they follow from the parents
+        for node_id in tree.all_node_ids() {
+            if let Some(parent_id) = tree.get_node_relatives(node_id).parent {
+                if let Some(parent) = tree.get_node_mut(parent_id) {
+                    parent.relatives.child_count += 1;
+                }
+            }
+        }
+        Some(tree)
+    }
+}
+
+/// Checks that every link between `Node`s is mirrored by the `Node` it points to, and that every
+/// `Node` is reachable exactly once from the `Node`s without parents.
+fn is_consistent<T>(tree: &Tree<T>) -> bool {
+    let (raw_slots, _, _) = tree.core_tree.slab().raw_parts();
+    let node_ids: Vec<NodeId> = raw_slots
+        .into_iter()
+        .filter_map(|slot| match slot {
+            RawSlot::Filled { index, .. } => Some(NodeId {
+                tree_id: tree.core_tree.id(),
+                index,
+            }),
+            RawSlot::Empty { .. } => None,
+        })
+        .collect();
+    let relatives = |node_id: Option<NodeId>| {
+        node_id
+            .and_then(|id| tree.get_node(id))
+            .map(|n| n.relatives)
+    };
+
+    if let Some(root_id) = tree.root_id {
+        match relatives(Some(root_id)) {
+            Some(root) if root.parent.is_none() => {}
+            _ => return false,
+        }
+    }
+
+    for &node_id in &node_ids {
+        let node = tree.get_node_relatives(node_id);
+
+        if node.parent.is_some() && relatives(node.parent).is_none() {
+            return false;
+        }
+        match (relatives(node.first_child), relatives(node.last_child)) {
+            (None, None) if node.first_child.is_none() && node.last_child.is_none() => {}
+            (Some(first), Some(last)) => {
+                if first.parent != Some(node_id)
+                    || first.prev_sibling.is_some()
+                    || last.parent != Some(node_id)
+                    || last.next_sibling.is_some()
+                {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+        if let Some(next_id) = node.next_sibling {
+            match relatives(Some(next_id)) {
+                Some(next) if next.prev_sibling == Some(node_id) && next.parent == node.parent => {}
+                _ => return false,
+            }
+        }
+        if let Some(prev_id) = node.prev_sibling {
+            match relatives(Some(prev_id)) {
+                Some(prev) if prev.next_sibling == Some(node_id) && prev.parent == node.parent => {}
+                _ => return false,
+            }
+        }
+    }
+
+    // walk every subtree hanging off a parentless chain of siblings
+    let mut visited = HashSet::new();
+    let mut stack: Vec<NodeId> = node_ids
+        .iter()
+        .copied()
+        .filter(|&node_id| {
+            let node = tree.get_node_relatives(node_id);
+            node.parent.is_none() && node.prev_sibling.is_none()
+        })
+        .collect();
+    while let Some(node_id) = stack.pop() {
+        if !visited.insert(node_id) {
+            return false;
+        }
+        let node = tree.get_node_relatives(node_id);
+        stack.extend(node.next_sibling);
+        stack.extend(node.first_child);
+    }
+
+    visited.len() == node_ids.len()
+}
+
+/// The tree id of every deserialized `NodeId`, which no `Tree` has.
+fn detached_tree_id() -> ProcessUniqueId {
+    static DETACHED: OnceLock<ProcessUniqueId> = OnceLock::new();
+    *DETACHED.get_or_init(ProcessUniqueId::new)
+}
+
+impl Serialize for NodeId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.index.into_parts().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<NodeId, D::Error> {
+        let (index, generation) = Position::deserialize(deserializer)?;
+        let index = Index::from_parts(index, generation)
+            .ok_or_else(|| D::Error::custom("node position out of range"))?;
+        Ok(NodeId {
+            tree_id: detached_tree_id(),
+            index,
+        })
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod serde_stable_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::tree::TreeBuilder;
+    use serde_json::json;
+    use serde_json::Value;
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper {
+        #[serde(with = "super")]
+        tree: Tree<i32>,
+    }
+
+    fn to_value(tree: Tree<i32>) -> Value {
+        serde_json::to_value(Wrapper { tree }).unwrap()["tree"].clone()
+    }
+
+    fn from_value(value: Value) -> Result<Tree<i32>, serde_json::Error> {
+        serde_json::from_value::<Wrapper>(json!({ "tree": value })).map(|w| w.tree)
+    }
+
+    fn sample() -> (Tree<i32>, Vec<NodeId>) {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append(2).node_id();
+        let three_id = root.append(3).node_id();
+        let four_id = tree.get_mut(two_id).unwrap().append(4).node_id();
+        let five_id = tree.get_mut(two_id).unwrap().append(5).node_id();
+        tree.remove(three_id, DropChildren);
+        let six_id = tree.get_mut(four_id).unwrap().append(6).node_id();
+        let root_id = tree.root_id().unwrap();
+        (tree, vec![root_id, two_id, four_id, five_id, six_id])
+    }
+
+    #[test]
+    fn round_trip_preserves_positions() {
+        let (tree, ids) = sample();
+        let data: Vec<i32> = ids
+            .iter()
+            .map(|&id| *tree.get(id).unwrap().data())
+            .collect();
+
+        let value = to_value(tree);
+        let mut tree = from_value(value.clone()).unwrap();
+
+        for (&id, data) in ids.iter().zip(data) {
+            let id = tree.rebind(id).unwrap();
+            let node = tree.get(id).unwrap();
+            assert_eq!(node.data(), &data);
+            assert_eq!(node.child_count(), node.children().count());
+        }
+        let root_id = tree.rebind(ids[0]).unwrap();
+        assert_eq!(tree.root_id(), Some(root_id));
+
+        // the free list and generation survive too, so the trees keep behaving the same
+        assert_eq!(to_value(from_value(value.clone()).unwrap()), value);
+        let seven_id = tree.root_mut().unwrap().append(7).node_id();
+        let (mut original, _) = sample();
+        let original_seven_id = original.root_mut().unwrap().append(7).node_id();
+        assert_eq!(
+            serde_json::to_value(seven_id).unwrap(),
+            serde_json::to_value(original_seven_id).unwrap()
+        );
+    }
+
+    #[test]
+    fn round_trip_borrowed() {
+        #[derive(Serialize, Deserialize)]
+        struct Borrowed<'a> {
+            #[serde(borrow, with = "super")]
+            tree: Tree<&'a str>,
+        }
+
+        let mut tree = TreeBuilder::new().with_root("a").build();
+        let b_id = tree.root_mut().unwrap().append("b").node_id();
+        let json = serde_json::to_string(&Borrowed { tree }).unwrap();
+
+        let borrowed: Borrowed = serde_json::from_str(&json).unwrap();
+        let b = borrowed
+            .tree
+            .get(borrowed.tree.rebind(b_id).unwrap())
+            .unwrap();
+        assert_eq!(*b.data(), "b");
+        assert!(json.as_bytes().as_ptr_range().contains(&b.data().as_ptr()));
+    }
+
+    #[test]
+    fn round_trip_with_orphans() {
+        let (mut tree, ids) = sample();
+        tree.remove(ids[1], OrphanChildren);
+
+        let tree = from_value(to_value(tree)).unwrap();
+        let four_id = tree.rebind(ids[2]).unwrap();
+        let five_id = tree.rebind(ids[3]).unwrap();
+        assert!(tree.rebind(ids[1]).is_none());
+        assert!(tree.get(four_id).unwrap().parent().is_none());
+        assert_eq!(
+            tree.get(four_id).unwrap().next_sibling().unwrap().node_id(),
+            five_id
+        );
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        let tree = from_value(to_value(Tree::new())).unwrap();
+        assert!(tree.root_id().is_none());
+    }
+
+    #[test]
+    fn node_id_round_trip() {
+        let (tree, ids) = sample();
+        let json = serde_json::to_string(&ids).unwrap();
+        let detached: Vec<NodeId> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            detached,
+            serde_json::from_str::<Vec<NodeId>>(&json).unwrap()
+        );
+
+        for (detached, id) in detached.into_iter().zip(ids) {
+            assert!(tree.get(detached).is_none());
+            assert_eq!(tree.rebind(detached), Some(id));
+        }
+    }
+
+    #[test]
+    fn rejects_inconsistent_trees() {
+        let (tree, _) = sample();
+        let value = to_value(tree);
+
+        let mut bad_link = value.clone();
+        bad_link["slots"][0]["filled"]["first_child"] = json!([4, 1]);
+        assert!(from_value(bad_link).is_err());
+
+        let mut bad_generation = value.clone();
+        bad_generation["slots"][1]["filled"]["generation"] = json!(7);
+        assert!(from_value(bad_generation).is_err());
+
+        let mut bad_free_list = value.clone();
+        bad_free_list["first_free_slot"] = json!(0);
+        assert!(from_value(bad_free_list).is_err());
+
+        let mut self_link = value.clone();
+        self_link["slots"][3]["filled"]["next_sibling"] = json!([3, 0]);
+        assert!(from_value(self_link).is_err());
+
+        let mut bad_root = value;
+        bad_root["root"] = json!([1, 0]);
+        assert!(from_value(bad_root).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::observer::TreeIndex;
+use crate::observer::TreeObserver;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::any::Any;
+use std::collections::HashMap;
+
+/// The cached size of a `Node`'s sub-tree, along with the parent it was last counted under.
+#[derive(Clone, Copy)]
+struct Entry {
+    size: usize,
+    parent: Option<NodeId>,
+}
+
+/// Keeps the size of every `Node`'s sub-tree, adjusting only the sizes along the path to the
+/// root on each change.
+#[derive(Default)]
+pub(crate) struct SubtreeSizes {
+    entries: HashMap<NodeId, Entry>,
+}
+
+impl SubtreeSizes {
+    pub(crate) fn get(&self, node_id: NodeId) -> Option<usize> {
+        self.entries.get(&node_id).map(|entry| entry.size)
+    }
+
+    /// Grows (or shrinks) the sub-trees of `node_id` and all of its ancestors by `added` (or
+    /// `removed`) `Node`s.
+    fn adjust(&mut self, mut node_id: Option<NodeId>, added: usize, removed: usize) {
+        while let Some(entry) = node_id.and_then(|id| self.entries.get_mut(&id)) {
+            entry.size = entry.size + added - removed;
+            node_id = entry.parent;
+        }
+    }
+}
+
+impl<T> TreeObserver<T> for SubtreeSizes {
+    fn on_insert(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        // a new root already has the old root as its child, which takes it along when it moves
+        let mut size = 1;
+        for child in tree
+            .get(node_id)
+            .into_iter()
+            .flat_map(|node| node.children())
+        {
+            if let Some(entry) = self.entries.get_mut(&child.node_id()) {
+                size += entry.size;
+                entry.parent = Some(node_id);
+            }
+        }
+        let parent = tree.get_node_relatives(node_id).parent;
+        self.entries.insert(node_id, Entry { size, parent });
+        self.adjust(parent, size, 0);
+    }
+
+    fn on_remove(&mut self, _tree: &Tree<T>, node_id: NodeId) {
+        if let Some(entry) = self.entries.remove(&node_id) {
+            // when a whole sub-tree goes, only its root still has a counted parent
+            self.adjust(entry.parent, 0, entry.size);
+        }
+    }
+
+    fn on_move(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        let parent = tree.get_node_relatives(node_id).parent;
+        if let Some(entry) = self.entries.get_mut(&node_id) {
+            let Entry {
+                size,
+                parent: old_parent,
+            } = *entry;
+            if old_parent != parent {
+                entry.parent = parent;
+                self.adjust(old_parent, 0, size);
+                self.adjust(parent, size, 0);
+            }
+        }
+    }
+}
+
+impl<T> TreeIndex<T> for SubtreeSizes {
+    fn rebuild(&mut self, tree: &Tree<T>) {
+        self.entries.clear();
+        for node_id in tree.all_node_ids() {
+            if tree.get_node_relatives(node_id).parent.is_some() {
+                continue;
+            }
+            let top = tree.get(node_id).expect("listed node must exist");
+            for node in top.traverse_post_order() {
+                let size = 1 + node
+                    .children()
+                    .map(|child| self.entries[&child.node_id()].size)
+                    .sum::<usize>();
+                let parent = node.parent().map(|parent| parent.node_id());
+                self.entries.insert(node.node_id(), Entry { size, parent });
+            }
+        }
+    }
+
+    fn remap(&mut self, node_ids: &HashMap<NodeId, NodeId>) {
+        let remap = |node_id: NodeId| node_ids.get(&node_id).copied().unwrap_or(node_id);
+        self.entries = self
+            .entries
+            .drain()
+            .map(|(node_id, entry)| {
+                let entry = Entry {
+                    parent: entry.parent.map(remap),
+                    ..entry
+                };
+                (remap(node_id), entry)
+            })
+            .collect();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Makes the `Tree` keep the size of every `Node`'s sub-tree, so that
+    /// `NodeRef::subtree_size` can read it in constant time instead of walking the sub-tree.
+    /// Each structural change only adjusts the sizes along the path to the root.
+    ///
+    /// This is a cheaper alternative to keeping the sizes with `with_aggregate`, which
+    /// recomputes each size from the sizes of all of the `Node`'s children.  Setting up the
+    /// sizes on a `Tree` which already keeps them does nothing.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build().with_subtree_sizes();
+    /// let one_id = tree.root_mut().unwrap().append(1).node_id();
+    /// let two_id = tree.get_mut(one_id).unwrap().append(2).node_id();
+    /// tree.get_mut(two_id).unwrap().append(3);
+    /// assert_eq!(tree.root().unwrap().subtree_size(), 4);
+    ///
+    /// tree.remove(two_id, RemoveBehavior::DropChildren);
+    /// assert_eq!(tree.root().unwrap().subtree_size(), 2);
+    /// assert_eq!(tree.get(one_id).unwrap().subtree_size(), 1);
+    /// ```
+    ///
+    pub fn with_subtree_sizes(mut self) -> Tree<T> {
+        if self.subtree_sizes().is_none() {
+            let mut sizes = SubtreeSizes::default();
+            sizes.rebuild(&self);
+            self.hooks.add_index(Box::new(sizes));
+        }
+        self
+    }
+
+    pub(crate) fn subtree_sizes(&self) -> Option<&SubtreeSizes> {
+        self.hooks
+            .indexes()
+            .find_map(|index| index.as_any().downcast_ref::<SubtreeSizes>())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod sizes_tests {
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::reparent::InsertPosition;
+    use crate::tree::Tree;
+
+    // compares every cached size against a fresh walk of the sub-tree
+    fn assert_sizes(tree: &Tree<i32>) {
+        let sizes = tree.subtree_sizes().unwrap();
+        for node_id in tree.all_node_ids() {
+            let node = tree.get(node_id).unwrap();
+            assert_eq!(sizes.get(node_id), Some(node.traverse_pre_order().count()));
+        }
+    }
+
+    #[test]
+    fn follows_edits() {
+        let mut tree = Tree::unfold(4, |n| (n, (0..n).collect())).with_subtree_sizes();
+        assert_sizes(&tree);
+        let root_id = tree.root_id().unwrap();
+        let three_id = tree.root().unwrap().last_child().unwrap().node_id();
+        let two_id = tree.get(three_id).unwrap().last_child().unwrap().node_id();
+
+        tree.get_mut(two_id).unwrap().prepend(5).append(6);
+        assert_sizes(&tree);
+        tree.move_subtree(two_id, root_id, InsertPosition::First)
+            .unwrap();
+        assert_sizes(&tree);
+        tree.remove(two_id, OrphanChildren);
+        assert_sizes(&tree);
+        tree.remove(three_id, DropChildren);
+        assert_sizes(&tree);
+        tree.set_root(7);
+        assert_sizes(&tree);
+        assert_eq!(tree.root().unwrap().subtree_size(), 9);
+    }
+
+    #[test]
+    fn survives_compaction() {
+        let tree = Tree::unfold(3, |n| (n, (0..n).collect()));
+        let one_id = tree.root().unwrap().children().nth(1).unwrap().node_id();
+        let mut tree = tree.with_subtree_sizes().with_subtree_sizes();
+        tree.remove(one_id, DropChildren);
+        tree.compact();
+        assert_sizes(&tree);
+        assert_eq!(tree.root().unwrap().subtree_size(), 6);
+    }
+}
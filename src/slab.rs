@@ -1,30 +1,110 @@
+use std::collections::HashSet;
+use std::collections::TryReserveError;
 use std::mem;
 
+// The integer types used to address slots and tell their occupants apart; both are narrowed
+// to `u32` by the `u32-index` feature (narrowing only the index wouldn't shrink `Index`, since
+// the generation would still dictate its alignment).
+#[cfg(not(feature = "u32-index"))]
+type RawIndex = usize;
+#[cfg(feature = "u32-index")]
+type RawIndex = u32;
+#[cfg(not(feature = "u32-index"))]
+type Generation = u64;
+#[cfg(feature = "u32-index")]
+type Generation = u32;
+
+#[cfg(not(feature = "u32-index"))]
+fn to_raw(index: usize) -> RawIndex {
+    index
+}
+
+#[cfg(feature = "u32-index")]
+fn to_raw(index: usize) -> RawIndex {
+    use std::convert::TryFrom;
+    RawIndex::try_from(index).expect("slab index exceeds u32::MAX")
+}
+
+#[cfg(not(feature = "u32-index"))]
+fn from_raw(index: RawIndex) -> usize {
+    index
+}
+
+#[cfg(feature = "u32-index")]
+fn from_raw(index: RawIndex) -> usize {
+    index as usize
+}
+
 #[derive(Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
 pub(super) struct Index {
-    index: usize,
-    generation: u64,
+    index: RawIndex,
+    generation: Generation,
 }
 
 #[derive(Debug, PartialEq)]
 enum Slot<T> {
-    Empty { next_free_slot: Option<usize> },
-    Filled { item: T, generation: u64 },
+    Empty { next_free_slot: Option<RawIndex> },
+    Filled { item: T, generation: Generation },
 }
 
+#[cfg(not(feature = "chunked-slab"))]
+type Slots<T> = Vec<Slot<T>>;
+
+#[cfg(feature = "chunked-slab")]
+type Slots<T> = crate::chunks::Chunks<Slot<T>>;
+
 #[derive(Debug, PartialEq)]
 pub(super) struct Slab<T> {
-    data: Vec<Slot<T>>,
-    first_free_slot: Option<usize>,
-    generation: u64,
+    data: Slots<T>,
+    first_free_slot: Option<RawIndex>,
+    generation: Generation,
+    // number of filled slots
+    len: usize,
+    journal: Option<Journal<T>>,
+}
+
+/// Records the original contents of each slot touched after a snapshot so it can be restored.
+#[derive(Debug)]
+struct Journal<T> {
+    clone_item: fn(&T) -> T,
+    entries: Vec<(usize, Slot<T>)>,
+    marks: Vec<Mark>,
+    // slots already recorded since the latest mark
+    touched: HashSet<usize>,
+    next_serial: u64,
+}
+
+#[derive(Debug)]
+struct Mark {
+    serial: u64,
+    entries: usize,
+    len: usize,
+    filled: usize,
+    first_free_slot: Option<RawIndex>,
+}
+
+/// Journals don't take part in comparisons between slabs.
+impl<T> PartialEq for Journal<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// Identifies a snapshot taken with `Slab::snapshot`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub(super) struct SnapshotMark {
+    index: usize,
+    serial: u64,
 }
 
 impl<T> Slab<T> {
     pub(super) fn new(capacity: usize) -> Slab<T> {
         Slab {
-            data: Vec::with_capacity(capacity),
+            data: Slots::with_capacity(capacity),
             first_free_slot: None,
             generation: 0,
+            len: 0,
+            journal: None,
         }
     }
 
@@ -32,14 +112,50 @@ impl<T> Slab<T> {
         self.data.capacity()
     }
 
+    /// Makes room for at least `additional` more items without reallocating, reporting an
+    /// allocation failure instead of aborting.
+    pub(super) fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.data.try_reserve(additional)
+    }
+
+    /// Makes room for at least `additional` more items, so inserting them won't reallocate.
+    pub(super) fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional);
+    }
+
+    /// Grows the slab ahead of time if the next `insert` would have to.
+    pub(super) fn reserve_slot(&mut self) {
+        if self.first_free_slot.is_none() {
+            self.data.reserve(1);
+        }
+    }
+
+    /// Makes sure the next `insert` won't have to grow the slab.
+    pub(super) fn try_reserve_slot(&mut self) -> Result<(), TryReserveError> {
+        match self.first_free_slot {
+            Some(_) => Ok(()),
+            None => self.data.try_reserve(1),
+        }
+    }
+
+    /// Empties the slab (and drops any journal) while keeping its allocation.
+    pub(super) fn clear(&mut self) {
+        self.data.clear();
+        self.first_free_slot = None;
+        self.len = 0;
+        self.journal = None;
+    }
+
     pub(super) fn insert(&mut self, item: T) -> Index {
         let new_slot = Slot::Filled {
             item,
             generation: self.generation,
         };
+        self.len += 1;
 
         if let Some(index) = self.first_free_slot {
-            match mem::replace(&mut self.data[index], new_slot) {
+            self.record(from_raw(index));
+            match mem::replace(&mut self.data[from_raw(index)], new_slot) {
                 Slot::Empty { next_free_slot } => {
                     self.first_free_slot = next_free_slot;
                 }
@@ -53,64 +169,576 @@ impl<T> Slab<T> {
         } else {
             self.data.push(new_slot);
             Index {
-                index: self.data.len() - 1,
+                index: to_raw(self.data.len() - 1),
                 generation: self.generation,
             }
         }
     }
 
     pub(super) fn remove(&mut self, index: Index) -> Option<T> {
-        if index.index >= self.data.len() {
-            return None;
-        }
+        // only filled slots with a matching generation can be removed
+        self.get(index)?;
+        self.record(from_raw(index.index));
 
         let slot = mem::replace(
-            &mut self.data[index.index],
+            &mut self.data[from_raw(index.index)],
             Slot::Empty {
                 next_free_slot: self.first_free_slot,
             },
         );
 
         match slot {
-            Slot::Filled { item, generation } => {
-                if index.generation == generation {
-                    self.generation += 1;
-                    self.first_free_slot = Some(index.index);
-                    Some(item)
-                } else {
-                    self.data[index.index] = Slot::Filled { item, generation };
+            Slot::Filled { item, .. } => {
+                self.generation = self.generation.wrapping_add(1);
+                self.first_free_slot = Some(index.index);
+                self.len -= 1;
+                Some(item)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub(super) fn get(&self, index: Index) -> Option<&T> {
+        self.data
+            .get(from_raw(index.index))
+            .and_then(|slot| match slot {
+                Slot::Filled { item, generation } => {
+                    if index.generation == *generation {
+                        return Some(item);
+                    }
                     None
                 }
+                _ => None,
+            })
+    }
+
+    pub(super) fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        self.get(index)?;
+        self.record(from_raw(index.index));
+        self.data
+            .get_mut(from_raw(index.index))
+            .and_then(|slot| match slot {
+                Slot::Filled { item, generation } => {
+                    if index.generation == *generation {
+                        return Some(item);
+                    }
+                    None
+                }
+                _ => None,
+            })
+    }
+
+    /// Returns the number of filled slots.
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the indexes of all filled slots, in order.
+    pub(super) fn indices(&self) -> impl Iterator<Item = Index> + '_ {
+        self.data
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot {
+                Slot::Filled { generation, .. } => Some(Index {
+                    index: to_raw(index),
+                    generation: *generation,
+                }),
+                Slot::Empty { .. } => None,
+            })
+    }
+
+    /// Returns the number of bytes each slot takes up.
+    pub(super) fn slot_size(&self) -> usize {
+        mem::size_of::<Slot<T>>()
+    }
+
+    /// Moves every filled slot down over the empty ones (keeping their order), releases the
+    /// unused capacity and drops any journal.  Returns the old and new index of every moved item.
+    ///
+    /// Moved items get a generation no index has had before, so indexes that were stale before
+    /// compaction can't refer to them afterwards.
+    pub(super) fn compact(&mut self) -> Vec<(Index, Index)> {
+        self.journal = None;
+        self.first_free_slot = None;
+
+        let mut moves = Vec::new();
+        let mut generation = None;
+        let mut write = 0;
+        for read in 0..self.data.len() {
+            if let Slot::Filled { .. } = self.data[read] {
+                if read != write {
+                    let new_generation = *generation.get_or_insert_with(|| {
+                        self.generation = self.generation.wrapping_add(1);
+                        self.generation
+                    });
+                    self.data.swap(read, write);
+                    if let Slot::Filled { generation, .. } = &mut self.data[write] {
+                        moves.push((
+                            Index {
+                                index: to_raw(read),
+                                generation: *generation,
+                            },
+                            Index {
+                                index: to_raw(write),
+                                generation: new_generation,
+                            },
+                        ));
+                        *generation = new_generation;
+                    }
+                }
+                write += 1;
             }
-            s => {
-                self.data[index.index] = s;
-                None
+        }
+
+        self.data.truncate(write);
+        self.data.shrink_to_fit();
+        moves
+    }
+
+    /// Moves the items to the front of the slab in the order their indexes are listed in
+    /// `order`, which must list every filled slot exactly once, keeping the slab's capacity.
+    /// Returns the old and new index of every item that moved; moved items get a fresh
+    /// generation so their old indexes go stale.  Any journal is dropped.
+    pub(super) fn reorder(&mut self, order: &[Index]) -> Vec<(Index, Index)> {
+        self.journal = None;
+        self.first_free_slot = None;
+
+        let capacity = self.data.capacity();
+        let mut old = mem::replace(&mut self.data, Slots::with_capacity(capacity));
+        let mut moves = Vec::new();
+        let mut new_generation = None;
+        for &index in order {
+            let read = from_raw(index.index);
+            let write = self.data.len();
+            let slot = mem::replace(
+                &mut old[read],
+                Slot::Empty {
+                    next_free_slot: None,
+                },
+            );
+            if let Slot::Filled {
+                item,
+                mut generation,
+            } = slot
+            {
+                if read != write {
+                    let new_generation = *new_generation.get_or_insert_with(|| {
+                        self.generation = self.generation.wrapping_add(1);
+                        self.generation
+                    });
+                    moves.push((
+                        index,
+                        Index {
+                            index: to_raw(write),
+                            generation: new_generation,
+                        },
+                    ));
+                    generation = new_generation;
+                }
+                self.data.push(Slot::Filled { item, generation });
             }
         }
+        debug_assert!(
+            old.iter().all(|slot| matches!(slot, Slot::Empty { .. })),
+            "every filled slot must be listed"
+        );
+        moves
     }
 
-    pub(super) fn get(&self, index: Index) -> Option<&T> {
-        self.data.get(index.index).and_then(|slot| match slot {
-            Slot::Filled { item, generation } => {
-                if index.generation == *generation {
-                    return Some(item);
+    /// Copies the slab slot for slot (keeping every index valid), mapping each item with `f`.
+    /// The copy has no journal.
+    pub(super) fn map<'a, U, F: FnMut(&'a T) -> U>(&'a self, mut f: F) -> Slab<U> {
+        let mut data = Slots::with_capacity(self.data.capacity());
+        data.extend(self.data.iter().map(|slot| match slot {
+            Slot::Empty { next_free_slot } => Slot::Empty {
+                next_free_slot: *next_free_slot,
+            },
+            Slot::Filled { item, generation } => Slot::Filled {
+                item: f(item),
+                generation: *generation,
+            },
+        }));
+        Slab {
+            data,
+            first_free_slot: self.first_free_slot,
+            generation: self.generation,
+            len: self.len,
+            journal: None,
+        }
+    }
+
+    /// Copies the slab slot for slot into `target` (keeping every index valid), reusing the
+    /// allocation of `target` and reserving room for `extra` more slots.  The copy has no journal.
+    pub(super) fn clone_into(&self, target: &mut Slab<T>, extra: usize)
+    where
+        T: Clone,
+    {
+        target.data.clear();
+        target.data.reserve(self.data.len() + extra);
+        target.data.extend(self.data.iter().map(|slot| match slot {
+            Slot::Empty { next_free_slot } => Slot::Empty {
+                next_free_slot: *next_free_slot,
+            },
+            Slot::Filled { item, generation } => Slot::Filled {
+                item: item.clone(),
+                generation: *generation,
+            },
+        }));
+        target.first_free_slot = self.first_free_slot;
+        target.generation = self.generation;
+        target.len = self.len;
+        target.journal = None;
+    }
+
+    /// Consumes the slab, mapping each item with `f` in parallel while keeping every index
+    /// valid.  The result has no journal.
+    #[cfg(feature = "rayon")]
+    pub(super) fn par_map<U, F>(self, f: F) -> Slab<U>
+    where
+        T: Send,
+        U: Send,
+        F: Fn(T) -> U + Sync + Send,
+    {
+        let map_slot = |slot: Slot<T>| match slot {
+            Slot::Empty { next_free_slot } => Slot::Empty { next_free_slot },
+            Slot::Filled { item, generation } => Slot::Filled {
+                item: f(item),
+                generation,
+            },
+        };
+        #[cfg(not(feature = "chunked-slab"))]
+        let data = {
+            use rayon::prelude::*;
+
+            let mut data = Vec::with_capacity(self.data.capacity());
+            self.data
+                .into_par_iter()
+                .map(map_slot)
+                .collect_into_vec(&mut data);
+            data
+        };
+        #[cfg(feature = "chunked-slab")]
+        let data = self.data.par_map(map_slot);
+        Slab {
+            data,
+            first_free_slot: self.first_free_slot,
+            generation: self.generation,
+            len: self.len,
+            journal: None,
+        }
+    }
+
+    /// Calls `f` with every filled slot's index and item, bypassing any journal.
+    pub(super) fn for_each_mut<F: FnMut(Index, &mut T)>(&mut self, mut f: F) {
+        for (index, slot) in self.data.iter_mut().enumerate() {
+            if let Slot::Filled { item, generation } = slot {
+                let index = Index {
+                    index: to_raw(index),
+                    generation: *generation,
+                };
+                f(index, item);
+            }
+        }
+    }
+}
+
+impl<T> Slab<T> {
+    /// Starts journaling changes (if it isn't already) and marks the current state.
+    pub(super) fn snapshot(&mut self, clone_item: fn(&T) -> T) -> SnapshotMark {
+        let journal = self.journal.get_or_insert_with(|| Journal {
+            clone_item,
+            entries: Vec::new(),
+            marks: Vec::new(),
+            touched: HashSet::new(),
+            next_serial: 0,
+        });
+
+        let serial = journal.next_serial;
+        journal.next_serial += 1;
+        journal.touched.clear();
+        journal.marks.push(Mark {
+            serial,
+            entries: journal.entries.len(),
+            len: self.data.len(),
+            filled: self.len,
+            first_free_slot: self.first_free_slot,
+        });
+
+        SnapshotMark {
+            index: journal.marks.len() - 1,
+            serial,
+        }
+    }
+
+    /// Rolls back to the given mark, discarding any marks taken after it.
+    pub(super) fn restore(&mut self, snapshot: SnapshotMark) -> bool {
+        let journal = match self.journal.as_mut() {
+            Some(journal) => journal,
+            None => return false,
+        };
+        let mark = match journal.marks.get(snapshot.index) {
+            Some(mark) if mark.serial == snapshot.serial => mark,
+            _ => return false,
+        };
+
+        for (index, slot) in journal.entries.drain(mark.entries..).rev() {
+            self.data[index] = slot;
+        }
+        self.data.truncate(mark.len);
+        self.first_free_slot = mark.first_free_slot;
+        self.len = mark.filled;
+        // never hand out the same generation twice, or ids from before the restore could alias
+        // nodes inserted after it
+        self.generation = self.generation.wrapping_add(1);
+
+        journal.marks.truncate(snapshot.index + 1);
+        journal.touched.clear();
+        true
+    }
+
+    /// Stops journaling and forgets all marks.
+    pub(super) fn clear_snapshots(&mut self) {
+        self.journal = None;
+    }
+
+    fn record(&mut self, index: usize) {
+        if let Some(journal) = self.journal.as_mut() {
+            let len = journal.marks.last().map_or(0, |mark| mark.len);
+            if index < len && journal.touched.insert(index) {
+                let slot = match &self.data[index] {
+                    Slot::Empty { next_free_slot } => Slot::Empty {
+                        next_free_slot: *next_free_slot,
+                    },
+                    Slot::Filled { item, generation } => Slot::Filled {
+                        item: (journal.clone_item)(item),
+                        generation: *generation,
+                    },
+                };
+                journal.entries.push((index, slot));
+            }
+        }
+    }
+}
+
+impl Index {
+    /// Splits the index into its slot index and generation.
+    pub(crate) fn into_parts(self) -> (usize, u64) {
+        #[cfg(not(feature = "u32-index"))]
+        let generation = self.generation;
+        #[cfg(feature = "u32-index")]
+        let generation = u64::from(self.generation);
+        (from_raw(self.index), generation)
+    }
+
+    /// Rebuilds an index from its parts, if they fit in the index types.
+    pub(crate) fn from_parts(index: usize, generation: u64) -> Option<Index> {
+        #[cfg(not(feature = "u32-index"))]
+        let (index, generation) = (index, generation);
+        #[cfg(feature = "u32-index")]
+        let (index, generation) = {
+            use std::convert::TryFrom;
+            (
+                RawIndex::try_from(index).ok()?,
+                Generation::try_from(generation).ok()?,
+            )
+        };
+        Some(Index { index, generation })
+    }
+}
+
+/// The contents of a slot, as exposed for serializing a slab verbatim.
+#[cfg(feature = "serde")]
+pub(super) enum RawSlot<T> {
+    Empty { next_free_slot: Option<usize> },
+    Filled { item: T, index: Index },
+}
+
+#[cfg(feature = "serde")]
+impl<T> Slab<T> {
+    /// Returns the slots of the slab in order, followed by its first free slot and generation.
+    pub(super) fn raw_parts(&self) -> (Vec<RawSlot<&T>>, Option<usize>, u64) {
+        let slots = self
+            .data
+            .iter()
+            .enumerate()
+            .map(|(index, slot)| match slot {
+                Slot::Empty { next_free_slot } => RawSlot::Empty {
+                    next_free_slot: next_free_slot.map(from_raw),
+                },
+                Slot::Filled { item, generation } => RawSlot::Filled {
+                    item,
+                    index: Index {
+                        index: to_raw(index),
+                        generation: *generation,
+                    },
+                },
+            })
+            .collect();
+        #[cfg(not(feature = "u32-index"))]
+        let generation = self.generation;
+        #[cfg(feature = "u32-index")]
+        let generation = u64::from(self.generation);
+        (slots, self.first_free_slot.map(from_raw), generation)
+    }
+
+    /// Rebuilds a slab from the output of `raw_parts`, if it describes a consistent slab: the
+    /// free list must be acyclic and cover exactly the empty slots, and no filled slot may have a
+    /// generation beyond the slab's.
+    pub(super) fn from_raw_parts(
+        slots: Vec<RawSlot<T>>,
+        first_free_slot: Option<usize>,
+        generation: u64,
+    ) -> Option<Slab<T>> {
+        let generation = Index::from_parts(0, generation)?.generation;
+        let empty_count = slots
+            .iter()
+            .filter(|slot| matches!(slot, RawSlot::Empty { .. }))
+            .count();
+
+        let mut seen = HashSet::new();
+        let mut next = first_free_slot;
+        while let Some(index) = next {
+            match slots.get(index) {
+                Some(RawSlot::Empty { next_free_slot }) if seen.insert(index) => {
+                    next = *next_free_slot;
                 }
-                None
+                _ => return None,
             }
-            _ => None,
+        }
+        if seen.len() != empty_count {
+            return None;
+        }
+
+        let len = slots.len() - empty_count;
+        let mut data = Slots::with_capacity(slots.len());
+        for (position, slot) in slots.into_iter().enumerate() {
+            data.push(match slot {
+                RawSlot::Empty { next_free_slot } => Slot::Empty {
+                    next_free_slot: match next_free_slot {
+                        Some(index) => Some(Index::from_parts(index, 0)?.index),
+                        None => None,
+                    },
+                },
+                RawSlot::Filled { item, index } => {
+                    if from_raw(index.index) != position || index.generation > generation {
+                        return None;
+                    }
+                    Slot::Filled {
+                        item,
+                        generation: index.generation,
+                    }
+                }
+            });
+        }
+
+        Some(Slab {
+            data,
+            first_free_slot: match first_free_slot {
+                Some(index) => Some(Index::from_parts(index, 0)?.index),
+                None => None,
+            },
+            generation,
+            len,
+            journal: None,
         })
     }
+}
 
-    pub(super) fn get_mut(&mut self, index: Index) -> Option<&mut T> {
-        self.data.get_mut(index.index).and_then(|slot| match slot {
-            Slot::Filled { item, generation } => {
-                if index.generation == *generation {
-                    return Some(item);
+/// A slab whose slots are allocated from a `bumpalo::Bump`, used by `arena::ArenaTree`.
+///
+/// It hands out the same generational `Index`es as `Slab`, but keeps no journal.
+#[cfg(feature = "bumpalo")]
+#[derive(Debug)]
+pub(super) struct ArenaSlab<'bump, T> {
+    data: bumpalo::collections::Vec<'bump, Slot<T>>,
+    first_free_slot: Option<RawIndex>,
+    generation: Generation,
+    len: usize,
+}
+
+#[cfg(feature = "bumpalo")]
+impl<'bump, T> ArenaSlab<'bump, T> {
+    pub(super) fn new_in(capacity: usize, bump: &'bump bumpalo::Bump) -> ArenaSlab<'bump, T> {
+        ArenaSlab {
+            data: bumpalo::collections::Vec::with_capacity_in(capacity, bump),
+            first_free_slot: None,
+            generation: 0,
+            len: 0,
+        }
+    }
+
+    pub(super) fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(super) fn insert(&mut self, item: T) -> Index {
+        let new_slot = Slot::Filled {
+            item,
+            generation: self.generation,
+        };
+        self.len += 1;
+
+        let index = match self.first_free_slot {
+            Some(index) => {
+                match mem::replace(&mut self.data[from_raw(index)], new_slot) {
+                    Slot::Empty { next_free_slot } => self.first_free_slot = next_free_slot,
+                    _ => unreachable!(),
                 }
-                None
+                index
+            }
+            None => {
+                self.data.push(new_slot);
+                to_raw(self.data.len() - 1)
+            }
+        };
+
+        Index {
+            index,
+            generation: self.generation,
+        }
+    }
+
+    pub(super) fn remove(&mut self, index: Index) -> Option<T> {
+        self.get(index)?;
+
+        let slot = mem::replace(
+            &mut self.data[from_raw(index.index)],
+            Slot::Empty {
+                next_free_slot: self.first_free_slot,
+            },
+        );
+
+        match slot {
+            Slot::Filled { item, .. } => {
+                self.generation = self.generation.wrapping_add(1);
+                self.first_free_slot = Some(index.index);
+                self.len -= 1;
+                Some(item)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub(super) fn get(&self, index: Index) -> Option<&T> {
+        match self.data.get(from_raw(index.index)) {
+            Some(Slot::Filled { item, generation }) if *generation == index.generation => {
+                Some(item)
             }
             _ => None,
-        })
+        }
+    }
+
+    pub(super) fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        match self.data.get_mut(from_raw(index.index)) {
+            Some(Slot::Filled { item, generation }) if *generation == index.generation => {
+                Some(item)
+            }
+            _ => None,
+        }
     }
 }
 
@@ -119,6 +747,12 @@ impl<T> Slab<T> {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "u32-index")]
+    #[test]
+    fn u32_index_size() {
+        assert_eq!(mem::size_of::<Index>(), 8);
+    }
+
     #[test]
     fn capacity() {
         let capacity = 5;
@@ -181,7 +815,7 @@ mod tests {
         assert_eq!(slab.first_free_slot.unwrap_or(10), 1);
         assert_eq!(slab.generation, 1);
 
-        let six_slot = slab.data.get(0);
+        let six_slot = slab.data.first();
         assert!(six_slot.is_some());
 
         match six_slot.unwrap() {
@@ -253,7 +887,7 @@ mod tests {
         assert_eq!(slab.first_free_slot.unwrap_or(10), 1);
         assert_eq!(slab.generation, 1);
 
-        let six_slot = slab.data.get(0);
+        let six_slot = slab.data.first();
         assert!(six_slot.is_some());
 
         match six_slot.unwrap() {
@@ -299,7 +933,7 @@ mod tests {
         assert_eq!(slab.first_free_slot.unwrap_or(10), 2);
         assert_eq!(slab.generation, 2);
 
-        let six_slot = slab.data.get(0);
+        let six_slot = slab.data.first();
         assert!(six_slot.is_some());
 
         match six_slot.unwrap() {
@@ -370,7 +1004,7 @@ mod tests {
         let eight_again = slab.remove(eight);
         assert!(eight_again.is_none());
 
-        let six_slot = slab.data.get(0);
+        let six_slot = slab.data.first();
         assert!(six_slot.is_some());
 
         match six_slot.unwrap() {
@@ -409,6 +1043,103 @@ mod tests {
         }
     }
 
+    #[test]
+    fn compact() {
+        let mut slab = Slab::new(5);
+        let six = slab.insert(6);
+        let seven = slab.insert(7);
+        let eight = slab.insert(8);
+        slab.remove(six);
+        slab.remove(seven);
+
+        let moves = slab.compact();
+        assert_eq!(moves.len(), 1);
+        let (old, new) = moves[0];
+        assert_eq!(old, eight);
+        assert_eq!(new.index, 0);
+        assert_eq!(new.generation, 3);
+        assert_eq!(slab.data.len(), 1);
+        assert!(slab.first_free_slot.is_none());
+        assert!(slab.get(eight).is_none());
+        assert!(slab.get(six).is_none());
+        assert_eq!(slab.get(new), Some(&8));
+
+        let nine = slab.insert(9);
+        assert_eq!(nine.index, 1);
+        assert_eq!(slab.len(), 2);
+    }
+
+    #[test]
+    fn len() {
+        let mut slab = Slab::new(5);
+        let six = slab.insert(6);
+        let seven = slab.insert(7);
+        assert_eq!(slab.len(), 2);
+
+        let mark = slab.snapshot(|item| *item);
+        slab.remove(six);
+        slab.remove(six);
+        assert_eq!(slab.len(), 1);
+        slab.insert(8);
+        slab.insert(9);
+        assert_eq!(slab.len(), 3);
+
+        assert!(slab.restore(mark));
+        assert_eq!(slab.len(), 2);
+
+        slab.remove(seven);
+        slab.compact();
+        assert_eq!(slab.len(), 1);
+        assert_eq!(slab.map(|item| *item).len(), 1);
+
+        slab.clear();
+        assert_eq!(slab.len(), 0);
+    }
+
+    #[test]
+    fn reorder() {
+        let mut slab = Slab::new(5);
+        let six = slab.insert(6);
+        let seven = slab.insert(7);
+        let eight = slab.insert(8);
+        slab.remove(seven);
+
+        let moves = slab.reorder(&[six, eight]);
+        assert_eq!(moves.len(), 1);
+        let (old, new) = moves[0];
+        assert_eq!(old, eight);
+        assert_eq!(new.index, 1);
+        assert_eq!(slab.get(six), Some(&6));
+        assert_eq!(slab.get(eight), None);
+        assert_eq!(slab.get(new), Some(&8));
+        assert_eq!(slab.capacity(), 5);
+
+        let moves = slab.reorder(&[new, six]);
+        assert_eq!(moves.len(), 2);
+        assert_eq!(slab.data.len(), 2);
+        assert_eq!(slab.get(moves[0].1), Some(&8));
+        assert_eq!(slab.get(moves[1].1), Some(&6));
+
+        let nine = slab.insert(9);
+        assert_eq!(nine.index, 2);
+    }
+
+    #[test]
+    fn map() {
+        let mut slab = Slab::new(4);
+        let one = slab.insert(1);
+        let two = slab.insert(2);
+        slab.remove(one);
+
+        let mut mapped = slab.map(|item| item * 10);
+        assert_eq!(mapped.capacity(), slab.capacity());
+        assert_eq!(mapped.get(one), None);
+        assert_eq!(mapped.get(two), Some(&20));
+
+        // the free list carries over, so both reuse the same slot
+        assert_eq!(mapped.insert(30), slab.insert(3));
+    }
+
     #[test]
     fn remove_with_bad_index() {
         let mut slab = Slab::new(5);
@@ -0,0 +1,208 @@
+//!
+//! Cheap snapshots of a `Tree` which it can later be rolled back to.
+//!
+//! Taking a snapshot doesn't copy the `Tree`.  Instead, while any snapshots exist, the first
+//! change to each `Node` after the most recent snapshot saves a copy of that `Node` in a journal,
+//! and restoring a snapshot replays the journal backwards.  The cost of snapshots is therefore
+//! proportional to the number of `Node`s changed rather than the size of the `Tree`.
+//!
+
+use crate::slab::SnapshotMark;
+use crate::tree::Tree;
+use crate::NodeId;
+use snowflake::ProcessUniqueId;
+
+///
+/// Identifies a snapshot taken with `Tree::snapshot`.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct SnapshotId {
+    tree_id: ProcessUniqueId,
+    mark: SnapshotMark,
+    root_id: Option<NodeId>,
+}
+
+impl<T: Clone> Tree<T> {
+    ///
+    /// Takes a snapshot of the current state of the `Tree` which can later be returned to with
+    /// `Tree::restore`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let snapshot = tree.snapshot();
+    ///
+    /// tree.root_mut().unwrap().append(2);
+    /// *tree.root_mut().unwrap().data() = 10;
+    ///
+    /// assert!(tree.restore(snapshot));
+    /// let root = tree.root().unwrap();
+    /// assert_eq!(*root.data(), 1);
+    /// assert!(root.first_child().is_none());
+    /// ```
+    ///
+    pub fn snapshot(&mut self) -> SnapshotId {
+        SnapshotId {
+            tree_id: self.core_tree.id(),
+            mark: self.core_tree.snapshot(),
+            root_id: self.root_id,
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Rolls the `Tree` back to the state it was in when `snapshot` was taken.  Any snapshots
+    /// taken after `snapshot` are discarded, while `snapshot` itself can be restored again.
+    ///
+    /// `NodeId`s from before the snapshot are valid again afterwards, and `NodeId`s of `Node`s
+    /// inserted after the snapshot will never refer to any other `Node`.  Observers are not
+    /// notified of the changes made by restoring.
+    ///
+    /// Returns `false` (and leaves the `Tree` untouched) if `snapshot` was taken from a different
+    /// `Tree`, or has been discarded.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let first = tree.snapshot();
+    /// tree.root_mut().unwrap().append(2);
+    /// let second = tree.snapshot();
+    ///
+    /// assert!(tree.restore(first));
+    /// assert!(!tree.restore(second));
+    /// assert!(tree.restore(first));
+    /// ```
+    ///
+    pub fn restore(&mut self, snapshot: SnapshotId) -> bool {
+        if snapshot.tree_id != self.core_tree.id() || !self.core_tree.restore(snapshot.mark) {
+            return false;
+        }
+        self.root_id = snapshot.root_id;
+        self.rebuild_indexes();
+        true
+    }
+
+    ///
+    /// Discards all snapshots of the `Tree`, which stops any further journaling of changes.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let snapshot = tree.snapshot();
+    /// tree.clear_snapshots();
+    ///
+    /// assert!(!tree.restore(snapshot));
+    /// ```
+    ///
+    pub fn clear_snapshots(&mut self) {
+        self.core_tree.clear_snapshots();
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::DropChildren;
+    use crate::tree::TreeBuilder;
+
+    fn pre_order(tree: &Tree<i32>) -> Vec<(i32, usize)> {
+        tree.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .map(|node| (*node.data(), node.ancestors().count()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn sample() -> Tree<i32> {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        one.append(2);
+        one.append(3);
+        root.append(4);
+        tree
+    }
+
+    #[test]
+    fn restore_undoes_all_kinds_of_changes() {
+        let mut tree = sample();
+        let expected = pre_order(&tree);
+        let one_id = tree.root().unwrap().first_child().unwrap().node_id();
+        let snapshot = tree.snapshot();
+
+        tree.remove(one_id, DropChildren);
+        tree.root_mut().unwrap().append(5).append(6);
+        tree.root_mut().unwrap().prepend(7);
+        tree.set_root(8);
+        *tree.root_mut().unwrap().data() = 9;
+        assert_ne!(pre_order(&tree), expected);
+
+        assert!(tree.restore(snapshot));
+        assert_eq!(pre_order(&tree), expected);
+        assert_eq!(*tree.get(one_id).unwrap().data(), 1);
+    }
+
+    #[test]
+    fn nested_snapshots() {
+        let mut tree = sample();
+        let first_state = pre_order(&tree);
+        let first = tree.snapshot();
+
+        tree.root_mut().unwrap().append(5);
+        let second_state = pre_order(&tree);
+        let second = tree.snapshot();
+
+        tree.root_mut().unwrap().remove_first(DropChildren);
+        tree.root_mut().unwrap().append(6);
+
+        assert!(tree.restore(second));
+        assert_eq!(pre_order(&tree), second_state);
+
+        tree.root_mut().unwrap().append(7);
+        assert!(tree.restore(second));
+        assert_eq!(pre_order(&tree), second_state);
+
+        assert!(tree.restore(first));
+        assert_eq!(pre_order(&tree), first_state);
+        assert!(!tree.restore(second));
+    }
+
+    #[test]
+    fn stale_ids_do_not_alias_after_restore() {
+        let mut tree = sample();
+        let snapshot = tree.snapshot();
+        let new_id = tree.root_mut().unwrap().append(5).node_id();
+
+        assert!(tree.restore(snapshot));
+        assert!(tree.get(new_id).is_none());
+
+        let newer_id = tree.root_mut().unwrap().append(6).node_id();
+        assert_ne!(new_id, newer_id);
+        assert!(tree.get(new_id).is_none());
+    }
+
+    #[test]
+    fn restore_empty_tree() {
+        let mut tree = Tree::new();
+        let snapshot = tree.snapshot();
+        tree.set_root(1);
+
+        assert!(tree.restore(snapshot));
+        assert!(tree.root().is_none());
+    }
+
+    #[test]
+    fn restore_from_other_tree() {
+        let mut tree = sample();
+        let mut other = sample();
+        let snapshot = other.snapshot();
+        assert!(!tree.restore(snapshot));
+    }
+}
@@ -0,0 +1,145 @@
+//!
+//! Summary statistics describing the shape of a `Tree`.
+//!
+
+use crate::tree::Tree;
+use crate::NodeId;
+
+///
+/// Statistics describing the shape of a `Tree`, as returned by `Tree::stats`.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TreeStats {
+    ///
+    /// The number of `Node`s reachable from the root.
+    ///
+    pub node_count: usize,
+
+    ///
+    /// The number of reachable `Node`s without any children.
+    ///
+    pub leaf_count: usize,
+
+    ///
+    /// The depth of the deepest `Node`, where the root is at depth 0.
+    ///
+    pub height: usize,
+
+    ///
+    /// The largest number of children of any single `Node`.
+    ///
+    pub max_branching: usize,
+
+    ///
+    /// The mean number of children of the `Node`s which have any children (0 if there are none).
+    ///
+    pub mean_branching: f64,
+
+    ///
+    /// The number of `Node`s at each depth, starting with the root's depth.
+    ///
+    pub depth_histogram: Vec<usize>,
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Computes statistics about the shape of the `Tree` in a single traversal.  An empty `Tree`
+    /// produces all-zero statistics with an empty depth histogram.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(1).append(2);
+    /// root.append(3);
+    /// root.append(4);
+    ///
+    /// let stats = tree.stats();
+    /// assert_eq!(stats.node_count, 5);
+    /// assert_eq!(stats.leaf_count, 3);
+    /// assert_eq!(stats.height, 2);
+    /// assert_eq!(stats.max_branching, 3);
+    /// assert_eq!(stats.mean_branching, 2.0);
+    /// assert_eq!(stats.depth_histogram, vec![1, 3, 1]);
+    /// ```
+    ///
+    pub fn stats(&self) -> TreeStats {
+        let mut stats = TreeStats::default();
+        let mut internal_count = 0;
+
+        let mut stack: Vec<(NodeId, usize)> =
+            self.root_id().map(|id| (id, 0)).into_iter().collect();
+        while let Some((node_id, depth)) = stack.pop() {
+            stats.node_count += 1;
+            if stats.depth_histogram.len() <= depth {
+                stats.depth_histogram.push(0);
+            }
+            stats.depth_histogram[depth] += 1;
+
+            let mut child_count = 0;
+            let mut child_id = self.get_node_relatives(node_id).first_child;
+            while let Some(id) = child_id {
+                child_count += 1;
+                stack.push((id, depth + 1));
+                child_id = self.get_node_next_sibling_id(id);
+            }
+
+            if child_count == 0 {
+                stats.leaf_count += 1;
+            } else {
+                internal_count += 1;
+                stats.max_branching = stats.max_branching.max(child_count);
+            }
+        }
+
+        stats.height = stats.depth_histogram.len().saturating_sub(1);
+        if internal_count > 0 {
+            stats.mean_branching = (stats.node_count - 1) as f64 / internal_count as f64;
+        }
+        stats
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    #[test]
+    fn stats_empty() {
+        let tree: Tree<i32> = Tree::new();
+        assert_eq!(tree.stats(), TreeStats::default());
+    }
+
+    #[test]
+    fn stats_single_node() {
+        let tree = TreeBuilder::new().with_root(0).build();
+        let stats = tree.stats();
+        assert_eq!(stats.node_count, 1);
+        assert_eq!(stats.leaf_count, 1);
+        assert_eq!(stats.height, 0);
+        assert_eq!(stats.max_branching, 0);
+        assert_eq!(stats.mean_branching, 0.0);
+        assert_eq!(stats.depth_histogram, vec![1]);
+    }
+
+    #[test]
+    fn stats_uneven() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let mut one = root.append(1);
+        one.append(2).append(3).append(4);
+        one.append(5);
+        root.append(6);
+
+        let stats = tree.stats();
+        assert_eq!(stats.node_count, 7);
+        assert_eq!(stats.leaf_count, 3);
+        assert_eq!(stats.height, 4);
+        assert_eq!(stats.max_branching, 2);
+        assert_eq!(stats.mean_branching, 1.5);
+        assert_eq!(stats.depth_histogram, vec![1, 2, 2, 1, 1]);
+    }
+}
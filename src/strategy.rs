@@ -0,0 +1,135 @@
+//!
+//! `proptest` strategies for generating `Tree`s.
+//!
+//! Generated trees shrink structurally: failing cases are reduced by dropping children and
+//! whole sub-trees (and by shrinking the data of the remaining `Node`s) until a minimal failing
+//! `Tree` is found.
+//!
+
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use proptest::collection::vec;
+use proptest::strategy::Strategy;
+use std::fmt::Debug;
+
+///
+/// Returns a `Strategy` producing non-empty `Tree`s whose `Node` data is generated by `leaf`.
+///
+/// No `Node` will be more than `depth` levels below the root and no `Node` will have more than
+/// `branching` children.
+///
+/// ```
+/// use proptest::prelude::*;
+/// use proptest::test_runner::TestRunner;
+/// use slab_tree::strategy::tree_strategy;
+///
+/// let mut runner = TestRunner::default();
+/// runner
+///     .run(&tree_strategy(3, 4, any::<u8>()), |tree| {
+///         let root = tree.root().expect("generated trees are never empty");
+///         prop_assert!(root.traverse_pre_order().count() >= 1);
+///         Ok(())
+///     })
+///     .unwrap();
+/// ```
+///
+pub fn tree_strategy<S>(
+    depth: u32,
+    branching: usize,
+    leaf: S,
+) -> impl Strategy<Value = Tree<S::Value>>
+where
+    S: Strategy + Clone + 'static,
+    S::Value: Clone + Debug + 'static,
+{
+    let data = leaf.clone();
+    let desired_size = (branching as u32).saturating_pow(depth).clamp(1, 1024);
+
+    leaf.prop_map(|data| Shape {
+        data,
+        children: Vec::new(),
+    })
+    .prop_recursive(depth, desired_size, branching as u32, move |inner| {
+        (data.clone(), vec(inner, 0..=branching))
+            .prop_map(|(data, children)| Shape { data, children })
+    })
+    .prop_map(Shape::into_tree)
+}
+
+/// The shrinkable, nested form of a generated `Tree`.
+#[derive(Clone, Debug)]
+struct Shape<T> {
+    data: T,
+    children: Vec<Shape<T>>,
+}
+
+impl<T> Shape<T> {
+    fn into_tree(self) -> Tree<T> {
+        let Shape { data, children } = self;
+        let mut tree = TreeBuilder::new().with_root(data).build();
+        let root_id = tree.root_id().expect("root was just set");
+
+        let mut stack: Vec<(NodeId, Shape<T>)> = children
+            .into_iter()
+            .rev()
+            .map(|child| (root_id, child))
+            .collect();
+
+        while let Some((parent_id, Shape { data, children })) = stack.pop() {
+            let node_id = tree
+                .get_mut(parent_id)
+                .expect("parent was inserted before its children")
+                .append(data)
+                .node_id();
+            stack.extend(children.into_iter().rev().map(|child| (node_id, child)));
+        }
+
+        tree
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod strategy_tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn shape_into_tree_keeps_child_order() {
+        let leaf = |data| Shape {
+            data,
+            children: Vec::new(),
+        };
+        let shape = Shape {
+            data: 0,
+            children: vec![
+                Shape {
+                    data: 1,
+                    children: vec![leaf(2), leaf(3)],
+                },
+                leaf(4),
+            ],
+        };
+
+        let tree = shape.into_tree();
+        let pre_order: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node_ref| *node_ref.data())
+            .collect();
+        assert_eq!(pre_order, vec![0, 1, 2, 3, 4]);
+    }
+
+    proptest! {
+        #[test]
+        fn respects_depth_and_branching(tree in tree_strategy(3, 4, any::<u8>())) {
+            let root = tree.root().unwrap();
+            for node in root.traverse_pre_order() {
+                prop_assert!(node.children().count() <= 4);
+                prop_assert!(node.ancestors().count() <= 3);
+            }
+        }
+    }
+}
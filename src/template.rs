@@ -0,0 +1,209 @@
+//!
+//! Instantiating a template `Tree` by cloning it while filling in its placeholder `Node`s.
+//!
+
+use crate::node::NodeRef;
+use crate::tree::Tree;
+use crate::NodeId;
+
+///
+/// What `Tree::instantiate` puts in the place of a placeholder `Node`.
+///
+#[derive(Debug)]
+pub enum Fill<T> {
+    ///
+    /// Keeps the placeholder `Node` with this data instead of its own.  Its children are
+    /// instantiated like any others.
+    ///
+    Value(T),
+
+    ///
+    /// Puts the root's sub-tree of this `Tree` in the place of the placeholder `Node` and its
+    /// sub-tree.  The data is moved, not cloned, and an empty `Tree` leaves the placeholder out.
+    ///
+    Subtree(Box<Tree<T>>),
+}
+
+impl<T: Clone> Tree<T> {
+    ///
+    /// Returns a copy of the root's sub-tree in which every `Node` for which `is_placeholder`
+    /// returns `true` has been filled in with what `fill` makes of its data: either new data for
+    /// the `Node`, or a whole sub-tree to replace it.  The sub-trees of placeholders replaced by
+    /// a sub-tree aren't visited.  This `Tree` is left as it is, so it can be instantiated again.
+    ///
+    /// If the root itself is replaced by a sub-tree, the `Tree` given by `fill` is returned as
+    /// it is.
+    ///
+    /// ```
+    /// use slab_tree::template::Fill;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// // <list><title/><items/></list>
+    /// let mut template = TreeBuilder::new().with_root("list").build();
+    /// let mut root = template.root_mut().unwrap();
+    /// root.append("$title");
+    /// root.append("$items");
+    ///
+    /// let page = template.instantiate(
+    ///     |node| node.data().starts_with('$'),
+    ///     |data| match *data {
+    ///         "$title" => Fill::Value("Groceries"),
+    ///         _ => {
+    ///             let mut items = TreeBuilder::new().with_root("ul").build();
+    ///             items.root_mut().unwrap().append("milk");
+    ///             items.root_mut().unwrap().append("eggs");
+    ///             Fill::Subtree(Box::new(items))
+    ///         }
+    ///     },
+    /// );
+    ///
+    /// let root = page.root().unwrap();
+    /// let pre_order: Vec<&str> = root.traverse_pre_order().map(|node| *node.data()).collect();
+    /// assert_eq!(pre_order, vec!["list", "Groceries", "ul", "milk", "eggs"]);
+    /// assert_eq!(template.root().unwrap().first_child().unwrap().data(), &"$title");
+    /// ```
+    ///
+    pub fn instantiate<P, F>(&self, mut is_placeholder: P, mut fill: F) -> Tree<T>
+    where
+        P: FnMut(&NodeRef<'_, T>) -> bool,
+        F: FnMut(&T) -> Fill<T>,
+    {
+        let mut instance = Tree::new();
+        // (node of the template, parent of its copy in the instance); each node's children are
+        // pushed last to first so they're appended in order
+        let mut stack: Vec<(NodeRef<'_, T>, Option<NodeId>)> =
+            self.root().map(|root| (root, None)).into_iter().collect();
+
+        while let Some((node, parent_id)) = stack.pop() {
+            let data = if is_placeholder(&node) {
+                match fill(node.data()) {
+                    Fill::Value(data) => data,
+                    Fill::Subtree(subtree) => {
+                        let mut subtree = *subtree;
+                        match (parent_id, subtree.root_id()) {
+                            (Some(parent_id), Some(subtree_root_id)) => {
+                                instance
+                                    .transplant(&mut subtree, subtree_root_id, parent_id)
+                                    .expect("both nodes exist in different trees");
+                            }
+                            (Some(_), None) => {}
+                            (None, _) => return subtree,
+                        }
+                        continue;
+                    }
+                }
+            } else {
+                node.data().clone()
+            };
+
+            let node_id = match parent_id {
+                Some(parent_id) => instance
+                    .get_mut(parent_id)
+                    .expect("parent must exist")
+                    .append(data)
+                    .node_id(),
+                None => instance.set_root(data),
+            };
+            let start = stack.len();
+            stack.extend(node.children().map(|child| (child, Some(node_id))));
+            stack[start..].reverse();
+        }
+
+        instance
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod template_tests {
+    use super::Fill;
+    use crate::tree::Tree;
+    use crate::tree::TreeBuilder;
+
+    fn pre_order(tree: &Tree<String>) -> Vec<(String, usize)> {
+        tree.root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (node.data().clone(), node.ancestors().count()))
+            .collect()
+    }
+
+    fn template() -> Tree<String> {
+        let mut template = TreeBuilder::new().with_root("root".to_string()).build();
+        let mut root = template.root_mut().unwrap();
+        let mut slot = root.append("$a".to_string());
+        slot.append("inner".to_string());
+        slot.append("$b".to_string());
+        root.append("$c".to_string()).append("dropped".to_string());
+        root.append("tail".to_string());
+        template
+    }
+
+    #[test]
+    fn fills_values_and_subtrees() {
+        let template = template();
+        let mut counter = 0;
+        let instance = template.instantiate(
+            |node| node.data().starts_with('$'),
+            |data| {
+                counter += 1;
+                match data.as_str() {
+                    "$c" => {
+                        let mut subtree = TreeBuilder::new().with_root("c".to_string()).build();
+                        subtree.root_mut().unwrap().append("c1".to_string());
+                        Fill::Subtree(Box::new(subtree))
+                    }
+                    _ => Fill::Value(format!("{}{}", &data[1..], counter)),
+                }
+            },
+        );
+        assert_eq!(counter, 3);
+        let expected: Vec<(String, usize)> = vec![
+            ("root", 0),
+            ("a1", 1),
+            ("inner", 2),
+            ("b2", 2),
+            ("c", 1),
+            ("c1", 2),
+            ("tail", 1),
+        ]
+        .into_iter()
+        .map(|(data, depth)| (data.to_string(), depth))
+        .collect();
+        assert_eq!(pre_order(&instance), expected);
+        assert_eq!(instance.all_node_ids().len(), expected.len());
+    }
+
+    #[test]
+    fn empty_subtrees_drop_placeholders() {
+        let instance = template().instantiate(
+            |node| node.data().starts_with('$'),
+            |_| Fill::Subtree(Box::new(Tree::new())),
+        );
+        assert_eq!(
+            pre_order(&instance),
+            vec![("root".to_string(), 0), ("tail".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn replacing_the_root() {
+        let template = template();
+        let instance = template.instantiate(
+            |_| true,
+            |_| {
+                Fill::Subtree(Box::new(
+                    TreeBuilder::new().with_root("only".to_string()).build(),
+                ))
+            },
+        );
+        assert_eq!(pre_order(&instance), vec![("only".to_string(), 0)]);
+
+        let instance = template.instantiate(|_| true, |_| Fill::Subtree(Box::new(Tree::new())));
+        assert!(instance.root().is_none());
+
+        let empty: Tree<String> = Tree::new();
+        let instance = empty.instantiate(|_| true, |_| Fill::Value(String::new()));
+        assert!(instance.root().is_none());
+    }
+}
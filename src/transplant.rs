@@ -0,0 +1,281 @@
+//!
+//! Moving a sub-tree from one `Tree` into another without cloning its data.
+//!
+
+use crate::tree::Tree;
+use crate::NodeId;
+use std::error::Error;
+use std::fmt;
+
+///
+/// Describes why `Tree::transplant` couldn't move a sub-tree.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransplantError {
+    ///
+    /// The source `NodeId` doesn't refer to a `Node` in the `Tree` being moved from.
+    ///
+    MissingSource(NodeId),
+
+    ///
+    /// The destination parent's `NodeId` doesn't refer to a `Node` in the `Tree` being moved
+    /// into.
+    ///
+    MissingParent(NodeId),
+
+    ///
+    /// Both `NodeId`s refer to the `Tree` being moved into, so there is nothing to transplant
+    /// between `Tree`s; `Tree::move_subtree` moves sub-trees within one `Tree`.  This is also
+    /// returned if the two `Tree`s share their `NodeId`s (because one was copied from the other
+    /// along with its `NodeId`s), as they can't be told apart.
+    ///
+    SameTree,
+
+    ///
+    /// Both `NodeId`s refer to the `Tree` being moved into, and the destination parent is inside
+    /// the sub-tree being moved, so moving it would create a cycle.
+    ///
+    Cycle,
+}
+
+impl fmt::Display for TransplantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransplantError::MissingSource(node_id) => {
+                write!(f, "{:?} is not a node of the source tree", node_id)
+            }
+            TransplantError::MissingParent(node_id) => {
+                write!(f, "{:?} is not a node of the destination tree", node_id)
+            }
+            TransplantError::SameTree => {
+                write!(f, "the source and destination trees are the same tree")
+            }
+            TransplantError::Cycle => {
+                write!(
+                    f,
+                    "the destination parent is inside the sub-tree being moved"
+                )
+            }
+        }
+    }
+}
+
+impl Error for TransplantError {}
+
+impl<T> Tree<T> {
+    ///
+    /// Moves the `Node` with the `NodeId` `src` (along with its sub-tree) out of `other`,
+    /// appending it as the last child of the `Node` with the `NodeId` `dest_parent` in this
+    /// `Tree`.  The data is moved, not cloned.  Returns the new `NodeId` of the moved `Node`;
+    /// the moved `Node`s all get new `NodeId`s, and their old ones are no longer valid in either
+    /// `Tree`.
+    ///
+    /// If `src` is the root of `other`, `other` is left without a root.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// let mut other = TreeBuilder::new().with_root("other").build();
+    /// let branch_id = other.root_mut().unwrap().append("branch").node_id();
+    /// other.get_mut(branch_id).unwrap().append("leaf");
+    ///
+    /// let moved_id = tree.transplant(&mut other, branch_id, root_id).unwrap();
+    ///
+    /// assert!(other.get(branch_id).is_none());
+    /// assert!(other.root().unwrap().first_child().is_none());
+    ///
+    /// let moved = tree.get(moved_id).unwrap();
+    /// assert_eq!(moved.data(), &"branch");
+    /// assert_eq!(moved.parent().unwrap().node_id(), root_id);
+    /// assert_eq!(moved.first_child().unwrap().data(), &"leaf");
+    /// ```
+    ///
+    /// Sub-trees can't be transplanted within a single `Tree`:
+    ///
+    /// ```
+    /// use slab_tree::transplant::TransplantError;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().unwrap();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    /// let mut other = TreeBuilder::new().with_root(3).build();
+    ///
+    /// assert_eq!(tree.transplant(&mut other, two_id, root_id), Err(TransplantError::SameTree));
+    /// assert_eq!(tree.transplant(&mut other, root_id, two_id), Err(TransplantError::Cycle));
+    /// ```
+    ///
+    pub fn transplant(
+        &mut self,
+        other: &mut Tree<T>,
+        src: NodeId,
+        dest_parent: NodeId,
+    ) -> Result<NodeId, TransplantError> {
+        if self.core_tree.id() == other.core_tree.id() {
+            return Err(TransplantError::SameTree);
+        }
+        if let Some(src_node) = self.get(src) {
+            if self.get(dest_parent).is_some() {
+                let is_cycle = src_node
+                    .traverse_pre_order()
+                    .any(|node| node.node_id() == dest_parent);
+                return Err(if is_cycle {
+                    TransplantError::Cycle
+                } else {
+                    TransplantError::SameTree
+                });
+            }
+        }
+        if other.get_node(src).is_none() {
+            return Err(TransplantError::MissingSource(src));
+        }
+        if self.get_node(dest_parent).is_none() {
+            return Err(TransplantError::MissingParent(dest_parent));
+        }
+
+        if !other.hooks.is_empty() {
+            let removed_ids: Vec<NodeId> = other
+                .get(src)
+                .expect("node must exist")
+                .traverse_pre_order()
+                .map(|node_ref| node_ref.node_id())
+                .collect();
+            for id in removed_ids {
+                other.notify_remove(id);
+            }
+        }
+        other.unlink(src);
+        if other.root_id == Some(src) {
+            other.root_id = None;
+        }
+
+        // (node in `other`, its new parent in `self`); each node's children are pushed last
+        // to first so they're appended in order
+        let mut stack = vec![(src, dest_parent)];
+        let mut moved_id = None;
+        while let Some((other_id, parent_id)) = stack.pop() {
+            let last_child = other.get_node_relatives(other_id).last_child;
+            let mut next_child = last_child;
+            let data = other.core_tree.remove(other_id).expect("node must exist");
+            let node_id = self
+                .get_mut(parent_id)
+                .expect("parent must exist")
+                .append(data)
+                .node_id();
+            moved_id.get_or_insert(node_id);
+
+            while let Some(child_id) = next_child {
+                next_child = other.get_node_relatives(child_id).prev_sibling;
+                stack.push((child_id, node_id));
+            }
+        }
+        Ok(moved_id.expect("source node must have been moved"))
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod transplant_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    fn pre_order<T: Copy>(tree: &Tree<T>) -> Vec<(T, usize)> {
+        tree.root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (*node.data(), node.ancestors().count()))
+            .collect()
+    }
+
+    #[test]
+    fn transplant_subtree() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        tree.root_mut().unwrap().append(1);
+
+        let mut other = Tree::unfold(13, |n: i32| {
+            let children = if n < 1000 {
+                vec![n * 10 + 1, n * 10 + 2]
+            } else {
+                vec![]
+            };
+            (n, children)
+        });
+        let src = other.root().unwrap().first_child().unwrap().node_id();
+
+        let moved_id = tree.transplant(&mut other, src, root_id).unwrap();
+        assert_eq!(
+            pre_order(&tree),
+            vec![(0, 0), (1, 1), (131, 1), (1311, 2), (1312, 2)]
+        );
+        assert_eq!(tree.get(moved_id).unwrap().data(), &131);
+        assert_eq!(
+            pre_order(&other),
+            vec![(13, 0), (132, 1), (1321, 2), (1322, 2)]
+        );
+    }
+
+    #[test]
+    fn transplant_root() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let mut other = Tree::unfold(2, |n: i32| (n, (0..n).collect()));
+        let src = other.root_id().unwrap();
+
+        tree.transplant(&mut other, src, root_id).unwrap();
+        assert!(other.root().is_none());
+        assert_eq!(
+            pre_order(&tree),
+            vec![(0, 0), (2, 1), (0, 2), (1, 2), (0, 3)]
+        );
+    }
+
+    #[test]
+    fn transplant_errors() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let mut other = TreeBuilder::new().with_root(1).build();
+        let other_root_id = other.root_id().unwrap();
+
+        assert_eq!(
+            tree.transplant(&mut other, other_root_id, other_root_id),
+            Err(TransplantError::MissingParent(other_root_id))
+        );
+        assert_eq!(
+            tree.transplant(&mut other, root_id, root_id),
+            Err(TransplantError::Cycle)
+        );
+        let mut alias = Tree {
+            root_id: tree.root_id,
+            core_tree: tree.core_tree.map(|data| *data),
+            hooks: Default::default(),
+        };
+        assert_eq!(
+            tree.transplant(&mut alias, root_id, root_id),
+            Err(TransplantError::SameTree)
+        );
+
+        other.remove(other_root_id, crate::RemoveBehavior::DropChildren);
+        assert_eq!(
+            tree.transplant(&mut other, other_root_id, root_id),
+            Err(TransplantError::MissingSource(other_root_id))
+        );
+        assert_eq!(pre_order(&tree), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn transplant_from_a_copy() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        tree.root_mut().unwrap().append(1);
+        let mut copy = tree.clone_with_capacity(0);
+        let copy_one_id = copy.root().unwrap().first_child().unwrap().node_id();
+
+        assert!(tree.transplant(&mut copy, copy_one_id, root_id).is_ok());
+        assert_eq!(pre_order(&tree), vec![(0, 0), (1, 1), (1, 1)]);
+        assert_eq!(pre_order(&copy), vec![(0, 0)]);
+    }
+}
@@ -1,7 +1,15 @@
 use crate::behaviors::*;
 use crate::core_tree::CoreTree;
+use crate::iter::AllRootsFirst;
 use crate::node::*;
+use crate::observer::Hooks;
 use crate::NodeId;
+use std::collections::TryReserveError;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::mem;
+
+type Generator<T> = Box<dyn FnMut(&T) -> Vec<T>>;
 
 ///
 /// A `Tree` builder. Provides more control over how a `Tree` is created.
@@ -9,6 +17,7 @@ use crate::NodeId;
 pub struct TreeBuilder<T> {
     root: Option<T>,
     capacity: Option<usize>,
+    generator: Option<(Generator<T>, usize)>,
 }
 
 impl<T> Default for TreeBuilder<T> {
@@ -33,6 +42,7 @@ impl<T> TreeBuilder<T> {
         TreeBuilder {
             root: None,
             capacity: None,
+            generator: None,
         }
     }
 
@@ -48,7 +58,7 @@ impl<T> TreeBuilder<T> {
     pub fn with_root(self, root: T) -> TreeBuilder<T> {
         TreeBuilder {
             root: Some(root),
-            capacity: self.capacity,
+            ..self
         }
     }
 
@@ -68,8 +78,45 @@ impl<T> TreeBuilder<T> {
     ///
     pub fn with_capacity(self, capacity: usize) -> TreeBuilder<T> {
         TreeBuilder {
-            root: self.root,
             capacity: Some(capacity),
+            ..self
+        }
+    }
+
+    ///
+    /// Sets the root `Node` of the `TreeBuilder` to `root_seed` and has `build` expand it by
+    /// repeatedly calling `generator` with each `Node`'s data and appending the returned values as
+    /// that `Node`'s children.  `Node`s are expanded in level order, and `Node`s at `max_depth`
+    /// (where the root is at depth 0) are not expanded.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::new()
+    ///     .with_generator(1, |n| vec![n * 10, n * 10 + 1], 2)
+    ///     .build();
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 1
+    /// ├── 10
+    /// │   ├── 100
+    /// │   └── 101
+    /// └── 11
+    ///     ├── 110
+    ///     └── 111
+    /// ");
+    /// ```
+    ///
+    pub fn with_generator<F>(self, root_seed: T, generator: F, max_depth: usize) -> TreeBuilder<T>
+    where
+        F: FnMut(&T) -> Vec<T> + 'static,
+    {
+        TreeBuilder {
+            root: Some(root_seed),
+            generator: Some((Box::new(generator), max_depth)),
+            ..self
         }
     }
 
@@ -87,7 +134,28 @@ impl<T> TreeBuilder<T> {
         let mut core_tree: CoreTree<T> = CoreTree::new(capacity);
         let root_id = self.root.map(|val| core_tree.insert(val));
 
-        Tree { root_id, core_tree }
+        let mut tree = Tree {
+            root_id,
+            core_tree,
+            hooks: Hooks::default(),
+        };
+
+        if let (Some(root_id), Some((mut generator, max_depth))) = (root_id, self.generator) {
+            let mut queue = VecDeque::new();
+            queue.push_back((root_id, 0));
+            while let Some((node_id, depth)) = queue.pop_front() {
+                if depth >= max_depth {
+                    continue;
+                }
+                let children = generator(&tree.get_node(node_id).expect("node must exist").data);
+                let mut node = tree.get_mut(node_id).expect("node must exist");
+                for child in children {
+                    queue.push_back((node.append(child).node_id(), depth + 1));
+                }
+            }
+        }
+
+        tree
     }
 }
 
@@ -98,6 +166,7 @@ impl<T> TreeBuilder<T> {
 pub struct Tree<T> {
     pub(crate) root_id: Option<NodeId>,
     pub(crate) core_tree: CoreTree<T>,
+    pub(crate) hooks: Hooks<T>,
 }
 
 impl<T> Tree<T> {
@@ -116,7 +185,95 @@ impl<T> Tree<T> {
         TreeBuilder::new().build()
     }
 
-    //todo: write test for this
+    ///
+    /// Builds a `Tree` by unfolding `seed`: `f` turns a seed into a `Node`'s data and the seeds of
+    /// its children, which are unfolded in turn.  `f` is called in pre-order.
+    ///
+    /// ```
+    /// use slab_tree::tree::Tree;
+    ///
+    /// // the binary representations of numbers, split by their leading bits
+    /// let tree = Tree::unfold(String::from("1"), |bits| {
+    ///     let children = if bits.len() < 3 {
+    ///         vec![format!("{}0", bits), format!("{}1", bits)]
+    ///     } else {
+    ///         vec![]
+    ///     };
+    ///     (bits, children)
+    /// });
+    ///
+    /// let leaves: Vec<&String> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_pre_order()
+    ///     .filter(|node| node.is_leaf())
+    ///     .map(|node| node.data())
+    ///     .collect();
+    /// assert_eq!(leaves, vec!["100", "101", "110", "111"]);
+    /// ```
+    ///
+    pub fn unfold<S, F>(seed: S, mut f: F) -> Tree<T>
+    where
+        F: FnMut(S) -> (T, Vec<S>),
+    {
+        let mut tree = Tree::new();
+        let (data, children) = f(seed);
+        let root_id = tree.set_root(data);
+
+        let mut stack: Vec<(NodeId, S)> = children
+            .into_iter()
+            .rev()
+            .map(|child| (root_id, child))
+            .collect();
+        while let Some((parent_id, seed)) = stack.pop() {
+            let (data, children) = f(seed);
+            let node_id = tree
+                .get_mut(parent_id)
+                .expect("parent must exist")
+                .append(data)
+                .node_id();
+            stack.extend(children.into_iter().rev().map(|child| (node_id, child)));
+        }
+
+        tree
+    }
+
+    ///
+    /// Builds a `Tree` holding a single path, where each element of `chain` is the only child of
+    /// the element before it.  Returns the `Tree` along with the `NodeId` of the last element
+    /// (the leaf), or `None` if `chain` is empty.
+    ///
+    /// ```
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let (tree, leaf_id) = Tree::new_chain(vec!["home", "user", "projects"]);
+    /// let leaf = tree.get(leaf_id.unwrap()).unwrap();
+    ///
+    /// let path: Vec<&str> = leaf.ancestors_or_self().map(|node| *node.data()).collect();
+    /// assert_eq!(path, vec!["projects", "user", "home"]);
+    /// ```
+    ///
+    pub fn new_chain<I: IntoIterator<Item = T>>(chain: I) -> (Tree<T>, Option<NodeId>) {
+        let chain = chain.into_iter();
+        let mut tree = TreeBuilder::new()
+            .with_capacity(chain.size_hint().0)
+            .build();
+
+        let mut leaf_id = None;
+        for data in chain {
+            leaf_id = Some(match leaf_id {
+                Some(parent_id) => tree
+                    .get_mut(parent_id)
+                    .expect("parent must exist")
+                    .append(data)
+                    .node_id(),
+                None => tree.set_root(data),
+            });
+        }
+
+        (tree, leaf_id)
+    }
+
     ///
     /// Sets the "root" of the `Tree` to be `root`.
     ///
@@ -140,11 +297,17 @@ impl<T> Tree<T> {
         self.root_id = Some(new_root_id);
 
         self.set_first_child(new_root_id, old_root_id);
+        self.set_last_child(new_root_id, old_root_id);
 
         if let Some(node_id) = old_root_id {
             self.set_parent(node_id, self.root_id);
         }
 
+        self.notify_insert(new_root_id);
+        if let Some(node_id) = old_root_id {
+            self.notify_move(node_id);
+        }
+
         new_root_id
     }
 
@@ -164,6 +327,69 @@ impl<T> Tree<T> {
         self.core_tree.capacity()
     }
 
+    ///
+    /// Tries to make room for at least `additional` more `Node`s, so that inserting them won't
+    /// reallocate.  Like `Vec::try_reserve`, an allocation failure is returned as an error
+    /// instead of aborting the process, and the `Tree` is left unchanged.
+    ///
+    /// Only the storage for the `Node`s themselves is reserved: observers, indexes and
+    /// snapshots may still allocate as `Node`s are inserted.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.try_reserve(100).expect("out of memory");
+    /// assert!(tree.capacity() >= 101);
+    ///
+    /// assert!(tree.try_reserve(usize::MAX).is_err());
+    /// ```
+    ///
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.core_tree.try_reserve(additional)
+    }
+
+    ///
+    /// Removes every `Node` from the `Tree` (including any orphaned `Node`s) while keeping its
+    /// allocated capacity, so it can be reused for a new tree without reallocating.
+    ///
+    /// The cleared `Tree` behaves like a brand new one: `NodeId`s and snapshots from before the
+    /// call no longer refer to anything in it.  Registered observers are kept, and are notified
+    /// of the removal of every `Node` below the root (in pre-order) first.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).with_capacity(5).build();
+    /// let old_root_id = tree.root_id().unwrap();
+    ///
+    /// tree.clear();
+    ///
+    /// assert!(tree.root().is_none());
+    /// assert!(tree.get(old_root_id).is_none());
+    /// assert!(tree.capacity() >= 5);
+    /// ```
+    ///
+    pub fn clear(&mut self) {
+        if let Some(root_id) = self.root_id {
+            if !self.hooks.is_empty() {
+                let removed_ids: Vec<NodeId> = self
+                    .get(root_id)
+                    .expect("root must exist")
+                    .traverse_pre_order()
+                    .map(|node_ref| node_ref.node_id())
+                    .collect();
+                for id in removed_ids {
+                    self.notify_remove(id);
+                }
+            }
+        }
+
+        self.core_tree.clear();
+        self.root_id = None;
+        self.rebuild_indexes();
+    }
+
     ///
     /// Returns the `NodeId` of the root node of the `Tree`.
     ///
@@ -196,7 +422,7 @@ impl<T> Tree<T> {
     /// assert_eq!(root.data(), &1);
     /// ```
     ///
-    pub fn root(&self) -> Option<NodeRef<T>> {
+    pub fn root(&self) -> Option<NodeRef<'_, T>> {
         self.root_id.map(|id| self.new_node_ref(id))
     }
 
@@ -216,10 +442,98 @@ impl<T> Tree<T> {
     /// assert_eq!(root.data(), &mut 2);
     /// ```
     ///
-    pub fn root_mut(&mut self) -> Option<NodeMut<T>> {
+    pub fn root_mut(&mut self) -> Option<NodeMut<'_, T>> {
         self.root_id.map(move |id| self.new_node_mut(id))
     }
 
+    ///
+    /// Turns a pair returned by `NodeId::to_raw` back into a `NodeId` of this `Tree`, if there
+    /// is a `Node` in the matching slot with the matching generation.  Returns `None` for a
+    /// pair referring to a removed `Node` or a slot that doesn't exist.
+    ///
+    /// Only the slot and generation are checked, so a pair taken from an unrelated `Tree` can
+    /// succeed and refer to an unrelated `Node`.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    /// let raw = two_id.to_raw();
+    /// assert_eq!(tree.id_from_raw(raw), Some(two_id));
+    ///
+    /// tree.remove(two_id, RemoveBehavior::DropChildren);
+    /// tree.root_mut().unwrap().append(3);
+    /// assert_eq!(tree.id_from_raw(raw), None);
+    /// ```
+    ///
+    pub fn id_from_raw(&self, (index, generation): (u64, u64)) -> Option<NodeId> {
+        let index = usize::try_from(index).ok()?;
+        let node_id = NodeId {
+            tree_id: self.core_tree.id(),
+            index: crate::slab::Index::from_parts(index, generation)?,
+        };
+        self.get_node(node_id).map(|_| node_id)
+    }
+
+    ///
+    /// Translates a `NodeId` from a copy of this `Tree` (made by `Tree::clone_with_capacity`,
+    /// `Tree::clone_into` or, with the `serde` feature, a serialized copy) into the `NodeId` at
+    /// the same position in this `Tree`.  Returns `None` if there is no `Node` at that position.
+    ///
+    /// Only the `NodeId`'s position is checked, so rebinding a `NodeId` that came from an
+    /// unrelated `Tree` can succeed and refer to an unrelated `Node`.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    ///
+    /// let copy = tree.clone_with_capacity(0);
+    /// assert!(copy.get(two_id).is_none());
+    ///
+    /// let copied_two_id = copy.rebind(two_id).unwrap();
+    /// assert_eq!(*copy.get(copied_two_id).unwrap().data(), 2);
+    /// assert_eq!(tree.rebind(copied_two_id), Some(two_id));
+    /// ```
+    ///
+    pub fn rebind(&self, node_id: NodeId) -> Option<NodeId> {
+        let node_id = NodeId {
+            tree_id: self.core_tree.id(),
+            index: node_id.index,
+        };
+        self.get_node(node_id).map(|_| node_id)
+    }
+
+    ///
+    /// Returns an iterator over every `Node` in the `Tree`, including orphaned ones.  The root's
+    /// subtree is visited first, in pre-order, followed by the subtree of each orphaned `Node`
+    /// (one left without a parent by `RemoveBehavior::OrphanChildren`) in the order they are
+    /// stored.
+    ///
+    /// ```
+    /// use slab_tree::behaviors::RemoveBehavior::OrphanChildren;
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append(1).node_id();
+    /// root.append(4);
+    /// let mut one = tree.get_mut(one_id).unwrap();
+    /// one.append(2).append(3);
+    ///
+    /// tree.remove(one_id, OrphanChildren);
+    ///
+    /// let values: Vec<i32> = tree.iter_all_roots_first().map(|node| *node.data()).collect();
+    /// assert_eq!(values, vec![0, 4, 2, 3]);
+    /// ```
+    ///
+    pub fn iter_all_roots_first(&self) -> AllRootsFirst<'_, T> {
+        AllRootsFirst::new(self)
+    }
+
     ///
     /// Returns the `NodeRef` pointing to the `Node` that the given `NodeId` identifies.  If the
     /// `NodeId` in question points to nothing (or belongs to a different `Tree`) a `None`-value
@@ -239,7 +553,7 @@ impl<T> Tree<T> {
     /// assert_eq!(root.data(), &1);
     /// ```
     ///
-    pub fn get(&self, node_id: NodeId) -> Option<NodeRef<T>> {
+    pub fn get(&self, node_id: NodeId) -> Option<NodeRef<'_, T>> {
         let _ = self.core_tree.get(node_id)?;
         Some(self.new_node_ref(node_id))
     }
@@ -265,11 +579,115 @@ impl<T> Tree<T> {
     /// assert_eq!(root.data(), &mut 2);
     /// ```
     ///
-    pub fn get_mut(&mut self, node_id: NodeId) -> Option<NodeMut<T>> {
+    pub fn get_mut(&mut self, node_id: NodeId) -> Option<NodeMut<'_, T>> {
         let _ = self.core_tree.get_mut(node_id)?;
         Some(self.new_node_mut(node_id))
     }
 
+    ///
+    /// Returns the `NodeRef` pointing to the `Node` that the given `NodeId` identifies, without
+    /// checking that the `NodeId` belongs to this `Tree` or that its `Node` is still there.
+    /// This is meant for hot paths where the caller has just validated the `NodeId` (say with
+    /// `get` or `contains`) and doesn't want to pay for it again.  The returned `NodeRef` still
+    /// validates its own `NodeId` when looking up its data and relatives.
+    ///
+    /// Debug builds still check the `NodeId` and panic if it's invalid.
+    ///
+    /// # Safety
+    ///
+    /// `node_id` must refer to a `Node` which is currently in this `Tree`; otherwise the
+    /// behavior is undefined.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    ///
+    /// assert!(tree.get(two_id).is_some());
+    /// let two = unsafe { tree.get_unchecked(two_id) };
+    /// assert_eq!(two.data(), &2);
+    /// assert_eq!(two.parent().unwrap().data(), &1);
+    /// ```
+    ///
+    #[cfg(feature = "unchecked")]
+    #[allow(unsafe_code)]
+    pub unsafe fn get_unchecked(&self, node_id: NodeId) -> NodeRef<'_, T> {
+        debug_assert!(
+            self.core_tree.get(node_id).is_some(),
+            "unchecked NodeId must be valid"
+        );
+        self.new_node_ref(node_id)
+    }
+
+    ///
+    /// Returns the `NodeMut` pointing to the `Node` that the given `NodeId` identifies, without
+    /// checking that the `NodeId` belongs to this `Tree` or that its `Node` is still there.  See
+    /// `get_unchecked`.
+    ///
+    /// Debug builds still check the `NodeId` and panic if it's invalid.
+    ///
+    /// # Safety
+    ///
+    /// `node_id` must refer to a `Node` which is currently in this `Tree`; otherwise the
+    /// behavior is undefined.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// let mut root = unsafe { tree.get_unchecked_mut(root_id) };
+    /// *root.data() = 2;
+    /// root.append(3);
+    /// assert_eq!(tree.root().unwrap().first_child().unwrap().data(), &3);
+    /// assert_eq!(tree.root().unwrap().data(), &2);
+    /// ```
+    ///
+    #[cfg(feature = "unchecked")]
+    #[allow(unsafe_code)]
+    pub unsafe fn get_unchecked_mut(&mut self, node_id: NodeId) -> NodeMut<'_, T> {
+        debug_assert!(
+            self.core_tree.get(node_id).is_some(),
+            "unchecked NodeId must be valid"
+        );
+        self.new_node_mut(node_id)
+    }
+
+    ///
+    /// Returns a `Tree` with the same structure as this one whose `Node`s hold references to
+    /// this `Tree`'s data, for handing a read-only projection to code that wants a `Tree` of its
+    /// own without cloning any data.  Orphaned `Node`s are included.
+    ///
+    /// The view keeps every `Node` at the same position, so a `NodeId` from this `Tree` refers
+    /// to the corresponding `Node` in the view and vice versa.  That only holds for `Node`s that
+    /// existed when the view was made: `NodeId`s of `Node`s added to the view later may refer to
+    /// unrelated `Node`s (or none) in this `Tree`.  The view has no observers or indexes.
+    ///
+    /// ```
+    /// use slab_tree::tree::{Tree, TreeBuilder};
+    ///
+    /// fn count_leaves(tree: Tree<&String>) -> usize {
+    ///     tree.root().map_or(0, |root| root.traverse_pre_order().filter(|n| n.is_leaf()).count())
+    /// }
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(String::from("root")).build();
+    /// let leaf_id = tree.root_mut().unwrap().append(String::from("leaf")).node_id();
+    ///
+    /// let view = tree.as_ref_tree();
+    /// assert_eq!(view.get(leaf_id).unwrap().data().as_str(), "leaf");
+    /// assert_eq!(count_leaves(view), 1);
+    /// ```
+    ///
+    pub fn as_ref_tree(&self) -> Tree<&T> {
+        Tree {
+            root_id: self.root_id,
+            core_tree: self.core_tree.map(|data| data),
+            hooks: Hooks::default(),
+        }
+    }
+
     ///
     /// Remove a `Node` by its `NodeId` and return the data that it contained.
     /// Returns a `Some`-value if the `Node` exists; returns a `None`-value otherwise.
@@ -303,30 +721,23 @@ impl<T> Tree<T> {
     /// ```
     ///
     pub fn remove(&mut self, node_id: NodeId, behavior: RemoveBehavior) -> Option<T> {
-        if let Some(node) = self.get_node(node_id) {
-            let Relatives {
-                parent,
-                prev_sibling,
-                next_sibling,
-                ..
-            } = node.relatives;
-
-            let (is_first_child, is_last_child) = self.is_node_first_last_child(node_id);
-
-            if is_first_child {
-                // parent first child = my next sibling
-                self.set_first_child(parent.expect("parent must exist"), next_sibling);
-            }
-            if is_last_child {
-                // parent last child = my prev sibling
-                self.set_last_child(parent.expect("parent must exist"), prev_sibling);
-            }
-            if let Some(prev) = prev_sibling {
-                self.set_next_sibling(prev, next_sibling);
-            }
-            if let Some(next) = next_sibling {
-                self.set_prev_sibling(next, prev_sibling);
+        if self.get_node(node_id).is_some() && !self.hooks.is_empty() {
+            let removed_ids: Vec<NodeId> = match behavior {
+                RemoveBehavior::DropChildren => self
+                    .get(node_id)
+                    .expect("node must exist")
+                    .traverse_pre_order()
+                    .map(|node_ref| node_ref.node_id())
+                    .collect(),
+                RemoveBehavior::OrphanChildren => vec![node_id],
+            };
+            for id in removed_ids {
+                self.notify_remove(id);
             }
+        }
+
+        if self.get_node(node_id).is_some() {
+            self.unlink(node_id);
 
             match behavior {
                 RemoveBehavior::DropChildren => self.drop_children(node_id),
@@ -341,6 +752,65 @@ impl<T> Tree<T> {
         }
     }
 
+    /// Detaches a `Node` (along with its sub-tree) from its parent and siblings, leaving it
+    /// without any of them.  Doesn't notify observers or touch `root_id`.
+    pub(crate) fn unlink(&mut self, node_id: NodeId) {
+        let Relatives {
+            parent,
+            prev_sibling,
+            next_sibling,
+            ..
+        } = self.get_node_relatives(node_id);
+
+        let (is_first_child, is_last_child) = self.is_node_first_last_child(node_id);
+
+        if is_first_child {
+            // parent first child = my next sibling
+            self.set_first_child(parent.expect("parent must exist"), next_sibling);
+        }
+        if is_last_child {
+            // parent last child = my prev sibling
+            self.set_last_child(parent.expect("parent must exist"), prev_sibling);
+        }
+        if let Some(prev) = prev_sibling {
+            self.set_next_sibling(prev, next_sibling);
+        }
+        if let Some(next) = next_sibling {
+            self.set_prev_sibling(next, prev_sibling);
+        }
+
+        self.set_parent(node_id, None);
+        self.set_prev_sibling(node_id, None);
+        self.set_next_sibling(node_id, None);
+    }
+
+    /// Links an unlinked `Node` in as a child of `parent_id`, just before the child `before`,
+    /// or as the last child if `before` is `None`.  Doesn't notify observers.
+    pub(crate) fn link_before(
+        &mut self,
+        node_id: NodeId,
+        parent_id: NodeId,
+        before: Option<NodeId>,
+    ) {
+        let prev_sibling = match before {
+            Some(before_id) => self.get_node_prev_sibling_id(before_id),
+            None => self.get_node_relatives(parent_id).last_child,
+        };
+
+        self.set_parent(node_id, Some(parent_id));
+        self.set_prev_sibling(node_id, prev_sibling);
+        self.set_next_sibling(node_id, before);
+
+        match prev_sibling {
+            Some(prev_id) => self.set_next_sibling(prev_id, Some(node_id)),
+            None => self.set_first_child(parent_id, Some(node_id)),
+        }
+        match before {
+            Some(before_id) => self.set_prev_sibling(before_id, Some(node_id)),
+            None => self.set_last_child(parent_id, Some(node_id)),
+        }
+    }
+
     pub(crate) fn get_node(&self, node_id: NodeId) -> Option<&Node<T>> {
         self.core_tree.get(node_id)
     }
@@ -349,6 +819,12 @@ impl<T> Tree<T> {
         self.core_tree.get_mut(node_id)
     }
 
+    /// Takes the data out of a `Node` without fixing up the links of its relatives; only used
+    /// on `Tree`s which are being consumed.
+    pub(crate) fn take_data(&mut self, node_id: NodeId) -> T {
+        self.core_tree.remove(node_id).expect("node must exist")
+    }
+
     pub(crate) fn set_prev_siblings_next_sibling(
         &mut self,
         current_id: NodeId,
@@ -369,11 +845,21 @@ impl<T> Tree<T> {
         }
     }
 
+    /// Also keeps the child counts of the old and new parents up to date, so every change of
+    /// parent has to go through here.
     pub(crate) fn set_parent(&mut self, node_id: NodeId, parent_id: Option<NodeId>) {
-        if let Some(node) = self.get_node_mut(node_id) {
-            node.relatives.parent = parent_id;
+        let old_parent_id = if let Some(node) = self.get_node_mut(node_id) {
+            mem::replace(&mut node.relatives.parent, parent_id)
         } else {
             unreachable!()
+        };
+        if old_parent_id != parent_id {
+            if let Some(old_parent) = old_parent_id.and_then(|id| self.get_node_mut(id)) {
+                old_parent.relatives.child_count -= 1;
+            }
+            if let Some(parent) = parent_id.and_then(|id| self.get_node_mut(id)) {
+                parent.relatives.child_count += 1;
+            }
         }
     }
 
@@ -434,37 +920,54 @@ impl<T> Tree<T> {
     }
 
     fn drop_children(&mut self, node_id: NodeId) {
-        let sub_tree_ids: Vec<NodeId> = self
-            .get(node_id)
-            .expect("node must exist")
-            .traverse_level_order()
-            .skip(1) // skip the "root" of the sub-tree, which is the "current" node
-            .map(|node_ref| node_ref.node_id())
-            .collect();
-
-        for id in sub_tree_ids {
-            self.core_tree.remove(id);
+        // free the sub-tree leaf by leaf, unhooking each leaf from its parent as it goes so the
+        // parent becomes a leaf once its last child is gone; no ids need to be collected
+        let mut current = self.get_node_relatives(node_id).first_child;
+        while let Some(mut leaf_id) = current {
+            while let Some(child_id) = self.get_node_relatives(leaf_id).first_child {
+                leaf_id = child_id;
+            }
+            let Relatives {
+                parent,
+                next_sibling,
+                ..
+            } = self.get_node_relatives(leaf_id);
+            let parent_id = parent.expect("descendant must have a parent");
+            self.core_tree.remove(leaf_id);
+            self.set_first_child(parent_id, next_sibling);
+
+            current = match next_sibling {
+                Some(next_id) => Some(next_id),
+                None if parent_id == node_id => None,
+                None => Some(parent_id),
+            };
+        }
+        self.set_last_child(node_id, None);
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.relatives.child_count = 0;
         }
     }
 
     fn orphan_children(&mut self, node_id: NodeId) {
-        let child_ids: Vec<NodeId> = self
-            .get(node_id)
-            .expect("node must exist")
-            .children()
-            .map(|node_ref| node_ref.node_id())
-            .collect();
+        let first_child = self.get_node_relatives(node_id).first_child;
 
-        for id in child_ids {
-            self.set_parent(id, None);
+        let mut current = first_child;
+        while let Some(child_id) = current {
+            self.set_parent(child_id, None);
+            current = self.get_node_next_sibling_id(child_id);
+        }
+        let mut current = first_child;
+        while let Some(child_id) = current {
+            self.notify_move(child_id);
+            current = self.get_node_next_sibling_id(child_id);
         }
     }
 
-    fn new_node_ref(&self, node_id: NodeId) -> NodeRef<T> {
+    fn new_node_ref(&self, node_id: NodeId) -> NodeRef<'_, T> {
         NodeRef::new(node_id, self)
     }
 
-    fn new_node_mut(&mut self, node_id: NodeId) -> NodeMut<T> {
+    fn new_node_mut(&mut self, node_id: NodeId) -> NodeMut<'_, T> {
         NodeMut::new(node_id, self)
     }
 
@@ -491,6 +994,73 @@ impl<T> Tree<T> {
     }
 }
 
+impl<T: Clone> Tree<T> {
+    ///
+    /// Returns a copy of the `Tree` with room for `extra` more `Node`s than it currently holds,
+    /// so that growing the copy doesn't reallocate right away.  Orphaned `Node`s are included.
+    ///
+    /// The copy is a separate `Tree`, so `NodeId`s from this `Tree` don't work on it.  It keeps
+    /// every `Node` at the same position though, so `Tree::rebind` translates a `NodeId` from
+    /// this `Tree` into the `NodeId` of the corresponding `Node` in the copy (and vice versa).
+    /// The copy has no observers, indexes or snapshots.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    ///
+    /// let copy = tree.clone_with_capacity(10);
+    /// assert!(copy.capacity() >= 12);
+    /// assert!(copy.get(two_id).is_none());
+    /// assert_eq!(*copy.get(copy.rebind(two_id).unwrap()).unwrap().data(), 2);
+    /// ```
+    ///
+    pub fn clone_with_capacity(&self, extra: usize) -> Tree<T> {
+        let mut core_tree = CoreTree::new(0);
+        self.core_tree.clone_into(&mut core_tree, extra);
+        let mut copy = Tree {
+            root_id: None,
+            core_tree,
+            hooks: Hooks::default(),
+        };
+        copy.root_id = self.root_id.and_then(|root_id| copy.rebind(root_id));
+        copy
+    }
+
+    ///
+    /// Replaces the contents of `target` with a copy of this `Tree`, reusing the allocation of
+    /// `target` instead of making a new one.  As with `clone_with_capacity`, `target` stays a
+    /// separate `Tree`: `NodeId`s from this `Tree` (and those `target` had before) don't work on
+    /// it, but `Tree::rebind` translates them.
+    ///
+    /// Observers and indexes registered with `target` are kept; indexes are rebuilt, while
+    /// observers aren't notified.  Snapshots of `target` are discarded.
+    ///
+    /// ```
+    /// use slab_tree::tree::{Tree, TreeBuilder};
+    ///
+    /// let mut front = TreeBuilder::new().with_root(0).build();
+    /// let mut back: Tree<i32> = TreeBuilder::new().with_capacity(16).build();
+    ///
+    /// for tick in 1..=3 {
+    ///     front.root_mut().unwrap().append(tick);
+    ///     front.clone_into(&mut back);
+    ///     // `back` keeps the allocation it started with
+    ///     assert_eq!(back.capacity(), 16);
+    /// }
+    ///
+    /// let children: Vec<i32> = back.root().unwrap().children().map(|n| *n.data()).collect();
+    /// assert_eq!(children, vec![1, 2, 3]);
+    /// ```
+    ///
+    pub fn clone_into(&self, target: &mut Tree<T>) {
+        self.core_tree.clone_into(&mut target.core_tree, 0);
+        target.root_id = self.root_id.and_then(|root_id| target.rebind(root_id));
+        target.rebuild_indexes();
+    }
+}
+
 impl<T> Default for Tree<T> {
     fn default() -> Self {
         TreeBuilder::new().build()
@@ -531,52 +1101,7 @@ impl<T: std::fmt::Debug> Tree<T> {
     /// assert_eq!(&s, "");
     /// ```
     pub fn write_formatted<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
-        if let Some(root) = self.root() {
-            let node_id = root.node_id();
-            let childn = 0;
-            let level = 0;
-            let last = vec![];
-            let mut stack = vec![(node_id, childn, level, last)];
-            while let Some((node_id, childn, level, last)) = stack.pop() {
-                debug_assert_eq!(
-                    last.len(),
-                    level,
-                    "each previous level should indicate whether it has reached the last node"
-                );
-                let node = self
-                    .get(node_id)
-                    .expect("getting node of existing node ref id");
-                if childn == 0 {
-                    for i in 1..level {
-                        if last[i - 1] {
-                            write!(w, "    ")?;
-                        } else {
-                            write!(w, "│   ")?;
-                        }
-                    }
-                    if level > 0 {
-                        if last[level - 1] {
-                            write!(w, "└── ")?;
-                        } else {
-                            write!(w, "├── ")?;
-                        }
-                    }
-                    writeln!(w, "{:?}", node.data())?;
-                }
-                let mut children = node.children().skip(childn);
-                if let Some(child) = children.next() {
-                    let mut next_last = last.clone();
-                    if children.next().is_some() {
-                        stack.push((node_id, childn + 1, level, last));
-                        next_last.push(false);
-                    } else {
-                        next_last.push(true);
-                    }
-                    stack.push((child.node_id(), 0, level + 1, next_last));
-                }
-            }
-        }
-        Ok(())
+        self.write_formatted_with(w, &crate::format::FormatOptions::default())
     }
 }
 
@@ -586,12 +1111,192 @@ mod tree_tests {
     use super::*;
     use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
 
+    #[test]
+    fn id_from_raw() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        let (index, generation) = one_id.to_raw();
+
+        assert_eq!(tree.id_from_raw(root_id.to_raw()), Some(root_id));
+        assert_eq!(tree.id_from_raw((index, generation)), Some(one_id));
+        assert_eq!(tree.id_from_raw((index, generation + 1)), None);
+        assert_eq!(tree.id_from_raw((index + 1, generation)), None);
+        assert_eq!(tree.id_from_raw((u64::MAX, u64::MAX)), None);
+
+        tree.remove(one_id, DropChildren);
+        assert_eq!(tree.id_from_raw((index, generation)), None);
+    }
+
+    #[test]
+    fn iter_all_roots_first() {
+        let mut tree = Tree::unfold(3, |n: i32| (n, (0..n).collect()));
+        let two_id = tree.root().unwrap().last_child().unwrap().node_id();
+        tree.remove(two_id, OrphanChildren);
+        let values: Vec<i32> = tree.iter_all_roots_first().map(|n| *n.data()).collect();
+        assert_eq!(values, vec![3, 0, 1, 0, 0, 1, 0]);
+
+        let root_id = tree.root_id().unwrap();
+        tree.remove(root_id, OrphanChildren);
+        let mut values: Vec<i32> = tree.iter_all_roots_first().map(|n| *n.data()).collect();
+        values.sort();
+        assert_eq!(values, vec![0, 0, 0, 0, 1, 1]);
+
+        assert_eq!(Tree::<i32>::new().iter_all_roots_first().count(), 0);
+    }
+
     #[test]
     fn capacity() {
         let tree = TreeBuilder::new().with_root(1).with_capacity(5).build();
         assert_eq!(tree.capacity(), 5);
     }
 
+    #[test]
+    fn with_generator() {
+        let tree = TreeBuilder::new()
+            .with_generator(3, |n| (0..*n).collect(), 10)
+            .build();
+        let data: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(data, vec![3, 0, 1, 0, 2, 0, 1, 0]);
+
+        let tree = TreeBuilder::new()
+            .with_generator(0, |n| vec![n + 1], 0)
+            .build();
+        assert_eq!(tree.root().unwrap().subtree_size(), 1);
+
+        let tree = TreeBuilder::new()
+            .with_generator(0, |n| vec![n + 1], 5)
+            .with_capacity(6)
+            .build();
+        assert_eq!(tree.root().unwrap().subtree_size(), 6);
+        assert_eq!(tree.capacity(), 6);
+    }
+
+    #[test]
+    fn unfold() {
+        let mut calls = Vec::new();
+        let tree = Tree::unfold(3, |n| {
+            calls.push(n);
+            (n, (0..n).collect())
+        });
+        let data: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(data, vec![3, 0, 1, 0, 2, 0, 1, 0]);
+        assert_eq!(calls, data);
+    }
+
+    #[test]
+    fn as_ref_tree() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        let two_id = tree.get_mut(one_id).unwrap().append(2).node_id();
+        let three_id = tree.root_mut().unwrap().append(3).node_id();
+        tree.remove(one_id, OrphanChildren);
+
+        let view = tree.as_ref_tree();
+        assert_eq!(view.root_id(), tree.root_id());
+        assert_eq!(view.capacity(), tree.capacity());
+        assert!(view.get(one_id).is_none());
+        assert_eq!(view.get(two_id).unwrap().data(), &&2);
+        assert!(view.get(two_id).unwrap().parent().is_none());
+
+        let root = view.root().unwrap();
+        let children: Vec<NodeId> = root.children().map(|node| node.node_id()).collect();
+        assert_eq!(children, vec![three_id]);
+    }
+
+    #[test]
+    fn new_chain() {
+        let (tree, leaf_id) = Tree::new_chain(0..5);
+        assert_eq!(tree.capacity(), 5);
+        let data: Vec<(i32, usize)> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| (*node.data(), node.children().count()))
+            .collect();
+        assert_eq!(data, vec![(0, 1), (1, 1), (2, 1), (3, 1), (4, 0)]);
+        assert_eq!(tree.get(leaf_id.unwrap()).unwrap().data(), &4);
+
+        let (empty, leaf_id) = Tree::<i32>::new_chain(None);
+        assert!(empty.root().is_none());
+        assert!(leaf_id.is_none());
+    }
+
+    #[test]
+    fn deep_chain() {
+        const DEPTH: usize = 100_000;
+
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut node_id = tree.root_id().unwrap();
+        for i in 1..DEPTH {
+            node_id = tree.get_mut(node_id).unwrap().append(i).node_id();
+        }
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.traverse_pre_order().count(), DEPTH);
+        assert_eq!(root.traverse_post_order().count(), DEPTH);
+        assert_eq!(root.subtree_size(), DEPTH);
+        assert_eq!(tree.stats().height, DEPTH - 1);
+        assert_eq!(tree.get(node_id).unwrap().ancestors().count(), DEPTH - 1);
+
+        let first_id = root.first_child().unwrap().node_id();
+        assert_eq!(tree.remove(first_id, DropChildren), Some(1));
+        assert_eq!(tree.root().unwrap().subtree_size(), 1);
+        assert!(tree.get(node_id).is_none());
+
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut node_id = tree.root_id().unwrap();
+        for i in 1..DEPTH {
+            node_id = tree.get_mut(node_id).unwrap().append(i).node_id();
+        }
+        drop(tree);
+    }
+
+    #[test]
+    fn clear() {
+        let mut tree = TreeBuilder::new().with_root(1).with_capacity(5).build();
+        let one_id = tree.root_id().unwrap();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        tree.remove(two_id, OrphanChildren);
+        let snapshot = tree.snapshot();
+
+        tree.clear();
+        assert_eq!(tree.capacity(), 5);
+        assert!(tree.root_id().is_none());
+        assert!(tree.get(one_id).is_none());
+        assert!(!tree.restore(snapshot));
+
+        let new_root_id = tree.set_root(3);
+        assert_eq!(tree.capacity(), 5);
+        assert_ne!(new_root_id, one_id);
+        assert!(tree.get(one_id).is_none());
+        assert_eq!(tree.get(new_root_id).unwrap().data(), &3);
+    }
+
+    #[test]
+    fn set_root_shifts_old_root_down() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let old_root_id = tree.root_id().expect("root doesn't exist?");
+        let new_root_id = tree.set_root(0);
+
+        let root = tree.get_node(new_root_id).unwrap();
+        assert_eq!(root.relatives.first_child, Some(old_root_id));
+        assert_eq!(root.relatives.last_child, Some(old_root_id));
+
+        let old_root = tree.get_node(old_root_id).unwrap();
+        assert_eq!(old_root.relatives.parent, Some(new_root_id));
+    }
+
     #[test]
     fn root_id() {
         let tree = TreeBuilder::new().with_root(1).build();
@@ -690,6 +1395,65 @@ mod tree_tests {
         assert_eq!(root.data, 2);
     }
 
+    #[cfg(feature = "unchecked")]
+    #[test]
+    #[allow(unsafe_code)]
+    fn get_unchecked_walks_like_get() {
+        let tree = Tree::unfold(3, |n: i32| (n, (0..n).collect()));
+        for node_id in tree.all_node_ids() {
+            let checked = tree.get(node_id).unwrap();
+            let unchecked = unsafe { tree.get_unchecked(node_id) };
+            assert_eq!(unchecked.data(), checked.data());
+            let values: Vec<i32> = unchecked.traverse_pre_order().map(|n| *n.data()).collect();
+            let expected: Vec<i32> = checked.traverse_pre_order().map(|n| *n.data()).collect();
+            assert_eq!(values, expected);
+        }
+    }
+
+    #[cfg(all(feature = "unchecked", debug_assertions))]
+    #[test]
+    #[should_panic]
+    #[allow(unsafe_code)]
+    fn get_unchecked_panics_on_removed_node_in_debug() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        tree.remove(two_id, DropChildren);
+        let _ = unsafe { tree.get_unchecked(two_id) };
+    }
+
+    #[test]
+    fn remove_drop_large_subtree() {
+        let mut tree = Tree::unfold(4, |n: i32| (n, (0..n).collect()));
+        let root_id = tree.root_id().unwrap();
+        let three_id = tree.root().unwrap().last_child().unwrap().node_id();
+        let kept: Vec<NodeId> = tree
+            .root()
+            .unwrap()
+            .children()
+            .filter(|child| child.node_id() != three_id)
+            .flat_map(|child| child.traverse_pre_order().map(|node| node.node_id()))
+            .collect();
+        let dropped: Vec<NodeId> = tree
+            .get(three_id)
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| node.node_id())
+            .collect();
+
+        assert_eq!(tree.remove(three_id, DropChildren), Some(3));
+        assert_eq!(tree.core_tree.len(), kept.len() + 1);
+        assert!(dropped.iter().all(|&node_id| tree.get(node_id).is_none()));
+
+        let remaining: Vec<NodeId> = tree
+            .get(root_id)
+            .unwrap()
+            .traverse_pre_order()
+            .skip(1)
+            .map(|node| node.node_id())
+            .collect();
+        assert_eq!(remaining, kept);
+    }
+
     #[test]
     fn remove_drop() {
         let mut tree = TreeBuilder::new().with_root(1).build();
@@ -809,4 +1573,65 @@ mod tree_tests {
         let five = five.unwrap();
         assert_eq!(five.relatives.parent, None);
     }
+
+    #[test]
+    fn clones_are_independent() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let two_id = tree.root_mut().unwrap().append(2).node_id();
+        let three_id = tree.get_mut(two_id).unwrap().append(3).node_id();
+        tree.remove(three_id, DropChildren);
+        let four_id = tree.root_mut().unwrap().append(4).node_id();
+
+        let mut copy = tree.clone_with_capacity(0);
+        assert_ne!(copy, tree);
+        assert!(copy.get(two_id).is_none());
+        let copy_four_id = copy.rebind(four_id).unwrap();
+        assert_eq!(copy.root_id(), copy.rebind(tree.root_id().unwrap()));
+        let copy_two_id = copy.rebind(two_id).unwrap();
+        copy.remove(copy_two_id, DropChildren);
+        *copy.get_mut(copy_four_id).unwrap().data() = 40;
+        assert_eq!(*tree.get(two_id).unwrap().data(), 2);
+        assert_eq!(*tree.get(four_id).unwrap().data(), 4);
+        let values: Vec<i32> = copy
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|n| *n.data())
+            .collect();
+        assert_eq!(values, vec![1, 40]);
+
+        let mut target = TreeBuilder::new()
+            .with_root(0)
+            .with_capacity(8)
+            .build()
+            .with_key(|n: &i32| *n);
+        let target_root_id = target.root_id().unwrap();
+        copy.clone_into(&mut target);
+        assert_eq!(target.capacity(), 8);
+        assert!(target.get(target_root_id).is_none());
+        assert!(target.get(copy_four_id).is_none());
+        assert_eq!(
+            target.get_by_key(&40).unwrap().node_id(),
+            target.rebind(copy_four_id).unwrap()
+        );
+        assert!(target.rebind(three_id).is_none());
+        assert_eq!(target.root().unwrap().children().count(), 1);
+    }
+
+    #[test]
+    fn try_reserve_covers_inserts() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        tree.try_reserve(64).unwrap();
+        let capacity = tree.capacity();
+        let mut root = tree.root_mut().unwrap();
+        for n in 1..=64 {
+            root.try_append(n).unwrap();
+        }
+        assert_eq!(tree.capacity(), capacity);
+
+        let before = tree.capacity();
+        assert!(tree.try_reserve(usize::MAX).is_err());
+        assert_eq!(tree.capacity(), before);
+        assert_eq!(tree.root().unwrap().children().count(), 64);
+    }
 }
@@ -0,0 +1,235 @@
+use crate::observer::TreeIndex;
+use crate::observer::TreeObserver;
+use crate::tree::Tree;
+use crate::NodeId;
+use std::any::Any;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::hash::Hash;
+
+/// Maps the hash of every `Node`'s data to the `NodeId`s of the `Node`s with data hashing to it.
+#[derive(Default)]
+struct ValueIndex {
+    state: RandomState,
+    // every node whose data has a given hash, in the order they were indexed
+    ids: HashMap<u64, Vec<NodeId>>,
+    hashes: HashMap<NodeId, u64>,
+}
+
+impl ValueIndex {
+    fn hash<T: Hash>(&self, value: &T) -> u64 {
+        self.state.hash_one(value)
+    }
+
+    fn insert<T: Hash>(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        if let Some(node) = tree.get_node(node_id) {
+            let hash = self.hash(&node.data);
+            self.ids.entry(hash).or_default().push(node_id);
+            self.hashes.insert(node_id, hash);
+        }
+    }
+
+    fn remove(&mut self, node_id: NodeId) {
+        if let Some(hash) = self.hashes.remove(&node_id) {
+            if let Some(ids) = self.ids.get_mut(&hash) {
+                ids.retain(|&id| id != node_id);
+                if ids.is_empty() {
+                    self.ids.remove(&hash);
+                }
+            }
+        }
+    }
+}
+
+impl<T: Hash> TreeObserver<T> for ValueIndex {
+    fn on_insert(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        self.insert(tree, node_id);
+    }
+
+    fn on_remove(&mut self, _tree: &Tree<T>, node_id: NodeId) {
+        self.remove(node_id);
+    }
+
+    fn on_data_replaced(&mut self, tree: &Tree<T>, node_id: NodeId) {
+        self.remove(node_id);
+        self.insert(tree, node_id);
+    }
+}
+
+impl<T: Hash> TreeIndex<T> for ValueIndex {
+    fn rebuild(&mut self, tree: &Tree<T>) {
+        self.ids.clear();
+        self.hashes.clear();
+        for node_id in tree.all_node_ids() {
+            self.insert(tree, node_id);
+        }
+    }
+
+    fn remap(&mut self, node_ids: &HashMap<NodeId, NodeId>) {
+        let remap = |node_id: &mut NodeId| {
+            if let Some(&new_id) = node_ids.get(node_id) {
+                *node_id = new_id;
+            }
+        };
+        for ids in self.ids.values_mut() {
+            ids.iter_mut().for_each(remap);
+        }
+        self.hashes = self
+            .hashes
+            .drain()
+            .map(|(mut node_id, hash)| {
+                remap(&mut node_id);
+                (node_id, hash)
+            })
+            .collect();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T: Hash + Eq> Tree<T> {
+    ///
+    /// Makes the `Tree` maintain an index from each `Node`'s data to the `Node`'s `NodeId`, so
+    /// that `nodes_with` can find every `Node` holding a given value without scanning the
+    /// `Tree`.  The index covers every `Node` in the `Tree` (including orphans) and is kept up to
+    /// date as `Node`s are inserted, removed, or have their data replaced.
+    ///
+    /// As with `with_key`, changes made to the data through `NodeMut::data` aren't picked up; use
+    /// `NodeMut::replace_data` instead.  Setting up the index on a `Tree` which already has one
+    /// does nothing.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("src").build().with_value_index();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let lib_id = root.append("lib.rs").node_id();
+    /// let mod_id = root.append("tree").append("mod.rs").node_id();
+    ///
+    /// assert_eq!(tree.nodes_with(&"lib.rs"), vec![lib_id]);
+    ///
+    /// tree.get_mut(lib_id).unwrap().replace_data("mod.rs");
+    /// assert!(tree.nodes_with(&"lib.rs").is_empty());
+    /// assert_eq!(tree.nodes_with(&"mod.rs"), vec![mod_id, lib_id]);
+    /// ```
+    ///
+    pub fn with_value_index(mut self) -> Tree<T> {
+        if self.value_index().is_none() {
+            let mut index = ValueIndex::default();
+            index.rebuild(&self);
+            self.hooks.add_index(Box::new(index));
+        }
+        self
+    }
+
+    ///
+    /// Returns the `NodeId`s of every `Node` (including orphans) whose data is equal to `value`.
+    /// With an index set up by `with_value_index`, `Node`s are listed in the order they were
+    /// indexed and only those with a matching hash are compared; otherwise every `Node` in the
+    /// `Tree` is checked.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append(2).node_id();
+    ///
+    /// assert_eq!(tree.nodes_with(&2), vec![two_id]);
+    /// assert_eq!(tree.with_value_index().nodes_with(&2), vec![two_id]);
+    /// ```
+    ///
+    pub fn nodes_with(&self, value: &T) -> Vec<NodeId> {
+        let matches = |node_id: &NodeId| {
+            self.get_node(*node_id)
+                .is_some_and(|node| node.data == *value)
+        };
+        match self.value_index() {
+            Some(index) => index
+                .ids
+                .get(&index.hash(value))
+                .map(|ids| ids.iter().copied().filter(matches).collect())
+                .unwrap_or_default(),
+            None => self.all_node_ids().into_iter().filter(matches).collect(),
+        }
+    }
+
+    fn value_index(&self) -> Option<&ValueIndex> {
+        self.hooks
+            .indexes()
+            .find_map(|index| index.as_any().downcast_ref::<ValueIndex>())
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod value_index_tests {
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::tree::TreeBuilder;
+    use crate::NodeId;
+    use std::hash::Hash;
+    use std::hash::Hasher;
+
+    // every value hashes the same, so lookups have to fall back on equality
+    #[derive(Debug, PartialEq, Eq)]
+    struct Colliding(i32);
+
+    impl Hash for Colliding {
+        fn hash<H: Hasher>(&self, _state: &mut H) {}
+    }
+
+    #[test]
+    fn tracks_inserts_and_removals() {
+        let mut tree = TreeBuilder::new().with_root(0).build().with_value_index();
+        let mut root = tree.root_mut().unwrap();
+        let a_id = root.append(1).node_id();
+        let b_id = root.append(1).node_id();
+        let c_id = tree.get_mut(b_id).unwrap().append(2).node_id();
+        assert_eq!(tree.nodes_with(&1), vec![a_id, b_id]);
+        assert_eq!(tree.nodes_with(&2), vec![c_id]);
+
+        tree.remove(a_id, OrphanChildren);
+        assert_eq!(tree.nodes_with(&1), vec![b_id]);
+
+        tree.remove(b_id, DropChildren);
+        assert!(tree.nodes_with(&1).is_empty());
+        assert!(tree.nodes_with(&2).is_empty());
+        assert_eq!(tree.nodes_with(&0), vec![tree.root_id().unwrap()]);
+    }
+
+    #[test]
+    fn survives_compaction() {
+        let mut tree = TreeBuilder::new().with_root(0).build().with_value_index();
+        let one_id = tree.root_mut().unwrap().append(1).node_id();
+        tree.root_mut().unwrap().append(2);
+        tree.remove(one_id, DropChildren);
+        tree.compact();
+
+        let two_id = tree.root().unwrap().first_child().unwrap().node_id();
+        assert_eq!(tree.nodes_with(&2), vec![two_id]);
+        assert!(tree.nodes_with(&1).is_empty());
+    }
+
+    #[test]
+    fn compares_colliding_values() {
+        let mut tree = TreeBuilder::new()
+            .with_root(Colliding(0))
+            .build()
+            .with_value_index()
+            .with_value_index();
+        let one_id = tree.root_mut().unwrap().append(Colliding(1)).node_id();
+
+        assert_eq!(tree.nodes_with(&Colliding(1)), vec![one_id]);
+        assert_eq!(
+            tree.nodes_with(&Colliding(0)),
+            vec![tree.root_id().unwrap()]
+        );
+        assert_eq!(tree.nodes_with(&Colliding(2)), Vec::<NodeId>::new());
+    }
+}
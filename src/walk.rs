@@ -0,0 +1,399 @@
+//!
+//! Walking a `Tree` with a callback which can edit each `Node`'s data, either depth-first (and
+//! steering the walk) or level by level.
+//!
+
+use crate::tree::Tree;
+use crate::NodeId;
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+
+///
+/// Tells `Tree::walk_mut` whether to visit the children of the `Node` it just visited.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Descend {
+    ///
+    /// Visit the `Node`'s children next.
+    ///
+    Into,
+
+    ///
+    /// Skip the `Node`'s children and move on to its next sibling.
+    ///
+    Skip,
+}
+
+///
+/// Where the `Node` currently being visited by `Tree::walk_mut` sits in the `Tree`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WalkContext {
+    node_id: NodeId,
+    parent_id: Option<NodeId>,
+    depth: usize,
+    index: usize,
+}
+
+impl WalkContext {
+    ///
+    /// Returns the `NodeId` of the `Node` being visited.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    /// use slab_tree::walk::Descend;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().unwrap();
+    ///
+    /// tree.walk_mut(|_, context| {
+    ///     assert_eq!(context.node_id(), root_id);
+    ///     ControlFlow::Continue(Descend::Into)
+    /// });
+    /// ```
+    ///
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    ///
+    /// Returns the `NodeId` of the parent of the `Node` being visited, or `None` for the root.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    /// use slab_tree::walk::Descend;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// tree.root_mut().unwrap().append(2);
+    /// let root_id = tree.root_id();
+    ///
+    /// tree.walk_mut(|data, context| {
+    ///     let expected = if *data == 1 { None } else { root_id };
+    ///     assert_eq!(context.parent_id(), expected);
+    ///     ControlFlow::Continue(Descend::Into)
+    /// });
+    /// ```
+    ///
+    pub fn parent_id(&self) -> Option<NodeId> {
+        self.parent_id
+    }
+
+    ///
+    /// Returns the depth of the `Node` being visited; the root is at depth 0.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    /// use slab_tree::walk::Descend;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(0).append(0);
+    ///
+    /// tree.walk_mut(|data, context| {
+    ///     *data = context.depth();
+    ///     ControlFlow::Continue(Descend::Into)
+    /// });
+    ///
+    /// let depths: Vec<usize> = tree.root().unwrap().traverse_pre_order().map(|n| *n.data()).collect();
+    /// assert_eq!(depths, vec![0, 1, 2]);
+    /// ```
+    ///
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    ///
+    /// Returns the position of the `Node` being visited among its siblings; the first child of a
+    /// `Node` (and the root) is at index 0.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    /// use slab_tree::walk::Descend;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// tree.root_mut().unwrap().append(0);
+    /// tree.root_mut().unwrap().append(0);
+    ///
+    /// tree.walk_mut(|data, context| {
+    ///     *data = context.index();
+    ///     ControlFlow::Continue(Descend::Into)
+    /// });
+    ///
+    /// let indexes: Vec<usize> = tree.root().unwrap().children().map(|n| *n.data()).collect();
+    /// assert_eq!(indexes, vec![0, 1]);
+    /// ```
+    ///
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<T> Tree<T> {
+    ///
+    /// Walks the `Tree` from the root in pre-order, handing each `Node`'s data to `f` along with
+    /// a `WalkContext` describing where the `Node` is.  `f` may edit the data, and returns
+    /// `ControlFlow::Continue(Descend::Into)` to visit the `Node`'s children next,
+    /// `ControlFlow::Continue(Descend::Skip)` to skip them, or `ControlFlow::Break(())` to stop
+    /// the walk altogether.  Returns `ControlFlow::Break(())` if the walk was stopped early.
+    ///
+    /// As with `NodeMut::data`, edits made this way aren't reported to observers or indexes.
+    /// Orphaned `Node`s are not visited.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    /// use slab_tree::walk::Descend;
+    /// use std::ops::ControlFlow;
+    ///
+    /// //        "src"
+    /// //        /   \
+    /// // "target"   "lib.rs"
+    /// //     |
+    /// //  "debug"
+    /// let mut tree = TreeBuilder::new().with_root(String::from("src")).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(String::from("target")).append(String::from("debug"));
+    /// root.append(String::from("lib.rs"));
+    ///
+    /// // upper-case every name, without descending into "target"
+    /// tree.walk_mut(|name, _| {
+    ///     *name = name.to_uppercase();
+    ///     if name == "TARGET" {
+    ///         ControlFlow::Continue(Descend::Skip)
+    ///     } else {
+    ///         ControlFlow::Continue(Descend::Into)
+    ///     }
+    /// });
+    ///
+    /// let names: Vec<&str> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_pre_order()
+    ///     .map(|node| node.data().as_str())
+    ///     .collect();
+    /// assert_eq!(names, vec!["SRC", "TARGET", "debug", "LIB.RS"]);
+    /// ```
+    ///
+    pub fn walk_mut<F>(&mut self, mut f: F) -> ControlFlow<()>
+    where
+        F: FnMut(&mut T, &WalkContext) -> ControlFlow<(), Descend>,
+    {
+        // the next node to visit on each level, deepest last
+        let mut stack: Vec<WalkContext> = self
+            .root_id
+            .map(|node_id| WalkContext {
+                node_id,
+                parent_id: None,
+                depth: 0,
+                index: 0,
+            })
+            .into_iter()
+            .collect();
+
+        while let Some(context) = stack.pop() {
+            let node = self
+                .get_node_mut(context.node_id)
+                .expect("walked node doesn't exist?");
+            let descend = f(&mut node.data, &context)?;
+            let relatives = node.relatives;
+
+            if context.depth > 0 {
+                if let Some(node_id) = relatives.next_sibling {
+                    stack.push(WalkContext {
+                        node_id,
+                        index: context.index + 1,
+                        ..context
+                    });
+                }
+            }
+            if descend == Descend::Into {
+                if let Some(node_id) = relatives.first_child {
+                    stack.push(WalkContext {
+                        node_id,
+                        parent_id: Some(context.node_id),
+                        depth: context.depth + 1,
+                        index: 0,
+                    });
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    ///
+    /// Visits every `Node` reachable from the root level by level (in the same order as
+    /// `traverse_level_order`), handing `f` each `Node`'s depth (0 for the root) and mutable
+    /// access to its data.  Every `Node` on a level is visited before any `Node` on the next.
+    ///
+    /// As with `NodeMut::data`, edits made this way aren't reported to observers or indexes.
+    /// Orphaned `Node`s are not visited.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(0).append(0);
+    /// root.append(0);
+    ///
+    /// // number the nodes level by level, remembering where each level started
+    /// let mut next = 0;
+    /// let mut level_starts = Vec::new();
+    /// tree.for_each_level_mut(|depth, data| {
+    ///     if depth == level_starts.len() {
+    ///         level_starts.push(next);
+    ///     }
+    ///     *data = next;
+    ///     next += 1;
+    /// });
+    ///
+    /// assert_eq!(level_starts, vec![0, 1, 3]);
+    /// let numbers: Vec<i32> = tree
+    ///     .root()
+    ///     .unwrap()
+    ///     .traverse_pre_order()
+    ///     .map(|node| *node.data())
+    ///     .collect();
+    /// assert_eq!(numbers, vec![0, 1, 3, 2]);
+    /// ```
+    ///
+    pub fn for_each_level_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &mut T),
+    {
+        let mut queue: VecDeque<(NodeId, usize)> = self
+            .root_id
+            .map(|node_id| (node_id, 0))
+            .into_iter()
+            .collect();
+
+        while let Some((node_id, depth)) = queue.pop_front() {
+            let node = self
+                .get_node_mut(node_id)
+                .expect("queued node doesn't exist?");
+            f(depth, &mut node.data);
+
+            let mut child = node.relatives.first_child;
+            while let Some(child_id) = child {
+                queue.push_back((child_id, depth + 1));
+                child = self.get_node_relatives(child_id).next_sibling;
+            }
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod walk_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    fn sample() -> Tree<i32> {
+        Tree::unfold(3, |n| (n, (0..n).collect()))
+    }
+
+    #[test]
+    fn visits_in_pre_order() {
+        let mut tree = sample();
+        let expected: Vec<(NodeId, Option<NodeId>, usize)> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| {
+                let parent_id = node.parent().map(|parent| parent.node_id());
+                (node.node_id(), parent_id, node.ancestors().count())
+            })
+            .collect();
+
+        let mut visited = Vec::new();
+        let flow = tree.walk_mut(|_, context| {
+            visited.push((context.node_id(), context.parent_id(), context.depth()));
+            ControlFlow::Continue(Descend::Into)
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn skip_and_break() {
+        let mut tree = sample();
+        let mut visited = Vec::new();
+        let flow = tree.walk_mut(|data, _| {
+            visited.push(*data);
+            if *data == 2 {
+                ControlFlow::Continue(Descend::Skip)
+            } else {
+                ControlFlow::Continue(Descend::Into)
+            }
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(visited, vec![3, 0, 1, 0, 2]);
+
+        let mut visited = Vec::new();
+        let flow = tree.walk_mut(|data, _| {
+            visited.push(*data);
+            if *data == 1 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(Descend::Into)
+            }
+        });
+        assert_eq!(flow, ControlFlow::Break(()));
+        assert_eq!(visited, vec![3, 0, 1]);
+    }
+
+    #[test]
+    fn root_siblings_are_not_visited() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let old_root_id = tree.root_id().unwrap();
+        tree.set_root(0);
+
+        let mut visited = Vec::new();
+        let flow = tree.walk_mut(|data, context| {
+            visited.push((*data, context.index()));
+            ControlFlow::Continue(Descend::Skip)
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(visited, vec![(0, 0)]);
+        assert!(tree.get(old_root_id).is_some());
+    }
+
+    #[test]
+    fn empty() {
+        let mut tree: Tree<i32> = Tree::new();
+        let flow = tree.walk_mut(|_, _| ControlFlow::Break(()));
+        assert_eq!(flow, ControlFlow::Continue(()));
+        tree.for_each_level_mut(|_, _| panic!("empty tree has no nodes"));
+    }
+
+    #[test]
+    fn levels_match_level_order() {
+        let mut tree = sample();
+        let expected: Vec<(NodeId, usize)> = tree
+            .root()
+            .unwrap()
+            .traverse_level_order()
+            .map(|node| (node.node_id(), node.ancestors().count()))
+            .collect();
+        let ids: Vec<NodeId> = expected.iter().map(|&(node_id, _)| node_id).collect();
+
+        // tag each node with its position in the visit, then compare against the expected order
+        let mut depths = Vec::new();
+        let mut position = 0;
+        tree.for_each_level_mut(|depth, data| {
+            depths.push(depth);
+            *data = position;
+            position += 1;
+        });
+        let positions: Vec<i32> = ids
+            .iter()
+            .map(|&id| *tree.get(id).unwrap().data())
+            .collect();
+        assert_eq!(positions, (0..expected.len() as i32).collect::<Vec<i32>>());
+        let expected_depths: Vec<usize> = expected.iter().map(|&(_, depth)| depth).collect();
+        assert_eq!(depths, expected_depths);
+    }
+}
@@ -0,0 +1,458 @@
+//!
+//! Reading and writing `Tree`s as XML documents, with one `Node` per element.
+//!
+//! Each element is handed to (and requested from) the caller as an `XmlElement`: its name, its
+//! attributes and the text directly inside it.  The elements nested in an element become the
+//! children of its `Node`, in order.  When reading, each run of text (or CDATA) is trimmed,
+//! whitespace-only runs are skipped, and the remaining runs inside an element are joined with
+//! single spaces; comments, processing instructions and the XML declaration are skipped.
+//!
+//! Both reading and writing use heap-allocated stacks rather than recursion, so deeply nested
+//! documents can't overflow the call stack.
+//!
+
+use crate::node::NodeRef;
+use crate::tree::Tree;
+use crate::tree::TreeBuilder;
+use crate::NodeId;
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+///
+/// The name, attributes and text of a single XML element.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XmlElement {
+    ///
+    /// The element's (qualified) name.
+    ///
+    pub name: String,
+
+    ///
+    /// The element's attributes as `(name, value)` pairs, in document order.
+    ///
+    pub attributes: Vec<(String, String)>,
+
+    ///
+    /// The text directly inside the element; empty if there is none.  It is written before any
+    /// child elements.
+    ///
+    pub text: String,
+}
+
+impl XmlElement {
+    ///
+    /// Creates an `XmlElement` with the given name and no attributes or text.
+    ///
+    /// ```
+    /// use slab_tree::xml::XmlElement;
+    ///
+    /// let element = XmlElement::new("item");
+    /// assert_eq!(element.name, "item");
+    /// assert!(element.attributes.is_empty());
+    /// assert!(element.text.is_empty());
+    /// ```
+    ///
+    pub fn new<S: Into<String>>(name: S) -> XmlElement {
+        XmlElement {
+            name: name.into(),
+            ..XmlElement::default()
+        }
+    }
+
+    ///
+    /// Adds an attribute to the `XmlElement`.
+    ///
+    /// ```
+    /// use slab_tree::xml::XmlElement;
+    ///
+    /// let element = XmlElement::new("item").with_attribute("id", "7");
+    /// assert_eq!(element.attribute("id"), Some("7"));
+    /// ```
+    ///
+    pub fn with_attribute<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.attributes.push((name.into(), value.into()));
+        self
+    }
+
+    ///
+    /// Sets the text of the `XmlElement`.
+    ///
+    /// ```
+    /// use slab_tree::xml::XmlElement;
+    ///
+    /// let element = XmlElement::new("title").with_text("Hello");
+    /// assert_eq!(element.text, "Hello");
+    /// ```
+    ///
+    pub fn with_text<S: Into<String>>(mut self, text: S) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    ///
+    /// Returns the value of the first attribute with the given name, if there is one.
+    ///
+    /// ```
+    /// use slab_tree::xml::XmlElement;
+    ///
+    /// let element = XmlElement::new("item").with_attribute("id", "7");
+    /// assert_eq!(element.attribute("id"), Some("7"));
+    /// assert_eq!(element.attribute("class"), None);
+    /// ```
+    ///
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+///
+/// Describes the ways in which reading an XML document can fail.  Positions are byte offsets
+/// into the input.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlError {
+    ///
+    /// The input isn't well-formed XML (or couldn't be read) at the given position.
+    ///
+    Syntax {
+        /// Where the problem was found.
+        position: usize,
+        /// What the problem is.
+        message: String,
+    },
+
+    ///
+    /// The input ended before every element was closed.
+    ///
+    UnexpectedEof,
+
+    ///
+    /// The element starting at the given position is a second root element.
+    ///
+    MultipleRoots(usize),
+
+    ///
+    /// There is text outside the root element at the given position.
+    ///
+    TextOutsideRoot(usize),
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XmlError::Syntax { position, message } => {
+                write!(f, "invalid XML at position {}: {}", position, message)
+            }
+            XmlError::UnexpectedEof => write!(f, "unexpected end of input"),
+            XmlError::MultipleRoots(position) => {
+                write!(f, "second root element at position {}", position)
+            }
+            XmlError::TextOutsideRoot(position) => {
+                write!(f, "text outside the root element at position {}", position)
+            }
+        }
+    }
+}
+
+impl Error for XmlError {}
+
+impl<T> Tree<T> {
+    ///
+    /// Reads a `Tree` from an XML document, as described in the `xml` module, using
+    /// `element_to_data` to turn each element into its `Node`'s data.  `element_to_data` is
+    /// called in pre-order, once the whole document has been read.  A document without elements
+    /// produces an empty `Tree`.
+    ///
+    /// ```
+    /// use slab_tree::tree::Tree;
+    ///
+    /// let input = r#"<?xml version="1.0"?>
+    /// <menu id="file">
+    ///   <item action="open">Open&#8230;</item>
+    ///   <item action="quit"><![CDATA[Quit & close]]></item>
+    /// </menu>"#;
+    /// let tree = Tree::from_xml(input.as_bytes(), |element| element).unwrap();
+    ///
+    /// let root = tree.root().expect("root doesn't exist?");
+    /// assert_eq!(root.data().attribute("id"), Some("file"));
+    ///
+    /// let items: Vec<&str> = root.children().map(|node| node.data().text.as_str()).collect();
+    /// assert_eq!(items, vec!["Open…", "Quit & close"]);
+    /// ```
+    ///
+    pub fn from_xml<R, F>(reader: R, mut element_to_data: F) -> Result<Tree<T>, XmlError>
+    where
+        R: io::BufRead,
+        F: FnMut(XmlElement) -> T,
+    {
+        let mut pending = parse_elements(reader)?;
+        let mut tree = TreeBuilder::new().with_capacity(pending.len()).build();
+        if pending.is_empty() {
+            return Ok(tree);
+        }
+
+        let mut stack: Vec<(Option<NodeId>, usize)> = vec![(None, 0)];
+        while let Some((parent_id, index)) = stack.pop() {
+            let (element, children) = &mut pending[index];
+            let value = element_to_data(element.take().expect("each element is visited once"));
+            let node_id = match parent_id {
+                Some(parent_id) => tree
+                    .get_mut(parent_id)
+                    .expect("parent was inserted before its children")
+                    .append(value)
+                    .node_id(),
+                None => tree.set_root(value),
+            };
+            stack.extend(children.iter().rev().map(|&child| (Some(node_id), child)));
+        }
+
+        Ok(tree)
+    }
+
+    ///
+    /// Writes the root's sub-tree to `w` as an XML document, using `data_to_element` to describe
+    /// each `Node`.  Text and attribute values are escaped, and elements without text or children
+    /// are written as empty-element tags.  Writes nothing if the `Tree` is empty.
+    ///
+    /// ```
+    /// use slab_tree::tree::TreeBuilder;
+    /// use slab_tree::xml::XmlElement;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(("list", "")).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append(("item", "fish & chips"));
+    /// root.append(("break", ""));
+    ///
+    /// let mut out = Vec::new();
+    /// tree.write_xml(&mut out, |&(name, text)| XmlElement::new(name).with_text(text))
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(out).unwrap(),
+    ///     "<list><item>fish &amp; chips</item><break/></list>"
+    /// );
+    /// ```
+    ///
+    pub fn write_xml<W, F>(&self, w: W, mut data_to_element: F) -> io::Result<()>
+    where
+        W: io::Write,
+        F: FnMut(&T) -> XmlElement,
+    {
+        enum Step<'a, T> {
+            Open(NodeRef<'a, T>),
+            Close(String),
+        }
+
+        let root = match self.root() {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+
+        let mut writer = Writer::new(w);
+        let mut stack = vec![Step::Open(root)];
+        while let Some(step) = stack.pop() {
+            let node = match step {
+                Step::Open(node) => node,
+                Step::Close(name) => {
+                    writer.write_event(Event::End(BytesEnd::new(name)))?;
+                    continue;
+                }
+            };
+
+            let element = data_to_element(node.data());
+            let start = BytesStart::new(element.name.as_str()).with_attributes(
+                element
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str())),
+            );
+            if element.text.is_empty() && node.first_child().is_none() {
+                writer.write_event(Event::Empty(start))?;
+                continue;
+            }
+            writer.write_event(Event::Start(start))?;
+            if !element.text.is_empty() {
+                writer.write_event(Event::Text(BytesText::new(&element.text)))?;
+            }
+            stack.push(Step::Close(element.name));
+            let start = stack.len();
+            stack.extend(node.children().map(Step::Open));
+            stack[start..].reverse();
+        }
+        Ok(())
+    }
+}
+
+/// Every element in a document, along with the indexes of its child elements.  The root element
+/// comes first.
+type Elements = Vec<(Option<XmlElement>, Vec<usize>)>;
+
+fn parse_elements<R: io::BufRead>(reader: R) -> Result<Elements, XmlError> {
+    let mut reader = Reader::from_reader(reader);
+    reader.config_mut().trim_text(true);
+
+    let mut elements: Elements = Vec::new();
+    // the indexes of the elements that are still open
+    let mut open: Vec<usize> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        let position = reader.buffer_position() as usize;
+        let syntax = |error: &dyn fmt::Display| XmlError::Syntax {
+            position,
+            message: error.to_string(),
+        };
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|error| XmlError::Syntax {
+                position: reader.error_position() as usize,
+                message: error.to_string(),
+            })?;
+
+        let text = match event {
+            Event::Start(ref start) | Event::Empty(ref start) => {
+                let name = reader
+                    .decoder()
+                    .decode(start.name().as_ref())
+                    .map_err(|error| syntax(&error))?
+                    .into_owned();
+                let mut element = XmlElement::new(name);
+                for attribute in start.attributes() {
+                    let attribute = attribute.map_err(|error| syntax(&error))?;
+                    let name = reader
+                        .decoder()
+                        .decode(attribute.key.as_ref())
+                        .map_err(|error| syntax(&error))?
+                        .into_owned();
+                    let value = attribute
+                        .decode_and_unescape_value(reader.decoder())
+                        .map_err(|error| syntax(&error))?
+                        .into_owned();
+                    element.attributes.push((name, value));
+                }
+
+                let index = elements.len();
+                match open.last() {
+                    Some(&parent) => elements[parent].1.push(index),
+                    None if index > 0 => return Err(XmlError::MultipleRoots(position)),
+                    None => {}
+                }
+                elements.push((Some(element), Vec::new()));
+                if let Event::Start(_) = event {
+                    open.push(index);
+                }
+                None
+            }
+            Event::End(_) => {
+                open.pop();
+                None
+            }
+            Event::Text(text) => Some(text.unescape().map_err(|error| syntax(&error))?),
+            Event::CData(data) => Some(data.decode().map_err(|error| syntax(&error))?),
+            Event::Eof if open.is_empty() => break,
+            Event::Eof => return Err(XmlError::UnexpectedEof),
+            _ => None,
+        };
+
+        if let Some(text) = text {
+            let text = text.trim();
+            if text.is_empty() {
+                // CDATA isn't trimmed by the reader
+            } else if let Some(&index) = open.last() {
+                let element = elements[index]
+                    .0
+                    .as_mut()
+                    .expect("elements are built later");
+                if !element.text.is_empty() {
+                    element.text.push(' ');
+                }
+                element.text.push_str(text);
+            } else {
+                return Err(XmlError::TextOutsideRoot(position));
+            }
+        }
+        buf.clear();
+    }
+
+    Ok(elements)
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod xml_tests {
+    use super::*;
+
+    fn read(input: &str) -> Result<Tree<XmlElement>, XmlError> {
+        Tree::from_xml(input.as_bytes(), |element| element)
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut tree = TreeBuilder::new()
+            .with_root(XmlElement::new("doc").with_attribute("lang", "en \"GB\""))
+            .build();
+        let mut root = tree.root_mut().unwrap();
+        let mut section = root.append(XmlElement::new("section").with_text("<intro> & more"));
+        section.append(XmlElement::new("p").with_text("one"));
+        section.append(XmlElement::new("br"));
+        root.append(
+            XmlElement::new("ns:end")
+                .with_attribute("a", "1")
+                .with_attribute("b", ""),
+        );
+
+        let mut out = Vec::new();
+        tree.write_xml(&mut out, |element| element.clone()).unwrap();
+        let read = Tree::from_xml(&out[..], |element| element).unwrap();
+        assert!(read
+            .root()
+            .unwrap()
+            .is_isomorphic_to(&tree.root().unwrap(), |a, b| a == b));
+
+        let mut out = Vec::new();
+        Tree::<XmlElement>::new()
+            .write_xml(&mut out, |element| element.clone())
+            .unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn joins_text_runs() {
+        let tree =
+            read("<p>\n  Hello <!-- greeting -->\n  <b>big</b> wide <![CDATA[ world ]]></p>")
+                .unwrap();
+        let root = tree.root().unwrap();
+        assert_eq!(root.data().text, "Hello wide world");
+        assert_eq!(root.first_child().unwrap().data().text, "big");
+    }
+
+    #[test]
+    fn empty_documents() {
+        assert!(read("").unwrap().root().is_none());
+        assert!(read("<?xml version=\"1.0\"?>\n<!-- nothing -->\n")
+            .unwrap()
+            .root()
+            .is_none());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(read("<a><b></b>").unwrap_err(), XmlError::UnexpectedEof);
+        assert_eq!(read("<a/><b/>").unwrap_err(), XmlError::MultipleRoots(4));
+        assert_eq!(read("<a/>tail").unwrap_err(), XmlError::TextOutsideRoot(4));
+        match read("<a></b>").unwrap_err() {
+            XmlError::Syntax { message, .. } => assert!(message.contains("b")),
+            error => panic!("unexpected error {:?}", error),
+        }
+        match read("<a x=\"1\" x=\"2\"/>").unwrap_err() {
+            XmlError::Syntax { .. } => {}
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
+}